@@ -0,0 +1,43 @@
+//! Base64 encoding, for the websocket handshake of `aoc serve` and for
+//! sharing intcode programs. Decoding is left to the users, which want
+//! their own errors.
+
+/// The alphabet of RFC 4648, used with padding.
+pub const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The URL and file name safe alphabet, usually without padding.
+pub const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encodes the bytes six bits a character, filling the last group of four
+/// with `=` when `pad` is set.
+pub fn encode(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, byte)| {
+            bits | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                text.push(alphabet[(bits >> (18 - 6 * index) & 0x3f) as usize] as char);
+            } else if pad {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode, STANDARD, URL_SAFE};
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode(b"Man", STANDARD, true), "TWFu");
+        assert_eq!(encode(b"Ma", STANDARD, true), "TWE=");
+        assert_eq!(encode(b"M", STANDARD, true), "TQ==");
+        assert_eq!(encode(b"M", URL_SAFE, false), "TQ");
+        assert_eq!(encode(&[0xfb, 0xff], STANDARD, true), "+/8=");
+        assert_eq!(encode(&[0xfb, 0xff], URL_SAFE, false), "-_8");
+    }
+}
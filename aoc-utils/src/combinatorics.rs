@@ -0,0 +1,152 @@
+//! Lexicographic permutations and combinations. Both iterators work on the
+//! positions of the items, so the order they come out in follows the order
+//! the items were given in, whatever that order means for the caller.
+
+/// Rearranges `values` into the next permutation in lexicographic order.
+/// Returns `false`, leaving the values sorted, after the last permutation.
+pub fn next_permutation<T: Ord>(values: &mut [T]) -> bool {
+    if values.len() < 2 {
+        return false;
+    }
+    // Find the longest non-increasing suffix, the value right before it is
+    // swapped with the smallest larger value in the suffix
+    let mut pivot = values.len() - 1;
+    while pivot > 0 && values[pivot - 1] >= values[pivot] {
+        pivot -= 1;
+    }
+    if pivot == 0 {
+        values.reverse();
+        return false;
+    }
+    let mut swap = values.len() - 1;
+    while values[swap] <= values[pivot - 1] {
+        swap -= 1;
+    }
+    values.swap(pivot - 1, swap);
+    values[pivot..].reverse();
+    true
+}
+
+pub struct Permutations<T> {
+    items: Vec<T>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<T: Clone> Iterator for Permutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let permutation = self
+            .indices
+            .iter()
+            .map(|index| self.items[*index].clone())
+            .collect();
+        self.done = !next_permutation(&mut self.indices);
+        Some(permutation)
+    }
+}
+
+/// Every ordering of `items`, starting with the given one.
+pub fn permutations<T: Clone>(items: &[T]) -> Permutations<T> {
+    Permutations {
+        items: items.to_vec(),
+        indices: (0..items.len()).collect(),
+        done: false,
+    }
+}
+
+pub struct Combinations<T> {
+    items: Vec<T>,
+    indices: Vec<usize>,
+    done: bool,
+}
+
+impl<T: Clone> Iterator for Combinations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let combination = self
+            .indices
+            .iter()
+            .map(|index| self.items[*index].clone())
+            .collect();
+
+        // Advance the rightmost index that still has room to move, and put
+        // every index after it right next to each other
+        let size = self.indices.len();
+        let total = self.items.len();
+        match (0..size)
+            .rev()
+            .find(|i| self.indices[*i] < total - size + i)
+        {
+            Some(i) => {
+                self.indices[i] += 1;
+                for j in i + 1..size {
+                    self.indices[j] = self.indices[j - 1] + 1;
+                }
+            }
+            None => self.done = true,
+        }
+        Some(combination)
+    }
+}
+
+/// Every choice of `size` items, keeping the order they were given in.
+pub fn combinations<T: Clone>(items: &[T], size: usize) -> Combinations<T> {
+    Combinations {
+        items: items.to_vec(),
+        indices: (0..size).collect(),
+        done: size > items.len(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{combinations, next_permutation, permutations};
+
+    #[test]
+    fn test_next_permutation() {
+        let mut values = [1, 2, 2];
+        assert!(next_permutation(&mut values));
+        assert_eq!(values, [2, 1, 2]);
+        assert!(next_permutation(&mut values));
+        assert_eq!(values, [2, 2, 1]);
+        assert!(!next_permutation(&mut values));
+        assert_eq!(values, [1, 2, 2]);
+    }
+
+    #[test]
+    fn test_permutations() {
+        let all: Vec<_> = permutations(&['c', 'a', 'b']).collect();
+        assert_eq!(
+            all,
+            [
+                ['c', 'a', 'b'],
+                ['c', 'b', 'a'],
+                ['a', 'c', 'b'],
+                ['a', 'b', 'c'],
+                ['b', 'c', 'a'],
+                ['b', 'a', 'c'],
+            ]
+        );
+        assert_eq!(permutations(&[0, 1, 2, 3, 4]).count(), 120);
+        assert_eq!(permutations::<u8>(&[]).count(), 1);
+    }
+
+    #[test]
+    fn test_combinations() {
+        let all: Vec<_> = combinations(&[1, 2, 3, 4], 2).collect();
+        assert_eq!(all, [[1, 2], [1, 3], [1, 4], [2, 3], [2, 4], [3, 4]]);
+        assert_eq!(combinations(&[1, 2, 3], 0).count(), 1);
+        assert_eq!(combinations(&[1, 2, 3], 3).count(), 1);
+        assert_eq!(combinations(&[1, 2, 3], 4).count(), 0);
+        assert_eq!(combinations(&[0; 8], 4).count(), 70);
+    }
+}
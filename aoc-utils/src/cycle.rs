@@ -0,0 +1,194 @@
+//! Cycle detection for sequences produced by repeatedly applying a step
+//! function to a state, as in most simulation puzzles.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cycle in the sequence `x0, f(x0), f(f(x0)), ...`. The states from index
+/// `start` onwards repeat every `period` steps, and `state` is the state at
+/// index `start`, the first one to be seen twice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cycle<T> {
+    pub start: usize,
+    pub period: usize,
+    pub state: T,
+}
+
+impl<T> Cycle<T> {
+    /// Maps an index in the sequence to the earliest index holding the same
+    /// state, so huge step counts can be answered from a short simulation.
+    pub fn equivalent_index(&self, index: usize) -> usize {
+        if index < self.start {
+            index
+        } else {
+            self.start + (index - self.start) % self.period
+        }
+    }
+}
+
+/// Floyd's tortoise and hare. Uses constant memory, but applies `step`
+/// roughly three times per element of the sequence.
+pub fn floyd<T, F>(initial: T, mut step: F) -> Cycle<T>
+where
+    T: PartialEq + Clone,
+    F: FnMut(&T) -> T,
+{
+    let mut tortoise = step(&initial);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        hare = step(&hare);
+    }
+
+    // The distance from the start to the cycle equals the distance from the
+    // meeting point to the cycle, modulo the period
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+
+    Cycle {
+        start,
+        period,
+        state: tortoise,
+    }
+}
+
+/// Brent's algorithm. Also uses constant memory, and usually calls `step`
+/// fewer times than Floyd's.
+pub fn brent<T, F>(initial: T, mut step: F) -> Cycle<T>
+where
+    T: PartialEq + Clone,
+    F: FnMut(&T) -> T,
+{
+    // Find the period by teleporting the tortoise to the hare at every power
+    // of two until the hare runs into it
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // With the hare `period` steps ahead, both meet at the start of the cycle
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle {
+        start,
+        period,
+        state: tortoise,
+    }
+}
+
+/// Remembers every state until one repeats. Uses memory proportional to the
+/// length of the sequence, but calls `step` only once per state.
+pub fn find_repeat<T, F>(initial: T, mut step: F) -> Cycle<T>
+where
+    T: Eq + Hash + Clone,
+    F: FnMut(&T) -> T,
+{
+    let mut seen = HashMap::new();
+    let mut state = initial;
+    let mut index = 0;
+    loop {
+        if let Some(start) = seen.insert(state.clone(), index) {
+            return Cycle {
+                start,
+                period: index - start,
+                state,
+            };
+        }
+        state = step(&state);
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{brent, find_repeat, floyd, Cycle};
+
+    // 0, 1, 2, 3, 4, 5, 3, 4, 5, ...
+    fn step(value: &u32) -> u32 {
+        if *value == 5 {
+            3
+        } else {
+            value + 1
+        }
+    }
+
+    fn expected() -> Cycle<u32> {
+        Cycle {
+            start: 3,
+            period: 3,
+            state: 3,
+        }
+    }
+
+    #[test]
+    fn test_floyd() {
+        assert_eq!(floyd(0, step), expected());
+    }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(brent(0, step), expected());
+    }
+
+    #[test]
+    fn test_find_repeat() {
+        assert_eq!(find_repeat(0, step), expected());
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        let step = |value: &u64| (value * value + 1) % 255;
+        for initial in 0..255 {
+            let expected = find_repeat(initial, step);
+            assert_eq!(floyd(initial, step), expected);
+            assert_eq!(brent(initial, step), expected);
+        }
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        let cycle = brent(7, |_: &u32| 7);
+        assert_eq!((cycle.start, cycle.period), (0, 1));
+    }
+
+    #[test]
+    fn test_equivalent_index() {
+        let cycle = expected();
+        assert_eq!(cycle.equivalent_index(2), 2);
+        assert_eq!(cycle.equivalent_index(3), 3);
+        assert_eq!(cycle.equivalent_index(7), 4);
+        assert_eq!(cycle.equivalent_index(1_000_000_000), 4);
+    }
+}
@@ -0,0 +1,95 @@
+//! Splitting numbers into decimal digits and putting them back together.
+
+/// The decimal digits of a number, most significant first.
+#[derive(Debug, Clone)]
+pub struct Digits {
+    digits: [u8; 20],
+    front: usize,
+    back: usize,
+}
+
+impl Iterator for Digits {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.digits[self.front - 1])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Digits {
+    fn next_back(&mut self) -> Option<u8> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.digits[self.back])
+    }
+}
+
+impl ExactSizeIterator for Digits {}
+
+/// Iterates over the decimal digits of `n`, most significant first. Zero
+/// has a single digit.
+pub fn digits(mut n: u64) -> Digits {
+    // u64::MAX has 20 digits, filled from the end
+    let mut digits = [0; 20];
+    let mut front = digits.len();
+    loop {
+        front -= 1;
+        digits[front] = (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    Digits {
+        digits,
+        front,
+        back: 20,
+    }
+}
+
+/// Builds a number from its decimal digits, most significant first.
+pub fn from_digits<I: IntoIterator<Item = u8>>(digits: I) -> u64 {
+    digits
+        .into_iter()
+        .fold(0, |number, digit| number * 10 + digit as u64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{digits, from_digits};
+
+    #[test]
+    fn test_digits() {
+        assert_eq!(digits(172851).collect::<Vec<_>>(), [1, 7, 2, 8, 5, 1]);
+        assert_eq!(digits(0).collect::<Vec<_>>(), [0]);
+        assert_eq!(digits(120).rev().collect::<Vec<_>>(), [0, 2, 1]);
+        assert_eq!(digits(u64::MAX).len(), 20);
+    }
+
+    #[test]
+    fn test_both_ends() {
+        let mut iter = digits(12345);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(5));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(iter.collect::<Vec<_>>(), [2, 3, 4]);
+    }
+
+    #[test]
+    fn test_from_digits() {
+        assert_eq!(from_digits(vec![6, 7, 5, 8, 6, 9]), 675869);
+        assert_eq!(from_digits(digits(987).rev()), 789);
+        assert_eq!(from_digits(vec![]), 0);
+    }
+}
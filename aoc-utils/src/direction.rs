@@ -0,0 +1,134 @@
+use crate::Point2;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// All directions, clockwise starting from up
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        self.turn_left().turn_left()
+    }
+
+    /// One step in this direction, with the y axis pointing up
+    pub fn delta(self) -> Point2 {
+        match self {
+            Direction::Up => Point2::new(0, 1),
+            Direction::Right => Point2::new(1, 0),
+            Direction::Down => Point2::new(0, -1),
+            Direction::Left => Point2::new(-1, 0),
+        }
+    }
+
+    /// One step in this direction in screen coordinates, where rows grow
+    /// downwards
+    pub fn screen_delta(self) -> Point2 {
+        let delta = self.delta();
+        Point2::new(delta.x, -delta.y)
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            'U' | '^' => Some(Direction::Up),
+            'R' | '>' => Some(Direction::Right),
+            'D' | 'v' => Some(Direction::Down),
+            'L' | '<' => Some(Direction::Left),
+            _ => None,
+        }
+    }
+
+    /// The arrow used to draw something facing this direction
+    pub fn arrow(self) -> char {
+        match self {
+            Direction::Up => '^',
+            Direction::Right => '>',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next().and_then(Direction::from_char), chars.next()) {
+            (Some(direction), None) => Ok(direction),
+            _ => Err("Invalid direction character"),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.arrow())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Direction;
+    use crate::Point2;
+
+    #[test]
+    fn test_turns() {
+        for direction in Direction::ALL.iter() {
+            assert_eq!(direction.turn_left().turn_right(), *direction);
+            assert_eq!(direction.opposite().opposite(), *direction);
+            assert_eq!(
+                direction.turn_left().delta(),
+                direction.delta().rotate_left()
+            );
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+    }
+
+    #[test]
+    fn test_deltas() {
+        assert_eq!(Direction::Up.delta(), Point2::new(0, 1));
+        assert_eq!(Direction::Up.screen_delta(), Point2::new(0, -1));
+        assert_eq!(Direction::Left.screen_delta(), Point2::new(-1, 0));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("U".parse(), Ok(Direction::Up));
+        assert_eq!("v".parse(), Ok(Direction::Down));
+        assert_eq!("<".parse(), Ok(Direction::Left));
+        assert_eq!("R".parse(), Ok(Direction::Right));
+        assert!("X".parse::<Direction>().is_err());
+        assert!("UU".parse::<Direction>().is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::Point2;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+/// A fixed size two dimensional grid, stored row by row. Points use screen
+/// coordinates: `x` is the column and `y` is the row, growing downwards.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+const DIAGONALS: [Point2; 4] = [
+    Point2::new(-1, -1),
+    Point2::new(1, -1),
+    Point2::new(1, 1),
+    Point2::new(-1, 1),
+];
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its cells, row by row. Fails if the number of cells
+    /// doesn't match the size.
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Result<Self, &'static str> {
+        if cells.len() != width * height {
+            return Err("Number of cells doesn't match the grid size");
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Parses a grid drawn as text, one row per line. Empty lines are ignored
+    /// and every row must have the same length.
+    pub fn parse<F, E>(text: &str, mut parse_cell: F) -> Result<Self, E>
+    where
+        F: FnMut(char) -> Result<T, E>,
+        E: From<&'static str>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+        for line in text.lines().filter(|line| !line.is_empty()) {
+            let before = cells.len();
+            for c in line.chars() {
+                cells.push(parse_cell(c)?);
+            }
+            let length = cells.len() - before;
+            if *width.get_or_insert(length) != length {
+                return Err(E::from("All grid rows must have the same length"));
+            }
+            height += 1;
+        }
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point2) -> bool {
+        point.x >= 0
+            && point.y >= 0
+            && (point.x as usize) < self.width
+            && (point.y as usize) < self.height
+    }
+
+    fn offset(&self, point: Point2) -> Option<usize> {
+        if self.contains(point) {
+            Some(point.y as usize * self.width + point.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, point: Point2) -> Option<&T> {
+        self.offset(point).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, point: Point2) -> Option<&mut T> {
+        match self.offset(point) {
+            Some(offset) => Some(&mut self.cells[offset]),
+            None => None,
+        }
+    }
+
+    /// Every point in the grid, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point2> {
+        let width = self.width as i64;
+        (0..self.height as i64).flat_map(move |y| (0..width).map(move |x| Point2::new(x, y)))
+    }
+
+    /// Every cell with its point, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Point2, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn find<F>(&self, mut predicate: F) -> Option<Point2>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(point, _)| point)
+    }
+
+    /// The orthogonal neighbours of a point that are inside the grid
+    pub fn neighbours4(&self, point: Point2) -> impl Iterator<Item = Point2> + '_ {
+        point
+            .neighbours()
+            .to_vec()
+            .into_iter()
+            .filter(move |neighbour| self.contains(*neighbour))
+    }
+
+    /// The orthogonal and diagonal neighbours of a point that are inside the
+    /// grid
+    pub fn neighbours8(&self, point: Point2) -> impl Iterator<Item = Point2> + '_ {
+        let diagonals = DIAGONALS.iter().map(move |delta| point + *delta);
+        point
+            .neighbours()
+            .to_vec()
+            .into_iter()
+            .chain(diagonals)
+            .filter(move |neighbour| self.contains(*neighbour))
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        if y < self.height {
+            Some(&self.cells[y * self.width..(y + 1) * self.width])
+        } else {
+            None
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        // chunks panics with a zero size, but then there are no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        let cells = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Draws the grid with one character per cell
+    pub fn render<F>(&self, mut draw: F) -> String
+    where
+        F: FnMut(&T) -> char,
+    {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.rows() {
+            text.extend(row.iter().map(&mut draw));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl<T> Index<Point2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point2) -> &T {
+        self.get(point).expect("Point outside of the grid")
+    }
+}
+
+impl<T> IndexMut<Point2> for Grid<T> {
+    fn index_mut(&mut self, point: Point2) -> &mut T {
+        self.get_mut(point).expect("Point outside of the grid")
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Grid;
+    use crate::Point2;
+
+    fn parse(text: &str) -> Result<Grid<char>, &'static str> {
+        Grid::parse(text, Ok)
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid = parse("ab\ncd\nef\n").unwrap();
+        assert_eq!(grid.width(), 2);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid[Point2::new(1, 2)], 'f');
+        assert_eq!(grid.to_string(), "ab\ncd\nef\n");
+        assert!(parse("ab\nc").is_err());
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = Grid::new(3, 2, 0);
+        assert!(grid.contains(Point2::new(2, 1)));
+        assert!(!grid.contains(Point2::new(3, 1)));
+        assert!(!grid.contains(Point2::new(-1, 0)));
+        assert_eq!(grid.get(Point2::new(0, 2)), None);
+        grid[Point2::new(2, 1)] = 5;
+        assert_eq!(grid.row(1), Some(&[0, 0, 5][..]));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, ());
+        assert_eq!(grid.neighbours4(Point2::new(1, 1)).count(), 4);
+        assert_eq!(grid.neighbours8(Point2::new(1, 1)).count(), 8);
+        assert_eq!(grid.neighbours4(Point2::new(0, 0)).count(), 2);
+        assert_eq!(grid.neighbours8(Point2::new(0, 0)).count(), 3);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = parse("abc\ndef").unwrap();
+        let rows: Vec<String> = grid.rows().map(|row| row.iter().collect()).collect();
+        assert_eq!(rows, ["abc", "def"]);
+        let column: String = grid.column(1).collect();
+        assert_eq!(column, "be");
+        assert_eq!(grid.find(|c| *c == 'e'), Some(Point2::new(1, 1)));
+        assert_eq!(
+            grid.map(|c| c.to_ascii_uppercase()).render(|c| *c),
+            "ABC\nDEF\n"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Saving grids as pictures. Grids are first turned into a grid of colors,
+//! and every cell is drawn as a square of `scale` pixels. PNGs need the
+//! `png` feature, SVGs are always available.
+
+use crate::{Grid, SparseGrid};
+use std::fmt;
+#[cfg(feature = "png")]
+use std::fs::File;
+use std::io;
+#[cfg(feature = "png")]
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub type Color = [u8; 3];
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    #[cfg(feature = "png")]
+    Encoding(png::EncodingError),
+    UnknownFormat,
+    Empty,
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "Could not write image: {}", error),
+            #[cfg(feature = "png")]
+            ImageError::Encoding(error) => write!(f, "Could not encode image: {}", error),
+            #[cfg(feature = "png")]
+            ImageError::UnknownFormat => write!(f, "Images can only be saved as .png or .svg"),
+            #[cfg(not(feature = "png"))]
+            ImageError::UnknownFormat => write!(f, "Images can only be saved as .svg"),
+            ImageError::Empty => write!(f, "Can't save an empty image"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<io::Error> for ImageError {
+    fn from(error: io::Error) -> Self {
+        ImageError::Io(error)
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<png::EncodingError> for ImageError {
+    fn from(error: png::EncodingError) -> Self {
+        ImageError::Encoding(error)
+    }
+}
+
+/// Colors the bounding box of a sparse grid. Cells that were never set are
+/// passed as `None`.
+pub fn render_sparse<T, F>(grid: &SparseGrid<T>, mut color: F) -> Grid<Color>
+where
+    F: FnMut(Option<&T>) -> Color,
+{
+    let (min, max) = match grid.bounds() {
+        Some(bounds) => bounds,
+        None => return Grid::new(0, 0, [0, 0, 0]),
+    };
+    let size = max - min;
+    let (width, height) = (size.x as usize + 1, size.y as usize + 1);
+    let mut image = Grid::new(width, height, [0, 0, 0]);
+    for point in image.points().collect::<Vec<_>>() {
+        image[point] = color(grid.get(point + min));
+    }
+    image
+}
+
+/// Encodes the image as a PNG.
+#[cfg(feature = "png")]
+pub fn write_png<W: Write>(image: &Grid<Color>, scale: usize, out: W) -> Result<(), ImageError> {
+    if image.width() == 0 || image.height() == 0 || scale == 0 {
+        return Err(ImageError::Empty);
+    }
+    let (width, height) = (image.width() * scale, image.height() * scale);
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+
+    let mut data = Vec::with_capacity(width * height * 3);
+    for row in image.rows() {
+        let line: Vec<u8> = row
+            .iter()
+            .flat_map(|color| (0..scale).flat_map(move |_| color.iter().copied()))
+            .collect();
+        for _ in 0..scale {
+            data.extend_from_slice(&line);
+        }
+    }
+    writer.write_image_data(&data)?;
+    Ok(())
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Draws the image as an SVG document. Runs of cells with the same color on
+/// a row become a single rectangle, painted over a background of the most
+/// common color.
+pub fn svg(image: &Grid<Color>, scale: usize) -> String {
+    let (width, height) = (image.width() * scale, image.height() * scale);
+    let mut counts: Vec<(Color, usize)> = Vec::new();
+    for (_, color) in image.iter() {
+        match counts.iter_mut().find(|(c, _)| c == color) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((*color, 1)),
+        }
+    }
+    let background = counts
+        .iter()
+        .max_by_key(|(_, count)| *count)
+        .map_or([0, 0, 0], |(color, _)| *color);
+
+    let mut text = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">\n",
+        width, height
+    );
+    text.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        width,
+        height,
+        hex(background)
+    ));
+    for (y, row) in image.rows().enumerate() {
+        let mut x = 0;
+        while x < row.len() {
+            let start = x;
+            while x < row.len() && row[x] == row[start] {
+                x += 1;
+            }
+            if row[start] != background {
+                text.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    start * scale,
+                    y * scale,
+                    (x - start) * scale,
+                    scale,
+                    hex(row[start])
+                ));
+            }
+        }
+    }
+    text.push_str("</svg>\n");
+    text
+}
+
+/// Saves the image, picking the format from the file extension.
+pub fn save<P: AsRef<Path>>(image: &Grid<Color>, scale: usize, path: P) -> Result<(), ImageError> {
+    let path = path.as_ref();
+    match path.extension().and_then(|extension| extension.to_str()) {
+        #[cfg(feature = "png")]
+        Some("png") => write_png(image, scale, BufWriter::new(File::create(path)?)),
+        Some("svg") => {
+            if image.width() == 0 || image.height() == 0 {
+                return Err(ImageError::Empty);
+            }
+            Ok(std::fs::write(path, svg(image, scale))?)
+        }
+        _ => Err(ImageError::UnknownFormat),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render_sparse, svg, Color};
+    use crate::{Grid, Point2, SparseGrid};
+
+    const BLACK: Color = [0, 0, 0];
+    const WHITE: Color = [255, 255, 255];
+
+    #[test]
+    fn test_render_sparse() {
+        let grid: SparseGrid<bool> = vec![(Point2::new(-1, 2), true), (Point2::new(1, 3), true)]
+            .into_iter()
+            .collect();
+        let image = render_sparse(&grid, |cell| if cell.is_some() { WHITE } else { BLACK });
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(image[Point2::new(0, 0)], WHITE);
+        assert_eq!(image[Point2::new(1, 0)], BLACK);
+        assert_eq!(image[Point2::new(2, 1)], WHITE);
+    }
+
+    #[test]
+    fn test_svg_merges_runs() {
+        let image = Grid::from_vec(5, 1, vec![BLACK, WHITE, WHITE, BLACK, BLACK]).unwrap();
+        let text = svg(&image, 10);
+        assert!(text.contains("<rect width=\"50\" height=\"10\" fill=\"#000000\"/>"));
+        assert!(
+            text.contains("<rect x=\"10\" y=\"0\" width=\"20\" height=\"10\" fill=\"#ffffff\"/>")
+        );
+        assert_eq!(text.matches("<rect").count(), 2);
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_write_png() {
+        use super::write_png;
+
+        let image = Grid::from_vec(2, 1, vec![BLACK, WHITE]).unwrap();
+        let mut data = Vec::new();
+        write_png(&image, 3, &mut data).unwrap();
+        assert!(data.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        let decoder = png::Decoder::new(data.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (6, 3));
+        assert_eq!(&pixels[..3], &BLACK);
+        assert_eq!(&pixels[15..18], &WHITE);
+    }
+}
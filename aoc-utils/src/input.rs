@@ -0,0 +1,283 @@
+//! Helpers for the usual shapes of puzzle input. Errors point at the line
+//! and column of the value that failed to parse.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read};
+use std::str::FromStr;
+
+/// A value that couldn't be parsed. Lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, column: usize, message: impl fmt::Display) -> Self {
+        Self {
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+
+    /// Moves an error found while parsing a single line to the given line
+    /// of the whole input.
+    pub fn on_line(self, line: usize) -> Self {
+        Self {
+            line: line + self.line - 1,
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+fn parse_at<T>(value: &str, line: usize, column: usize) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    value.parse().map_err(|error: T::Err| {
+        ParseError::new(
+            line,
+            column,
+            format!("invalid value {:?}: {}", value, error),
+        )
+    })
+}
+
+/// Parses every non-blank line, ignoring surrounding whitespace.
+pub fn lines_of<T>(text: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let value = line.trim();
+            let column = line.len() - line.trim_start().len() + 1;
+            parse_at(value, index + 1, column)
+        })
+        .collect()
+}
+
+/// Parses a single comma separated list, ignoring whitespace around each
+/// value and the trailing newline.
+pub fn comma_separated<T>(text: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let line_start = text.len() - text.trim_start_matches('\n').len();
+    let line = text.trim_start_matches('\n').lines().next().unwrap_or("");
+    let line_number = text[..line_start].matches('\n').count() + 1;
+
+    let mut column = 1;
+    let mut values = Vec::new();
+    for field in line.split(',') {
+        let value = field.trim();
+        if !(value.is_empty() && line.trim().is_empty()) {
+            let value_column = column + field.len() - field.trim_start().len();
+            values.push(parse_at(value, line_number, value_column)?);
+        }
+        column += field.len() + 1;
+    }
+    Ok(values)
+}
+
+/// Splits the input into groups of lines separated by blank lines.
+pub fn blank_line_blocks(text: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(start) = start.take() {
+                blocks.push(&text[start..end]);
+            }
+        } else {
+            start.get_or_insert(offset);
+            end = offset + line.trim_end_matches(&['\r', '\n'][..]).len();
+        }
+        offset += line.len();
+    }
+    if let Some(start) = start {
+        blocks.push(&text[start..end]);
+    }
+    blocks
+}
+
+/// Finds every integer in the text, with an optional leading minus sign,
+/// ignoring whatever is around them.
+pub fn extract_ints(text: &str) -> Result<Vec<i64>, ParseError> {
+    let mut values = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let bytes = line.as_bytes();
+        let mut position = 0;
+        while position < bytes.len() {
+            let negative =
+                bytes[position] == b'-' && bytes.get(position + 1).is_some_and(u8::is_ascii_digit);
+            if !negative && !bytes[position].is_ascii_digit() {
+                position += 1;
+                continue;
+            }
+            let start = position;
+            position += 1;
+            while position < bytes.len() && bytes[position].is_ascii_digit() {
+                position += 1;
+            }
+            values.push(parse_at(&line[start..position], index + 1, start + 1)?);
+        }
+    }
+    Ok(values)
+}
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// The reader, decompressed if it starts like a gzip or zstd file. The
+/// format is told by its first bytes rather than a file's extension, so
+/// it works on stdin too.
+#[cfg(feature = "compression")]
+pub fn decompressed<'a, R: BufRead + 'a>(mut reader: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    let start = reader.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(io::BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader),
+        )))
+    } else if start.starts_with(ZSTD_MAGIC) {
+        let decoder = ruzstd::decoding::StreamingDecoder::new(reader)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+        Ok(Box::new(io::BufReader::new(decoder)))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// The reader as it is, built without the `compression` feature. Compressed
+/// input is refused rather than read as garbled text.
+#[cfg(not(feature = "compression"))]
+pub fn decompressed<'a, R: BufRead + 'a>(mut reader: R) -> io::Result<Box<dyn BufRead + 'a>> {
+    let start = reader.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) || start.starts_with(ZSTD_MAGIC) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Compressed input needs the compression feature",
+        ));
+    }
+    Ok(Box::new(reader))
+}
+
+/// Drops the byte order mark some editors add and turns Windows line
+/// endings into plain ones.
+pub fn normalize(text: &str) -> String {
+    text.strip_prefix('\u{feff}')
+        .unwrap_or(text)
+        .replace("\r\n", "\n")
+}
+
+/// Reads a whole puzzle input, compressed or not, see [`decompressed`] and
+/// [`normalize`].
+pub fn read_text<R: BufRead>(reader: R) -> io::Result<String> {
+    let mut text = String::new();
+    decompressed(reader)?.read_to_string(&mut text)?;
+    Ok(normalize(&text))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        blank_line_blocks, comma_separated, extract_ints, lines_of, normalize, read_text,
+        ParseError,
+    };
+
+    #[test]
+    fn test_lines_of() {
+        assert_eq!(lines_of::<i64>("12\n\n  -4 \n7\n"), Ok(vec![12, -4, 7]));
+        let error = lines_of::<i64>("12\n\n  x4\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+    }
+
+    #[test]
+    fn test_comma_separated() {
+        assert_eq!(comma_separated::<i64>("1,2, -3\n"), Ok(vec![1, 2, -3]));
+        assert_eq!(comma_separated::<i64>("\n"), Ok(vec![]));
+        let error = comma_separated::<i64>("\n1,2,, 4").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 5));
+    }
+
+    #[test]
+    fn test_on_line() {
+        let error = ParseError::new(1, 4, "bad").on_line(2);
+        assert_eq!(error.to_string(), "line 2, column 4: bad");
+    }
+
+    #[test]
+    fn test_blank_line_blocks() {
+        let text = "\na\nb\n\n\nc\r\n\r\nd";
+        assert_eq!(blank_line_blocks(text), ["a\nb", "c", "d"]);
+        assert!(blank_line_blocks("\n\n").is_empty());
+    }
+
+    #[test]
+    fn test_extract_ints() {
+        let text = "<x=-1, y=0, z=2>\n<x=4, y=-10, z=8>";
+        assert_eq!(extract_ints(text), Ok(vec![-1, 0, 2, 4, -10, 8]));
+        assert_eq!(extract_ints("a-b 3-4"), Ok(vec![3, -4]));
+        let error = extract_ints("1\nx 99999999999999999999").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("\u{feff}1,2\r\n3\r\n"), "1,2\n3\n");
+        assert_eq!(normalize("1\n2"), "1\n2");
+    }
+
+    #[test]
+    fn test_read_text() {
+        let text = "\u{feff}1,9,10,3\r\n";
+        assert_eq!(read_text(text.as_bytes()).unwrap(), "1,9,10,3\n");
+        // Without the feature, compressed input is refused
+        #[cfg(not(feature = "compression"))]
+        assert!(read_text(&[0x28, 0xb5, 0x2f, 0xfd, 0][..]).is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn test_read_compressed() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let text = "\u{feff}1,9,10,3\r\n";
+
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(text.as_bytes()).unwrap();
+        let gzip = encoder.finish().unwrap();
+        assert_eq!(read_text(&gzip[..]).unwrap(), "1,9,10,3\n");
+
+        let zstd = ruzstd::encoding::compress_to_vec(
+            text.as_bytes(),
+            ruzstd::encoding::CompressionLevel::Fastest,
+        );
+        assert_eq!(read_text(&zstd[..]).unwrap(), "1,9,10,3\n");
+
+        assert!(read_text(&[0x1f, 0x8b, 0, 0][..]).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+//! Maps strings to small numeric IDs, so names read from the input can be
+//! used as cheap keys and array indices.
+
+use std::collections::HashMap;
+
+/// Hands out IDs in order, starting from 0, and remembers the string behind
+/// each one.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    ids: HashMap<String, u32>,
+    names: Vec<String>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ID of `name`, giving it the next free one if it's new.
+    pub fn intern(&mut self, name: &str) -> u32 {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.len() as u32;
+        self.ids.insert(name.to_owned(), id);
+        self.names.push(name.to_owned());
+        id
+    }
+
+    /// The ID of `name`, if it was interned before.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    /// The string behind an ID.
+    pub fn name(&self, id: u32) -> Option<&str> {
+        self.names.get(id as usize).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Every interned string with its ID, in ID order.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (id as u32, name.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Interner;
+
+    #[test]
+    fn test_intern() {
+        let mut interner = Interner::new();
+        assert_eq!(interner.intern("COM"), 0);
+        assert_eq!(interner.intern("B"), 1);
+        assert_eq!(interner.intern("COM"), 0);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.get("B"), Some(1));
+        assert_eq!(interner.get("SAN"), None);
+        assert_eq!(interner.name(1), Some("B"));
+        assert_eq!(interner.name(2), None);
+        assert_eq!(interner.iter().collect::<Vec<_>>(), [(0, "COM"), (1, "B")]);
+    }
+}
@@ -0,0 +1,213 @@
+//! Inclusive ranges of integers, and sets built out of them.
+
+use std::fmt;
+
+/// The integers from `start` to `end`, both included. Never empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    /// Builds an interval from two ends given in any order.
+    pub fn new(a: i64, b: i64) -> Self {
+        Self {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    pub fn single(value: i64) -> Self {
+        Self::new(value, value)
+    }
+
+    /// Number of integers in the interval.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        (self.end - self.start) as u64 + 1
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    /// Whether every value of `other` is also in this interval.
+    pub fn contains_interval(&self, other: &Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        if self.overlaps(other) {
+            Some(Interval {
+                start: self.start.max(other.start),
+                end: self.end.min(other.end),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Joins two intervals that overlap or touch. Intervals with a gap
+    /// between them can't be joined into one.
+    pub fn union(&self, other: &Interval) -> Option<Interval> {
+        if self.start <= other.end.saturating_add(1) && other.start <= self.end.saturating_add(1) {
+            Some(Interval {
+                start: self.start.min(other.start),
+                end: self.end.max(other.end),
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> std::ops::RangeInclusive<i64> {
+        self.start..=self.end
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}..={}", self.start, self.end)
+    }
+}
+
+/// A set of integers kept as sorted, disjoint and non-touching intervals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds every value of the interval, merging it with the intervals it
+    /// overlaps or touches.
+    pub fn insert(&mut self, interval: Interval) {
+        let mut merged = interval;
+        let mut index = 0;
+        while index < self.intervals.len() {
+            match self.intervals[index].union(&merged) {
+                Some(union) => {
+                    merged = union;
+                    self.intervals.remove(index);
+                }
+                None if self.intervals[index].start > merged.end => break,
+                None => index += 1,
+            }
+        }
+        self.intervals.insert(index, merged);
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        // Intervals are sorted, so only the last one starting before the
+        // value can contain it
+        let index = self
+            .intervals
+            .partition_point(|interval| interval.start <= value);
+        index > 0 && self.intervals[index - 1].contains(value)
+    }
+
+    /// Number of integers in the set.
+    pub fn len(&self) -> u64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut result = IntervalSet::new();
+        for a in self.intervals.iter() {
+            for b in other.intervals.iter() {
+                if let Some(both) = a.intersection(b) {
+                    result.insert(both);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl std::iter::FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for interval in iter {
+            set.insert(interval);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Interval, IntervalSet};
+
+    #[test]
+    fn test_interval_operations() {
+        let a = Interval::new(5, 1);
+        let b = Interval::new(4, 8);
+        assert_eq!(a, Interval { start: 1, end: 5 });
+        assert_eq!(a.len(), 5);
+        assert!(a.contains(1) && a.contains(5) && !a.contains(6));
+        assert_eq!(a.intersection(&b), Some(Interval::new(4, 5)));
+        assert_eq!(a.union(&b), Some(Interval::new(1, 8)));
+        assert!(Interval::new(0, 10).contains_interval(&b));
+        assert!(!b.contains_interval(&a));
+    }
+
+    #[test]
+    fn test_disjoint_intervals() {
+        let a = Interval::new(1, 3);
+        assert_eq!(a.intersection(&Interval::new(5, 6)), None);
+        assert_eq!(a.union(&Interval::new(5, 6)), None);
+        assert_eq!(a.union(&Interval::new(4, 6)), Some(Interval::new(1, 6)));
+    }
+
+    #[test]
+    fn test_interval_set() {
+        let mut set: IntervalSet = vec![Interval::new(10, 12), Interval::new(1, 2)]
+            .into_iter()
+            .collect();
+        set.insert(Interval::new(3, 4));
+        set.insert(Interval::new(20, 20));
+        assert_eq!(
+            set.intervals(),
+            [
+                Interval::new(1, 4),
+                Interval::new(10, 12),
+                Interval::single(20)
+            ]
+        );
+        set.insert(Interval::new(0, 15));
+        assert_eq!(
+            set.intervals(),
+            [Interval::new(0, 15), Interval::single(20)]
+        );
+        assert_eq!(set.len(), 17);
+        assert!(set.contains(7) && set.contains(20) && !set.contains(16));
+    }
+
+    #[test]
+    fn test_interval_set_intersection() {
+        let a: IntervalSet = vec![Interval::new(0, 5), Interval::new(10, 15)]
+            .into_iter()
+            .collect();
+        let b: IntervalSet = vec![Interval::new(4, 11)].into_iter().collect();
+        assert_eq!(
+            a.intersection(&b).intervals(),
+            [Interval::new(4, 5), Interval::new(10, 11)]
+        );
+    }
+}
@@ -0,0 +1,31 @@
+pub mod base64;
+pub mod combinatorics;
+pub mod cycle;
+pub mod digits;
+pub mod direction;
+pub mod grid;
+pub mod image;
+pub mod input;
+pub mod interner;
+pub mod interval;
+pub mod math;
+pub mod memo;
+pub mod ocr;
+pub mod point;
+pub mod progress;
+pub mod queue;
+pub mod ring;
+pub mod search;
+pub mod solution;
+pub mod sparse;
+pub mod terminal;
+
+// Used by register_solution!
+#[doc(hidden)]
+pub use inventory;
+
+pub use direction::Direction;
+pub use grid::Grid;
+pub use point::{Point2, Point3};
+pub use solution::{Puzzle, Solution};
+pub use sparse::SparseGrid;
@@ -0,0 +1,270 @@
+//! Number theory helpers. Products are computed with 128-bit intermediates,
+//! so any modulus that fits in an `i64` is safe to use.
+
+/// Greatest common divisor, always non-negative.
+pub fn gcd(a: i64, b: i64) -> i64 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        let rest = a % b;
+        a = b;
+        b = rest;
+    }
+    a
+}
+
+/// Least common multiple, always non-negative. Zero if either value is zero.
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        (a / gcd(a, b) * b).abs()
+    }
+}
+
+/// Least common multiple of every value, or 1 for no values.
+pub fn lcm_all<I: IntoIterator<Item = i64>>(values: I) -> i64 {
+    values.into_iter().fold(1, lcm)
+}
+
+/// Remainder in the range `0..modulus`, even for negative values.
+pub fn modulo(value: i64, modulus: i64) -> i64 {
+    value.rem_euclid(modulus)
+}
+
+/// `a * b` modulo `modulus`, without overflowing.
+pub fn mod_mul(a: i64, b: i64, modulus: i64) -> i64 {
+    (a as i128 * b as i128).rem_euclid(modulus as i128) as i64
+}
+
+/// `base` to the power of `exponent` modulo `modulus`, by squaring.
+pub fn mod_pow(base: i64, mut exponent: u64, modulus: i64) -> i64 {
+    let mut result = modulo(1, modulus);
+    let mut base = modulo(base, modulus);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mod_mul(result, base, modulus);
+        }
+        base = mod_mul(base, base, modulus);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y == g`, where `g` is the
+/// greatest common divisor of `a` and `b`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a as i128, b as i128);
+    let (mut old_x, mut x) = (1i128, 0i128);
+    let (mut old_y, mut y) = (0i128, 1i128);
+    while r != 0 {
+        let quotient = old_r / r;
+        let next = (r, old_r - quotient * r);
+        old_r = next.0;
+        r = next.1;
+        let next = (x, old_x - quotient * x);
+        old_x = next.0;
+        x = next.1;
+        let next = (y, old_y - quotient * y);
+        old_y = next.0;
+        y = next.1;
+    }
+    if old_r < 0 {
+        (-old_r as i64, -old_x as i64, -old_y as i64)
+    } else {
+        (old_r as i64, old_x as i64, old_y as i64)
+    }
+}
+
+/// The value `x` in `0..modulus` such that `a * x` is 1 modulo `modulus`.
+/// Only exists when `a` and `modulus` are coprime, the modulus doesn't have
+/// to be prime.
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(modulo(a, modulus), modulus);
+    if g == 1 {
+        Some(modulo(x, modulus))
+    } else {
+        None
+    }
+}
+
+/// Chinese remainder theorem. Given congruences `x = residue (mod modulus)`,
+/// returns `(x, m)` such that every solution is `x` plus a multiple of `m`,
+/// the lcm of the moduli. Moduli don't have to be coprime, and `None` is
+/// returned if the congruences contradict each other.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut result = (0, 1);
+    for &(residue, modulus) in congruences {
+        let (x, m) = result;
+        let residue = modulo(residue, modulus);
+        let (g, p, _) = extended_gcd(m, modulus);
+        let difference = residue - x;
+        if difference % g != 0 {
+            return None;
+        }
+        // x + m * k = residue (mod modulus), solving for k
+        let step = modulus / g;
+        let k = mod_mul(difference / g, p, step);
+        let combined = lcm(m, modulus);
+        result = (
+            modulo((x as i128 + m as i128 * k as i128) as i64, combined),
+            combined,
+        );
+    }
+    Some(result)
+}
+
+/// The map `x -> a * x + b` modulo `m`. Composing two of them gives another
+/// one, so a long chain of them, or one repeated many times, can be collapsed
+/// into a single map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Affine {
+    pub a: i64,
+    pub b: i64,
+    pub m: i64,
+}
+
+impl Affine {
+    /// The map with `a` and `b` reduced modulo `m`.
+    pub fn new(a: i64, b: i64, m: i64) -> Self {
+        Self {
+            a: modulo(a, m),
+            b: modulo(b, m),
+            m,
+        }
+    }
+
+    pub fn identity(m: i64) -> Self {
+        Self::new(1, 0, m)
+    }
+
+    /// This map followed by `other`, which must have the same modulus.
+    pub fn then(&self, other: &Self) -> Self {
+        debug_assert_eq!(self.m, other.m, "maps have different moduli");
+        Self {
+            a: mod_mul(self.a, other.a, self.m),
+            b: modulo(mod_mul(self.b, other.a, self.m) + other.b, self.m),
+            m: self.m,
+        }
+    }
+
+    /// This map applied `exponent` times in a row, by squaring.
+    pub fn pow(&self, mut exponent: u64) -> Self {
+        let mut result = Self::identity(self.m);
+        let mut base = *self;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.then(&base);
+            }
+            base = base.then(&base);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// The map that undoes this one. Only exists when `a` and the modulus
+    /// are coprime, which is always the case for a prime modulus.
+    pub fn inverse(&self) -> Option<Self> {
+        let a = mod_inverse(self.a, self.m)?;
+        Some(Self {
+            a,
+            b: mod_mul(-self.b, a, self.m),
+            m: self.m,
+        })
+    }
+
+    pub fn apply(&self, x: i64) -> i64 {
+        modulo(mod_mul(self.a, x, self.m) + self.b, self.m)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crt, extended_gcd, gcd, lcm, lcm_all, mod_inverse, mod_mul, mod_pow, Affine};
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(0, 6), 0);
+        assert_eq!(lcm_all(vec![18, 28, 44]), 2772);
+    }
+
+    #[test]
+    fn test_mod_mul_large() {
+        let modulus = 119_315_717_514_047;
+        let a = modulus - 1;
+        assert_eq!(mod_mul(a, a, modulus), 1);
+        assert_eq!(mod_mul(-3, 5, 7), 6);
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+        // Fermat's little theorem
+        let prime = 119_315_717_514_047;
+        assert_eq!(mod_pow(123_456_789, prime as u64 - 1, prime), 1);
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        let (g, x, y) = extended_gcd(240, 46);
+        assert_eq!(g, 2);
+        assert_eq!(240 * x + 46 * y, 2);
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(mod_inverse(3, 10), Some(7));
+        assert_eq!(mod_inverse(-3, 10), Some(3));
+        assert_eq!(mod_inverse(4, 10), None);
+        let prime = 119_315_717_514_047;
+        let inverse = mod_inverse(987_654_321, prime).unwrap();
+        assert_eq!(mod_mul(inverse, 987_654_321, prime), 1);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_affine_composition() {
+        let double = Affine::new(2, 0, 11);
+        let add_three = Affine::new(1, 3, 11);
+        assert_eq!(double.then(&add_three).apply(5), 2);
+        assert_eq!(add_three.then(&double).apply(5), 5);
+        assert_eq!(Affine::new(-1, -1, 10), Affine::new(9, 9, 10));
+        assert_eq!(double.then(&Affine::identity(11)), double);
+    }
+
+    #[test]
+    fn test_affine_pow() {
+        let map = Affine::new(7, -3, 10_007);
+        let mut x = 2019;
+        for _ in 0..25 {
+            x = map.apply(x);
+        }
+        assert_eq!(map.pow(25).apply(2019), x);
+        assert_eq!(map.pow(0), Affine::identity(10_007));
+        let prime = 119_315_717_514_047;
+        let large = Affine::new(987_654_321, 123_456_789, prime);
+        assert_eq!(large.pow(prime as u64 - 1).a, 1);
+    }
+
+    #[test]
+    fn test_affine_inverse() {
+        let map = Affine::new(7, 4, 10);
+        let inverse = map.inverse().unwrap();
+        for x in 0..10 {
+            assert_eq!(inverse.apply(map.apply(x)), x);
+        }
+        assert_eq!(map.then(&inverse), Affine::identity(10));
+        assert_eq!(Affine::new(4, 1, 10).inverse(), None);
+    }
+}
@@ -0,0 +1,165 @@
+//! Caching the results of expensive, usually recursive, functions.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cache of computed values. With a capacity bound, the whole cache is
+/// dropped when it fills up, which keeps memory in check without having to
+/// track how recently each entry was used.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    capacity: Option<usize>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            capacity: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn with_capacity_bound(capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..Self::new()
+        }
+    }
+
+    /// Returns the cached value for `key`, computing it with `compute` on a
+    /// miss. `compute` gets the memo back, so it can recurse through it.
+    pub fn get_or_compute<F>(&mut self, key: K, compute: F) -> V
+    where
+        K: Clone,
+        F: FnOnce(&mut Self, &K) -> V,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+        self.misses += 1;
+        let value = compute(self, &key);
+        if self
+            .capacity
+            .is_some_and(|capacity| self.cache.len() >= capacity)
+        {
+            self.cache.clear();
+        }
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// How many lookups were answered from the cache, and how many had to be
+    /// computed.
+    pub fn stats(&self) -> (usize, usize) {
+        (self.hits, self.misses)
+    }
+}
+
+fn call<K, V, F>(memo: &mut Memo<K, V>, f: &F, key: K) -> V
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, &K) -> V,
+{
+    memo.get_or_compute(key, |memo, key| {
+        f(&mut |inner: K| call(memo, f, inner), key)
+    })
+}
+
+/// Wraps a recursive function so every result is cached. The function gets
+/// a callback to use for its recursive calls instead of calling itself.
+///
+/// ```
+/// use aoc_utils::memo::memoize;
+///
+/// let mut fibonacci = memoize(|fibonacci, n: &u64| {
+///     if *n < 2 {
+///         *n
+///     } else {
+///         fibonacci(n - 1) + fibonacci(n - 2)
+///     }
+/// });
+/// assert_eq!(fibonacci(90), 2_880_067_194_370_816_120);
+/// ```
+pub fn memoize<K, V, F>(f: F) -> impl FnMut(K) -> V
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+    F: Fn(&mut dyn FnMut(K) -> V, &K) -> V,
+{
+    let mut memo = Memo::new();
+    move |key| call(&mut memo, &f, key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{memoize, Memo};
+    use std::cell::Cell;
+
+    fn paths(memo: &mut Memo<(u32, u32), u64>, x: u32, y: u32) -> u64 {
+        memo.get_or_compute((x, y), |memo, &(x, y)| {
+            if x == 0 || y == 0 {
+                1
+            } else {
+                paths(memo, x - 1, y) + paths(memo, x, y - 1)
+            }
+        })
+    }
+
+    #[test]
+    fn test_memo_recursion() {
+        let mut memo = Memo::new();
+        assert_eq!(paths(&mut memo, 16, 16), 601_080_390);
+        // Every point of the grid is computed once, except for the origin
+        // which is never reached
+        assert_eq!(memo.len(), 17 * 17 - 1);
+        let (hits, misses) = memo.stats();
+        assert_eq!(misses, 17 * 17 - 1);
+        assert!(hits > 0);
+    }
+
+    #[test]
+    fn test_capacity_bound() {
+        let mut memo = Memo::with_capacity_bound(10);
+        assert_eq!(paths(&mut memo, 16, 16), 601_080_390);
+        assert!(memo.len() <= 10);
+    }
+
+    #[test]
+    fn test_memoize() {
+        let calls = Cell::new(0);
+        let mut collatz = memoize(|collatz, n: &u64| {
+            calls.set(calls.get() + 1);
+            match *n {
+                1 => 0,
+                n if n % 2 == 0 => 1 + collatz(n / 2),
+                n => 1 + collatz(3 * n + 1),
+            }
+        });
+        assert_eq!(collatz(27), 111);
+        let first = calls.get();
+        assert_eq!(collatz(27), 111);
+        assert_eq!(collatz(54), 112);
+        assert_eq!(calls.get(), first + 1);
+    }
+}
@@ -0,0 +1,119 @@
+//! Reads the block letters some puzzles draw as their answer. The letters
+//! are six pixels tall, four wide (five for `Y`), with at least one empty
+//! column between them.
+
+use crate::grid::Grid;
+
+const GLYPHS: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', "###\n.#.\n.#.\n.#.\n.#.\n###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const HEIGHT: usize = 6;
+
+/// Character used for glyphs that don't match any known letter.
+pub const UNKNOWN: char = '?';
+
+fn column(grid: &Grid<bool>, x: usize) -> Vec<bool> {
+    grid.column(x).copied().collect()
+}
+
+fn glyph_columns(glyph: &str) -> Vec<Vec<bool>> {
+    let rows: Vec<&[u8]> = glyph.lines().map(str::as_bytes).collect();
+    (0..rows[0].len())
+        .map(|x| rows.iter().map(|row| row[x] == b'#').collect())
+        .collect()
+}
+
+/// Reads the letters drawn on a grid six pixels tall. Empty columns
+/// separate letters, and anything that isn't a known letter comes out as
+/// [`UNKNOWN`]. Grids of any other height can't hold letters and give an
+/// empty string.
+pub fn recognize(grid: &Grid<bool>) -> String {
+    if grid.height() != HEIGHT {
+        return String::new();
+    }
+
+    let mut text = String::new();
+    let mut letter: Vec<Vec<bool>> = Vec::new();
+    for x in 0..=grid.width() {
+        let column = if x < grid.width() {
+            column(grid, x)
+        } else {
+            vec![false; HEIGHT]
+        };
+        if column.iter().any(|pixel| *pixel) {
+            letter.push(column);
+        } else if !letter.is_empty() {
+            let found = GLYPHS
+                .iter()
+                .find(|(_, glyph)| glyph_columns(glyph) == letter)
+                .map_or(UNKNOWN, |(c, _)| *c);
+            text.push(found);
+            letter.clear();
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::{recognize, GLYPHS};
+    use crate::grid::Grid;
+
+    fn parse(text: &str) -> Grid<bool> {
+        Grid::parse(text, |c| match c {
+            '#' => Ok(true),
+            '.' | ' ' => Ok(false),
+            _ => Err("Invalid pixel"),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_recognize() {
+        let image = "\
+.####.###..####.#..#.###..
+.#....#..#....#.#..#.#..#.
+.###..#..#...#..####.###..
+.#....###...#...#..#.#..#.
+.#....#.#..#....#..#.#..#.
+.#....#..#.####.#..#.###..";
+        assert_eq!(recognize(&parse(image)), "FRZHB");
+    }
+
+    #[test]
+    fn test_every_glyph() {
+        for (letter, glyph) in GLYPHS.iter() {
+            let image = glyph.replace('\n', "..\n") + "..";
+            assert_eq!(recognize(&parse(&image)), letter.to_string());
+        }
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        let image = "#.#\n.#.\n#.#\n.#.\n#.#\n.#.";
+        assert_eq!(recognize(&parse(image)), "?");
+    }
+
+    #[test]
+    fn test_wrong_height() {
+        assert_eq!(recognize(&parse("#..#\n####")), "");
+    }
+}
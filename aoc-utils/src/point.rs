@@ -0,0 +1,262 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A point, or vector, on an integer grid. Rotations assume the y axis
+/// points up; for screen coordinates where y grows downwards, left and right
+/// are swapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point2 {
+    pub const ORIGIN: Point2 = Point2 { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// Manhattan distance from the origin
+    pub fn manhattan(self) -> u64 {
+        self.x.unsigned_abs() + self.y.unsigned_abs()
+    }
+
+    pub fn manhattan_distance(self, other: Point2) -> u64 {
+        (self - other).manhattan()
+    }
+
+    /// Rotates 90 degrees counterclockwise around the origin
+    pub fn rotate_left(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Rotates 90 degrees clockwise around the origin
+    pub fn rotate_right(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    /// The four orthogonally adjacent points
+    pub fn neighbours(self) -> [Point2; 4] {
+        [
+            Self::new(self.x, self.y + 1),
+            Self::new(self.x + 1, self.y),
+            Self::new(self.x, self.y - 1),
+            Self::new(self.x - 1, self.y),
+        ]
+    }
+}
+
+impl fmt::Display for Point2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl From<(i64, i64)> for Point2 {
+    fn from((x, y): (i64, i64)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl Add for Point2 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point2 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point2 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point2 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Point2 {
+    type Output = Self;
+
+    fn mul(self, factor: i64) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Point2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+/// A point, or vector, in integer 3D space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Point3 {
+    pub const ORIGIN: Point3 = Point3 { x: 0, y: 0, z: 0 };
+
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Manhattan distance from the origin, which is also the energy of a
+    /// position or velocity in the N-body puzzles
+    pub fn manhattan(self) -> u64 {
+        self.x.unsigned_abs() + self.y.unsigned_abs() + self.z.unsigned_abs()
+    }
+
+    pub fn manhattan_distance(self, other: Point3) -> u64 {
+        (self - other).manhattan()
+    }
+
+    /// The sign of every component, as -1, 0 or 1
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum(), self.z.signum())
+    }
+
+    /// The unit step on every axis that moves this point towards `other`,
+    /// as gravity pulls moons together
+    pub fn pull_towards(self, other: Point3) -> Self {
+        (other - self).signum()
+    }
+
+    /// The six orthogonally adjacent points
+    pub fn neighbours(self) -> [Point3; 6] {
+        [
+            Self::new(self.x + 1, self.y, self.z),
+            Self::new(self.x - 1, self.y, self.z),
+            Self::new(self.x, self.y + 1, self.z),
+            Self::new(self.x, self.y - 1, self.z),
+            Self::new(self.x, self.y, self.z + 1),
+            Self::new(self.x, self.y, self.z - 1),
+        ]
+    }
+}
+
+impl fmt::Display for Point3 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+impl From<(i64, i64, i64)> for Point3 {
+    fn from((x, y, z): (i64, i64, i64)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl Add for Point3 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl AddAssign for Point3 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl SubAssign for Point3 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Point3 {
+    type Output = Self;
+
+    fn mul(self, factor: i64) -> Self {
+        Self::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Neg for Point3 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Point2, Point3};
+
+    #[test]
+    fn test_arithmetic() {
+        let mut point = Point2::new(1, 2) + Point2::new(3, -4);
+        assert_eq!(point, Point2::new(4, -2));
+        point -= Point2::new(1, 1);
+        assert_eq!(point, Point2::new(3, -3));
+        assert_eq!(point * 2, Point2::new(6, -6));
+        assert_eq!(-point, Point2::new(-3, 3));
+    }
+
+    #[test]
+    fn test_manhattan() {
+        assert_eq!(Point2::new(3, -3).manhattan(), 6);
+        assert_eq!(Point2::new(1, 1).manhattan_distance(Point2::new(-2, 5)), 7);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let up = Point2::new(0, 1);
+        assert_eq!(up.rotate_left(), Point2::new(-1, 0));
+        assert_eq!(up.rotate_right(), Point2::new(1, 0));
+        assert_eq!(
+            up.rotate_left().rotate_left().rotate_left(),
+            up.rotate_right()
+        );
+    }
+
+    #[test]
+    fn test_point3_arithmetic() {
+        let mut point = Point3::new(1, 2, 3) + Point3::new(3, -4, 0);
+        assert_eq!(point, Point3::new(4, -2, 3));
+        point -= Point3::new(1, 1, 1);
+        assert_eq!(point, Point3::new(3, -3, 2));
+        assert_eq!(point * 2, Point3::new(6, -6, 4));
+        assert_eq!(-point, Point3::new(-3, 3, -2));
+        assert_eq!(point.manhattan(), 8);
+    }
+
+    #[test]
+    fn test_point3_gravity() {
+        // Two of the moons from the first day 12 example
+        let io = Point3::new(-1, 0, 2);
+        let europa = Point3::new(2, -10, -7);
+        assert_eq!(io.pull_towards(europa), Point3::new(1, -1, -1));
+        assert_eq!(europa.pull_towards(io), Point3::new(-1, 1, 1));
+        assert_eq!(io.pull_towards(io), Point3::ORIGIN);
+    }
+}
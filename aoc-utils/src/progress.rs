@@ -0,0 +1,149 @@
+//! A progress bar on stderr for searches that take long enough to look like
+//! they hung.
+//!
+//! Bars are only drawn once enabled, either with [`set_enabled`] or by
+//! setting `AOC_PROGRESS`, and only when stderr is a terminal, so they never
+//! end up in the answers or in piped output.
+
+use std::env;
+use std::io::{stderr, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const BAR_WIDTH: usize = 30;
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed) || env::var_os("AOC_PROGRESS").is_some()
+}
+
+/// A bar `width` characters wide, filled in proportion to `done / total`.
+pub fn bar(done: u64, total: u64, width: usize) -> String {
+    let filled = if total == 0 {
+        width
+    } else {
+        (done.min(total) as u128 * width as u128 / total as u128) as usize
+    };
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+/// Progress through a known amount of work, or a plain counter when the
+/// total isn't known. The line is cleared when the progress is dropped.
+pub struct Progress {
+    label: String,
+    total: Option<u64>,
+    done: u64,
+    start: Instant,
+    last_draw: Option<Instant>,
+    visible: bool,
+}
+
+impl Progress {
+    fn with_total(label: &str, total: Option<u64>) -> Self {
+        Self {
+            label: label.to_string(),
+            total,
+            done: 0,
+            start: Instant::now(),
+            last_draw: None,
+            visible: enabled() && stderr().is_terminal(),
+        }
+    }
+
+    pub fn new(label: &str, total: u64) -> Self {
+        Self::with_total(label, Some(total))
+    }
+
+    /// Progress with no known end, shown as a count.
+    pub fn unbounded(label: &str) -> Self {
+        Self::with_total(label, None)
+    }
+
+    pub fn done(&self) -> u64 {
+        self.done
+    }
+
+    pub fn inc(&mut self, amount: u64) {
+        self.set(self.done + amount);
+    }
+
+    pub fn set(&mut self, done: u64) {
+        self.done = done;
+        if !self.visible {
+            return;
+        }
+        let now = Instant::now();
+        if self
+            .last_draw
+            .is_some_and(|last| now.duration_since(last) < REDRAW_INTERVAL)
+        {
+            return;
+        }
+        self.last_draw = Some(now);
+        self.draw();
+    }
+
+    /// The line as it's drawn, without the elapsed time.
+    fn line(&self) -> String {
+        match self.total {
+            Some(total) => format!(
+                "{} {} {}/{}",
+                self.label,
+                bar(self.done, total, BAR_WIDTH),
+                self.done,
+                total
+            ),
+            None => format!("{} {}", self.label, self.done),
+        }
+    }
+
+    fn draw(&self) {
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let mut out = stderr().lock();
+        // \x1b[K clears what's left of a longer previous line
+        let _ = write!(out, "\r{} {:.1}s\x1b[K", self.line(), elapsed);
+        let _ = out.flush();
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if self.visible && self.last_draw.is_some() {
+            let _ = write!(stderr(), "\r\x1b[K");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bar, Progress};
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(0, 10, 5), "[-----]");
+        assert_eq!(bar(5, 10, 4), "[##--]");
+        assert_eq!(bar(12, 10, 4), "[####]");
+        assert_eq!(bar(0, 0, 3), "[###]");
+    }
+
+    #[test]
+    fn test_progress_line() {
+        let mut progress = Progress::new("Scanning", 200);
+        progress.inc(50);
+        progress.inc(50);
+        assert_eq!(progress.done(), 100);
+        assert_eq!(
+            progress.line(),
+            format!("Scanning {} 100/200", bar(1, 2, 30))
+        );
+        let mut progress = Progress::unbounded("Tried");
+        progress.set(7);
+        assert_eq!(progress.line(), "Tried 7");
+    }
+}
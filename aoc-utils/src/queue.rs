@@ -0,0 +1,165 @@
+//! A min-priority queue that doesn't need its states to be ordered, and
+//! pops ties in a predictable order.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+struct Entry<C, S> {
+    cost: C,
+    tiebreak: i64,
+    order: u64,
+    state: S,
+}
+
+// Only the cost, tiebreak and insertion order take part in the ordering,
+// reversed so the standard max-heap pops the cheapest entry
+impl<C: Ord, S> Ord for Entry<C, S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&other.cost, other.tiebreak, other.order).cmp(&(&self.cost, self.tiebreak, self.order))
+    }
+}
+
+impl<C: Ord, S> PartialOrd for Entry<C, S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<C: Ord, S> PartialEq for Entry<C, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<C: Ord, S> Eq for Entry<C, S> {}
+
+/// Pops states by lowest cost, then lowest tiebreak, then in the order they
+/// were pushed.
+///
+/// There is no decrease-key: when a cheaper way to reach a state is found,
+/// push it again and skip the outdated entries when they come out with
+/// [`MinQueue::pop_fresh`]:
+///
+/// ```
+/// use aoc_utils::queue::MinQueue;
+/// use std::collections::HashMap;
+///
+/// let edges = [(0, 1, 4), (0, 2, 1), (2, 1, 2)];
+/// let mut best = HashMap::new();
+/// let mut queue = MinQueue::new();
+/// best.insert(0, 0);
+/// queue.push(0, 0);
+/// while let Some((cost, node)) = queue.pop_fresh(|cost, node| best[node] < *cost) {
+///     for &(_, next, step) in edges.iter().filter(|(from, _, _)| *from == node) {
+///         if best.get(&next).map_or(true, |best| cost + step < *best) {
+///             best.insert(next, cost + step);
+///             queue.push(cost + step, next);
+///         }
+///     }
+/// }
+/// assert_eq!(best[&1], 3);
+/// ```
+pub struct MinQueue<C, S> {
+    heap: BinaryHeap<Entry<C, S>>,
+    pushed: u64,
+}
+
+impl<C: Ord, S> Default for MinQueue<C, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Ord, S> MinQueue<C, S> {
+    pub fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            pushed: 0,
+        }
+    }
+
+    pub fn push(&mut self, cost: C, state: S) {
+        self.push_with_tiebreak(cost, 0, state);
+    }
+
+    /// Pushes a state that comes out before others of the same cost with a
+    /// higher tiebreak, like the one closer to the goal.
+    pub fn push_with_tiebreak(&mut self, cost: C, tiebreak: i64, state: S) {
+        self.heap.push(Entry {
+            cost,
+            tiebreak,
+            order: self.pushed,
+            state,
+        });
+        self.pushed += 1;
+    }
+
+    pub fn pop(&mut self) -> Option<(C, S)> {
+        self.heap.pop().map(|entry| (entry.cost, entry.state))
+    }
+
+    /// Pops the cheapest entry that isn't stale, dropping any stale ones on
+    /// the way.
+    pub fn pop_fresh<F>(&mut self, mut is_stale: F) -> Option<(C, S)>
+    where
+        F: FnMut(&C, &S) -> bool,
+    {
+        while let Some((cost, state)) = self.pop() {
+            if !is_stale(&cost, &state) {
+                return Some((cost, state));
+            }
+        }
+        None
+    }
+
+    pub fn peek_cost(&self) -> Option<&C> {
+        self.heap.peek().map(|entry| &entry.cost)
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MinQueue;
+
+    #[test]
+    fn test_pops_cheapest_first() {
+        let mut queue = MinQueue::new();
+        queue.push(5, "five");
+        queue.push(1, "one");
+        queue.push(3, "three");
+        assert_eq!(queue.peek_cost(), Some(&1));
+        assert_eq!(queue.pop(), Some((1, "one")));
+        assert_eq!(queue.pop(), Some((3, "three")));
+        assert_eq!(queue.pop(), Some((5, "five")));
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    fn test_ties_are_stable() {
+        let mut queue = MinQueue::new();
+        for state in ["a", "b", "c", "d"].iter() {
+            queue.push(1, *state);
+        }
+        queue.push_with_tiebreak(1, -1, "first");
+        let order: Vec<_> = std::iter::from_fn(|| queue.pop()).map(|(_, s)| s).collect();
+        assert_eq!(order, ["first", "a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_pop_fresh() {
+        let mut queue = MinQueue::new();
+        queue.push(1, 10);
+        queue.push(2, 20);
+        queue.push(3, 30);
+        assert_eq!(queue.pop_fresh(|_, state| *state < 25), Some((3, 30)));
+        assert!(queue.is_empty());
+    }
+}
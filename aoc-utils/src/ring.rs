@@ -0,0 +1,297 @@
+//! Circular containers: a fixed size buffer that keeps the most recent
+//! values, and a circular doubly linked list addressed by index.
+
+use std::ops::{Index, IndexMut};
+
+/// Keeps the last `capacity` values pushed into it, dropping the oldest.
+#[derive(Debug, Clone)]
+pub struct RingBuffer<T> {
+    values: Vec<T>,
+    capacity: usize,
+    // Position of the oldest value once the buffer is full
+    start: usize,
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            capacity,
+            start: 0,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.values.len() == self.capacity
+    }
+
+    /// Adds a value, returning the oldest one if it had to make room.
+    pub fn push(&mut self, value: T) -> Option<T> {
+        if self.capacity == 0 {
+            return Some(value);
+        }
+        if !self.is_full() {
+            self.values.push(value);
+            return None;
+        }
+        let old = std::mem::replace(&mut self.values[self.start], value);
+        self.start = (self.start + 1) % self.capacity;
+        Some(old)
+    }
+
+    /// The value `index` positions after the oldest one.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index < self.values.len() {
+            Some(&self.values[(self.start + index) % self.values.len()])
+        } else {
+            None
+        }
+    }
+
+    pub fn oldest(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn newest(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.start = 0;
+    }
+
+    /// Iterates from the oldest to the newest value.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> {
+        let (newer, older) = self.values.split_at(self.start);
+        older.iter().chain(newer.iter())
+    }
+}
+
+struct Node<T> {
+    value: T,
+    previous: usize,
+    next: usize,
+}
+
+/// A circular doubly linked list. Every value is addressed by the index it
+/// got when inserted, which stays valid until that value is removed, so
+/// moving around and splicing are constant time. Removed slots are reused.
+pub struct CircularList<T> {
+    nodes: Vec<Option<Node<T>>>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<T> Default for CircularList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> CircularList<T> {
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn node(&self, index: usize) -> &Node<T> {
+        self.nodes[index].as_ref().expect("Index was removed")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        self.nodes[index].as_mut().expect("Index was removed")
+    }
+
+    fn allocate(&mut self, node: Node<T>) -> usize {
+        self.len += 1;
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Adds a value to an empty list, where it is its own neighbour.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list isn't empty.
+    pub fn push_first(&mut self, value: T) -> usize {
+        assert!(self.is_empty(), "List already has values");
+        let index = self.free.last().copied().unwrap_or(self.nodes.len());
+        self.allocate(Node {
+            value,
+            previous: index,
+            next: index,
+        })
+    }
+
+    /// Inserts a value right after the one at `index`, returning its index.
+    pub fn insert_after(&mut self, index: usize, value: T) -> usize {
+        let next = self.node(index).next;
+        let new = self.allocate(Node {
+            value,
+            previous: index,
+            next,
+        });
+        self.node_mut(index).next = new;
+        self.node_mut(next).previous = new;
+        new
+    }
+
+    /// Inserts a value right before the one at `index`, returning its index.
+    pub fn insert_before(&mut self, index: usize, value: T) -> usize {
+        let previous = self.node(index).previous;
+        self.insert_after(previous, value)
+    }
+
+    /// Removes the value at `index`, returning it together with the index
+    /// of the value that followed it, if any are left.
+    pub fn remove(&mut self, index: usize) -> (T, Option<usize>) {
+        let node = self.nodes[index].take().expect("Index was removed");
+        self.free.push(index);
+        self.len -= 1;
+        if self.len == 0 {
+            return (node.value, None);
+        }
+        self.node_mut(node.previous).next = node.next;
+        self.node_mut(node.next).previous = node.previous;
+        (node.value, Some(node.next))
+    }
+
+    pub fn next(&self, index: usize) -> usize {
+        self.node(index).next
+    }
+
+    pub fn previous(&self, index: usize) -> usize {
+        self.node(index).previous
+    }
+
+    /// Moves `steps` values forwards, or backwards for negative steps.
+    pub fn step(&self, mut index: usize, steps: isize) -> usize {
+        let steps = steps % self.len as isize;
+        for _ in 0..steps.abs() {
+            index = if steps > 0 {
+                self.next(index)
+            } else {
+                self.previous(index)
+            };
+        }
+        index
+    }
+
+    /// Iterates once around the list, starting at `index`.
+    pub fn iter_from(&self, index: usize) -> impl Iterator<Item = &T> {
+        let mut current = index;
+        (0..self.len).map(move |_| {
+            let node = self.node(current);
+            current = node.next;
+            &node.value
+        })
+    }
+}
+
+impl<T> Index<usize> for CircularList<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        &self.node(index).value
+    }
+}
+
+impl<T> IndexMut<usize> for CircularList<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.node_mut(index).value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CircularList, RingBuffer};
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut buffer = RingBuffer::new(3);
+        assert_eq!(buffer.push(1), None);
+        assert_eq!(buffer.push(2), None);
+        assert_eq!(buffer.push(3), None);
+        assert_eq!(buffer.push(4), Some(1));
+        assert_eq!(buffer.push(5), Some(2));
+        assert_eq!(buffer.iter().copied().collect::<Vec<_>>(), [3, 4, 5]);
+        assert_eq!(buffer.iter().rev().copied().collect::<Vec<_>>(), [5, 4, 3]);
+        assert_eq!(buffer.oldest(), Some(&3));
+        assert_eq!(buffer.newest(), Some(&5));
+        assert_eq!(buffer.get(3), None);
+    }
+
+    #[test]
+    fn test_ring_buffer_without_capacity() {
+        let mut buffer = RingBuffer::new(0);
+        assert_eq!(buffer.push(1), Some(1));
+        assert!(buffer.is_empty());
+        assert_eq!(buffer.newest(), None);
+    }
+
+    #[test]
+    fn test_circular_list() {
+        let mut list = CircularList::new();
+        let zero = list.push_first(0);
+        let two = list.insert_after(zero, 2);
+        let one = list.insert_before(two, 1);
+        list.insert_after(two, 3);
+        assert_eq!(
+            list.iter_from(zero).copied().collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(list[list.step(zero, -1)], 3);
+        assert_eq!(list[list.step(zero, 6)], 2);
+
+        let (value, next) = list.remove(one);
+        assert_eq!((value, next), (1, Some(two)));
+        list[two] = 20;
+        assert_eq!(list.iter_from(two).copied().collect::<Vec<_>>(), [20, 3, 0]);
+
+        // The freed slot gets reused
+        assert_eq!(list.insert_after(zero, 10), one);
+        assert_eq!(list.len(), 4);
+    }
+
+    #[test]
+    fn test_circular_list_remove_last() {
+        let mut list = CircularList::new();
+        let only = list.push_first("a");
+        assert_eq!(list.remove(only), ("a", None));
+        assert!(list.is_empty());
+        let again = list.push_first("b");
+        assert_eq!(list.next(again), again);
+    }
+}
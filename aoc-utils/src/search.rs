@@ -0,0 +1,248 @@
+//! Graph searches over implicit graphs. Nodes are any hashable value, and the
+//! graph is described by a closure returning the successors of a node.
+
+use crate::queue::MinQueue;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+fn reconstruct_path<N>(parents: &HashMap<N, N>, end: N) -> Vec<N>
+where
+    N: Eq + Hash + Clone,
+{
+    let mut path = vec![end];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Breadth first search from `start` until a node satisfying `goal` is
+/// found. Returns the number of steps and the path, including both ends.
+pub fn bfs<N, FN, IN, FG>(start: N, mut successors: FN, mut goal: FG) -> Option<(usize, Vec<N>)>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut parents: HashMap<N, N> = HashMap::new();
+    let mut visited: HashMap<N, usize> = HashMap::new();
+    let mut queue = VecDeque::new();
+    visited.insert(start.clone(), 0);
+    queue.push_back((start, 0));
+
+    while let Some((node, distance)) = queue.pop_front() {
+        if goal(&node) {
+            return Some((distance, reconstruct_path(&parents, node)));
+        }
+        for next in successors(&node) {
+            if !visited.contains_key(&next) {
+                visited.insert(next.clone(), distance + 1);
+                parents.insert(next.clone(), node.clone());
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    None
+}
+
+/// Breadth first search over everything reachable from `start`, returning
+/// the distance to every node.
+pub fn bfs_distances<N, FN, IN>(start: N, mut successors: FN) -> HashMap<N, usize>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    distances.insert(start.clone(), 0);
+    queue.push_back((start, 0));
+
+    while let Some((node, distance)) = queue.pop_front() {
+        for next in successors(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+struct Search<N, C> {
+    best: HashMap<N, C>,
+    parents: HashMap<N, N>,
+    found: Option<(N, C)>,
+}
+
+// Shared core of Dijkstra and A*. Stops at the first goal node settled, or
+// explores everything reachable when there is none.
+fn best_first<N, C, FN, IN, FH, FG>(
+    start: N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut goal: FG,
+) -> Search<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FG: FnMut(&N) -> bool,
+{
+    let mut search = Search {
+        best: HashMap::new(),
+        parents: HashMap::new(),
+        found: None,
+    };
+    let mut queue = MinQueue::new();
+    search.best.insert(start.clone(), C::default());
+    queue.push(heuristic(&start), (start, C::default()));
+
+    // Nodes reached again at a lower cost are pushed again, so entries with
+    // a higher cost than the best known one are outdated
+    let outdated = |search: &Search<N, C>, (node, cost): &(N, C)| {
+        search.best.get(node).is_some_and(|best| best < cost)
+    };
+    while let Some((_, (node, cost))) = queue.pop_fresh(|_, entry| outdated(&search, entry)) {
+        if goal(&node) {
+            search.found = Some((node, cost));
+            return search;
+        }
+        for (next, step) in successors(&node) {
+            let next_cost = cost + step;
+            if search
+                .best
+                .get(&next)
+                .is_some_and(|best| *best <= next_cost)
+            {
+                continue;
+            }
+            search.best.insert(next.clone(), next_cost);
+            search.parents.insert(next.clone(), node.clone());
+            queue.push(next_cost + heuristic(&next), (next, next_cost));
+        }
+    }
+    search
+}
+
+/// A* search: Dijkstra guided by a heuristic that must never overestimate
+/// the remaining cost to a goal. Successors are returned along with the
+/// cost of moving to them. Returns the total cost and the path.
+pub fn astar<N, C, FN, IN, FH, FG>(
+    start: N,
+    successors: FN,
+    heuristic: FH,
+    goal: FG,
+) -> Option<(C, Vec<N>)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FG: FnMut(&N) -> bool,
+{
+    let search = best_first(start, successors, heuristic, goal);
+    let (end, cost) = search.found?;
+    Some((cost, reconstruct_path(&search.parents, end)))
+}
+
+/// Dijkstra's shortest path from `start` until a node satisfying `goal` is
+/// reached. Returns the total cost and the path.
+pub fn dijkstra<N, C, FN, IN, FG>(start: N, successors: FN, goal: FG) -> Option<(C, Vec<N>)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| C::default(), goal)
+}
+
+/// Dijkstra over everything reachable from `start`, returning the lowest
+/// cost to every node.
+pub fn dijkstra_distances<N, C, FN, IN>(start: N, successors: FN) -> HashMap<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+{
+    best_first(start, successors, |_| C::default(), |_| false).best
+}
+
+#[cfg(test)]
+mod test {
+    use super::{astar, bfs, bfs_distances, dijkstra, dijkstra_distances};
+    use crate::{Grid, Point2};
+
+    const MAZE: &str = "\
+S.#.....
+.##.###.
+....#E..
+.##.....";
+
+    fn maze() -> Grid<char> {
+        Grid::parse(MAZE, Ok::<char, &'static str>).unwrap()
+    }
+
+    fn open_neighbours(grid: &Grid<char>, point: Point2) -> Vec<Point2> {
+        grid.neighbours4(point)
+            .filter(|next| grid[*next] != '#')
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = maze();
+        let start = grid.find(|c| *c == 'S').unwrap();
+        let end = grid.find(|c| *c == 'E').unwrap();
+        let (distance, path) = bfs(start, |p| open_neighbours(&grid, *p), |p| *p == end).unwrap();
+        assert_eq!(distance, 9);
+        assert_eq!(path.len(), 10);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        assert!(path.windows(2).all(|w| w[0].manhattan_distance(w[1]) == 1));
+
+        let distances = bfs_distances(start, |p| open_neighbours(&grid, *p));
+        assert_eq!(distances[&end], 9);
+        assert_eq!(bfs(start, |p| open_neighbours(&grid, *p), |_| false), None);
+    }
+
+    #[test]
+    fn test_dijkstra_and_astar() {
+        // Walking costs one, except on the last row, which makes the
+        // shortest path in steps more expensive than going around
+        let grid = maze();
+        let start = grid.find(|c| *c == 'S').unwrap();
+        let end = grid.find(|c| *c == 'E').unwrap();
+        let successors = |p: &Point2| {
+            open_neighbours(&grid, *p)
+                .into_iter()
+                .map(|next| (next, if next.y == 3 { 10 } else { 1 }))
+                .collect::<Vec<_>>()
+        };
+        let (cost, path) = dijkstra(start, successors, |p| *p == end).unwrap();
+        assert_eq!(cost, 15);
+        assert!(path.iter().all(|p| p.y != 3));
+
+        let (cost, _) = astar(
+            start,
+            successors,
+            |p| p.manhattan_distance(end),
+            |p| *p == end,
+        )
+        .unwrap();
+        assert_eq!(cost, 15);
+
+        let distances = dijkstra_distances(start, successors);
+        assert_eq!(distances[&end], 15);
+        assert_eq!(distances[&start], 0);
+    }
+}
@@ -0,0 +1,305 @@
+//! The common interface every day implements, so the runner, tests and
+//! benchmarks can call the solutions without going through stdin.
+
+use std::env;
+use std::fmt;
+use std::io::stdin;
+use std::process;
+use std::time::{Duration, Instant};
+
+/// Both answers, along with how long each step took to compute them.
+#[derive(Debug, Clone)]
+pub struct Solved {
+    pub part1: String,
+    pub part2: String,
+    /// `None` for solutions that parse the input in each part.
+    pub parse_time: Option<Duration>,
+    pub part1_time: Duration,
+    pub part2_time: Duration,
+}
+
+impl Solved {
+    pub fn total_time(&self) -> Duration {
+        self.parse_time.unwrap_or_default() + self.part1_time + self.part2_time
+    }
+}
+
+impl fmt::Display for Solved {
+    /// The time breakdown, in microseconds.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(parse_time) = self.parse_time {
+            writeln!(f, "Parse  - {:>10}µs", parse_time.as_micros())?;
+        }
+        writeln!(f, "Part 1 - {:>10}µs", self.part1_time.as_micros())?;
+        writeln!(f, "Part 2 - {:>10}µs", self.part2_time.as_micros())?;
+        writeln!(f, "Total  - {:>10}µs", self.total_time().as_micros())
+    }
+}
+
+/// Runs a step of a solution, returning its result and how long it took.
+/// Built with the `tracing` feature, the step is also a span named after
+/// it.
+macro_rules! timed {
+    ($step:literal, $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($step).entered();
+        let start = Instant::now();
+        let value = $body;
+        (value, start.elapsed())
+    }};
+}
+
+/// The answers of a solution, or why the input couldn't be solved.
+pub trait Solution: Sync {
+    fn part1(&self, input: &str) -> Result<String, String>;
+    fn part2(&self, input: &str) -> Result<String, String>;
+
+    /// Solves both parts, timing each of them.
+    fn solve(&self, input: &str) -> Result<Solved, String> {
+        let (part1, part1_time) = timed!("part1", self.part1(input));
+        let (part2, part2_time) = timed!("part2", self.part2(input));
+        Ok(Solved {
+            part1: part1?,
+            part2: part2?,
+            parse_time: None,
+            part1_time,
+            part2_time,
+        })
+    }
+}
+
+/// A solution that parses its input once for both parts. Every puzzle is a
+/// [`Solution`], with the parsing timed on its own.
+pub trait Puzzle: Sync {
+    type Input;
+
+    /// The input, or a description of what's wrong with it.
+    fn parse(&self, input: &str) -> Result<Self::Input, String>;
+    /// The answer, or why the parsed input has none.
+    fn part1(&self, input: &Self::Input) -> Result<String, String>;
+    fn part2(&self, input: &Self::Input) -> Result<String, String>;
+}
+
+impl<P: Puzzle> Solution for P {
+    fn part1(&self, input: &str) -> Result<String, String> {
+        Puzzle::part1(self, &self.parse(input)?)
+    }
+
+    fn part2(&self, input: &str) -> Result<String, String> {
+        Puzzle::part2(self, &self.parse(input)?)
+    }
+
+    fn solve(&self, input: &str) -> Result<Solved, String> {
+        let (parsed, parse_time) = timed!("parse", self.parse(input));
+        let parsed = parsed?;
+        let (part1, part1_time) = timed!("part1", Puzzle::part1(self, &parsed));
+        let (part2, part2_time) = timed!("part2", Puzzle::part2(self, &parsed));
+        Ok(Solved {
+            part1: part1?,
+            part2: part2?,
+            parse_time: Some(parse_time),
+            part1_time,
+            part2_time,
+        })
+    }
+}
+
+/// The year of the solutions registered without one.
+pub const DEFAULT_YEAR: u32 = 2019;
+
+/// A solution registered with [`register_solution!`], so the runner can
+/// find it by year and day without a list of every day.
+pub struct Registration {
+    pub year: u32,
+    pub day: u32,
+    pub solution: &'static dyn Solution,
+}
+
+inventory::collect!(Registration);
+
+/// Registers the solution for a day, of [`DEFAULT_YEAR`] unless a year is
+/// given first. The crate still has to be linked into the binary looking
+/// for it.
+///
+/// ```ignore
+/// pub struct Day1;
+///
+/// impl Puzzle for Day1 { ... }
+///
+/// aoc_utils::register_solution!(1, Day1);
+/// aoc_utils::register_solution!(2020, 1, Day1Of2020);
+/// ```
+#[macro_export]
+macro_rules! register_solution {
+    ($year:expr, $day:expr, $solution:expr) => {
+        $crate::inventory::submit! {
+            $crate::solution::Registration {
+                year: $year,
+                day: $day,
+                solution: &$solution,
+            }
+        }
+    };
+    ($day:expr, $solution:expr) => {
+        $crate::register_solution!($crate::solution::DEFAULT_YEAR, $day, $solution);
+    };
+}
+
+/// Turns the examples from a puzzle's description into tests. Each day keeps
+/// its examples as text files under `examples/` in its crate, and each test
+/// names the part to run on a file and the answer it should give.
+///
+/// ```ignore
+/// #[cfg(test)]
+/// mod test {
+///     use super::Day1;
+///
+///     aoc_utils::example_tests!(Day1, {
+///         part1_masses: part1("masses.txt") == "34241",
+///         part2_masses: part2("masses.txt") == "51316",
+///     });
+/// }
+/// ```
+#[macro_export]
+macro_rules! example_tests {
+    ($puzzle:expr, { $($name:ident: $part:ident($file:literal) == $expected:expr),* $(,)? }) => {
+        $(
+            #[test]
+            fn $name() {
+                let input = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/", $file));
+                assert_eq!(
+                    $crate::solution::Solution::$part(&$puzzle, input).as_deref(),
+                    Ok($expected)
+                );
+            }
+        )*
+    };
+}
+
+/// Every registered solution, ordered by year and day.
+pub fn registered() -> Vec<&'static Registration> {
+    let mut registrations: Vec<_> = inventory::iter::<Registration>.into_iter().collect();
+    registrations.sort_by_key(|registration| (registration.year, registration.day));
+    registrations
+}
+
+/// The registered solutions of a year, ordered by day.
+pub fn registered_in(year: u32) -> Vec<&'static Registration> {
+    let mut registrations = registered();
+    registrations.retain(|registration| registration.year == year);
+    registrations
+}
+
+/// The registered solution for a day of a year.
+pub fn find_in(year: u32, day: u32) -> Option<&'static dyn Solution> {
+    inventory::iter::<Registration>
+        .into_iter()
+        .find(|registration| registration.year == year && registration.day == day)
+        .map(|registration| registration.solution)
+}
+
+/// The registered solution for a day of [`DEFAULT_YEAR`].
+pub fn find(day: u32) -> Option<&'static dyn Solution> {
+    find_in(DEFAULT_YEAR, day)
+}
+
+/// The answer, or a note saying there is none.
+pub fn or_no_solution<T: ToString>(answer: Option<T>) -> String {
+    answer.map_or_else(
+        || "no solution found".to_string(),
+        |answer| answer.to_string(),
+    )
+}
+
+/// Reads the whole puzzle input from stdin, which can be compressed, see
+/// [`crate::input::read_text`].
+pub fn read_stdin() -> String {
+    crate::input::read_text(stdin().lock()).expect("Could not read the puzzle input")
+}
+
+/// Whether `--time` was passed on the command line.
+pub fn timing_requested() -> bool {
+    env::args().skip(1).any(|arg| arg == "--time")
+}
+
+/// Prints the answers, followed by the time taken when asked to.
+pub fn print_solved(solved: &Solved, time: bool) {
+    println!("Puzzle 1 - {}", solved.part1);
+    println!("Puzzle 2 - {}", solved.part2);
+    if time {
+        print!("{}", solved);
+    }
+}
+
+/// Solves both parts and prints the answers, exiting with an error when
+/// the input can't be solved.
+pub fn print_answers<S: Solution + ?Sized>(solution: &S, input: &str, time: bool) {
+    match solution.solve(input) {
+        Ok(solved) => print_solved(&solved, time),
+        Err(error) => {
+            eprintln!("Invalid puzzle input: {}", error);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find, find_in, registered_in, Puzzle, Solution, DEFAULT_YEAR};
+
+    struct Sum;
+
+    impl Puzzle for Sum {
+        type Input = Vec<u32>;
+
+        fn parse(&self, input: &str) -> Result<Vec<u32>, String> {
+            input
+                .split(',')
+                .map(|n| n.parse().map_err(|_| format!("Invalid number {}", n)))
+                .collect()
+        }
+
+        fn part1(&self, input: &Vec<u32>) -> Result<String, String> {
+            Ok(input.iter().sum::<u32>().to_string())
+        }
+
+        fn part2(&self, input: &Vec<u32>) -> Result<String, String> {
+            input
+                .iter()
+                .try_fold(1u32, |product, n| product.checked_mul(*n))
+                .map(|product| product.to_string())
+                .ok_or_else(|| "Product is too large".to_string())
+        }
+    }
+
+    #[test]
+    fn test_puzzle_solution() {
+        let solution: &dyn Solution = &Sum;
+        assert_eq!(solution.part1("2,3,4"), Ok("9".to_string()));
+        let solved = solution.solve("2,3,4").unwrap();
+        assert_eq!((solved.part1.as_str(), solved.part2.as_str()), ("9", "24"));
+        assert!(solved.parse_time.is_some());
+        let breakdown = solved.to_string();
+        assert_eq!(breakdown.lines().count(), 4);
+        assert!(breakdown.starts_with("Parse  - "));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let solution: &dyn Solution = &Sum;
+        assert_eq!(solution.part2("2,x"), Err("Invalid number x".to_string()));
+        assert!(solution.solve("").is_err());
+    }
+
+    crate::register_solution!(2020, 25, Sum);
+
+    #[test]
+    fn test_registered_years() {
+        assert!(
+            find_in(2020, 25).is_some_and(|solution| solution.part1("1,2").as_deref() == Ok("3"))
+        );
+        assert!(find(25).is_none());
+        assert!(registered_in(DEFAULT_YEAR).is_empty());
+        assert_eq!(registered_in(2020).len(), 1);
+    }
+}
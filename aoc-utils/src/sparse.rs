@@ -0,0 +1,184 @@
+use crate::{Grid, Point2};
+use std::collections::hash_map::{self, HashMap};
+
+/// An unbounded grid that only stores the cells that were set, for maps
+/// whose extents aren't known up front. Keeps track of the bounding box of
+/// every cell set so far.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point2, T>,
+    bounds: Option<(Point2, Point2)>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, point: Point2) -> Option<&T> {
+        self.cells.get(&point)
+    }
+
+    pub fn get_mut(&mut self, point: Point2) -> Option<&mut T> {
+        self.cells.get_mut(&point)
+    }
+
+    pub fn contains(&self, point: Point2) -> bool {
+        self.cells.contains_key(&point)
+    }
+
+    pub fn insert(&mut self, point: Point2, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            Some((min, max)) => (
+                Point2::new(min.x.min(point.x), min.y.min(point.y)),
+                Point2::new(max.x.max(point.x), max.y.max(point.y)),
+            ),
+            None => (point, point),
+        });
+        self.cells.insert(point, value)
+    }
+
+    pub fn remove(&mut self, point: Point2) -> Option<T> {
+        let value = self.cells.remove(&point)?;
+        let on_edge = self.bounds.is_some_and(|(min, max)| {
+            point.x == min.x || point.y == min.y || point.x == max.x || point.y == max.y
+        });
+        if on_edge {
+            self.bounds = None;
+            for point in self.cells.keys() {
+                let (min, max) = self.bounds.get_or_insert((*point, *point));
+                *min = Point2::new(min.x.min(point.x), min.y.min(point.y));
+                *max = Point2::new(max.x.max(point.x), max.y.max(point.y));
+            }
+        }
+        Some(value)
+    }
+
+    pub fn entry(&mut self, point: Point2) -> hash_map::Entry<'_, Point2, T>
+    where
+        T: Default,
+    {
+        if !self.contains(point) {
+            self.insert(point, T::default());
+        }
+        self.cells.entry(point)
+    }
+
+    /// The smallest and largest coordinates of any cell, or `None` when the
+    /// grid is empty
+    pub fn bounds(&self) -> Option<(Point2, Point2)> {
+        self.bounds
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, Point2, T> {
+        self.cells.iter()
+    }
+
+    /// Draws the bounding box of the grid, one line per row with `y` growing
+    /// downwards. Cells that were never set are passed as `None`.
+    pub fn render<F>(&self, mut draw: F) -> String
+    where
+        F: FnMut(Option<&T>) -> char,
+    {
+        let (min, max) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+        let mut text = String::new();
+        for y in min.y..=max.y {
+            text.extend((min.x..=max.x).map(|x| draw(self.get(Point2::new(x, y)))));
+            text.push('\n');
+        }
+        text
+    }
+
+    /// Copies the bounding box into a dense grid, filling the missing cells.
+    /// Returns the grid together with the point that became its origin.
+    pub fn to_dense(&self, fill: T) -> (Grid<T>, Point2)
+    where
+        T: Clone,
+    {
+        let (min, max) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return (Grid::new(0, 0, fill), Point2::ORIGIN),
+        };
+        let size = max - min;
+        let mut grid = Grid::new(size.x as usize + 1, size.y as usize + 1, fill);
+        for (point, value) in self.cells.iter() {
+            grid[*point - min] = value.clone();
+        }
+        (grid, min)
+    }
+}
+
+impl<T> std::iter::FromIterator<(Point2, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point2, T)>>(iter: I) -> Self {
+        let mut grid = SparseGrid::new();
+        for (point, value) in iter {
+            grid.insert(point, value);
+        }
+        grid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SparseGrid;
+    use crate::Point2;
+
+    #[test]
+    fn test_bounds() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(grid.bounds(), None);
+        grid.insert(Point2::new(1, 1), 'a');
+        grid.insert(Point2::new(-2, 3), 'b');
+        assert_eq!(grid.bounds(), Some((Point2::new(-2, 1), Point2::new(1, 3))));
+        grid.remove(Point2::new(-2, 3));
+        assert_eq!(grid.bounds(), Some((Point2::new(1, 1), Point2::new(1, 1))));
+    }
+
+    #[test]
+    fn test_render() {
+        let grid: SparseGrid<bool> = vec![
+            (Point2::new(0, 0), true),
+            (Point2::new(2, 1), true),
+            (Point2::new(1, 1), false),
+        ]
+        .into_iter()
+        .collect();
+        let text = grid.render(|cell| match cell {
+            Some(true) => '#',
+            Some(false) => '.',
+            None => ' ',
+        });
+        assert_eq!(text, "#  \n .#\n");
+    }
+
+    #[test]
+    fn test_to_dense() {
+        let mut grid = SparseGrid::new();
+        grid.insert(Point2::new(-1, -1), 1);
+        grid.insert(Point2::new(1, 0), 2);
+        *grid.entry(Point2::new(0, 0)).or_default() += 3;
+        let (dense, origin) = grid.to_dense(0);
+        assert_eq!(origin, Point2::new(-1, -1));
+        assert_eq!(dense.to_string(), "100\n032\n");
+    }
+}
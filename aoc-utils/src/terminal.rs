@@ -0,0 +1,209 @@
+//! Drawing animations in a terminal with ANSI escape codes. Only the cells
+//! that changed since the last frame are written, which keeps big grids from
+//! flickering.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const HIDE_CURSOR: &str = "\x1b[?25l";
+const SHOW_CURSOR: &str = "\x1b[?25h";
+const CLEAR: &str = "\x1b[2J";
+const HOME: &str = "\x1b[H";
+
+/// Moves the cursor to a zero based row and column.
+fn move_to<W: Write>(out: &mut W, row: usize, column: usize) -> io::Result<()> {
+    write!(out, "\x1b[{};{}H", row + 1, column + 1)
+}
+
+/// Somewhere frames of text can be shown, like a terminal or a web page.
+pub trait FrameSink {
+    fn draw(&mut self, frame: &str) -> io::Result<()>;
+
+    /// Told the instructions an intcode machine ran for the frames, and the
+    /// values it read and printed, for sinks that keep count.
+    fn machine_ran(&mut self, _instructions: u64, _inputs: u64, _outputs: u64) {}
+}
+
+/// A terminal screen that draws frames of text. The cursor is hidden while
+/// drawing, and shown again below the last frame when dropped.
+pub struct Terminal<W: Write> {
+    out: W,
+    previous: Vec<Vec<char>>,
+}
+
+impl Terminal<io::Stdout> {
+    pub fn stdout() -> io::Result<Self> {
+        Self::new(io::stdout())
+    }
+}
+
+impl<W: Write> Terminal<W> {
+    /// Clears the screen and hides the cursor.
+    pub fn new(mut out: W) -> io::Result<Self> {
+        write!(out, "{}{}{}", HIDE_CURSOR, CLEAR, HOME)?;
+        out.flush()?;
+        Ok(Self {
+            out,
+            previous: Vec::new(),
+        })
+    }
+
+    /// Draws a frame, one line of text per row, rewriting only the cells
+    /// that differ from the previous frame.
+    pub fn draw(&mut self, frame: &str) -> io::Result<()> {
+        let frame: Vec<Vec<char>> = frame.lines().map(|line| line.chars().collect()).collect();
+        let rows = frame.len().max(self.previous.len());
+        for row in 0..rows {
+            let new = frame.get(row).map_or(&[][..], Vec::as_slice);
+            let old = self.previous.get(row).map_or(&[][..], Vec::as_slice);
+            let mut cursor = None;
+            for column in 0..new.len().max(old.len()) {
+                // Cells the new frame doesn't cover get blanked out
+                let cell = new.get(column).copied().unwrap_or(' ');
+                if old.get(column).copied().unwrap_or(' ') == cell {
+                    continue;
+                }
+                if cursor != Some(column) {
+                    move_to(&mut self.out, row, column)?;
+                }
+                write!(self.out, "{}", cell)?;
+                cursor = Some(column + 1);
+            }
+        }
+        self.previous = frame;
+        self.out.flush()
+    }
+
+    /// Forgets the last frame so the next one is drawn from scratch.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.previous.clear();
+        write!(self.out, "{}{}", CLEAR, HOME)?;
+        self.out.flush()
+    }
+}
+
+impl<W: Write> FrameSink for Terminal<W> {
+    fn draw(&mut self, frame: &str) -> io::Result<()> {
+        Terminal::draw(self, frame)
+    }
+}
+
+impl<W: Write> Drop for Terminal<W> {
+    fn drop(&mut self) {
+        let rows = self.previous.len();
+        let _ = move_to(&mut self.out, rows, 0)
+            .and_then(|_| write!(self.out, "{}", SHOW_CURSOR))
+            .and_then(|_| self.out.flush());
+    }
+}
+
+/// Keeps an animation from running faster than a given frame rate.
+pub struct FrameLimiter {
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl FrameLimiter {
+    pub fn new(frames_per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / frames_per_second.max(1),
+            last: None,
+        }
+    }
+
+    /// Sleeps until a frame interval has passed since the previous call.
+    pub fn wait(&mut self) {
+        if let Some(last) = self.last {
+            let elapsed = last.elapsed();
+            if elapsed < self.interval {
+                thread::sleep(self.interval - elapsed);
+            }
+        }
+        self.last = Some(Instant::now());
+    }
+}
+
+/// Hands every key to the program as soon as it's pressed, without echoing
+/// it, for as long as the guard lives. Goes through `stty` on the terminal
+/// itself, so it works while stdin is the puzzle input.
+pub struct RawMode {
+    saved: String,
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::from(File::open("/dev/tty")?))
+        .stderr(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stty failed"));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+impl RawMode {
+    pub fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", "1"])?;
+        Ok(Self { saved })
+    }
+
+    /// The terminal, to read the keys from.
+    pub fn keys(&self) -> io::Result<File> {
+        File::open("/dev/tty")
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FrameLimiter, Terminal};
+    use std::time::{Duration, Instant};
+
+    fn output(draw: impl FnOnce(&mut Terminal<&mut Vec<u8>>)) -> String {
+        let mut buffer = Vec::new();
+        {
+            let mut terminal = Terminal::new(&mut buffer).unwrap();
+            draw(&mut terminal);
+        }
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_draw_only_changes() {
+        let text = output(|terminal| {
+            terminal.draw("#.#\n...").unwrap();
+            terminal.draw("#.#\n.#.").unwrap();
+        });
+        let second = text.rsplit("...").next().unwrap();
+        assert_eq!(second, "\x1b[2;2H#\x1b[3;1H\x1b[?25h");
+    }
+
+    #[test]
+    fn test_draw_blanks_old_cells() {
+        let text = output(|terminal| {
+            terminal.draw("abc").unwrap();
+            terminal.draw("a").unwrap();
+        });
+        assert!(text.ends_with("\x1b[1;2H  \x1b[2;1H\x1b[?25h"));
+    }
+
+    #[test]
+    fn test_frame_limiter() {
+        let mut limiter = FrameLimiter::new(100);
+        let start = Instant::now();
+        for _ in 0..3 {
+            limiter.wait();
+        }
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
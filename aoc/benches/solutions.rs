@@ -0,0 +1,23 @@
+use aoc::{find_input, read_input};
+use aoc_utils::solution;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// Benchmarks both parts of every day with an input. The slower days take
+// seconds per run, so only a few samples are taken.
+fn solutions(c: &mut Criterion) {
+    for registration in solution::registered() {
+        let input = match find_input(registration.day, false).unwrap() {
+            Some(path) => read_input(&path).unwrap(),
+            None => continue,
+        };
+        let solution = registration.solution;
+        let mut group = c.benchmark_group(format!("day {}", registration.day));
+        group.sample_size(10);
+        group.bench_function("part 1", |b| b.iter(|| solution.part1(black_box(&input))));
+        group.bench_function("part 2", |b| b.iter(|| solution.part2(black_box(&input))));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, solutions);
+criterion_main!(benches);
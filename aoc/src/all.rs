@@ -0,0 +1,203 @@
+//! Running every day and summing up the answers and time taken in a table.
+
+use aoc_utils::solution::Solved;
+use aoc_utils::Solution;
+use std::time::Duration;
+
+pub struct DayResult {
+    pub day: u32,
+    pub parse_time: Option<Duration>,
+    pub part1: String,
+    pub part1_time: Duration,
+    pub part2: String,
+    pub part2_time: Duration,
+}
+
+impl DayResult {
+    pub fn new(day: u32, solved: Solved) -> Self {
+        Self {
+            day,
+            parse_time: solved.parse_time,
+            part1: solved.part1,
+            part1_time: solved.part1_time,
+            part2: solved.part2,
+            part2_time: solved.part2_time,
+        }
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.parse_time.unwrap_or_default() + self.part1_time + self.part2_time
+    }
+}
+
+pub fn solve(day: u32, solution: &dyn Solution, input: &str) -> Result<DayResult, String> {
+    let solved = solution
+        .solve(input)
+        .map_err(|error| format!("Invalid input for day {}: {}", day, error))?;
+    Ok(DayResult::new(day, solved))
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let micros = duration.as_micros();
+    if micros < 1_000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.2}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{:.2}s", duration.as_secs_f64())
+    }
+}
+
+// Answers that span several lines, like a picture, only show their first
+// line in the table
+pub(crate) fn cell(answer: &str) -> String {
+    let mut lines = answer.lines();
+    let first = lines.next().unwrap_or("");
+    if lines.next().is_some() {
+        format!("{}…", first)
+    } else {
+        first.to_string()
+    }
+}
+
+fn widths(header: &[&str], rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths: Vec<usize> = header.iter().map(|title| title.chars().count()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    widths
+}
+
+fn line<S: AsRef<str>>(values: &[S], widths: &[usize]) -> String {
+    let cells: Vec<String> = values
+        .iter()
+        .zip(widths.iter())
+        .map(|(value, width)| format!("{:<1$}", value.as_ref(), width))
+        .collect();
+    cells.join(" | ").trim_end().to_string()
+}
+
+fn separator(widths: &[usize]) -> String {
+    let dashes: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    dashes.join("-+-")
+}
+
+/// Lays the results out in a table, with a line for the total time at the
+/// bottom.
+pub fn table(results: &[DayResult]) -> String {
+    let header = ["Day", "Parse", "Part 1", "Time", "Part 2", "Time"];
+    let mut rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|result| {
+            vec![
+                result.day.to_string(),
+                result.parse_time.map_or_else(String::new, format_duration),
+                cell(&result.part1),
+                format_duration(result.part1_time),
+                cell(&result.part2),
+                format_duration(result.part2_time),
+            ]
+        })
+        .collect();
+    let total: Duration = results.iter().map(DayResult::total_time).sum();
+    let mut total_row = vec![String::new(); header.len()];
+    total_row[0] = "Total".to_string();
+    total_row[header.len() - 1] = format_duration(total);
+    rows.push(total_row);
+
+    let widths = widths(&header, &rows);
+    let mut lines = vec![line(&header, &widths), separator(&widths)];
+    let (total, days) = rows.split_last().unwrap();
+    lines.extend(days.iter().map(|row| line(row, &widths)));
+    lines.push(separator(&widths));
+    lines.push(line(total, &widths));
+    lines.join("\n")
+}
+
+/// Lays out the answers a day gave for several inputs, one row per input
+/// named after its file.
+pub fn inputs_table(results: &[(String, DayResult)]) -> String {
+    let header = ["Input", "Part 1", "Part 2", "Time"];
+    let rows: Vec<Vec<String>> = results
+        .iter()
+        .map(|(name, result)| {
+            vec![
+                name.clone(),
+                cell(&result.part1),
+                cell(&result.part2),
+                format_duration(result.total_time()),
+            ]
+        })
+        .collect();
+    let widths = widths(&header, &rows);
+    let mut lines = vec![line(&header, &widths), separator(&widths)];
+    lines.extend(rows.iter().map(|row| line(row, &widths)));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_duration, inputs_table, table, DayResult};
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_micros(532)), "532µs");
+        assert_eq!(format_duration(Duration::from_micros(12_345)), "12.35ms");
+        assert_eq!(format_duration(Duration::from_millis(2_500)), "2.50s");
+    }
+
+    #[test]
+    fn test_table() {
+        let results = vec![
+            DayResult {
+                day: 1,
+                parse_time: Some(Duration::from_micros(40)),
+                part1: "3273471".to_string(),
+                part1_time: Duration::from_micros(20),
+                part2: "4907345".to_string(),
+                part2_time: Duration::from_micros(30),
+            },
+            DayResult {
+                day: 21,
+                parse_time: None,
+                part1: "19357180".to_string(),
+                part1_time: Duration::from_micros(900),
+                part2: "droid fell:\n#####".to_string(),
+                part2_time: Duration::from_micros(1_100),
+            },
+        ];
+        let expected = "\
+Day   | Parse | Part 1   | Time  | Part 2       | Time
+------+-------+----------+-------+--------------+-------
+1     | 40µs  | 3273471  | 20µs  | 4907345      | 30µs
+21    |       | 19357180 | 900µs | droid fell:… | 1.10ms
+------+-------+----------+-------+--------------+-------
+Total |       |          |       |              | 2.09ms";
+        assert_eq!(table(&results), expected);
+    }
+
+    #[test]
+    fn test_inputs_table() {
+        let result = |part1: &str, part2: &str| DayResult {
+            day: 6,
+            parse_time: None,
+            part1: part1.to_string(),
+            part1_time: Duration::from_micros(50),
+            part2: part2.to_string(),
+            part2_time: Duration::from_micros(10),
+        };
+        let results = vec![
+            ("alice".to_string(), result("344238", "436")),
+            ("bob".to_string(), result("142497", "301")),
+        ];
+        let expected = "\
+Input | Part 1 | Part 2 | Time
+------+--------+--------+-----
+alice | 344238 | 436    | 60µs
+bob   | 142497 | 301    | 60µs";
+        assert_eq!(inputs_table(&results), expected);
+    }
+}
@@ -0,0 +1,312 @@
+//! Quick checks that an input looks like the real thing, to catch a broken
+//! download before a solution chokes on it: an error page saved in place of
+//! the input, a file cut short, or the input of another day.
+//!
+//! The checks only look at the shape of the input, the tokens on every
+//! line and how many lines there are, they don't solve anything.
+
+use aoc_utils::solution::DEFAULT_YEAR;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// Something off about an input, on a given line when it's about one.
+#[derive(Debug, PartialEq)]
+pub struct Anomaly {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Anomaly {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    fn on_line(index: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: Some(index + 1),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+type LineCheck = fn(&str) -> Result<(), String>;
+
+enum Shape {
+    Intcode,
+    Lines {
+        count: RangeInclusive<usize>,
+        check: LineCheck,
+    },
+    Grid {
+        cells: &'static str,
+        size: Option<(usize, usize)>,
+    },
+}
+
+fn shape(year: u32, day: u32) -> Option<Shape> {
+    let lines = |count, check| Some(Shape::Lines { count, check });
+    if year != DEFAULT_YEAR {
+        return None;
+    }
+    match day {
+        1 => lines(100..=100, |line| match line.parse::<u32>() {
+            Ok(mass) if mass > 0 => Ok(()),
+            _ => Err("expected a positive mass".to_string()),
+        }),
+        2 | 5 | 7 | 17 | 19 | 21 | 23 | 25 => Some(Shape::Intcode),
+        3 => lines(2..=2, |line| {
+            for step in line.split(',') {
+                let mut chars = step.chars();
+                let direction = chars.next();
+                if !matches!(direction, Some('U' | 'D' | 'L' | 'R'))
+                    || chars.as_str().parse::<u32>().is_err()
+                {
+                    return Err(format!("expected a step like R75, found {:?}", step));
+                }
+            }
+            Ok(())
+        }),
+        4 => lines(1..=1, |line| {
+            let digits =
+                |value: &str| value.len() == 6 && value.bytes().all(|b| b.is_ascii_digit());
+            match line.split_once('-') {
+                Some((low, high)) if digits(low) && digits(high) && low <= high => Ok(()),
+                _ => Err("expected a range of six digit numbers".to_string()),
+            }
+        }),
+        6 => lines(1..=usize::MAX, |line| match line.split_once(')') {
+            Some((center, orbiter))
+                if [center, orbiter]
+                    .iter()
+                    .all(|name| !name.is_empty() && name.chars().all(char::is_alphanumeric)) =>
+            {
+                Ok(())
+            }
+            _ => Err("expected an orbit like COM)B".to_string()),
+        }),
+        16 => lines(1..=1, |line| {
+            if line.len() >= 8 && line.bytes().all(|b| b.is_ascii_digit()) {
+                Ok(())
+            } else {
+                Err("expected a signal of at least eight digits".to_string())
+            }
+        }),
+        18 => Some(Shape::Grid {
+            cells: "#.@abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            size: None,
+        }),
+        20 => Some(Shape::Grid {
+            cells: "#. ABCDEFGHIJKLMNOPQRSTUVWXYZ",
+            size: None,
+        }),
+        22 => lines(1..=usize::MAX, |line| {
+            line.parse::<day_22::Technique>()
+                .map(|_| ())
+                .map_err(str::to_string)
+        }),
+        24 => Some(Shape::Grid {
+            cells: "#.",
+            size: Some((5, 5)),
+        }),
+        _ => None,
+    }
+}
+
+// What the site sends instead of an input, when something goes wrong
+const ERROR_PAGES: [&str; 4] = [
+    "<!DOCTYPE",
+    "<html",
+    "Please log in",
+    "Please don't repeatedly request",
+];
+
+fn check_common(input: &str) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    if input.trim().is_empty() {
+        anomalies.push(Anomaly::new("the input is empty"));
+        return anomalies;
+    }
+    if let Some(page) = ERROR_PAGES.iter().find(|page| input.contains(*page)) {
+        anomalies.push(Anomaly::new(format!(
+            "the input looks like an error page from the site, it has {:?}",
+            page
+        )));
+    }
+    // Inputs are downloaded ending in a newline, a file that doesn't was
+    // probably cut short
+    if !input.ends_with('\n') {
+        anomalies.push(Anomaly::new(
+            "the input doesn't end in a newline, it may be truncated",
+        ));
+    }
+    anomalies
+}
+
+fn check_intcode(input: &str) -> Vec<Anomaly> {
+    let lines: Vec<&str> = input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if lines.len() != 1 {
+        return vec![Anomaly::new(format!(
+            "expected the program on a single line, found {} lines",
+            lines.len()
+        ))];
+    }
+    let mut anomalies = Vec::new();
+    let values = lines[0].trim().split(',').collect::<Vec<_>>();
+    for (index, value) in values.iter().enumerate() {
+        if value.trim().parse::<i64>().is_err() {
+            anomalies.push(Anomaly::on_line(
+                0,
+                format!("value {} is not a number: {:?}", index + 1, value),
+            ));
+        }
+    }
+    // Even the shortest program of the year has over a hundred values
+    if values.len() < 100 {
+        anomalies.push(Anomaly::new(format!(
+            "the program only has {} values, it may be truncated",
+            values.len()
+        )));
+    }
+    anomalies
+}
+
+fn check_lines(input: &str, count: &RangeInclusive<usize>, check: LineCheck) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    let lines: Vec<&str> = input.lines().collect();
+    for (index, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Err(message) = check(line.trim()) {
+            anomalies.push(Anomaly::on_line(index, message));
+        }
+    }
+    let found = lines.iter().filter(|line| !line.trim().is_empty()).count();
+    if !count.contains(&found) {
+        let expected = if count.start() == count.end() {
+            count.start().to_string()
+        } else {
+            format!("at least {}", count.start())
+        };
+        anomalies.push(Anomaly::new(format!(
+            "expected {} lines, found {}",
+            expected, found
+        )));
+    }
+    anomalies
+}
+
+fn check_grid(input: &str, cells: &str, size: Option<(usize, usize)>) -> Vec<Anomaly> {
+    let mut anomalies = Vec::new();
+    let lines: Vec<&str> = input.lines().collect();
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(cell) = line.chars().find(|c| !cells.contains(*c)) {
+            anomalies.push(Anomaly::on_line(
+                index,
+                format!("unexpected character {:?}", cell),
+            ));
+        }
+    }
+    if let Some((width, height)) = size {
+        let fits = lines.len() == height && lines.iter().all(|line| line.chars().count() == width);
+        if !fits {
+            anomalies.push(Anomaly::new(format!(
+                "expected a {} by {} grid",
+                width, height
+            )));
+        }
+    }
+    anomalies
+}
+
+/// Everything that looks wrong with the input of a day of a year. Days
+/// without checks of their own, like the ones of other years, only get the
+/// ones every input gets.
+pub fn check(year: u32, day: u32, input: &str) -> Vec<Anomaly> {
+    let mut anomalies = check_common(input);
+    if input.trim().is_empty() {
+        return anomalies;
+    }
+    anomalies.extend(match shape(year, day) {
+        Some(Shape::Intcode) => check_intcode(input),
+        Some(Shape::Lines { count, check }) => check_lines(input, &count, check),
+        Some(Shape::Grid { cells, size }) => check_grid(input, cells, size),
+        None => Vec::new(),
+    });
+    anomalies
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check, Anomaly};
+    use aoc_utils::solution::DEFAULT_YEAR;
+
+    fn messages(day: u32, input: &str) -> Vec<String> {
+        check(DEFAULT_YEAR, day, input)
+            .iter()
+            .map(Anomaly::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_inputs() {
+        let program: Vec<String> = (0..120).map(|value| value.to_string()).collect();
+        assert!(check(DEFAULT_YEAR, 2, &format!("{}\n", program.join(","))).is_empty());
+        assert!(check(DEFAULT_YEAR, 3, "R8,U5,L5,D3\nU7,R6,D4,L4\n").is_empty());
+        assert!(check(DEFAULT_YEAR, 4, "123456-654321\n").is_empty());
+        assert!(check(DEFAULT_YEAR, 6, "COM)B\nB)C\n").is_empty());
+        assert!(check(
+            DEFAULT_YEAR,
+            22,
+            "deal into new stack\ncut -2\ndeal with increment 7\n"
+        )
+        .is_empty());
+        assert!(check(DEFAULT_YEAR, 24, "....#\n#..#.\n#..##\n..#..\n#....\n").is_empty());
+    }
+
+    #[test]
+    fn test_broken_downloads() {
+        assert_eq!(messages(1, ""), ["the input is empty"]);
+        let page = messages(1, "<!DOCTYPE html>\n<html>\n");
+        assert!(page[0].starts_with("the input looks like an error page"));
+        assert!(messages(4, "123456-654321")
+            .contains(&"the input doesn't end in a newline, it may be truncated".to_string()));
+        assert_eq!(
+            messages(5, "1,0,0,3,99\n"),
+            ["the program only has 5 values, it may be truncated"]
+        );
+    }
+
+    #[test]
+    fn test_wrong_tokens() {
+        assert_eq!(
+            messages(3, "R8,U5\nU7,X6\n"),
+            ["line 2: expected a step like R75, found \"X6\""]
+        );
+        assert_eq!(
+            messages(22, "cut 3\nshuffle\n"),
+            ["line 2: Unknown shuffle technique"]
+        );
+        assert_eq!(
+            messages(24, "....#\n#..#.\n#.?##\n"),
+            ["line 3: unexpected character '?'", "expected a 5 by 5 grid"]
+        );
+        assert_eq!(messages(1, "12\n-3\n").len(), 2);
+        assert!(check(2020, 1, "12\n-3\n").is_empty());
+    }
+}
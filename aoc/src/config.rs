@@ -0,0 +1,284 @@
+//! Settings for the runner, kept in `~/.config/aoc2019.toml` so the session
+//! doesn't have to be exported every time.
+//!
+//! ```toml
+//! session = "53616c7465645f5f..."
+//! # The year to work on, 2019 unless set here or with --year
+//! year = 2019
+//! input_dir = "/home/me/aoc/inputs"
+//! # How much slower than usual a day can get before `aoc all` warns
+//! regression_threshold = 0.25
+//!
+//! # Flags every run starts with
+//! [defaults]
+//! time = true
+//!
+//! # Flags and inputs for a single day
+//! [day.18]
+//! input = "/home/me/aoc/day-18-part-2.txt"
+//! progress = true
+//! ```
+//!
+//! Relative paths are taken from the directory the file is in.
+//! `AOC_CONFIG` points at another file, and `AOC_SESSION` and
+//! `AOC_INPUT_DIR` take the place of the values in the file.
+
+use aoc_utils::solution::DEFAULT_YEAR;
+use log::warn;
+use serde::{de, Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+const CONFIG_VARIABLE: &str = "AOC_CONFIG";
+const INPUT_DIR_VARIABLE: &str = "AOC_INPUT_DIR";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Flags that can be turned on without passing them.
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Flags {
+    pub time: Option<bool>,
+    pub progress: Option<bool>,
+    pub parallel: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DayConfig {
+    pub input: Option<PathBuf>,
+    pub time: Option<bool>,
+    pub progress: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub session: Option<String>,
+    year: Option<u32>,
+    pub input_dir: Option<PathBuf>,
+    pub regression_threshold: Option<f64>,
+    pub defaults: Flags,
+    #[serde(deserialize_with = "days")]
+    day: BTreeMap<u8, DayConfig>,
+}
+
+// TOML keys are always strings, so the days are read from them here
+fn days<'de, D>(deserializer: D) -> Result<BTreeMap<u8, DayConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut days = BTreeMap::new();
+    for (key, config) in BTreeMap::<String, DayConfig>::deserialize(deserializer)? {
+        let day = match key.parse::<u8>() {
+            Ok(day @ 1..=25) => day,
+            _ => return Err(de::Error::custom(format!("Invalid day in [day.{}]", key))),
+        };
+        if days.insert(day, config).is_some() {
+            return Err(de::Error::custom(format!(
+                "Day {} is set more than once",
+                day
+            )));
+        }
+    }
+    Ok(days)
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(text).map_err(|error| error.to_string())?;
+        if let Some(year) = config.year {
+            check_year(year)?;
+        }
+        Ok(config)
+    }
+
+    pub fn year(&self) -> u32 {
+        self.year.unwrap_or(DEFAULT_YEAR)
+    }
+
+    /// The settings for a single day, if there are any.
+    pub fn day(&self, day: u32) -> Option<&DayConfig> {
+        u8::try_from(day).ok().and_then(|day| self.day.get(&day))
+    }
+
+    /// Whether to time a day, with its own setting taking the place of the
+    /// default one.
+    pub fn time(&self, day: u32) -> bool {
+        self.day(day)
+            .and_then(|config| config.time)
+            .or(self.defaults.time)
+            .unwrap_or(false)
+    }
+
+    pub fn progress(&self, day: u32) -> bool {
+        self.day(day)
+            .and_then(|config| config.progress)
+            .or(self.defaults.progress)
+            .unwrap_or(false)
+    }
+
+    // Paths in the file are written from where the file is, not from where
+    // the runner happens to be started
+    fn resolve_paths(&mut self, dir: &Path) {
+        let inputs = self.day.values_mut().filter_map(|day| day.input.as_mut());
+        for path in self.input_dir.iter_mut().chain(inputs) {
+            if path.is_relative() {
+                *path = dir.join(&*path);
+            }
+        }
+    }
+
+    fn override_from_env(&mut self) {
+        if let Ok(session) = env::var(crate::fetch::SESSION_VARIABLE) {
+            self.session = Some(session);
+        }
+        if let Some(dir) = env::var_os(INPUT_DIR_VARIABLE) {
+            self.input_dir = Some(PathBuf::from(dir));
+        }
+    }
+}
+
+/// The first year of Advent of Code was 2015.
+pub fn check_year(year: u32) -> Result<(), String> {
+    if year >= 2015 {
+        Ok(())
+    } else {
+        Err(format!("Invalid year {}, the first one was 2015", year))
+    }
+}
+
+/// Where the configuration is read from, if there is a home to look in.
+pub fn path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(CONFIG_VARIABLE) {
+        return Some(PathBuf::from(path));
+    }
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config_home.join("aoc2019.toml"))
+}
+
+/// Reads the configuration file, if there is one, and applies the
+/// environment on top.
+pub fn load() -> Result<Config, String> {
+    let mut config = match path() {
+        Some(path) if path.is_file() => {
+            let text = fs::read_to_string(&path)
+                .map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+            let mut config = Config::parse(&text)
+                .map_err(|error| format!("Invalid {}: {}", path.display(), error))?;
+            if let Some(dir) = path.parent() {
+                config.resolve_paths(dir);
+            }
+            config
+        }
+        _ => Config::default(),
+    };
+    config.override_from_env();
+    Ok(config)
+}
+
+/// Loads the configuration for the rest of the run, so a broken file is
+/// reported up front. A year passed on the command line takes the place of
+/// the configured one.
+pub fn init(year: Option<u32>) -> Result<&'static Config, String> {
+    let mut config = load()?;
+    if year.is_some() {
+        config.year = year;
+    }
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// The configuration of this run. When it wasn't loaded with [`init`], as
+/// in tests and benchmarks, a broken file is ignored with a warning.
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        load().unwrap_or_else(|error| {
+            warn!("{}", error);
+            Config::default()
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::Config;
+    use aoc_utils::solution::DEFAULT_YEAR;
+    use std::path::Path;
+
+    #[test]
+    fn test_parse() {
+        let config = Config::parse(
+            "session = \"abc\"\n\
+             regression_threshold = 0.5\n\
+             year = 2020\n\
+             [defaults]\n\
+             time = true\n\
+             [day.18]\n\
+             input = \"maze.txt\"\n\
+             time = false\n",
+        )
+        .unwrap();
+        assert_eq!(config.session.as_deref(), Some("abc"));
+        assert_eq!(config.input_dir, None);
+        assert_eq!(config.year(), 2020);
+        assert_eq!(config.regression_threshold, Some(0.5));
+        assert!(config.time(1));
+        assert!(!config.time(18));
+        assert!(!config.progress(18));
+        assert_eq!(
+            config.day(18).and_then(|day| day.input.as_deref()),
+            Some(Path::new("maze.txt"))
+        );
+    }
+
+    #[test]
+    fn test_day_keys() {
+        let config = Config::parse("[day.07]\ntime = true").unwrap();
+        assert!(config.time(7));
+        assert!(!config.time(263));
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        let mut config = Config::parse(
+            "input_dir = \"inputs\"\n\
+             [day.3]\n\
+             input = \"wires.txt\"\n\
+             [day.4]\n\
+             input = \"/tmp/range.txt\"\n",
+        )
+        .unwrap();
+        config.resolve_paths(Path::new("/home/me/aoc"));
+        assert_eq!(
+            config.input_dir.as_deref(),
+            Some(Path::new("/home/me/aoc/inputs"))
+        );
+        assert_eq!(
+            config.day(3).and_then(|day| day.input.as_deref()),
+            Some(Path::new("/home/me/aoc/wires.txt"))
+        );
+        assert_eq!(
+            config.day(4).and_then(|day| day.input.as_deref()),
+            Some(Path::new("/tmp/range.txt"))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Config::parse("[day.26]\ntime = true").is_err());
+        assert!(Config::parse("[day.0]\ntime = true").is_err());
+        assert!(Config::parse("[day.x]\ntime = true").is_err());
+        assert!(Config::parse("[day.18]\ntime = true\n[day.018]\ntime = false").is_err());
+        assert!(Config::parse("[defaults]\nrecord = true").is_err());
+        assert!(Config::parse("session = 5").is_err());
+        assert!(Config::parse("year = 2014").is_err());
+        assert_eq!(Config::default().year(), DEFAULT_YEAR);
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+    }
+}
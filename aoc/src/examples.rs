@@ -0,0 +1,179 @@
+//! Pulling the examples out of a puzzle's description, for
+//! [`aoc_utils::example_tests`]. Every `<pre>` block of the page becomes a
+//! file in the day's `examples/` directory.
+//!
+//! The answer of an example is a guess: the last highlighted value, like
+//! `<code><em>34241</em></code>`, between the block and the next one. The
+//! guesses are written to `examples/fetched.txt`, to be checked by hand
+//! before they become tests.
+
+use crate::fetch;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq)]
+pub struct Example {
+    pub part: u8,
+    pub text: String,
+    pub expected: Option<String>,
+}
+
+/// Replaces the entities the site uses and drops any tags, like the `<em>`
+/// highlighting parts of an example.
+fn decode(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = match rest[start..].find('>') {
+            Some(end) => &rest[start + end + 1..],
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn last_highlighted(html: &str) -> Option<String> {
+    const OPEN: &str = "<code><em>";
+    const CLOSE: &str = "</em></code>";
+    let start = html.rfind(OPEN)? + OPEN.len();
+    let end = html[start..].find(CLOSE)? + start;
+    Some(decode(&html[start..end]))
+}
+
+/// The examples of a puzzle page, in order. Blocks after the heading of
+/// the second part belong to it.
+pub fn extract(html: &str) -> Vec<Example> {
+    let part2 = html.find("id=\"part2\"").unwrap_or(html.len());
+    let mut blocks = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = html[offset..].find("<pre>") {
+        let start = offset + start;
+        let end = match html[start..].find("</pre>") {
+            Some(end) => start + end,
+            None => break,
+        };
+        blocks.push((start, end));
+        offset = end;
+    }
+
+    let mut examples = Vec::new();
+    for (index, &(start, end)) in blocks.iter().enumerate() {
+        let part = if start > part2 { 2 } else { 1 };
+        // The answer comes before the next block, and in the same part
+        let mut next = blocks.get(index + 1).map_or(html.len(), |block| block.0);
+        if start < part2 {
+            next = next.min(part2);
+        }
+        let after = &html[end..next];
+        let mut text = decode(&html[start..end]);
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        examples.push(Example {
+            part,
+            text,
+            expected: last_highlighted(after),
+        });
+    }
+    examples
+}
+
+/// Writes the examples to `dir` as `example-1.txt` and so on, with the
+/// guessed answers in `fetched.txt`. Files that are already there are kept
+/// unless `force` is set. Returns the files written.
+pub fn save(dir: &Path, examples: &[Example], force: bool) -> Result<Vec<PathBuf>, String> {
+    let write = |path: &Path, text: &str| {
+        fs::write(path, text)
+            .map_err(|error| format!("Could not write {}: {}", path.display(), error))
+    };
+    fs::create_dir_all(dir)
+        .map_err(|error| format!("Could not create {}: {}", dir.display(), error))?;
+    let mut written = Vec::new();
+    let mut index = String::from("# Guessed from the puzzle page, check them before use\n");
+    for (number, example) in examples.iter().enumerate() {
+        let name = format!("example-{}.txt", number + 1);
+        let path = dir.join(&name);
+        if force || !path.exists() {
+            write(&path, &example.text)?;
+            written.push(path);
+        }
+        index.push_str(&format!(
+            "{}: part {} == {}\n",
+            name,
+            example.part,
+            example.expected.as_deref().unwrap_or("?")
+        ));
+    }
+    let path = dir.join("fetched.txt");
+    write(&path, &index)?;
+    written.push(path);
+    Ok(written)
+}
+
+/// Downloads a day's description and saves its examples in the day's
+/// crate, see [`crate::day_dir`].
+pub fn fetch_examples(root: &Path, day: u32, force: bool) -> Result<Vec<PathBuf>, String> {
+    let examples = extract(&fetch::puzzle_page(day)?);
+    if examples.is_empty() {
+        return Err(format!("The puzzle for day {} has no examples", day));
+    }
+    save(
+        &crate::day_dir(root, day).join("examples"),
+        &examples,
+        force,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, extract, Example};
+
+    const PAGE: &str = r#"<article class="day-desc"><h2>--- Day 6: Universal Orbit Map ---</h2>
+<p>For example:</p>
+<pre><code>COM)B
+B)C
+</code></pre>
+<p>In this example, the total number of orbits is <code><em>42</em></code>.</p>
+</article><article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<pre><code>K)YOU
+I)<em>SAN</em></code></pre>
+<p>Between them there are <code>4</code> jumps, so <code><em>4</em></code> transfers.</p>
+<pre><code>a &lt; b &amp;&amp; c</code></pre>
+</article>"#;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("<em>a</em> &lt;= &quot;b&quot;"), "a <= \"b\"");
+        assert_eq!(decode("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn test_extract() {
+        assert_eq!(
+            extract(PAGE),
+            [
+                Example {
+                    part: 1,
+                    text: "COM)B\nB)C\n".to_string(),
+                    expected: Some("42".to_string()),
+                },
+                Example {
+                    part: 2,
+                    text: "K)YOU\nI)SAN\n".to_string(),
+                    expected: Some("4".to_string()),
+                },
+                Example {
+                    part: 2,
+                    text: "a < b && c\n".to_string(),
+                    expected: None,
+                },
+            ]
+        );
+    }
+}
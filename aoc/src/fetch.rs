@@ -0,0 +1,106 @@
+//! Downloading puzzle inputs, keeping a copy under `inputs/` so each one is
+//! only requested once. Years other than 2019 get a directory of their own,
+//! like `inputs/2020/`.
+
+use crate::config;
+use aoc_utils::solution::DEFAULT_YEAR;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(crate) const SESSION_VARIABLE: &str = "AOC_SESSION";
+
+/// The directory downloaded inputs are kept in, `inputs/` in the workspace
+/// unless configured otherwise.
+pub fn inputs_dir(root: &Path) -> PathBuf {
+    config::get()
+        .input_dir
+        .clone()
+        .unwrap_or_else(|| root.join("inputs"))
+}
+
+/// The directory the inputs, answers and timings of the configured year
+/// are kept in.
+pub fn year_dir(root: &Path) -> PathBuf {
+    match config::get().year() {
+        DEFAULT_YEAR => inputs_dir(root),
+        year => inputs_dir(root).join(year.to_string()),
+    }
+}
+
+/// Where the input for a day is cached.
+pub fn cached_input(root: &Path, day: u32) -> PathBuf {
+    year_dir(root).join(format!("day-{}.txt", day))
+}
+
+/// The session cookie of a logged in user, taken from the environment or
+/// the configuration.
+pub fn session() -> Option<String> {
+    config::get()
+        .session
+        .as_ref()
+        .map(|session| session.trim().to_string())
+        .filter(|session| !session.is_empty())
+}
+
+// Errors name `what` was being downloaded
+fn get(url: &str, session: &str, what: &str) -> Result<String, String> {
+    let response = ureq::get(url)
+        .set("Cookie", &format!("session={}", session))
+        .set(
+            "User-Agent",
+            "github.com/gperinazzo/advent-of-code-2019 by gperinazzo@gmail.com",
+        )
+        .call()
+        .map_err(|error| match error {
+            ureq::Error::Status(400, _) | ureq::Error::Status(401, _) => {
+                format!("The session in {} was rejected", SESSION_VARIABLE)
+            }
+            ureq::Error::Status(404, _) => format!("The {} isn't out yet", what),
+            error => format!("Could not download the {}: {}", what, error),
+        })?;
+    response
+        .into_string()
+        .map_err(|error| format!("Could not read the {}: {}", what, error))
+}
+
+fn download(day: u32, session: &str) -> Result<String, String> {
+    let year = config::get().year();
+    let url = format!("https://adventofcode.com/{}/day/{}/input", year, day);
+    get(&url, session, &format!("input for day {}", day))
+}
+
+/// Downloads the description of a day's puzzle, as HTML. The second part
+/// is only there once the first one is solved by the session's user.
+pub fn puzzle_page(day: u32) -> Result<String, String> {
+    let session = require_session()?;
+    let year = config::get().year();
+    let url = format!("https://adventofcode.com/{}/day/{}", year, day);
+    get(&url, &session, &format!("puzzle for day {}", day))
+}
+
+fn require_session() -> Result<String, String> {
+    session().ok_or_else(|| {
+        format!(
+            "Set {} or the session in the configuration to download from the site",
+            SESSION_VARIABLE
+        )
+    })
+}
+
+/// Downloads the input for a day into the cache, unless it's there already.
+/// Returns the path of the cached file.
+pub fn fetch(root: &Path, day: u32, force: bool) -> Result<PathBuf, String> {
+    let path = cached_input(root, day);
+    if path.is_file() && !force {
+        return Ok(path);
+    }
+    let session = require_session()?;
+    let input = download(day, &session)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Could not create {}: {}", parent.display(), error))?;
+    }
+    fs::write(&path, input)
+        .map_err(|error| format!("Could not write {}: {}", path.display(), error))?;
+    Ok(path)
+}
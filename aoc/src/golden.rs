@@ -0,0 +1,256 @@
+//! Expected answers for the real inputs, to catch a day that starts giving
+//! a different answer after a change to the shared crates.
+//!
+//! The answers are kept next to the cached inputs in `inputs/answers.txt`,
+//! one per line, like `6.1: 344238` for the first part of day 6.
+
+use crate::all::{self, DayResult};
+use crate::fetch;
+use aoc_utils::input::ParseError;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Answers by day and part.
+pub type Answers = BTreeMap<(u32, u8), String>;
+
+pub fn answers_path(root: &Path) -> PathBuf {
+    fetch::year_dir(root).join("answers.txt")
+}
+
+fn parse_line(line: &str) -> Result<((u32, u8), String), &'static str> {
+    let (key, answer) = line.split_once(':').ok_or("expected day.part: answer")?;
+    let (day, part) = key.trim().split_once('.').ok_or("expected day.part")?;
+    let day = day.parse().map_err(|_| "invalid day")?;
+    let part = match part {
+        "1" => 1,
+        "2" => 2,
+        _ => return Err("part must be 1 or 2"),
+    };
+    Ok(((day, part), answer.trim().to_string()))
+}
+
+pub fn parse_answers(text: &str) -> Result<Answers, ParseError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            parse_line(line).map_err(|message| ParseError::new(index + 1, 1, message))
+        })
+        .collect()
+}
+
+/// The answers of the results, in the format read by [`parse_answers`].
+pub fn format_answers(results: &[DayResult]) -> String {
+    results
+        .iter()
+        .map(|result| {
+            format!(
+                "{}.1: {}\n{}.2: {}\n",
+                result.day, result.part1, result.day, result.part2
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Mismatch {
+    pub day: u32,
+    pub part: u8,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Day {} part {}: expected {}, got {}",
+            self.day, self.part, self.expected, self.actual
+        )
+    }
+}
+
+/// Every answer in the results that doesn't match the expected one. Parts
+/// without an expected answer aren't checked.
+pub fn compare(expected: &Answers, results: &[DayResult]) -> Vec<Mismatch> {
+    let mut mismatches = Vec::new();
+    for result in results {
+        for (part, actual) in [(1, &result.part1), (2, &result.part2)].iter() {
+            if let Some(expected) = expected.get(&(result.day, *part)) {
+                if expected != *actual {
+                    mismatches.push(Mismatch {
+                        day: result.day,
+                        part: *part,
+                        expected: expected.clone(),
+                        actual: actual.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    mismatches
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// The expected and actual answers side by side, with the time each part
+/// took. Parts that drifted are marked, and in red when `color` is set.
+pub fn diff(expected: &Answers, results: &[DayResult], color: bool) -> String {
+    let mut rows = Vec::new();
+    for result in results {
+        for (part, actual, time) in [
+            (1, &result.part1, result.part1_time),
+            (2, &result.part2, result.part2_time),
+        ] {
+            if let Some(expected) = expected.get(&(result.day, part)) {
+                rows.push((
+                    expected == actual,
+                    [
+                        format!("{}.{}", result.day, part),
+                        all::cell(expected),
+                        all::cell(actual),
+                        all::format_duration(time),
+                    ],
+                ));
+            }
+        }
+    }
+
+    let header = ["Part", "Expected", "Actual", "Time"].map(str::to_string);
+    let mut widths = header.clone().map(|title| title.chars().count());
+    for (_, row) in &rows {
+        for (width, value) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    // Padding is done before coloring, escape codes take no room on screen
+    let line = |mark: &str, values: &[String]| {
+        let cells: Vec<String> = values
+            .iter()
+            .zip(widths.iter())
+            .map(|(value, width)| format!("{:<1$}", value, width))
+            .collect();
+        format!("{} {}", mark, cells.join(" | "))
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![line(" ", &header)];
+    for (matches, row) in &rows {
+        let (mark, paint) = if *matches {
+            ("✓", GREEN)
+        } else {
+            ("✗", RED)
+        };
+        let text = line(mark, row);
+        lines.push(if color {
+            format!("{}{}{}", paint, text, RESET)
+        } else {
+            text
+        });
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{answers_path, compare, diff, format_answers, parse_answers, Mismatch};
+    use crate::all::DayResult;
+    use crate::{solve_day, workspace_root};
+    use std::collections::BTreeSet;
+    use std::env;
+    use std::fs;
+    use std::time::Duration;
+
+    fn result(day: u32, part1: &str, part2: &str) -> DayResult {
+        DayResult {
+            day,
+            parse_time: None,
+            part1: part1.to_string(),
+            part1_time: Duration::default(),
+            part2: part2.to_string(),
+            part2_time: Duration::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = parse_answers("# day 6\n6.1: 344238\n\n6.2: 436\n").unwrap();
+        assert_eq!(answers[&(6, 1)], "344238");
+        assert_eq!(answers[&(6, 2)], "436");
+        let error = parse_answers("6.1: 344238\n6.3: 1").unwrap_err();
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let results = vec![result(1, "3273471", "4907345"), result(6, "344238", "436")];
+        let answers = parse_answers(&format_answers(&results)).unwrap();
+        assert!(compare(&answers, &results).is_empty());
+    }
+
+    #[test]
+    fn test_compare() {
+        let answers = parse_answers("6.1: 344238\n6.2: 436").unwrap();
+        let mismatches = compare(&answers, &[result(6, "344238", "437"), result(7, "1", "2")]);
+        assert_eq!(
+            mismatches,
+            [Mismatch {
+                day: 6,
+                part: 2,
+                expected: "436".to_string(),
+                actual: "437".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let answers = parse_answers("6.1: 344238\n6.2: 436").unwrap();
+        let expected = [
+            "  Part | Expected | Actual | Time",
+            "✓ 6.1  | 344238   | 344238 | 0µs",
+            "✗ 6.2  | 436      | 437    | 0µs",
+        ];
+        assert_eq!(
+            diff(&answers, &[result(6, "344238", "437")], false),
+            expected.join("\n")
+        );
+        let colored = diff(&answers, &[result(6, "344238", "437")], true);
+        assert!(colored.contains("\x1b[31m✗ 6.2  | 436      | 437    | 0µs\x1b[0m"));
+    }
+
+    // Checks every day with a recorded answer against its real input. The
+    // answers aren't part of the repository, so without them there is
+    // nothing to check.
+    #[test]
+    fn test_golden_answers() {
+        let path = answers_path(workspace_root());
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => {
+                eprintln!("No answers in {}, skipping", path.display());
+                return;
+            }
+        };
+        let answers = parse_answers(&text).unwrap();
+        let days: BTreeSet<u32> = answers.keys().map(|(day, _)| *day).collect();
+        let mut results = Vec::new();
+        for day in days {
+            match solve_day(day).unwrap() {
+                Some(result) => results.push(result),
+                None => panic!("Day {} has answers but no input", day),
+            }
+        }
+        let mismatches = compare(&answers, &results);
+        let color = env::var_os("NO_COLOR").is_none();
+        assert!(
+            mismatches.is_empty(),
+            "Answers drifted from {}:\n{}",
+            path.display(),
+            diff(&answers, &results, color)
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! How long every day took on past runs of `aoc all`, to notice when a
+//! change to the shared crates slows a day down.
+//!
+//! The history is kept next to the cached inputs in `inputs/timings.csv`,
+//! one line per day and run, like `1576000000,18,830000` for day 18 taking
+//! 830ms in a run started at that Unix time.
+
+use crate::all::{format_duration, DayResult};
+use crate::fetch;
+use aoc_utils::input::ParseError;
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How many of the latest runs of a day make up its baseline.
+const BASELINE_RUNS: usize = 5;
+
+/// Days this fast are left out, a millisecond more is just noise.
+const MIN_REGRESSION: Duration = Duration::from_millis(1);
+
+/// How much slower than its baseline a day can get before it's reported,
+/// unless configured otherwise.
+pub const DEFAULT_THRESHOLD: f64 = 0.25;
+
+pub fn history_path(root: &Path) -> PathBuf {
+    fetch::year_dir(root).join("timings.csv")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub timestamp: u64,
+    pub day: u32,
+    pub elapsed: Duration,
+}
+
+fn parse_line(line: &str) -> Result<Entry, &'static str> {
+    let mut fields = line.split(',').map(str::trim);
+    let mut next = |name| fields.next().ok_or(name);
+    let timestamp = next("missing time")?.parse().map_err(|_| "invalid time")?;
+    let day = next("missing day")?.parse().map_err(|_| "invalid day")?;
+    let micros = next("missing elapsed time")?
+        .parse()
+        .map_err(|_| "invalid elapsed time")?;
+    Ok(Entry {
+        timestamp,
+        day,
+        elapsed: Duration::from_micros(micros),
+    })
+}
+
+pub fn parse_history(text: &str) -> Result<Vec<Entry>, ParseError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            parse_line(line).map_err(|message| ParseError::new(index + 1, 1, message))
+        })
+        .collect()
+}
+
+/// The results of a run as lines of history, in the format read by
+/// [`parse_history`].
+pub fn format_entries(timestamp: u64, results: &[DayResult]) -> String {
+    results
+        .iter()
+        .map(|result| {
+            format!(
+                "{},{},{}\n",
+                timestamp,
+                result.day,
+                result.total_time().as_micros()
+            )
+        })
+        .collect()
+}
+
+/// The median time of the latest runs of a day, if it was ever run.
+pub fn baseline(history: &[Entry], day: u32) -> Option<Duration> {
+    let mut times: Vec<Duration> = history
+        .iter()
+        .rev()
+        .filter(|entry| entry.day == day)
+        .take(BASELINE_RUNS)
+        .map(|entry| entry.elapsed)
+        .collect();
+    times.sort();
+    times.get(times.len() / 2).copied()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Regression {
+    pub day: u32,
+    pub elapsed: Duration,
+    pub baseline: Duration,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let slower = self.elapsed.as_secs_f64() / self.baseline.as_secs_f64() - 1.0;
+        write!(
+            f,
+            "Day {} took {}, {:.0}% slower than its baseline of {}",
+            self.day,
+            format_duration(self.elapsed),
+            slower * 100.0,
+            format_duration(self.baseline)
+        )
+    }
+}
+
+/// The days of the results that are more than `threshold` slower than
+/// their baseline, as a fraction of it.
+pub fn regressions(history: &[Entry], results: &[DayResult], threshold: f64) -> Vec<Regression> {
+    results
+        .iter()
+        .filter_map(|result| {
+            let baseline = baseline(history, result.day)?;
+            let elapsed = result.total_time();
+            let limit = baseline.as_secs_f64() * (1.0 + threshold);
+            if elapsed >= MIN_REGRESSION && elapsed.as_secs_f64() > limit {
+                Some(Regression {
+                    day: result.day,
+                    elapsed,
+                    baseline,
+                })
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Checks the results against the history in `path`, then adds them to it.
+/// Returns the days that got slower.
+pub fn record(
+    path: &Path,
+    timestamp: u64,
+    results: &[DayResult],
+    threshold: f64,
+) -> Result<Vec<Regression>, String> {
+    let history = if path.is_file() {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Could not read {}: {}", path.display(), error))?;
+        parse_history(&text).map_err(|error| format!("Invalid {}: {}", path.display(), error))?
+    } else {
+        Vec::new()
+    };
+    let found = regressions(&history, results, threshold);
+
+    let write_error =
+        |error: std::io::Error| format!("Could not write {}: {}", path.display(), error);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(write_error)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(write_error)?;
+    file.write_all(format_entries(timestamp, results).as_bytes())
+        .map_err(write_error)?;
+    Ok(found)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{baseline, format_entries, parse_history, regressions, Entry, Regression};
+    use crate::all::DayResult;
+    use std::time::Duration;
+
+    fn result(day: u32, millis: u64) -> DayResult {
+        DayResult {
+            day,
+            parse_time: None,
+            part1: String::new(),
+            part1_time: Duration::from_millis(millis),
+            part2: String::new(),
+            part2_time: Duration::default(),
+        }
+    }
+
+    fn entry(day: u32, millis: u64) -> Entry {
+        Entry {
+            timestamp: 0,
+            day,
+            elapsed: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = format_entries(1_576_000_000, &[result(18, 830), result(6, 2)]);
+        assert_eq!(text, "1576000000,18,830000\n1576000000,6,2000\n");
+        let history = parse_history(&text).unwrap();
+        assert_eq!(history[0].day, 18);
+        assert_eq!(history[1].elapsed, Duration::from_millis(2));
+        let error = parse_history("1,2,3\n1,x,3").unwrap_err();
+        assert!(error.to_string().starts_with("line 2"));
+    }
+
+    #[test]
+    fn test_baseline() {
+        // Only the latest five runs count, and a single slow one doesn't
+        // move the median
+        let history: Vec<Entry> = [900, 100, 110, 500, 90, 105]
+            .iter()
+            .map(|millis| entry(18, *millis))
+            .collect();
+        assert_eq!(baseline(&history, 18), Some(Duration::from_millis(105)));
+        assert_eq!(baseline(&history, 6), None);
+    }
+
+    #[test]
+    fn test_regressions() {
+        let history = vec![entry(18, 100), entry(6, 0), entry(20, 100)];
+        let results = vec![
+            result(18, 130),
+            result(6, 0),
+            result(20, 120),
+            result(22, 50),
+        ];
+        assert_eq!(
+            regressions(&history, &results, 0.25),
+            [Regression {
+                day: 18,
+                elapsed: Duration::from_millis(130),
+                baseline: Duration::from_millis(100),
+            }]
+        );
+        assert_eq!(
+            regressions(&history, &results, 0.25)[0].to_string(),
+            "Day 18 took 130.00ms, 30% slower than its baseline of 100.00ms"
+        );
+        assert!(regressions(&history, &results, 0.5).is_empty());
+    }
+}
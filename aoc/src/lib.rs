@@ -0,0 +1,237 @@
+//! The pieces of the runner shared by its commands and tests.
+
+use aoc_utils::{input, solution};
+use rayon::prelude::*;
+use std::fs;
+use std::io::BufReader;
+use std::panic::{self, UnwindSafe};
+use std::path::{Path, PathBuf};
+
+pub mod all;
+pub mod check;
+pub mod config;
+pub mod examples;
+pub mod fetch;
+pub mod golden;
+pub mod history;
+pub mod profile;
+pub mod report;
+pub mod serve;
+pub mod watch;
+
+// Solutions register themselves, but only the crates that are linked in can
+// be found
+use day_1 as _;
+use day_10 as _;
+use day_12 as _;
+use day_13 as _;
+use day_14 as _;
+use day_15 as _;
+use day_16 as _;
+use day_17 as _;
+use day_18 as _;
+use day_19 as _;
+use day_2 as _;
+use day_20 as _;
+use day_21 as _;
+use day_22 as _;
+use day_23 as _;
+use day_24 as _;
+use day_25 as _;
+use day_3 as _;
+use day_4 as _;
+use day_5 as _;
+use day_6 as _;
+use day_7 as _;
+
+pub fn workspace_root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("The runner lives inside the workspace")
+}
+
+/// The crate of a day of the configured year, `day-1` and so on for 2019
+/// and under a directory named after the year, like `2020/day-1`, for the
+/// others.
+pub fn day_dir(root: &Path, day: u32) -> PathBuf {
+    let name = format!("day-{}", day);
+    match config::get().year() {
+        solution::DEFAULT_YEAR => root.join(name),
+        year => root.join(year.to_string()).join(name),
+    }
+}
+
+/// The solution for a day of the configured year.
+pub fn find_solution(day: u32) -> Result<&'static dyn solution::Solution, String> {
+    let year = config::get().year();
+    solution::find_in(year, day).ok_or_else(|| match year {
+        solution::DEFAULT_YEAR => format!("Day {} is not solved yet", day),
+        year => format!("Day {} of {} is not solved yet", day, year),
+    })
+}
+
+/// The input configured for the day, the `input.txt` kept next to the
+/// day's crate, or the downloaded input. Missing inputs are fetched if
+/// asked to and there is a session to do so.
+pub fn find_input(day: u32, fetch_missing: bool) -> Result<Option<PathBuf>, String> {
+    if let Some(path) = config::get().day(day).and_then(|day| day.input.clone()) {
+        return Ok(Some(path));
+    }
+    let root = workspace_root();
+    let path = day_dir(root, day).join("input.txt");
+    if path.is_file() {
+        return Ok(Some(path));
+    }
+    let path = fetch::cached_input(root, day);
+    if path.is_file() {
+        return Ok(Some(path));
+    }
+    if fetch_missing && fetch::session().is_some() {
+        return fetch::fetch(root, day, false).map(Some);
+    }
+    Ok(None)
+}
+
+/// Reads an input file, which can be compressed, like `day-18.txt.gz`. See
+/// [`aoc_utils::input::read_text`].
+pub fn read_input(path: &Path) -> Result<String, String> {
+    fs::File::open(path)
+        .and_then(|file| input::read_text(BufReader::new(file)))
+        .map_err(|error| format!("Could not read {}: {}", path.display(), error))
+}
+
+/// The input of an intcode day from a program shared with
+/// [`intcode::share::encode`].
+pub fn shared_input(code: &str) -> Result<String, String> {
+    let program = intcode::share::decode(code)
+        .map_err(|error| format!("Invalid shared program: {}", error))?;
+    let values: Vec<String> = program.iter().map(|value| value.to_string()).collect();
+    Ok(format!("{}\n", values.join(",")))
+}
+
+/// Solves a day if it has an input, without fetching it.
+pub fn solve_day(day: u32) -> Result<Option<all::DayResult>, String> {
+    let solution = find_solution(day)?;
+    match find_input(day, false)? {
+        Some(path) => all::solve(day, solution, &read_input(&path)?).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Solves a day once for every file in `dir`, like the inputs of everyone
+/// in a group, in the order of their names. Every result is named after
+/// its file, without the extension.
+pub fn solve_inputs(day: u32, dir: &Path) -> Result<Vec<(String, all::DayResult)>, String> {
+    let solution = find_solution(day)?;
+    let entries = fs::read_dir(dir)
+        .map_err(|error| format!("Could not read {}: {}", dir.display(), error))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|error| format!("Could not read {}: {}", dir.display(), error))?
+            .path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    if paths.is_empty() {
+        return Err(format!("There are no inputs in {}", dir.display()));
+    }
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let name = path
+                .file_stem()
+                .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+            Ok((name, all::solve(day, solution, &read_input(path)?)?))
+        })
+        .collect()
+}
+
+/// Runs `solve` for a day, turning a panic into an error so that one day
+/// going wrong doesn't take the others down with it.
+fn catch_panic<T>(
+    day: u32,
+    solve: impl FnOnce() -> Result<T, String> + UnwindSafe,
+) -> Result<T, String> {
+    panic::catch_unwind(solve).unwrap_or_else(|payload| {
+        let message = match payload.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => payload
+                .downcast_ref::<String>()
+                .cloned()
+                .unwrap_or_else(|| "unknown panic".to_string()),
+        };
+        Err(format!("Day {} panicked: {}", day, message))
+    })
+}
+
+/// Solves every registered day of the configured year that has an input,
+/// calling `skipped` with the ones that don't and `failed` with the ones
+/// that couldn't be solved.
+pub fn solve_all<S, F>(mut skipped: S, mut failed: F) -> Vec<all::DayResult>
+where
+    S: FnMut(u32),
+    F: FnMut(u32, String),
+{
+    let mut results = Vec::new();
+    for registration in solution::registered_in(config::get().year()) {
+        let day = registration.day;
+        match catch_panic(day, || solve_day(day)) {
+            Ok(Some(result)) => results.push(result),
+            Ok(None) => skipped(day),
+            Err(error) => failed(day, error),
+        }
+    }
+    results
+}
+
+/// Like [`solve_all`], but solving the days on a thread pool. The results
+/// keep the order of the days.
+///
+/// Solutions only get their input, none of them read stdin or share any
+/// state, so every day is safe to solve alongside the others. The times
+/// measured do include the contention for the cores, though.
+pub fn solve_all_parallel<S, F>(mut skipped: S, mut failed: F) -> Vec<all::DayResult>
+where
+    S: FnMut(u32),
+    F: FnMut(u32, String),
+{
+    let days: Vec<u32> = solution::registered_in(config::get().year())
+        .iter()
+        .map(|registration| registration.day)
+        .collect();
+    let solved: Vec<_> = days
+        .par_iter()
+        .map(|&day| (day, catch_panic(day, || solve_day(day))))
+        .collect();
+    let mut results = Vec::new();
+    for (day, result) in solved {
+        match result {
+            Ok(Some(result)) => results.push(result),
+            Ok(None) => skipped(day),
+            Err(error) => failed(day, error),
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod test {
+    use super::catch_panic;
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(3, || Ok(7)), Ok(7));
+        assert_eq!(
+            catch_panic::<()>(3, || panic!("Invalid camera output")),
+            Err("Day 3 panicked: Invalid camera output".to_string())
+        );
+        let index = 4;
+        assert_eq!(
+            catch_panic::<()>(5, || panic!("index {} out of range", index)),
+            Err("Day 5 panicked: index 4 out of range".to_string())
+        );
+    }
+}
@@ -0,0 +1,679 @@
+use aoc::{
+    all, check, config, examples, fetch, find_input, find_solution, golden, history, profile,
+    read_input, report, serve, shared_input, watch, workspace_root,
+};
+use aoc_utils::progress;
+use aoc_utils::solution::{print_solved, read_stdin};
+use log::LevelFilter;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::exit;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const USAGE: &str = "\
+Usage: aoc [-v|-vv] run <day> [--input file | --inputs-dir dir | --shared code] [--time]
+                [--progress] [--report file] [--profile]
+       aoc [-v|-vv] fetch <day> [--force]
+       aoc [-v|-vv] fetch-examples <day> [--force]
+       aoc [-v|-vv] check <day> [--input file]
+       aoc [-v|-vv] watch <day> [--input file]
+       aoc [-v|-vv] all [--record] [--report file] [--progress] [--parallel] [--no-history]
+       aoc [-v|-vv] serve <day> [--input file] [--port port]
+       aoc [-v|-vv] share <program file>
+
+-v logs what the solutions are doing, -vv every intcode instruction too
+--year <year> works on another year than 2019, or the configured one";
+
+#[derive(Debug, PartialEq)]
+struct RunOptions {
+    day: u32,
+    input: Option<PathBuf>,
+    inputs_dir: Option<PathBuf>,
+    shared: Option<String>,
+    time: bool,
+    progress: bool,
+    report: Option<PathBuf>,
+    profile: bool,
+}
+
+fn parse_day(day: &str) -> Result<u32, String> {
+    match day.parse() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        _ => Err(format!(
+            "Invalid day {}, expected a number from 1 to 25",
+            day
+        )),
+    }
+}
+
+fn parse_run_options(args: &[String]) -> Result<RunOptions, String> {
+    let mut day = None;
+    let mut input = None;
+    let mut inputs_dir = None;
+    let mut shared = None;
+    let mut time = false;
+    let mut progress = false;
+    let mut report = None;
+    let mut profile = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let path = args.next().ok_or("Missing input file")?;
+                input = Some(PathBuf::from(path));
+            }
+            "--inputs-dir" => {
+                let path = args.next().ok_or("Missing inputs directory")?;
+                inputs_dir = Some(PathBuf::from(path));
+            }
+            "--shared" => shared = Some(args.next().ok_or("Missing shared program")?.clone()),
+            "--time" => time = true,
+            "--progress" => progress = true,
+            "--report" => {
+                let path = args.next().ok_or("Missing report file")?;
+                report = Some(PathBuf::from(path));
+            }
+            "--profile" => profile = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if day.is_none() => day = Some(parse_day(arg)?),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    let sources = [input.is_some(), inputs_dir.is_some(), shared.is_some()];
+    if sources.iter().filter(|given| **given).count() > 1 {
+        return Err("Only one of --input, --inputs-dir and --shared can be used".to_string());
+    }
+    if inputs_dir.is_some() {
+        if report.is_some() {
+            return Err("--report can't be used with --inputs-dir".to_string());
+        }
+        if profile {
+            return Err("--profile can't be used with --inputs-dir".to_string());
+        }
+    }
+    Ok(RunOptions {
+        day: day.ok_or("Missing day")?,
+        input,
+        inputs_dir,
+        shared,
+        time,
+        progress,
+        report,
+        profile,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct FetchOptions {
+    day: u32,
+    force: bool,
+}
+
+fn parse_fetch_options(args: &[String]) -> Result<FetchOptions, String> {
+    let mut day = None;
+    let mut force = false;
+    for arg in args {
+        match arg.as_str() {
+            "--force" => force = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if day.is_none() => day = Some(parse_day(arg)?),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    Ok(FetchOptions {
+        day: day.ok_or("Missing day")?,
+        force,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct InputOptions {
+    day: u32,
+    input: Option<PathBuf>,
+}
+
+fn parse_input_options(args: &[String]) -> Result<InputOptions, String> {
+    let mut day = None;
+    let mut input = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let path = args.next().ok_or("Missing input file")?;
+                input = Some(PathBuf::from(path));
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if day.is_none() => day = Some(parse_day(arg)?),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    Ok(InputOptions {
+        day: day.ok_or("Missing day")?,
+        input,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct AllOptions {
+    record: bool,
+    report: Option<PathBuf>,
+    progress: bool,
+    parallel: bool,
+    history: bool,
+}
+
+fn parse_all_options(args: &[String]) -> Result<AllOptions, String> {
+    let mut options = AllOptions {
+        record: false,
+        report: None,
+        progress: false,
+        parallel: false,
+        history: true,
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" => options.record = true,
+            "--report" => {
+                let path = args.next().ok_or("Missing report file")?;
+                options.report = Some(PathBuf::from(path));
+            }
+            "--progress" => options.progress = true,
+            "--parallel" => options.parallel = true,
+            "--no-history" => options.history = false,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    // The bars of days solved at the same time would draw over each other
+    if options.progress && options.parallel {
+        return Err("--progress can't be used with --parallel".to_string());
+    }
+    Ok(options)
+}
+
+const DEFAULT_PORT: u16 = 2019;
+
+#[derive(Debug, PartialEq)]
+struct ServeOptions {
+    day: u32,
+    input: Option<PathBuf>,
+    port: u16,
+}
+
+fn parse_serve_options(args: &[String]) -> Result<ServeOptions, String> {
+    let mut day = None;
+    let mut input = None;
+    let mut port = DEFAULT_PORT;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let path = args.next().ok_or("Missing input file")?;
+                input = Some(PathBuf::from(path));
+            }
+            "--port" => {
+                let value = args.next().ok_or("Missing port")?;
+                port = value
+                    .parse()
+                    .map_err(|_| format!("Invalid port {}", value))?;
+            }
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if day.is_none() => day = Some(parse_day(arg)?),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    Ok(ServeOptions {
+        day: day.ok_or("Missing day")?,
+        input,
+        port,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct ShareOptions {
+    program: PathBuf,
+}
+
+fn parse_share_options(args: &[String]) -> Result<ShareOptions, String> {
+    let mut program = None;
+    for arg in args {
+        match arg.as_str() {
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ if program.is_none() => program = Some(PathBuf::from(arg)),
+            _ => return Err(format!("Unexpected argument {}", arg)),
+        }
+    }
+    Ok(ShareOptions {
+        program: program.ok_or("Missing program file")?,
+    })
+}
+
+fn run(options: &RunOptions) -> Result<(), String> {
+    let config = config::get();
+    progress::set_enabled(options.progress || config.progress(options.day));
+    if let Some(dir) = &options.inputs_dir {
+        let results = aoc::solve_inputs(options.day, dir)?;
+        println!("{}", all::inputs_table(&results));
+        return Ok(());
+    }
+    let solution = find_solution(options.day)?;
+    let path = match &options.input {
+        Some(path) => Some(path.clone()),
+        None if options.shared.is_some() => None,
+        None => find_input(options.day, true)?,
+    };
+    let input = match (path, &options.shared) {
+        (Some(path), _) => read_input(&path)?,
+        (None, Some(code)) => shared_input(code)?,
+        (None, None) => {
+            eprintln!("No input found for day {}, reading from stdin", options.day);
+            read_stdin()
+        }
+    };
+    let time = options.time || config.time(options.day);
+    let answer = || {
+        let solved = solution.solve(&input)?;
+        print_solved(&solved, time);
+        match &options.report {
+            Some(path) => report::save(&[all::DayResult::new(options.day, solved)], path),
+            None => Ok(()),
+        }
+    };
+    if options.profile {
+        let path = profile::flamegraph_path(options.day);
+        profile::profile(&path, answer)??;
+        println!("Saved the flamegraph to {}", path.display());
+        Ok(())
+    } else {
+        answer()
+    }
+}
+
+fn run_watch(options: &InputOptions) -> Result<(), String> {
+    let path = match &options.input {
+        Some(path) => path.clone(),
+        None => find_input(options.day, true)?
+            .ok_or_else(|| format!("No input found for day {}", options.day))?,
+    };
+    watch::watch(workspace_root(), options.day, &path)
+}
+
+fn run_fetch_examples(options: &FetchOptions) -> Result<(), String> {
+    let written = examples::fetch_examples(workspace_root(), options.day, options.force)?;
+    for path in written {
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn run_fetch(options: &FetchOptions) -> Result<(), String> {
+    let path = fetch::fetch(workspace_root(), options.day, options.force)?;
+    println!("Input for day {} is in {}", options.day, path.display());
+    Ok(())
+}
+
+/// Prints a program packed to be shared, which `run --shared` reads back.
+fn run_share(options: &ShareOptions) -> Result<(), String> {
+    let text = read_input(&options.program)?;
+    let program = intcode::read_intcode_input(text.as_bytes())
+        .map_err(|error| format!("Invalid program {}: {}", options.program.display(), error))?;
+    println!("{}", intcode::share::encode(&program));
+    Ok(())
+}
+
+fn run_serve(options: &ServeOptions) -> Result<(), String> {
+    let visualization = serve::visualization(config::get().year(), options.day)
+        .ok_or_else(|| format!("Day {} has nothing to show", options.day))?;
+    let path = match &options.input {
+        Some(path) => path.clone(),
+        None => find_input(options.day, true)?
+            .ok_or_else(|| format!("No input found for day {}", options.day))?,
+    };
+    serve::serve(visualization, read_input(&path)?, options.port)
+}
+
+fn run_check(options: &InputOptions) -> Result<(), String> {
+    let path = match &options.input {
+        Some(path) => path.clone(),
+        None => find_input(options.day, false)?
+            .ok_or_else(|| format!("No input found for day {}", options.day))?,
+    };
+    let anomalies = check::check(config::get().year(), options.day, &read_input(&path)?);
+    if anomalies.is_empty() {
+        println!("{} looks fine", path.display());
+        return Ok(());
+    }
+    println!("{} looks wrong:", path.display());
+    for anomaly in anomalies {
+        println!("  {}", anomaly);
+    }
+    exit(1);
+}
+
+/// Solves every day that has an input, skipping the ones that don't. When
+/// recording, the answers become the expected ones for the golden tests.
+fn run_all(options: &AllOptions) -> Result<(), String> {
+    // Progress and solving in parallel don't mix, so a flag that was passed
+    // wins over the configured default for the other one
+    let defaults = &config::get().defaults;
+    let parallel = options.parallel || (defaults.parallel == Some(true) && !options.progress);
+    let progress = options.progress || (defaults.progress == Some(true) && !parallel);
+    progress::set_enabled(progress);
+    let skipped = |day| eprintln!("Skipping day {}, its input is missing", day);
+    let mut failures = Vec::new();
+    let failed = |day, error| {
+        eprintln!("{}", error);
+        failures.push(day);
+    };
+    let results = if parallel {
+        aoc::solve_all_parallel(skipped, failed)
+    } else {
+        aoc::solve_all(skipped, failed)
+    };
+    println!("{}", all::table(&results));
+    // Recording or comparing a run with days missing would lose their
+    // answers and times, so it stops at the table
+    if !failures.is_empty() {
+        let days: Vec<String> = failures.iter().map(|day| day.to_string()).collect();
+        eprintln!("Could not solve day {}", days.join(", "));
+        exit(1);
+    }
+    // Days solved in parallel fight over the cores, so their times aren't
+    // comparable with the ones in the history
+    if options.history && !parallel {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let threshold = config::get()
+            .regression_threshold
+            .unwrap_or(history::DEFAULT_THRESHOLD);
+        let path = history::history_path(workspace_root());
+        for regression in history::record(&path, timestamp, &results, threshold)? {
+            eprintln!("Warning: {}", regression);
+        }
+    }
+    if options.record {
+        let path = golden::answers_path(workspace_root());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|error| format!("Could not create {}: {}", parent.display(), error))?;
+        }
+        fs::write(&path, golden::format_answers(&results))
+            .map_err(|error| format!("Could not write {}: {}", path.display(), error))?;
+        println!("Recorded the answers in {}", path.display());
+    }
+    if let Some(path) = &options.report {
+        report::save(&results, path)?;
+        println!("Saved the report to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Takes the `-v` flags out of the arguments, returning how verbose the
+/// logs should be.
+fn take_verbosity(args: &mut Vec<String>) -> LevelFilter {
+    let mut verbosity = 0;
+    args.retain(|arg| {
+        let flag = arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|c| c == 'v');
+        if flag {
+            verbosity += arg.len() - 1;
+        }
+        !flag
+    });
+    match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Prints the spans of the solutions and intcode runs as they close, with
+/// the time spent in them, when `AOC_TRACE` holds a filter like `info` or
+/// `intcode=debug`.
+#[cfg(feature = "tracing")]
+fn init_tracing() {
+    use tracing_subscriber::fmt::format::FmtSpan;
+    if let Ok(filter) = env::var("AOC_TRACE") {
+        tracing_subscriber::fmt()
+            .with_env_filter(tracing_subscriber::EnvFilter::new(filter))
+            .with_span_events(FmtSpan::CLOSE)
+            .with_writer(std::io::stderr)
+            .init();
+    }
+}
+
+/// Takes `--year` and its value out of the arguments, wherever they are.
+fn take_year(args: &mut Vec<String>) -> Result<Option<u32>, String> {
+    let index = match args.iter().position(|arg| arg == "--year") {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    args.remove(index);
+    if index == args.len() {
+        return Err("Missing year".to_string());
+    }
+    let year = args.remove(index);
+    let year = year.parse().map_err(|_| format!("Invalid year {}", year))?;
+    config::check_year(year)?;
+    Ok(Some(year))
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let level = take_verbosity(&mut args);
+    let year = match take_year(&mut args) {
+        Ok(year) => year,
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+    // RUST_LOG can still narrow the logs down to some of the crates
+    env_logger::Builder::new()
+        .filter_level(level)
+        .parse_env("RUST_LOG")
+        .init();
+    #[cfg(feature = "tracing")]
+    init_tracing();
+    if let Err(error) = config::init(year) {
+        eprintln!("{}", error);
+        exit(2);
+    }
+    let result = match args.split_first() {
+        Some((command, rest)) if command == "run" => parse_run_options(rest).and_then(|o| run(&o)),
+        Some((command, rest)) if command == "fetch" => {
+            parse_fetch_options(rest).and_then(|o| run_fetch(&o))
+        }
+        Some((command, rest)) if command == "fetch-examples" => {
+            parse_fetch_options(rest).and_then(|o| run_fetch_examples(&o))
+        }
+        Some((command, rest)) if command == "check" => {
+            parse_input_options(rest).and_then(|o| run_check(&o))
+        }
+        Some((command, rest)) if command == "watch" => {
+            parse_input_options(rest).and_then(|o| run_watch(&o))
+        }
+        Some((command, rest)) if command == "all" => {
+            parse_all_options(rest).and_then(|o| run_all(&o))
+        }
+        Some((command, rest)) if command == "serve" => {
+            parse_serve_options(rest).and_then(|o| run_serve(&o))
+        }
+        Some((command, rest)) if command == "share" => {
+            parse_share_options(rest).and_then(|o| run_share(&o))
+        }
+        Some((command, _)) => Err(format!("Unknown command {}", command)),
+        None => Err("Missing command".to_string()),
+    };
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        eprintln!("{}", USAGE);
+        exit(2);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        parse_all_options, parse_fetch_options, parse_input_options, parse_run_options,
+        parse_serve_options, parse_share_options, take_verbosity, take_year, AllOptions,
+        FetchOptions, InputOptions, RunOptions, ServeOptions, ShareOptions,
+    };
+    use aoc_utils::solution;
+    use log::LevelFilter;
+    use std::path::PathBuf;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_run_options() {
+        let options = parse_run_options(&args(&["17", "--input", "in.txt", "--time"]));
+        assert_eq!(
+            options,
+            Ok(RunOptions {
+                day: 17,
+                input: Some(PathBuf::from("in.txt")),
+                inputs_dir: None,
+                shared: None,
+                time: true,
+                progress: false,
+                report: None,
+                profile: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_run_options() {
+        assert!(parse_run_options(&args(&[])).is_err());
+        assert!(parse_run_options(&args(&["26"])).is_err());
+        assert!(parse_run_options(&args(&["1", "2"])).is_err());
+        assert!(parse_run_options(&args(&["1", "--fast"])).is_err());
+        assert!(
+            parse_run_options(&args(&["1", "--input", "a.txt", "--inputs-dir", "inputs"])).is_err()
+        );
+        assert!(
+            parse_run_options(&args(&["1", "--report", "a.csv", "--inputs-dir", "inputs"]))
+                .is_err()
+        );
+        assert!(parse_run_options(&args(&["1", "--profile", "--inputs-dir", "inputs"])).is_err());
+        assert!(
+            parse_run_options(&args(&["2", "--input", "a.txt", "--shared", "AgGAAQ"])).is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_fetch_options() {
+        assert_eq!(
+            parse_fetch_options(&args(&["--force", "3"])),
+            Ok(FetchOptions {
+                day: 3,
+                force: true
+            })
+        );
+        assert!(parse_fetch_options(&args(&["3", "--input", "in.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_input_options() {
+        assert_eq!(
+            parse_input_options(&args(&["18", "--input", "maze.txt"])),
+            Ok(InputOptions {
+                day: 18,
+                input: Some(PathBuf::from("maze.txt")),
+            })
+        );
+        assert!(parse_input_options(&args(&["18", "--force"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_all_options() {
+        assert_eq!(
+            parse_all_options(&args(&["--progress", "--report", "out.csv"])),
+            Ok(AllOptions {
+                record: false,
+                report: Some(PathBuf::from("out.csv")),
+                progress: true,
+                parallel: false,
+                history: true,
+            })
+        );
+        assert!(parse_all_options(&args(&["--parallel"])).is_ok_and(|options| options.parallel));
+        assert!(parse_all_options(&args(&["--parallel", "--progress"])).is_err());
+        assert!(parse_all_options(&args(&["--no-history"])).is_ok_and(|options| !options.history));
+        assert!(parse_all_options(&args(&["3"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_serve_options() {
+        assert_eq!(
+            parse_serve_options(&args(&["20", "--port", "8080"])),
+            Ok(ServeOptions {
+                day: 20,
+                input: None,
+                port: 8080,
+            })
+        );
+        assert!(parse_serve_options(&args(&["20"])).is_ok_and(|options| options.port == 2019));
+        assert!(parse_serve_options(&args(&["20", "--port", "http"])).is_err());
+        assert!(parse_serve_options(&args(&["--port", "80"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_share_options() {
+        assert_eq!(
+            parse_share_options(&args(&["day-2/input.txt"])),
+            Ok(ShareOptions {
+                program: PathBuf::from("day-2/input.txt"),
+            })
+        );
+        assert!(parse_share_options(&args(&[])).is_err());
+        assert!(parse_share_options(&args(&["--input", "a.txt"])).is_err());
+        assert!(parse_share_options(&args(&["a.txt", "b.txt"])).is_err());
+    }
+
+    #[test]
+    fn test_take_verbosity() {
+        let mut options = args(&["-v", "run", "3", "--time"]);
+        assert_eq!(take_verbosity(&mut options), LevelFilter::Debug);
+        assert_eq!(options, args(&["run", "3", "--time"]));
+        assert_eq!(
+            take_verbosity(&mut args(&["all", "-vv"])),
+            LevelFilter::Trace
+        );
+        assert_eq!(
+            take_verbosity(&mut args(&["-v", "all", "-v"])),
+            LevelFilter::Trace
+        );
+        assert_eq!(take_verbosity(&mut args(&["all", "-"])), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn test_take_year() {
+        let mut options = args(&["run", "--year", "2020", "3"]);
+        assert_eq!(take_year(&mut options), Ok(Some(2020)));
+        assert_eq!(options, args(&["run", "3"]));
+        assert_eq!(take_year(&mut args(&["all"])), Ok(None));
+        assert!(take_year(&mut args(&["all", "--year"])).is_err());
+        assert!(take_year(&mut args(&["--year", "2014", "all"])).is_err());
+    }
+
+    #[test]
+    fn test_solutions() {
+        let days: Vec<u32> = solution::registered().iter().map(|r| r.day).collect();
+        assert_eq!(
+            days,
+            [1, 2, 3, 4, 5, 6, 7, 10, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25]
+        );
+        let day_1 = solution::find(1).unwrap();
+        assert_eq!(day_1.part1("12\n14\n1969").as_deref(), Ok("658"));
+        assert_eq!(day_1.part2("1969").as_deref(), Ok("966"));
+        assert!(day_1.part1("12\nfuel").is_err());
+        assert!(solution::find(9).is_none());
+    }
+}
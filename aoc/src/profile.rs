@@ -0,0 +1,48 @@
+//! Flamegraphs of a single run, to find where a slow day spends its time.
+//!
+//! The run is sampled by pprof, which the runner only links when built with
+//! the `profile` feature, best in release:
+//!
+//! ```text
+//! cargo run --release --features profile -- run 18 --profile
+//! ```
+
+use std::path::{Path, PathBuf};
+
+/// How many times a second the run is sampled.
+#[cfg(feature = "profile")]
+const FREQUENCY: i32 = 1000;
+
+/// Where the flamegraph of a day goes, in the current directory.
+pub fn flamegraph_path(day: u32) -> PathBuf {
+    PathBuf::from(format!("flamegraph-day-{}.svg", day))
+}
+
+/// Samples `run` while it runs and writes the flamegraph to `path`.
+#[cfg(feature = "profile")]
+pub fn profile<T>(path: &Path, run: impl FnOnce() -> T) -> Result<T, String> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(FREQUENCY)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .map_err(|error| format!("Could not start the profiler: {}", error))?;
+    let value = run();
+    let report = guard
+        .report()
+        .build()
+        .map_err(|error| format!("Could not build the profile: {}", error))?;
+    if report.data.is_empty() {
+        return Err("The run was over before it could be sampled".to_string());
+    }
+    let file = std::fs::File::create(path)
+        .map_err(|error| format!("Could not create {}: {}", path.display(), error))?;
+    report
+        .flamegraph(file)
+        .map_err(|error| format!("Could not write {}: {}", path.display(), error))?;
+    Ok(value)
+}
+
+#[cfg(not(feature = "profile"))]
+pub fn profile<T>(_path: &Path, _run: impl FnOnce() -> T) -> Result<T, String> {
+    Err("Profiling needs the runner built with --features profile".to_string())
+}
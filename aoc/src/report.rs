@@ -0,0 +1,185 @@
+//! Writing the results of `aoc all` to a file, to compare run times across
+//! machines or repositories. Answers are only written as checksums, so runs
+//! can be told apart when they disagree, but the checksums don't hide them:
+//! most answers are short numbers, and trying every candidate finds the one
+//! that matches.
+
+use crate::all::DayResult;
+use aoc_utils::input::ParseError;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// A checksum of the answer, with FNV-1a, which is stable across platforms
+/// and Rust versions unlike the standard library's hasher. It isn't keyed,
+/// so anyone can work out the answer it came from.
+pub fn answer_hash(answer: &str) -> String {
+    let hash = answer
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{:016x}", hash)
+}
+
+/// The time taken by a part of a day, or by parsing its input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub day: u32,
+    pub part: &'static str,
+    pub hash: Option<String>,
+    pub elapsed: Duration,
+}
+
+// Parsing gets a row of its own, without an answer
+pub fn rows(results: &[DayResult]) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for result in results {
+        if let Some(parse_time) = result.parse_time {
+            rows.push(Row {
+                day: result.day,
+                part: "parse",
+                hash: None,
+                elapsed: parse_time,
+            });
+        }
+        rows.push(Row {
+            day: result.day,
+            part: "1",
+            hash: Some(answer_hash(&result.part1)),
+            elapsed: result.part1_time,
+        });
+        rows.push(Row {
+            day: result.day,
+            part: "2",
+            hash: Some(answer_hash(&result.part2)),
+            elapsed: result.part2_time,
+        });
+    }
+    rows
+}
+
+pub fn csv(results: &[DayResult]) -> String {
+    let mut out = String::from("day,part,answer_hash,elapsed_us\n");
+    for row in rows(results) {
+        out.push_str(&format!(
+            "{},{},{},{}\n",
+            row.day,
+            row.part,
+            row.hash.unwrap_or_default(),
+            row.elapsed.as_micros()
+        ));
+    }
+    out
+}
+
+fn parse_row(line: &str) -> Result<Row, &'static str> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    if fields.len() != 4 {
+        return Err("expected day,part,answer_hash,elapsed_us");
+    }
+    let day = fields[0].parse().map_err(|_| "invalid day")?;
+    let part = match fields[1] {
+        "parse" => "parse",
+        "1" => "1",
+        "2" => "2",
+        _ => return Err("part must be parse, 1 or 2"),
+    };
+    let hash = Some(fields[2].to_string()).filter(|hash| !hash.is_empty());
+    let micros = fields[3].parse().map_err(|_| "invalid elapsed time")?;
+    Ok(Row {
+        day,
+        part,
+        hash,
+        elapsed: Duration::from_micros(micros),
+    })
+}
+
+/// Reads back a report written by [`csv`].
+pub fn parse_csv(text: &str) -> Result<Vec<Row>, ParseError> {
+    text.lines()
+        .enumerate()
+        .skip(1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            parse_row(line).map_err(|message| ParseError::new(index + 1, 1, message))
+        })
+        .collect()
+}
+
+pub fn json(results: &[DayResult]) -> String {
+    let entries: Vec<String> = rows(results)
+        .into_iter()
+        .map(|row| {
+            let hash = row
+                .hash
+                .map_or_else(|| "null".to_string(), |hash| format!("\"{}\"", hash));
+            format!(
+                "  {{\"day\": {}, \"part\": \"{}\", \"answer_hash\": {}, \"elapsed_us\": {}}}",
+                row.day,
+                row.part,
+                hash,
+                row.elapsed.as_micros()
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+/// Writes the report as JSON or CSV, depending on the file's extension.
+pub fn save(results: &[DayResult], path: &Path) -> Result<(), String> {
+    let report = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => json(results),
+        Some("csv") => csv(results),
+        _ => return Err("Reports can be saved as .json or .csv".to_string()),
+    };
+    fs::write(path, report)
+        .map_err(|error| format!("Could not write {}: {}", path.display(), error))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{answer_hash, csv, json, parse_csv, rows};
+    use crate::all::DayResult;
+    use std::time::Duration;
+
+    fn results() -> Vec<DayResult> {
+        vec![DayResult {
+            day: 6,
+            parse_time: Some(Duration::from_micros(7)),
+            part1: "344238".to_string(),
+            part1_time: Duration::from_micros(52),
+            part2: "436".to_string(),
+            part2_time: Duration::from_micros(9),
+        }]
+    }
+
+    #[test]
+    fn test_answer_hash() {
+        assert_eq!(answer_hash(""), "cbf29ce484222325");
+        assert_eq!(answer_hash("a"), "af63dc4c8601ec8c");
+        assert_ne!(answer_hash("436"), answer_hash("437"));
+    }
+
+    #[test]
+    fn test_csv() {
+        let expected = format!(
+            "day,part,answer_hash,elapsed_us\n6,parse,,7\n6,1,{},52\n6,2,{},9\n",
+            answer_hash("344238"),
+            answer_hash("436")
+        );
+        assert_eq!(csv(&results()), expected);
+        assert_eq!(parse_csv(&expected).unwrap(), rows(&results()));
+        assert!(parse_csv("day,part,answer_hash,elapsed_us\n6,3,,1\n").is_err());
+    }
+
+    #[test]
+    fn test_json() {
+        let report = json(&results());
+        assert!(report.starts_with("[\n  {\"day\": 6, \"part\": \"parse\", \"answer_hash\": null"));
+        assert!(report.contains(&format!(
+            "{{\"day\": 6, \"part\": \"2\", \"answer_hash\": \"{}\", \"elapsed_us\": 9}}\n]",
+            answer_hash("436")
+        )));
+    }
+}
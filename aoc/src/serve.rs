@@ -0,0 +1,502 @@
+//! `aoc serve`, a local web page that plays a day's animation as it runs.
+//! The page gets the frames over a websocket, and can zoom in and out or
+//! pause the solution, which waits for the page before drawing again.
+//!
+//! Only what the page needs of the websocket protocol is here: the
+//! handshake, unfragmented text frames, and closing.
+//!
+//! `/metrics` reports the pages connected, what was sent to them and the
+//! intcode work done for them in the Prometheus text format, to keep an eye
+//! on a server left running.
+
+use aoc_utils::base64::{self, STANDARD};
+use aoc_utils::solution::DEFAULT_YEAR;
+use aoc_utils::terminal::FrameSink;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+const PAGE: &str = include_str!("serve.html");
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// The page only sends "pause" and "resume", anything much longer is not
+// from it
+const MAX_FRAME_LENGTH: u64 = 4096;
+
+const TEXT: u8 = 0x1;
+const CLOSE: u8 = 0x8;
+
+/// Counts kept while serving, for `/metrics`.
+#[derive(Default)]
+struct Metrics {
+    connected: AtomicU64,
+    connections: AtomicU64,
+    frames: AtomicU64,
+    errors: AtomicU64,
+    instructions: AtomicU64,
+    inputs: AtomicU64,
+    outputs: AtomicU64,
+}
+
+impl Metrics {
+    fn render(&self) -> String {
+        let mut text = String::new();
+        let metrics = [
+            (
+                "clients",
+                "gauge",
+                "Pages playing an animation",
+                &self.connected,
+            ),
+            (
+                "connections_total",
+                "counter",
+                "Pages that connected",
+                &self.connections,
+            ),
+            (
+                "frames_total",
+                "counter",
+                "Frames sent to pages",
+                &self.frames,
+            ),
+            (
+                "errors_total",
+                "counter",
+                "Connections that failed",
+                &self.errors,
+            ),
+            (
+                "instructions_total",
+                "counter",
+                "Intcode instructions run for pages",
+                &self.instructions,
+            ),
+            (
+                "inputs_total",
+                "counter",
+                "Values read by intcode machines",
+                &self.inputs,
+            ),
+            (
+                "outputs_total",
+                "counter",
+                "Values printed by intcode machines",
+                &self.outputs,
+            ),
+        ];
+        for (name, kind, help, value) in metrics {
+            text.push_str(&format!(
+                "# HELP aoc_serve_{0} {1}\n\
+                 # TYPE aoc_serve_{0} {2}\n\
+                 aoc_serve_{0} {3}\n",
+                name,
+                help,
+                kind,
+                value.load(Ordering::SeqCst)
+            ));
+        }
+        text
+    }
+}
+
+/// Plays a day's animation from its input on a frame sink.
+pub type Visualization = fn(&str, &mut dyn FrameSink) -> Result<(), String>;
+
+/// The animation of a day of a year, for the days that have one.
+pub fn visualization(year: u32, day: u32) -> Option<Visualization> {
+    if year != DEFAULT_YEAR {
+        return None;
+    }
+    match day {
+        17 => Some(|input, sink| {
+            let memory = day_17::parse(input)?;
+            let routines = day_17::find_routines(&memory)?;
+            day_17::play_video(&memory, &routines, sink).map_err(|error| error.to_string())
+        }),
+        20 => Some(|input, sink| {
+            let maze = day_20::Maze::parse(input)?;
+            day_20::play_search(&maze, true, sink)
+                .map(|_| ())
+                .map_err(|error| error.to_string())
+        }),
+        _ => None,
+    }
+}
+
+/// Serves the page on `port` until the process is stopped. Every page that
+/// connects gets the animation played from the start.
+pub fn serve(visualization: Visualization, input: String, port: u16) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|error| format!("Could not listen on port {}: {}", port, error))?;
+    println!("Serving on http://127.0.0.1:{}/", port);
+    let input = Arc::new(input);
+    let metrics = Arc::new(Metrics::default());
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("Could not accept a connection: {}", error);
+                metrics.errors.fetch_add(1, Ordering::SeqCst);
+                continue;
+            }
+        };
+        let input = Arc::clone(&input);
+        let metrics = Arc::clone(&metrics);
+        thread::spawn(move || {
+            if let Err(error) = handle(stream, visualization, &input, &metrics) {
+                eprintln!("{}", error);
+                metrics.errors.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle(
+    stream: TcpStream,
+    visualization: Visualization,
+    input: &str,
+    metrics: &Arc<Metrics>,
+) -> Result<(), String> {
+    let mut reader = BufReader::new(stream.try_clone().map_err(|error| error.to_string())?);
+    let mut request = String::new();
+    reader
+        .read_line(&mut request)
+        .map_err(|error| error.to_string())?;
+    let mut key = None;
+    loop {
+        let mut line = String::new();
+        if reader
+            .read_line(&mut line)
+            .map_err(|error| error.to_string())?
+            == 0
+        {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+    let mut stream = stream;
+    match (path, key) {
+        ("/", _) => respond(&mut stream, "200 OK", "text/html; charset=utf-8", PAGE),
+        ("/metrics", _) => respond(
+            &mut stream,
+            "200 OK",
+            "text/plain; version=0.0.4",
+            &metrics.render(),
+        ),
+        ("/frames", Some(key)) => {
+            write!(
+                stream,
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(&key)
+            )
+            .map_err(|error| error.to_string())?;
+            play(stream, reader, visualization, input, Arc::clone(metrics))
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "Not found"),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, kind: &str, body: &str) -> Result<(), String> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        kind,
+        body.len(),
+        body
+    )
+    .map_err(|error| error.to_string())
+}
+
+// Messages from the page pause and resume the animation, from a thread of
+// their own so they are heard while a frame is being waited for
+fn play<R: Read + Send + 'static>(
+    stream: TcpStream,
+    mut reader: R,
+    visualization: Visualization,
+    input: &str,
+    metrics: Arc<Metrics>,
+) -> Result<(), String> {
+    let paused = Arc::new(AtomicBool::new(false));
+    let closed = Arc::new(AtomicBool::new(false));
+    {
+        let paused = Arc::clone(&paused);
+        let closed = Arc::clone(&closed);
+        thread::spawn(move || {
+            while let Ok((opcode, payload)) = read_frame(&mut reader) {
+                match (opcode, &payload[..]) {
+                    (CLOSE, _) => break,
+                    (TEXT, b"pause") => paused.store(true, Ordering::SeqCst),
+                    (TEXT, b"resume") => paused.store(false, Ordering::SeqCst),
+                    _ => {}
+                }
+            }
+            closed.store(true, Ordering::SeqCst);
+        });
+    }
+    metrics.connections.fetch_add(1, Ordering::SeqCst);
+    metrics.connected.fetch_add(1, Ordering::SeqCst);
+    let mut socket = Socket {
+        stream,
+        paused,
+        closed,
+        metrics,
+    };
+    let result = visualization(input, &mut socket);
+    let _ = write_frame(&mut socket.stream, CLOSE, &[]);
+    socket.metrics.connected.fetch_sub(1, Ordering::SeqCst);
+    result
+}
+
+struct Socket {
+    stream: TcpStream,
+    paused: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+    metrics: Arc<Metrics>,
+}
+
+impl FrameSink for Socket {
+    fn draw(&mut self, frame: &str) -> io::Result<()> {
+        while self.paused.load(Ordering::SeqCst) && !self.closed.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(50));
+        }
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "The page was closed",
+            ));
+        }
+        write_frame(&mut self.stream, TEXT, frame.as_bytes())?;
+        self.metrics.frames.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn machine_ran(&mut self, instructions: u64, inputs: u64, outputs: u64) {
+        let metrics = &self.metrics;
+        metrics
+            .instructions
+            .fetch_add(instructions, Ordering::SeqCst);
+        metrics.inputs.fetch_add(inputs, Ordering::SeqCst);
+        metrics.outputs.fetch_add(outputs, Ordering::SeqCst);
+    }
+}
+
+fn accept_key(key: &str) -> String {
+    base64::encode(
+        &sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()),
+        STANDARD,
+        true,
+    )
+}
+
+/// Writes an unmasked frame, the way servers send them.
+fn write_frame<W: Write>(out: &mut W, opcode: u8, payload: &[u8]) -> io::Result<()> {
+    let mut header = vec![0x80 | opcode];
+    match payload.len() {
+        length if length < 126 => header.push(length as u8),
+        length if length <= 0xffff => {
+            header.push(126);
+            header.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            header.push(127);
+            header.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    out.write_all(&header)?;
+    out.write_all(payload)?;
+    out.flush()
+}
+
+/// Reads a frame, unmasking it if it was masked like the ones browsers send.
+fn read_frame<R: Read>(input: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0; 2];
+    input.read_exact(&mut header)?;
+    let opcode = header[0] & 0x0f;
+    let masked = header[1] & 0x80 != 0;
+    let length = match header[1] & 0x7f {
+        126 => {
+            let mut length = [0; 2];
+            input.read_exact(&mut length)?;
+            u16::from_be_bytes(length) as u64
+        }
+        127 => {
+            let mut length = [0; 8];
+            input.read_exact(&mut length)?;
+            u64::from_be_bytes(length)
+        }
+        length => length as u64,
+    };
+    if length > MAX_FRAME_LENGTH {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Frame of {} bytes is too long", length),
+        ));
+    }
+    let mut mask = [0; 4];
+    if masked {
+        input.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0; length as usize];
+    input.read_exact(&mut payload)?;
+    for (index, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[index % 4];
+    }
+    Ok((opcode, payload))
+}
+
+fn sha1(message: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+
+    for block in padded.chunks(64) {
+        let mut words = [0u32; 80];
+        for (index, word) in block.chunks(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..80 {
+            words[index] =
+                (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16])
+                    .rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let next = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = next;
+        }
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e].iter()) {
+            *value = value.wrapping_add(*added);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (chunk, value) in digest.chunks_mut(4).zip(state.iter()) {
+        chunk.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        accept_key, read_frame, sha1, visualization, write_frame, Metrics, DEFAULT_YEAR, TEXT,
+    };
+    use std::io;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_sha1() {
+        let hex: String = sha1(b"abc").iter().map(|b| format!("{:02x}", b)).collect();
+        assert_eq!(hex, "a9993e364706816aba3e25717850c26c9cd0d89d");
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // The example from RFC 6455
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_frames() {
+        let mut out = Vec::new();
+        write_frame(&mut out, TEXT, b"#.#").unwrap();
+        assert_eq!(out, [0x81, 3, b'#', b'.', b'#']);
+        assert_eq!(read_frame(&mut &out[..]).unwrap(), (TEXT, b"#.#".to_vec()));
+
+        let long = vec![b'.'; 300];
+        let mut out = Vec::new();
+        write_frame(&mut out, TEXT, &long).unwrap();
+        assert_eq!(out[1..4], [126, 1, 44]);
+        assert_eq!(read_frame(&mut &out[..]).unwrap().1, long);
+
+        // "pause", masked like a browser sends it
+        let mask = [1, 2, 3, 4];
+        let mut masked = vec![0x81, 0x80 | 5];
+        masked.extend_from_slice(&mask);
+        masked.extend(b"pause".iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        assert_eq!(
+            read_frame(&mut &masked[..]).unwrap(),
+            (TEXT, b"pause".to_vec())
+        );
+
+        // A header claiming 2^63 bytes is refused before reading any
+        let mut huge = vec![0x81, 127];
+        huge.extend_from_slice(&(1u64 << 63).to_be_bytes());
+        let error = read_frame(&mut &huge[..]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let metrics = Metrics::default();
+        metrics.connected.store(2, Ordering::SeqCst);
+        metrics.frames.store(120, Ordering::SeqCst);
+        let text = metrics.render();
+        assert!(text.starts_with(
+            "# HELP aoc_serve_clients Pages playing an animation\n\
+             # TYPE aoc_serve_clients gauge\n\
+             aoc_serve_clients 2\n"
+        ));
+        assert!(text.contains("\naoc_serve_frames_total 120\n"));
+        assert!(text.contains("\naoc_serve_errors_total 0\n"));
+        metrics.instructions.store(5000, Ordering::SeqCst);
+        let text = metrics.render();
+        assert!(text.contains("\naoc_serve_instructions_total 5000\n"));
+        assert!(text.contains("# TYPE aoc_serve_inputs_total counter\n"));
+        assert!(text.contains("\naoc_serve_outputs_total 0\n"));
+    }
+
+    #[test]
+    fn test_visualizations() {
+        assert!(visualization(DEFAULT_YEAR, 17).is_some());
+        assert!(visualization(DEFAULT_YEAR, 20).is_some());
+        assert!(visualization(DEFAULT_YEAR, 1).is_none());
+        assert!(visualization(2020, 17).is_none());
+    }
+}
@@ -0,0 +1,232 @@
+//! Solving a day again every time its code or input changes, while working
+//! on it.
+//!
+//! A change to the code needs a new build, so every run goes through
+//! `cargo run`, asking it for a report of the times taken. The reports of
+//! consecutive runs are compared to show whether a change made the day any
+//! faster.
+
+use crate::all::format_duration;
+use crate::report::{self, Row};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often the files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The last time every file was modified, to notice the ones that change.
+pub type Stamps = BTreeMap<PathBuf, SystemTime>;
+
+/// The crates in the workspace a day's crate depends on, from the `path`
+/// dependencies of its manifest.
+fn local_dependencies(root: &Path, manifest: &str) -> Vec<PathBuf> {
+    manifest
+        .lines()
+        .filter_map(|line| {
+            let start = line.find("path = \"")? + "path = \"".len();
+            let end = line[start..].find('"')? + start;
+            Path::new(&line[start..end])
+                .file_name()
+                .map(|name| root.join(name))
+        })
+        .collect()
+}
+
+/// The files and directories to watch for a day: its crate, the crates of
+/// the workspace it uses, and its input.
+pub fn watched_paths(root: &Path, day: u32, input: &Path) -> Vec<PathBuf> {
+    let crate_dir = crate::day_dir(root, day);
+    let mut paths = vec![crate_dir.join("Cargo.toml"), crate_dir.join("src")];
+    let manifest = fs::read_to_string(crate_dir.join("Cargo.toml")).unwrap_or_default();
+    for dependency in local_dependencies(root, &manifest) {
+        paths.push(dependency.join("src"));
+    }
+    paths.push(input.to_path_buf());
+    paths
+}
+
+fn collect_stamps(path: &Path, stamps: &mut Stamps) {
+    if path.is_dir() {
+        if let Ok(entries) = fs::read_dir(path) {
+            for entry in entries.flatten() {
+                collect_stamps(&entry.path(), stamps);
+            }
+        }
+    } else if let Ok(modified) = fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        stamps.insert(path.to_path_buf(), modified);
+    }
+}
+
+pub fn stamps(paths: &[PathBuf]) -> Stamps {
+    let mut stamps = Stamps::new();
+    for path in paths {
+        collect_stamps(path, &mut stamps);
+    }
+    stamps
+}
+
+fn part_name(part: &str) -> String {
+    match part {
+        "parse" => "Parse ".to_string(),
+        part => format!("Part {}", part),
+    }
+}
+
+fn change(elapsed: Duration, before: Duration) -> String {
+    let difference = elapsed.as_secs_f64() - before.as_secs_f64();
+    let sign = if difference < 0.0 { "-" } else { "+" };
+    let amount = format_duration(Duration::from_secs_f64(difference.abs()));
+    if before.is_zero() {
+        format!("{}{}", sign, amount)
+    } else {
+        format!(
+            "{}{}, {:+.0}%",
+            sign,
+            amount,
+            difference / before.as_secs_f64() * 100.0
+        )
+    }
+}
+
+/// The times of a run, next to how they changed since the previous one
+/// and whether the answers did.
+pub fn compare(previous: &[Row], current: &[Row]) -> Vec<String> {
+    current
+        .iter()
+        .map(|row| {
+            let mut line = format!("{} - {}", part_name(row.part), format_duration(row.elapsed));
+            if let Some(before) = previous.iter().find(|before| before.part == row.part) {
+                line.push_str(&format!(" ({})", change(row.elapsed, before.elapsed)));
+                if before.hash != row.hash {
+                    line.push_str(", new answer");
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// Builds and solves the day with its input, returning the report of the
+/// run, or `None` if the build or the solution failed. The answers are
+/// printed by the run itself.
+fn solve_once(root: &Path, year: u32, day: u32, input: &Path) -> Result<Option<Vec<Row>>, String> {
+    let report_path = std::env::temp_dir().join(format!("aoc-watch-{}.csv", std::process::id()));
+    let mut command = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".into()));
+    command.current_dir(root).args(["run", "--quiet"]);
+    // Times are only comparable when built like the runner watching them
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+    command
+        .args(["-p", "aoc", "--", "--year", &year.to_string()])
+        .args(["run", &day.to_string(), "--input"])
+        .arg(input)
+        .arg("--report")
+        .arg(&report_path);
+    let status = command
+        .status()
+        .map_err(|error| format!("Could not run cargo: {}", error))?;
+    if !status.success() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&report_path)
+        .map_err(|error| format!("Could not read {}: {}", report_path.display(), error))?;
+    let _ = fs::remove_file(&report_path);
+    report::parse_csv(&text)
+        .map(Some)
+        .map_err(|error| format!("Invalid report: {}", error))
+}
+
+/// Solves the day now and after every change, until interrupted.
+pub fn watch(root: &Path, day: u32, input: &Path) -> Result<(), String> {
+    let year = crate::config::get().year();
+    let paths = watched_paths(root, day, input);
+    let mut seen = Stamps::new();
+    let mut previous: Vec<Row> = Vec::new();
+    loop {
+        let current = stamps(&paths);
+        if current != seen {
+            seen = current;
+            println!("Solving day {}", day);
+            match solve_once(root, year, day, input)? {
+                Some(rows) => {
+                    for line in compare(&previous, &rows) {
+                        println!("  {}", line);
+                    }
+                    previous = rows;
+                }
+                None => eprintln!("The run failed, waiting for another change"),
+            }
+            println!();
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compare, local_dependencies, stamps};
+    use crate::report::Row;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    fn row(part: &'static str, hash: &str, micros: u64) -> Row {
+        Row {
+            day: 6,
+            part,
+            hash: Some(hash.to_string()).filter(|hash| !hash.is_empty()),
+            elapsed: Duration::from_micros(micros),
+        }
+    }
+
+    #[test]
+    fn test_local_dependencies() {
+        let manifest = "[dependencies]\n\
+            aoc-utils = { path = \"../aoc-utils\" }\n\
+            intcode = { path = \"../intcode\" }\n\
+            serde = \"1\"\n";
+        assert_eq!(
+            local_dependencies(Path::new("/aoc"), manifest),
+            [
+                PathBuf::from("/aoc/aoc-utils"),
+                PathBuf::from("/aoc/intcode")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let first = vec![row("parse", "", 10), row("1", "a", 2000), row("2", "b", 0)];
+        assert_eq!(
+            compare(&[], &first),
+            ["Parse  - 10µs", "Part 1 - 2.00ms", "Part 2 - 0µs"]
+        );
+        let second = vec![row("parse", "", 10), row("1", "a", 1500), row("2", "c", 4)];
+        assert_eq!(
+            compare(&first, &second),
+            [
+                "Parse  - 10µs (+0µs, +0%)",
+                "Part 1 - 1.50ms (-500µs, -25%)",
+                "Part 2 - 4µs (+4µs), new answer",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stamps() {
+        let dir = std::env::temp_dir().join(format!("aoc-watch-test-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src").join("lib.rs"), "").unwrap();
+        let found = stamps(&[dir.join("src"), dir.join("missing.txt")]);
+        assert_eq!(
+            found.keys().collect::<Vec<_>>(),
+            [&dir.join("src").join("lib.rs")]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,54 @@
+use aoc_utils::input::lines_of;
+use aoc_utils::Puzzle;
+
+fn module_fuel(mass: i64) -> i64 {
+    (mass / 3) - 2
+}
+
+fn calculate_final_fuel(mut fuel: i64) -> i64 {
+    let mut extra = 0;
+    while fuel > 0 {
+        extra += fuel;
+        fuel = (fuel / 3) - 2;
+    }
+    extra
+}
+
+pub fn get_modules_fuel(masses: &[i64]) -> i64 {
+    masses
+        .iter()
+        .map(|mass| module_fuel(*mass))
+        .map(calculate_final_fuel)
+        .sum()
+}
+
+pub struct Day1;
+
+impl Puzzle for Day1 {
+    type Input = Vec<i64>;
+
+    fn parse(&self, input: &str) -> Result<Vec<i64>, String> {
+        lines_of(input).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, masses: &Vec<i64>) -> Result<String, String> {
+        let fuel: i64 = masses.iter().map(|mass| module_fuel(*mass)).sum();
+        Ok(fuel.to_string())
+    }
+
+    fn part2(&self, masses: &Vec<i64>) -> Result<String, String> {
+        Ok(get_modules_fuel(masses).to_string())
+    }
+}
+
+aoc_utils::register_solution!(1, Day1);
+
+#[cfg(test)]
+mod test {
+    use super::Day1;
+
+    aoc_utils::example_tests!(Day1, {
+        part1_masses: part1("masses.txt") == "34241",
+        part2_masses: part2("masses.txt") == "51316",
+    });
+}
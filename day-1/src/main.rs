@@ -1,25 +1,6 @@
-use std::io::{stdin, BufRead};
-
-fn calculate_final_fuel(mut fuel: i64) -> i64 {
-    let mut extra = 0;
-    while fuel > 0 {
-        extra += fuel;
-        fuel = (fuel / 3) - 2;
-    }
-    extra
-}
-
-fn get_modules_fuel() -> i64 {
-    stdin()
-        .lock()
-        .lines()
-        .map(|line| line.unwrap().parse::<i64>().unwrap())
-        .map(|mass| (mass / 3) - 2)
-        .map(calculate_final_fuel)
-        .sum()
-}
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_1::Day1;
 
 fn main() {
-    let total_fuel = get_modules_fuel();
-    println!("{}", total_fuel);
+    print_answers(&Day1, &read_stdin(), timing_requested());
 }
@@ -0,0 +1,215 @@
+use aoc_utils::math::gcd;
+use aoc_utils::solution::or_no_solution;
+use aoc_utils::{Grid, Point2, Puzzle};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// How many asteroids the laser destroys up to the one part 2 asks for.
+pub const NTH_VAPORIZED: usize = 200;
+
+/// Every asteroid on the map, `#`, or `X` for the station in some examples.
+/// `y` grows downwards.
+pub fn parse_asteroids(input: &str) -> Result<Vec<Point2>, String> {
+    let map = Grid::parse(input.trim(), |c| match c {
+        '#' | 'X' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(format!("Invalid map cell {:?}", c)),
+    })?;
+    Ok(map
+        .iter()
+        .filter(|(_, asteroid)| **asteroid)
+        .map(|(point, _)| point)
+        .collect())
+}
+
+/// The direction from one asteroid to another, divided down so that every
+/// asteroid on the same line of sight has the same one.
+pub fn direction(from: Point2, to: Point2) -> Point2 {
+    let delta = to - from;
+    let divisor = gcd(delta.x, delta.y);
+    Point2::new(delta.x / divisor, delta.y / divisor)
+}
+
+// 0 for directions from straight up to just before straight down, going
+// clockwise, and 1 for the rest
+fn half(direction: Point2) -> u8 {
+    if direction.x > 0 || (direction.x == 0 && direction.y < 0) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Orders directions clockwise, starting straight up, the way the laser
+/// turns. Within a half turn, `a` comes before `b` when turning from `a` to
+/// `b` is clockwise, which the sign of their cross product tells exactly.
+pub fn clockwise(a: Point2, b: Point2) -> Ordering {
+    half(a)
+        .cmp(&half(b))
+        .then_with(|| (a.y * b.x).cmp(&(a.x * b.y)))
+}
+
+/// How many asteroids can be seen from the station, one for every line of
+/// sight.
+pub fn visible(asteroids: &[Point2], station: Point2) -> usize {
+    asteroids
+        .iter()
+        .filter(|asteroid| **asteroid != station)
+        .map(|asteroid| direction(station, *asteroid))
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// The asteroid that sees the most others, and how many it sees.
+pub fn best_station(asteroids: &[Point2]) -> Option<(Point2, usize)> {
+    asteroids
+        .iter()
+        .map(|station| (*station, visible(asteroids, *station)))
+        .max_by_key(|(_, visible)| *visible)
+}
+
+/// Every other asteroid, in the order the laser on the station destroys
+/// them. The laser only hits the closest asteroid on each line of sight
+/// every turn.
+pub fn vaporization_order(asteroids: &[Point2], station: Point2) -> Vec<Point2> {
+    let mut lines: HashMap<Point2, Vec<Point2>> = HashMap::new();
+    for asteroid in asteroids.iter().filter(|asteroid| **asteroid != station) {
+        lines
+            .entry(direction(station, *asteroid))
+            .or_default()
+            .push(*asteroid);
+    }
+    let mut lines: Vec<(Point2, Vec<Point2>)> = lines.into_iter().collect();
+    lines.sort_by(|(a, _), (b, _)| clockwise(*a, *b));
+    for (_, line) in lines.iter_mut() {
+        line.sort_by_key(|asteroid| asteroid.manhattan_distance(station));
+    }
+
+    let turns = lines.iter().map(|(_, line)| line.len()).max().unwrap_or(0);
+    (0..turns)
+        .flat_map(|turn| lines.iter().filter_map(move |(_, line)| line.get(turn)))
+        .copied()
+        .collect()
+}
+
+pub struct Day10;
+
+impl Puzzle for Day10 {
+    type Input = Vec<Point2>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Point2>, String> {
+        parse_asteroids(input)
+    }
+
+    fn part1(&self, asteroids: &Vec<Point2>) -> Result<String, String> {
+        Ok(or_no_solution(
+            best_station(asteroids).map(|(_, visible)| visible),
+        ))
+    }
+
+    fn part2(&self, asteroids: &Vec<Point2>) -> Result<String, String> {
+        let answer = best_station(asteroids).and_then(|(station, _)| {
+            let order = vaporization_order(asteroids, station);
+            let asteroid = order.get(NTH_VAPORIZED - 1)?;
+            Some(asteroid.x * 100 + asteroid.y)
+        });
+        Ok(or_no_solution(answer))
+    }
+}
+
+aoc_utils::register_solution!(10, Day10);
+
+#[cfg(test)]
+mod test {
+    use super::{best_station, clockwise, direction, parse_asteroids, vaporization_order, Day10};
+    use aoc_utils::Point2;
+    use std::cmp::Ordering;
+
+    aoc_utils::example_tests!(Day10, {
+        part1_small: part1("small.txt") == "8",
+        part1_large: part1("large.txt") == "210",
+        part2_large: part2("large.txt") == "802",
+    });
+
+    #[test]
+    fn test_best_station() {
+        let asteroids = parse_asteroids(include_str!("../examples/large.txt")).unwrap();
+        assert_eq!(best_station(&asteroids), Some((Point2::new(11, 13), 210)));
+    }
+
+    #[test]
+    fn test_vaporization_order() {
+        let asteroids = parse_asteroids(
+            ".#....#####...#..
+##...##.#####..##
+##...#...#.#####.
+..#.....X...###..
+..#.#.....#....##",
+        )
+        .unwrap();
+        let order = vaporization_order(&asteroids, Point2::new(8, 3));
+        let first: Vec<(i64, i64)> = order[..9].iter().map(|point| (point.x, point.y)).collect();
+        assert_eq!(
+            first,
+            [
+                (8, 1),
+                (9, 0),
+                (9, 1),
+                (10, 0),
+                (9, 2),
+                (11, 1),
+                (12, 1),
+                (11, 2),
+                (15, 1)
+            ]
+        );
+        assert_eq!(order.len(), asteroids.len() - 1);
+    }
+
+    #[test]
+    fn test_colinear() {
+        let station = Point2::new(0, 3);
+        let asteroids = [
+            station,
+            Point2::new(0, 0),
+            Point2::new(0, 2),
+            Point2::new(0, 1),
+            Point2::new(2, 1),
+            Point2::new(1, 2),
+        ];
+        assert_eq!(direction(station, Point2::new(2, 1)), Point2::new(1, -1));
+        assert_eq!(
+            vaporization_order(&asteroids, station),
+            [
+                Point2::new(0, 2),
+                Point2::new(1, 2),
+                Point2::new(0, 1),
+                Point2::new(2, 1),
+                Point2::new(0, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_clockwise() {
+        let turn = [
+            Point2::new(0, -1),
+            Point2::new(1, -3),
+            Point2::new(1, 0),
+            Point2::new(3, 1),
+            Point2::new(0, 1),
+            Point2::new(-1, 1),
+            Point2::new(-1, 0),
+            Point2::new(-1, -100),
+        ];
+        for (i, a) in turn.iter().enumerate() {
+            for (j, b) in turn.iter().enumerate() {
+                assert_eq!(clockwise(*a, *b), i.cmp(&j), "{} and {}", a, b);
+            }
+        }
+        assert_eq!(
+            clockwise(Point2::new(0, -1), Point2::new(0, -1)),
+            Ordering::Equal
+        );
+    }
+}
@@ -0,0 +1,6 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_10::Day10;
+
+fn main() {
+    print_answers(&Day10, &read_stdin(), timing_requested());
+}
@@ -0,0 +1,240 @@
+use aoc_utils::input::ParseError;
+use aoc_utils::math::lcm_all;
+use aoc_utils::{Point3, Puzzle};
+
+/// Steps simulated for part 1 when no other count is given.
+pub const STEPS: usize = 1000;
+
+const AXES: [&str; 3] = ["x", "y", "z"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Moon {
+    pub position: Point3,
+    pub velocity: Point3,
+}
+
+impl Moon {
+    /// A moon standing still at the position.
+    pub fn new(position: Point3) -> Self {
+        Self {
+            position,
+            velocity: Point3::ORIGIN,
+        }
+    }
+
+    pub fn energy(&self) -> u64 {
+        self.position.manhattan() * self.velocity.manhattan()
+    }
+}
+
+// A position like `<x=-1, y=0, z=2>`, with errors on line 1
+fn parse_position(line: &str) -> Result<Point3, ParseError> {
+    let error = |offset: usize, message: String| ParseError::new(1, offset + 1, message);
+    let start = line.len() - line.trim_start().len();
+    let end = line.trim_end().len();
+    if !line[start..].starts_with('<') {
+        return Err(error(start, "expected '<'".to_string()));
+    }
+    if end == start + 1 || !line[..end].ends_with('>') {
+        return Err(error(end, "expected '>'".to_string()));
+    }
+
+    let fields: Vec<&str> = line[start + 1..end - 1].split(',').collect();
+    let mut values = [0; 3];
+    let mut offset = start + 1;
+    for (index, field) in fields.iter().enumerate() {
+        let name_offset = offset + field.len() - field.trim_start().len();
+        let axis = match AXES.get(index) {
+            Some(axis) => axis,
+            None => return Err(error(name_offset, "expected only x, y and z".to_string())),
+        };
+        let (name, value) = match field.split_once('=') {
+            Some((name, value)) if name.trim() == *axis => (name, value),
+            _ => return Err(error(name_offset, format!("expected {}=", axis))),
+        };
+        let value_offset = offset + name.len() + 1 + value.len() - value.trim_start().len();
+        values[index] = value
+            .trim()
+            .parse()
+            .map_err(|_| error(value_offset, format!("invalid {} {:?}", axis, value.trim())))?;
+        offset += field.len() + 1;
+    }
+    if let Some(axis) = AXES.get(fields.len()) {
+        return Err(error(end - 1, format!("missing {}", axis)));
+    }
+    Ok(Point3::new(values[0], values[1], values[2]))
+}
+
+/// One moon for every non-blank line, standing still at its position.
+pub fn parse_moons(input: &str) -> Result<Vec<Moon>, ParseError> {
+    let moons = input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            parse_position(line)
+                .map(Moon::new)
+                .map_err(|error| error.on_line(index + 1))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if moons.is_empty() {
+        return Err(ParseError::new(1, 1, "no moons"));
+    }
+    Ok(moons)
+}
+
+/// Applies gravity between every pair of moons, then moves them all.
+pub fn step(moons: &mut [Moon]) {
+    for i in 0..moons.len() {
+        for j in i + 1..moons.len() {
+            let pull = moons[i].position.pull_towards(moons[j].position);
+            moons[i].velocity += pull;
+            moons[j].velocity -= pull;
+        }
+    }
+    for moon in moons.iter_mut() {
+        moon.position += moon.velocity;
+    }
+}
+
+pub fn simulate(moons: &[Moon], steps: usize) -> Vec<Moon> {
+    let mut moons = moons.to_vec();
+    for _ in 0..steps {
+        step(&mut moons);
+    }
+    moons
+}
+
+pub fn total_energy(moons: &[Moon]) -> u64 {
+    moons.iter().map(Moon::energy).sum()
+}
+
+// Steps until the moons are back where they started on one axis. Every
+// state comes from a single previous one, so the first state to repeat is
+// always the first.
+fn axis_period(moons: &[Moon], axis: fn(Point3) -> i64) -> i64 {
+    let initial: Vec<(i64, i64)> = moons
+        .iter()
+        .map(|moon| (axis(moon.position), axis(moon.velocity)))
+        .collect();
+    let mut state = initial.clone();
+    let mut steps = 0;
+    loop {
+        for i in 0..state.len() {
+            for j in i + 1..state.len() {
+                let pull = (state[j].0 - state[i].0).signum();
+                state[i].1 += pull;
+                state[j].1 -= pull;
+            }
+        }
+        for (position, velocity) in state.iter_mut() {
+            *position += *velocity;
+        }
+        steps += 1;
+        if state == initial {
+            return steps;
+        }
+    }
+}
+
+/// Steps until every moon is back where it started, moving the way it
+/// started. The axes don't affect each other, so that's when all of them
+/// have come round at once.
+pub fn period(moons: &[Moon]) -> i64 {
+    lcm_all(vec![
+        axis_period(moons, |point| point.x),
+        axis_period(moons, |point| point.y),
+        axis_period(moons, |point| point.z),
+    ])
+}
+
+pub struct Day12 {
+    /// Steps simulated before measuring the energy in part 1.
+    pub steps: usize,
+}
+
+impl Puzzle for Day12 {
+    type Input = Vec<Moon>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Moon>, String> {
+        parse_moons(input).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, moons: &Vec<Moon>) -> Result<String, String> {
+        Ok(total_energy(&simulate(moons, self.steps)).to_string())
+    }
+
+    fn part2(&self, moons: &Vec<Moon>) -> Result<String, String> {
+        Ok(period(moons).to_string())
+    }
+}
+
+aoc_utils::register_solution!(12, Day12 { steps: STEPS });
+
+#[cfg(test)]
+mod test {
+    use super::{parse_moons, period, simulate, Day12, Moon};
+    use aoc_utils::input::ParseError;
+    use aoc_utils::Point3;
+
+    aoc_utils::example_tests!(Day12 { steps: 10 }, {
+        part1_first: part1("first.txt") == "179",
+        part2_first: part2("first.txt") == "2772",
+    });
+
+    aoc_utils::example_tests!(Day12 { steps: 100 }, {
+        part1_second: part1("second.txt") == "1940",
+        part2_second: part2("second.txt") == "4686774924",
+    });
+
+    #[test]
+    fn test_simulate() {
+        let moons = parse_moons(include_str!("../examples/first.txt")).unwrap();
+        let moons = simulate(&moons, 1);
+        assert_eq!(
+            moons[0],
+            Moon {
+                position: Point3::new(2, -1, 1),
+                velocity: Point3::new(3, -1, -1),
+            }
+        );
+    }
+
+    #[test]
+    fn test_any_number_of_moons() {
+        let alone = parse_moons("<x=3, y=-2, z=7>").unwrap();
+        assert_eq!(simulate(&alone, 100), alone);
+        assert_eq!(period(&alone), 1);
+
+        let moons = parse_moons("<x=0, y=0, z=0>\n\n<x=2, y=0, z=0>\n<x=4,y=0,z=0>").unwrap();
+        assert_eq!(moons.len(), 3);
+        assert_eq!(simulate(&moons, period(&moons) as usize), moons);
+    }
+
+    #[test]
+    fn test_invalid_moons() {
+        let error = |input: &str| parse_moons(input).unwrap_err();
+        assert_eq!(error(""), ParseError::new(1, 1, "no moons"));
+        assert_eq!(
+            error("<x=1, y=2, z=3>\n  x=1, y=2, z=3>"),
+            ParseError::new(2, 3, "expected '<'")
+        );
+        assert_eq!(
+            error("<x=1, y=2, z=3"),
+            ParseError::new(1, 15, "expected '>'")
+        );
+        assert_eq!(
+            error("<x=1, z=2, y=3>"),
+            ParseError::new(1, 7, "expected y=")
+        );
+        assert_eq!(
+            error("<x=1, y= 2a, z=3>"),
+            ParseError::new(1, 10, "invalid y \"2a\"")
+        );
+        assert_eq!(error("<x=1, y=2>"), ParseError::new(1, 10, "missing z"));
+        assert_eq!(
+            error("<x=1, y=2, z=3, w=4>"),
+            ParseError::new(1, 17, "expected only x, y and z")
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use aoc_utils::input::read_text;
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_12::{Day12, STEPS};
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::process::exit;
+
+const USAGE: &str = "Usage: day-12 [--steps <count>] [input file] [--time]";
+
+// The steps for part 1 and the file to read instead of stdin
+fn arguments(args: &[String]) -> Option<(usize, Option<&str>)> {
+    let mut steps = STEPS;
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--steps" {
+            steps = args.next()?.parse().ok()?;
+        } else if arg.starts_with("--") || path.is_some() {
+            return None;
+        } else {
+            path = Some(arg.as_str());
+        }
+    }
+    Some((steps, path))
+}
+
+fn read_file(path: &str) -> String {
+    let text = File::open(path).and_then(|file| read_text(BufReader::new(file)));
+    match text {
+        Ok(text) => text,
+        Err(error) => {
+            eprintln!("Could not read {}: {}", path, error);
+            exit(1);
+        }
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--time").collect();
+    let (steps, path) = match arguments(&args) {
+        Some(arguments) => arguments,
+        None => {
+            eprintln!("{}", USAGE);
+            exit(2);
+        }
+    };
+    let input = match path {
+        Some(path) => read_file(path),
+        None => read_stdin(),
+    };
+    print_answers(&Day12 { steps }, &input, timing_requested());
+}
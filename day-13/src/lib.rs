@@ -0,0 +1,306 @@
+use aoc_utils::input::{comma_separated, ParseError};
+use aoc_utils::solution::Puzzle;
+use aoc_utils::terminal::{FrameLimiter, FrameSink};
+use intcode::replay::InputDevice;
+use intcode::screen::Screen;
+use intcode::{read_intcode_input, IntCodeError, IntCodeMachine, Machine};
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+pub const EMPTY: isize = 0;
+pub const WALL: isize = 1;
+pub const BLOCK: isize = 2;
+pub const PADDLE: isize = 3;
+pub const BALL: isize = 4;
+
+/// What the game printed after one move of the joystick, or before the
+/// first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub joystick: Option<isize>,
+    pub output: Vec<isize>,
+}
+
+/// A game, saved as what it drew so it can be played back without the
+/// program. The game only draws the tiles that change, so a whole game
+/// takes little more than its first frame.
+///
+/// Files have a frame per line, the joystick and the output separated by
+/// `;`, like `-1;17,20,4,18,20,3`. The first frame has no joystick.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Session {
+    frames: Vec<Frame>,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, frame: Frame) {
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+        for frame in &self.frames {
+            if let Some(joystick) = frame.joystick {
+                write!(out, "{}", joystick)?;
+            }
+            let output: Vec<String> = frame.output.iter().map(isize::to_string).collect();
+            writeln!(out, ";{}", output.join(","))?;
+        }
+        Ok(())
+    }
+
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.save(BufWriter::new(File::create(path)?))
+    }
+
+    /// Reads a session written by [`Session::save`].
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut session = Session::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let (joystick, output) = line
+                .split_once(';')
+                .ok_or_else(|| ParseError::new(line_number, 1, "expected ';'"))?;
+            let joystick = match (joystick.trim(), index) {
+                ("", 0) => None,
+                ("", _) => {
+                    return Err(ParseError::new(line_number, 1, "missing joystick"));
+                }
+                (_, 0) => {
+                    return Err(ParseError::new(
+                        line_number,
+                        1,
+                        "the first frame comes before the joystick moves",
+                    ));
+                }
+                (value, _) => Some(value.parse().map_err(|_| {
+                    ParseError::new(line_number, 1, format!("invalid joystick {:?}", value))
+                })?),
+            };
+            let output = comma_separated(output).map_err(|error| {
+                ParseError::new(
+                    line_number,
+                    output_start(line) + error.column,
+                    error.message,
+                )
+            })?;
+            session.push(Frame { joystick, output });
+        }
+        Ok(session)
+    }
+}
+
+// The column just before the output
+fn output_start(line: &str) -> usize {
+    line.find(';').map_or(0, |separator| separator + 1)
+}
+
+/// Moves the paddle under the ball.
+pub fn joystick(screen: &Screen) -> isize {
+    match (screen.find(BALL), screen.find(PADDLE)) {
+        (Some(ball), Some(paddle)) => (ball.x - paddle.x).signum() as isize,
+        _ => 0,
+    }
+}
+
+/// Plays the game to the end, following the ball with the paddle, and
+/// records every frame of it.
+pub fn autoplay<M: Machine>(machine: &mut M) -> Result<(Screen, Session), IntCodeError> {
+    let screen = Rc::new(RefCell::new(Screen::new()));
+    let watched = Rc::clone(&screen);
+    let mut device = InputDevice::joystick(move || Some(joystick(&watched.borrow())));
+    let mut outputs = Vec::new();
+    device.run(machine, |output| {
+        screen.borrow_mut().draw(output);
+        outputs.push(output.to_vec());
+    })?;
+
+    let joysticks = std::iter::once(None).chain(
+        device
+            .recording()
+            .iter()
+            .map(|batch| batch.first().copied()),
+    );
+    let mut session = Session::new();
+    for (joystick, output) in joysticks.zip(outputs) {
+        session.push(Frame { joystick, output });
+    }
+    drop(device);
+    let screen = Rc::try_unwrap(screen)
+        .map(RefCell::into_inner)
+        .unwrap_or_default();
+    Ok((screen, session))
+}
+
+/// The screen as text, with the score above it.
+pub fn render(screen: &Screen) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "Score: {}", screen.score().unwrap_or(0));
+    text.push_str(&screen.render(|tile| match tile {
+        Some(WALL) => '#',
+        Some(BLOCK) => '=',
+        Some(PADDLE) => '-',
+        Some(BALL) => 'o',
+        _ => ' ',
+    }));
+    text
+}
+
+/// Draws every frame of the session at the given speed, returning the
+/// screen at the end.
+pub fn replay(
+    session: &Session,
+    sink: &mut dyn FrameSink,
+    frames_per_second: u32,
+) -> io::Result<Screen> {
+    let mut limiter = FrameLimiter::new(frames_per_second);
+    let mut screen = Screen::new();
+    for frame in session.frames() {
+        screen.draw(&frame.output);
+        limiter.wait();
+        sink.draw(&render(&screen))?;
+    }
+    Ok(screen)
+}
+
+/// The game with two quarters inserted, so it can be played.
+pub fn free_play(mut memory: Vec<isize>) -> IntCodeMachine {
+    if let Some(quarters) = memory.first_mut() {
+        *quarters = 2;
+    }
+    IntCodeMachine::new(memory)
+}
+
+pub struct Day13;
+
+impl Puzzle for Day13 {
+    type Input = Vec<isize>;
+
+    fn parse(&self, input: &str) -> Result<Vec<isize>, String> {
+        read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, memory: &Vec<isize>) -> Result<String, String> {
+        let mut machine = IntCodeMachine::new(memory.clone());
+        let mut screen = Screen::new();
+        screen
+            .run(&mut machine, None)
+            .map_err(|error| error.to_string())?;
+        Ok(screen.count(BLOCK).to_string())
+    }
+
+    fn part2(&self, memory: &Vec<isize>) -> Result<String, String> {
+        let (screen, _) =
+            autoplay(&mut free_play(memory.clone())).map_err(|error| error.to_string())?;
+        match screen.count(BLOCK) {
+            0 => Ok(screen.score().unwrap_or(0).to_string()),
+            blocks => Err(format!("The ball was lost with {} blocks left", blocks)),
+        }
+    }
+}
+
+aoc_utils::register_solution!(13, Day13);
+
+#[cfg(test)]
+mod test {
+    use super::{autoplay, render, replay, Frame, Session, BLOCK};
+    use aoc_utils::input::ParseError;
+    use aoc_utils::terminal::FrameSink;
+    use intcode::compiler::compile;
+    use intcode::IntCodeMachine;
+    use std::io;
+
+    // A wall, a block, the ball at x = 1 and the paddle at x = 3. Moving
+    // the joystick breaks the block and scores the joystick's position.
+    const GAME: &str = "
+        out 0; out 0; out 1;
+        out 2; out 0; out 2;
+        out 1; out 1; out 4;
+        out 3; out 2; out 3;
+        in j;
+        out 2; out 0; out 0;
+        out 0 - 1; out 0; out 10 + j;
+    ";
+
+    #[derive(Default)]
+    struct Frames(Vec<String>);
+
+    impl FrameSink for Frames {
+        fn draw(&mut self, frame: &str) -> io::Result<()> {
+            self.0.push(frame.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_autoplay() {
+        let mut machine = IntCodeMachine::new(compile(GAME).unwrap());
+        let (screen, session) = autoplay(&mut machine).unwrap();
+        assert_eq!(screen.count(BLOCK), 0);
+        assert_eq!(screen.score(), Some(9));
+        assert_eq!(
+            session.frames(),
+            [
+                Frame {
+                    joystick: None,
+                    output: vec![0, 0, 1, 2, 0, 2, 1, 1, 4, 3, 2, 3],
+                },
+                Frame {
+                    joystick: Some(-1),
+                    output: vec![2, 0, 0, -1, 0, 9],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_save_and_replay() {
+        let mut machine = IntCodeMachine::new(compile(GAME).unwrap());
+        let (screen, session) = autoplay(&mut machine).unwrap();
+        let mut file = Vec::new();
+        session.save(&mut file).unwrap();
+        let text = String::from_utf8(file).unwrap();
+        assert_eq!(text, ";0,0,1,2,0,2,1,1,4,3,2,3\n-1;2,0,0,-1,0,9\n");
+        assert_eq!(Session::parse(&text).unwrap(), session);
+
+        let mut frames = Frames::default();
+        let replayed = replay(&session, &mut frames, 1000).unwrap();
+        assert_eq!(frames.0.len(), 2);
+        assert_eq!(frames.0[0], "Score: 0\n# = \n o  \n   -\n");
+        assert_eq!(render(&replayed), render(&screen));
+        assert_eq!(frames.0[1], "Score: 9\n#   \n o  \n   -\n");
+    }
+
+    #[test]
+    fn test_invalid_session() {
+        assert_eq!(
+            Session::parse(";1,2,3\n4,5,6"),
+            Err(ParseError::new(2, 1, "expected ';'"))
+        );
+        assert_eq!(
+            Session::parse("0;1,2,3"),
+            Err(ParseError::new(
+                1,
+                1,
+                "the first frame comes before the joystick moves"
+            ))
+        );
+        assert_eq!(
+            Session::parse(";1\n;2"),
+            Err(ParseError::new(2, 1, "missing joystick"))
+        );
+        let error = Session::parse(";1\n-1;2,x").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 6));
+    }
+}
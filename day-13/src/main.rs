@@ -0,0 +1,78 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use aoc_utils::terminal::Terminal;
+use day_13::{autoplay, free_play, replay, Day13, Session};
+use intcode::read_intcode_input;
+use std::{env, fs, process};
+
+const USAGE: &str = "Usage: day-13 [--time] [--record PATH] | day-13 --replay PATH [--speed FPS]";
+
+/// Frames per second when replaying, unless given.
+const SPEED: u32 = 60;
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn play_back(path: &str, speed: u32) {
+    let text = fs::read_to_string(path)
+        .unwrap_or_else(|error| fail(&format!("Could not read {}: {}", path, error)));
+    let session = Session::parse(&text)
+        .unwrap_or_else(|error| fail(&format!("Invalid session {}: {}", path, error)));
+    let screen = Terminal::stdout().and_then(|mut terminal| replay(&session, &mut terminal, speed));
+    match screen {
+        Ok(screen) => println!("Final score: {}", screen.score().unwrap_or(0)),
+        Err(error) => fail(&format!("Could not replay the game: {}", error)),
+    }
+}
+
+fn record(input: &str, path: &str) {
+    let memory = read_intcode_input(input.as_bytes())
+        .unwrap_or_else(|error| fail(&format!("Invalid puzzle input: {}", error)));
+    let (_, session) = autoplay(&mut free_play(memory))
+        .unwrap_or_else(|error| fail(&format!("The game failed: {}", error)));
+    match session.save_to(path) {
+        Ok(()) => println!("Saved {} frames to {}", session.frames().len(), path),
+        Err(error) => fail(&format!("Could not save the game: {}", error)),
+    }
+}
+
+enum Mode<'a> {
+    Solve { record: Option<&'a str> },
+    Replay { path: &'a str, speed: u32 },
+}
+
+fn mode(args: &[String]) -> Option<Mode<'_>> {
+    match args {
+        [] => Some(Mode::Solve { record: None }),
+        [flag, path] if flag == "--record" && !path.starts_with("--") => {
+            Some(Mode::Solve { record: Some(path) })
+        }
+        [flag, path] if flag == "--replay" && !path.starts_with("--") => {
+            Some(Mode::Replay { path, speed: SPEED })
+        }
+        [flag, path, speed_flag, speed] if flag == "--replay" && speed_flag == "--speed" => {
+            let speed = speed.parse().ok().filter(|speed| *speed > 0)?;
+            Some(Mode::Replay { path, speed })
+        }
+        _ => None,
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--time").collect();
+    match mode(&args) {
+        Some(Mode::Solve { record: path }) => {
+            let input = read_stdin();
+            print_answers(&Day13, &input, timing_requested());
+            if let Some(path) = path {
+                record(&input, path);
+            }
+        }
+        Some(Mode::Replay { path, speed }) => play_back(path, speed),
+        None => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
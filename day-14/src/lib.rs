@@ -0,0 +1,409 @@
+//! The nanofactory's reactions, as a graph from every chemical to the
+//! chemicals it's made of, worked through from the fuel down to the ore.
+
+use aoc_utils::input::{lines_of, ParseError};
+use aoc_utils::interner::Interner;
+use aoc_utils::solution::Puzzle;
+use graph::Graph;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+pub const ORE: &str = "ORE";
+pub const FUEL: &str = "FUEL";
+
+/// The ore in the cargo hold, for part 2.
+pub const ORE_IN_HOLD: u64 = 1_000_000_000_000;
+
+/// An amount of a chemical, like `7 A`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quantity {
+    pub amount: u64,
+    pub chemical: String,
+}
+
+impl Quantity {
+    pub fn new(amount: u64, chemical: &str) -> Self {
+        Self {
+            amount,
+            chemical: chemical.to_string(),
+        }
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (amount, chemical) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("Expected an amount and a chemical in {:?}", s.trim()))?;
+        let amount = amount
+            .parse()
+            .map_err(|_| format!("Invalid amount {:?}", amount))?;
+        let chemical = chemical.trim();
+        if chemical.is_empty() || !chemical.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("Invalid chemical {:?}", chemical));
+        }
+        Ok(Self::new(amount, chemical))
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.chemical)
+    }
+}
+
+/// A reaction, like `7 A, 1 B => 1 C`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reaction {
+    pub inputs: Vec<Quantity>,
+    pub output: Quantity,
+}
+
+impl FromStr for Reaction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (inputs, output) = s.split_once("=>").ok_or("Expected inputs => output")?;
+        let inputs = inputs
+            .split(',')
+            .map(str::parse)
+            .collect::<Result<Vec<Quantity>, _>>()?;
+        let output: Quantity = output.parse()?;
+        if output.amount == 0 {
+            return Err(format!("The reaction makes no {}", output.chemical));
+        }
+        Ok(Self { inputs, output })
+    }
+}
+
+impl fmt::Display for Reaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, input) in self.inputs.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", input)?;
+        }
+        write!(f, " => {}", self.output)
+    }
+}
+
+/// One reaction for every non-blank line.
+pub fn parse_reactions(input: &str) -> Result<Vec<Reaction>, ParseError> {
+    lines_of(input)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReactionError {
+    /// A chemical other than ore that no reaction makes.
+    Unmade(String),
+    MadeTwice(String),
+    /// Chemicals that end up being made of themselves.
+    Cycle,
+}
+
+impl fmt::Display for ReactionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReactionError::Unmade(chemical) => write!(f, "No reaction makes {}", chemical),
+            ReactionError::MadeTwice(chemical) => {
+                write!(f, "More than one reaction makes {}", chemical)
+            }
+            ReactionError::Cycle => write!(f, "A chemical is made from itself"),
+        }
+    }
+}
+
+impl Error for ReactionError {}
+
+// A reaction with its chemicals numbered
+#[derive(Debug, Clone)]
+struct Recipe {
+    output: u64,
+    inputs: Vec<(usize, u64)>,
+}
+
+/// Chemicals left over from earlier reactions, used up before running a
+/// reaction again.
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    leftovers: HashMap<String, u64>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn leftover(&self, chemical: &str) -> u64 {
+        self.leftovers.get(chemical).copied().unwrap_or(0)
+    }
+
+    /// Uses up what's left of the chemical towards `amount`, returning how
+    /// much still has to be made.
+    pub fn take(&mut self, chemical: &str, amount: u64) -> u64 {
+        let leftover = self.leftovers.entry(chemical.to_string()).or_insert(0);
+        let taken = amount.min(*leftover);
+        *leftover -= taken;
+        amount - taken
+    }
+
+    pub fn store(&mut self, chemical: &str, amount: u64) {
+        *self.leftovers.entry(chemical.to_string()).or_insert(0) += amount;
+    }
+}
+
+/// Every reaction, checked to make each chemical but ore exactly one way.
+#[derive(Debug, Clone)]
+pub struct Reactions {
+    names: Interner,
+    // Indexed by chemical, `None` for ore
+    recipes: Vec<Option<Recipe>>,
+    // Every chemical before the ones it's made of
+    order: Vec<usize>,
+}
+
+impl Reactions {
+    pub fn new(reactions: &[Reaction]) -> Result<Self, ReactionError> {
+        let mut names = Interner::new();
+        let ore = names.intern(ORE) as usize;
+        let mut recipes: HashMap<usize, Recipe> = HashMap::new();
+        for reaction in reactions {
+            let output = names.intern(&reaction.output.chemical) as usize;
+            let inputs = reaction
+                .inputs
+                .iter()
+                .map(|input| (names.intern(&input.chemical) as usize, input.amount))
+                .collect();
+            let recipe = Recipe {
+                output: reaction.output.amount,
+                inputs,
+            };
+            if output == ore || recipes.insert(output, recipe).is_some() {
+                return Err(ReactionError::MadeTwice(reaction.output.chemical.clone()));
+            }
+        }
+
+        let mut graph = Graph::directed();
+        for _ in 0..names.len() {
+            graph.add_node();
+        }
+        let recipes: Vec<Option<Recipe>> = (0..names.len())
+            .map(|chemical| recipes.remove(&chemical))
+            .collect();
+        for (chemical, recipe) in recipes.iter().enumerate() {
+            match recipe {
+                Some(recipe) => {
+                    for (input, _) in recipe.inputs.iter() {
+                        graph.add_edge(chemical, *input);
+                    }
+                }
+                None if chemical == ore => {}
+                None => {
+                    let name = names.name(chemical as u32).unwrap_or_default();
+                    return Err(ReactionError::Unmade(name.to_string()));
+                }
+            }
+        }
+        let order = graph.topological_sort().map_err(|_| ReactionError::Cycle)?;
+        Ok(Self {
+            names,
+            recipes,
+            order,
+        })
+    }
+
+    /// Every chemical, each one before the chemicals it's made of, so ore
+    /// always comes last.
+    pub fn chemicals(&self) -> impl Iterator<Item = &str> {
+        self.order
+            .iter()
+            .filter_map(move |chemical| self.names.name(*chemical as u32))
+    }
+
+    /// The ore needed to make the chemical, once what's left in the ledger
+    /// is used up. Whatever the reactions make on top of that goes back in
+    /// the ledger.
+    pub fn ore_needed(
+        &self,
+        chemical: &str,
+        amount: u64,
+        ledger: &mut Ledger,
+    ) -> Result<u64, ReactionError> {
+        let id = self
+            .names
+            .get(chemical)
+            .ok_or_else(|| ReactionError::Unmade(chemical.to_string()))?;
+        let mut needed = vec![0; self.recipes.len()];
+        needed[id as usize] = amount;
+        // Everything that needs a chemical comes before it, so its total is
+        // known by the time it's reached
+        for &chemical in self.order.iter() {
+            let recipe = match &self.recipes[chemical] {
+                Some(recipe) => recipe,
+                None => continue,
+            };
+            let name = self.names.name(chemical as u32).unwrap_or_default();
+            let missing = ledger.take(name, needed[chemical]);
+            let runs = missing.div_ceil(recipe.output);
+            ledger.store(name, runs * recipe.output - missing);
+            for (input, amount) in recipe.inputs.iter() {
+                needed[*input] += runs * amount;
+            }
+        }
+        Ok(needed[self.names.get(ORE).unwrap_or_default() as usize])
+    }
+
+    /// The most fuel that can be made from the ore, reusing what's left
+    /// over from every unit of fuel for the next ones.
+    pub fn max_fuel(&self, ore: u64) -> Result<u64, ReactionError> {
+        let fits = |fuel: u64| -> Result<bool, ReactionError> {
+            Ok(self.ore_needed(FUEL, fuel, &mut Ledger::new())? <= ore)
+        };
+        let for_one = self.ore_needed(FUEL, 1, &mut Ledger::new())?;
+        if for_one == 0 {
+            return Ok(u64::MAX);
+        }
+        // Leftovers only ever save ore, so this much always fits
+        let mut low = ore / for_one;
+        let mut high = low.max(1) * 2;
+        while fits(high)? {
+            low = high;
+            high *= 2;
+        }
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if fits(middle)? {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        Ok(low)
+    }
+}
+
+pub struct Day14;
+
+impl Puzzle for Day14 {
+    type Input = Reactions;
+
+    fn parse(&self, input: &str) -> Result<Reactions, String> {
+        let reactions = parse_reactions(input).map_err(|error| error.to_string())?;
+        Reactions::new(&reactions).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, reactions: &Reactions) -> Result<String, String> {
+        reactions
+            .ore_needed(FUEL, 1, &mut Ledger::new())
+            .map(|ore| ore.to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    fn part2(&self, reactions: &Reactions) -> Result<String, String> {
+        reactions
+            .max_fuel(ORE_IN_HOLD)
+            .map(|fuel| fuel.to_string())
+            .map_err(|error| error.to_string())
+    }
+}
+
+aoc_utils::register_solution!(14, Day14);
+
+#[cfg(test)]
+mod test {
+    use super::{parse_reactions, Day14, Ledger, Quantity, Reaction, ReactionError, Reactions};
+    use aoc_utils::input::ParseError;
+
+    aoc_utils::example_tests!(Day14, {
+        part1_simple: part1("simple.txt") == "31",
+        part1_leftovers: part1("leftovers.txt") == "165",
+        part1_third: part1("third.txt") == "13312",
+        part2_third: part2("third.txt") == "82892753",
+        part1_fourth: part1("fourth.txt") == "180697",
+        part2_fourth: part2("fourth.txt") == "5586022",
+        part1_fifth: part1("fifth.txt") == "2210736",
+        part2_fifth: part2("fifth.txt") == "460664",
+    });
+
+    fn reactions(input: &str) -> Result<Reactions, ReactionError> {
+        Reactions::new(&parse_reactions(input).unwrap())
+    }
+
+    #[test]
+    fn test_parse() {
+        let reaction: Reaction = "7 A, 1 B => 1 C".parse().unwrap();
+        assert_eq!(
+            reaction,
+            Reaction {
+                inputs: vec![Quantity::new(7, "A"), Quantity::new(1, "B")],
+                output: Quantity::new(1, "C"),
+            }
+        );
+        assert_eq!(reaction.to_string(), "7 A, 1 B => 1 C");
+
+        assert_eq!(
+            parse_reactions("1 ORE => 1 A\n7 A => 1 FUEL\n7 A -> 1 FUEL"),
+            Err(ParseError::new(
+                3,
+                1,
+                "invalid value \"7 A -> 1 FUEL\": Expected inputs => output"
+            ))
+        );
+        assert!("seven A => 1 B".parse::<Reaction>().is_err());
+        assert!("7 A => 0 B".parse::<Reaction>().is_err());
+        assert!("7 A, => 1 B".parse::<Reaction>().is_err());
+    }
+
+    #[test]
+    fn test_order() {
+        let reactions = reactions(include_str!("../examples/simple.txt")).unwrap();
+        let order: Vec<&str> = reactions.chemicals().collect();
+        assert_eq!(order, ["FUEL", "E", "D", "C", "A", "B", "ORE"]);
+    }
+
+    #[test]
+    fn test_ledger() {
+        let reactions = reactions(include_str!("../examples/simple.txt")).unwrap();
+        let mut ledger = Ledger::new();
+        assert_eq!(reactions.ore_needed("C", 1, &mut ledger), Ok(11));
+        assert_eq!(ledger.leftover("A"), 3);
+        // The next C only needs 4 more A, but A comes 10 at a time
+        assert_eq!(reactions.ore_needed("C", 1, &mut ledger), Ok(11));
+        assert_eq!(ledger.leftover("A"), 6);
+        assert_eq!(reactions.ore_needed("C", 1, &mut ledger), Ok(11));
+        assert_eq!(ledger.leftover("A"), 9);
+        assert_eq!(reactions.ore_needed("C", 1, &mut ledger), Ok(1));
+        assert_eq!(ledger.leftover("A"), 2);
+        assert_eq!(reactions.ore_needed("ORE", 5, &mut ledger), Ok(5));
+        assert_eq!(
+            reactions.ore_needed("GOLD", 1, &mut ledger),
+            Err(ReactionError::Unmade("GOLD".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_invalid_reactions() {
+        assert_eq!(
+            reactions("1 ORE => 1 A\n1 A, 1 B => 1 FUEL").unwrap_err(),
+            ReactionError::Unmade("B".to_string())
+        );
+        assert_eq!(
+            reactions("1 ORE => 1 A\n2 ORE => 1 A\n1 A => 1 FUEL").unwrap_err(),
+            ReactionError::MadeTwice("A".to_string())
+        );
+        assert_eq!(
+            reactions("1 A => 1 ORE").unwrap_err(),
+            ReactionError::MadeTwice("ORE".to_string())
+        );
+        assert_eq!(
+            reactions("1 ORE, 1 B => 1 A\n1 A => 1 B\n1 A => 1 FUEL").unwrap_err(),
+            ReactionError::Cycle
+        );
+    }
+}
@@ -0,0 +1,6 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_14::Day14;
+
+fn main() {
+    print_answers(&Day14, &read_stdin(), timing_requested());
+}
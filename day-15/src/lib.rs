@@ -0,0 +1,653 @@
+use aoc_utils::solution::{or_no_solution, Puzzle};
+use aoc_utils::terminal::{FrameLimiter, FrameSink, RawMode, Terminal};
+use aoc_utils::{Direction, Point2, SparseGrid};
+use intcode::{read_intcode_input, IntCodeError, IntCodeMachine, Machine};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::iter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Wall,
+    Open,
+    Oxygen,
+}
+
+#[derive(Debug)]
+pub enum DroidError {
+    Machine(IntCodeError),
+    UnexpectedOutput(Vec<isize>),
+    /// The droid couldn't step back the way it came.
+    Lost(Point2),
+}
+
+impl fmt::Display for DroidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DroidError::Machine(error) => write!(f, "Droid program failed: {}", error),
+            DroidError::UnexpectedOutput(output) => {
+                write!(f, "Expected a status code, got {:?}", output)
+            }
+            DroidError::Lost(position) => {
+                write!(f, "The droid can't go back the way it came at {}", position)
+            }
+        }
+    }
+}
+
+impl Error for DroidError {}
+
+impl From<IntCodeError> for DroidError {
+    fn from(error: IntCodeError) -> Self {
+        DroidError::Machine(error)
+    }
+}
+
+// North, south, west and east, in the order the droid numbers them. North
+// is drawn at the top of the screen.
+fn command(direction: Direction) -> isize {
+    match direction {
+        Direction::Up => 1,
+        Direction::Down => 2,
+        Direction::Left => 3,
+        Direction::Right => 4,
+    }
+}
+
+/// The repair droid, which only ever tells what is in the direction it was
+/// asked to move.
+pub struct Droid<M> {
+    machine: M,
+    position: Point2,
+}
+
+impl<M: Machine> Droid<M> {
+    /// A droid at the origin.
+    pub fn new(machine: M) -> Self {
+        Self {
+            machine,
+            position: Point2::ORIGIN,
+        }
+    }
+
+    pub fn position(&self) -> Point2 {
+        self.position
+    }
+
+    /// Tries to move one step, returning what is there. The droid stays
+    /// where it is when it hits a wall.
+    pub fn step(&mut self, direction: Direction) -> Result<Tile, DroidError> {
+        let output = self.machine.execute(vec![command(direction)])?;
+        let tile = match output.as_slice() {
+            [0] => Tile::Wall,
+            [1] => Tile::Open,
+            [2] => Tile::Oxygen,
+            _ => return Err(DroidError::UnexpectedOutput(output)),
+        };
+        if tile != Tile::Wall {
+            self.position += direction.screen_delta();
+        }
+        Ok(tile)
+    }
+}
+
+/// The part of the area the droid found, with `y` growing downwards.
+#[derive(Debug, Clone)]
+pub struct Map {
+    tiles: SparseGrid<Tile>,
+    oxygen: Option<Point2>,
+}
+
+impl Map {
+    /// A map with nothing on it but the floor under the droid.
+    pub fn new(start: Point2) -> Self {
+        let mut tiles = SparseGrid::new();
+        tiles.insert(start, Tile::Open);
+        Self {
+            tiles,
+            oxygen: None,
+        }
+    }
+
+    /// Moves the droid one step and puts what it found on the map.
+    pub fn step<M: Machine>(
+        &mut self,
+        droid: &mut Droid<M>,
+        direction: Direction,
+    ) -> Result<Tile, DroidError> {
+        let target = droid.position() + direction.screen_delta();
+        let tile = droid.step(direction)?;
+        self.tiles.insert(target, tile);
+        if tile == Tile::Oxygen {
+            self.oxygen = Some(target);
+        }
+        Ok(tile)
+    }
+
+    /// Walks the droid through every reachable tile, going back the way it
+    /// came once there's nothing new next to it. `visit` gets the map after
+    /// every step, together with where the droid is.
+    pub fn explore<M, F>(droid: &mut Droid<M>, visit: F) -> Result<Self, DroidError>
+    where
+        M: Machine,
+        F: FnMut(&Map, Point2),
+    {
+        let mut map = Map::new(droid.position());
+        map.finish_exploring(droid, visit)?;
+        Ok(map)
+    }
+
+    /// Explores like [`Map::explore`] from wherever the droid is, adding to
+    /// what's already on the map. Unexplored edges the droid can't reach
+    /// that way, left behind when driving it by hand, are walked to over
+    /// the known floor.
+    pub fn finish_exploring<M, F>(
+        &mut self,
+        droid: &mut Droid<M>,
+        mut visit: F,
+    ) -> Result<(), DroidError>
+    where
+        M: Machine,
+        F: FnMut(&Map, Point2),
+    {
+        let mut path: Vec<Direction> = Vec::new();
+        loop {
+            let position = droid.position();
+            let unknown = Direction::ALL
+                .iter()
+                .copied()
+                .find(|direction| !self.tiles.contains(position + direction.screen_delta()));
+            match unknown {
+                Some(direction) => {
+                    if self.step(droid, direction)? != Tile::Wall {
+                        path.push(direction);
+                    }
+                }
+                None => match path.pop() {
+                    Some(direction) => self.walk(droid, direction.opposite())?,
+                    None => match self.route_to_unknown(position) {
+                        Some(route) => {
+                            for direction in route {
+                                self.walk(droid, direction)?;
+                                visit(self, droid.position());
+                            }
+                            continue;
+                        }
+                        None => return Ok(()),
+                    },
+                },
+            }
+            visit(self, droid.position());
+        }
+    }
+
+    // Steps onto floor that's already known
+    fn walk<M: Machine>(
+        &mut self,
+        droid: &mut Droid<M>,
+        direction: Direction,
+    ) -> Result<(), DroidError> {
+        let position = droid.position();
+        if droid.step(direction)? == Tile::Wall {
+            return Err(DroidError::Lost(position));
+        }
+        Ok(())
+    }
+
+    fn is_floor(&self, point: Point2) -> bool {
+        matches!(self.tiles.get(point), Some(Tile::Open | Tile::Oxygen))
+    }
+
+    // The shortest way over known floor to a tile next to one that was
+    // never seen
+    fn route_to_unknown(&self, from: Point2) -> Option<Vec<Direction>> {
+        let mut came_from: HashMap<Point2, Direction> = HashMap::new();
+        let mut queue = VecDeque::from(vec![from]);
+        let mut seen: HashSet<Point2> = [from].iter().copied().collect();
+        while let Some(point) = queue.pop_front() {
+            let unexplored = Direction::ALL
+                .iter()
+                .any(|direction| !self.tiles.contains(point + direction.screen_delta()));
+            if unexplored {
+                let mut route = Vec::new();
+                let mut current = point;
+                while let Some(direction) = came_from.get(&current) {
+                    route.push(*direction);
+                    current -= direction.screen_delta();
+                }
+                route.reverse();
+                return Some(route);
+            }
+            for direction in Direction::ALL.iter().copied() {
+                let next = point + direction.screen_delta();
+                if self.is_floor(next) && seen.insert(next) {
+                    came_from.insert(next, direction);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    pub fn oxygen(&self) -> Option<Point2> {
+        self.oxygen
+    }
+
+    /// Spreads out from `start` one minute at a time, the way oxygen fills
+    /// the area. `visit` gets every minute with the tiles first reached in
+    /// it. Returns how many minutes it took to reach every tile.
+    pub fn spread<F>(&self, start: Point2, mut visit: F) -> HashMap<Point2, usize>
+    where
+        F: FnMut(usize, &[Point2]),
+    {
+        let mut reached = HashMap::new();
+        let mut front = vec![start];
+        reached.insert(start, 0);
+        let mut minute = 0;
+        while !front.is_empty() {
+            visit(minute, &front);
+            minute += 1;
+            front = front
+                .iter()
+                .flat_map(|position| position.neighbours())
+                .filter(|next| self.is_floor(*next))
+                .filter(|next| {
+                    if reached.contains_key(next) {
+                        false
+                    } else {
+                        reached.insert(*next, minute);
+                        true
+                    }
+                })
+                .collect();
+        }
+        reached
+    }
+
+    /// Fewest steps from where the droid started to the oxygen system.
+    pub fn oxygen_distance(&self) -> Option<usize> {
+        let oxygen = self.oxygen?;
+        self.spread(Point2::ORIGIN, |_, _| {}).get(&oxygen).copied()
+    }
+
+    /// Minutes until the oxygen has filled every open tile.
+    pub fn fill_time(&self) -> Option<usize> {
+        let oxygen = self.oxygen?;
+        self.spread(oxygen, |_, _| {}).values().max().copied()
+    }
+
+    /// Draws the map, with `D` on the droid, `O` on the oxygen front, `~` on
+    /// the tiles filled before it and `S` on the oxygen system. Tiles that
+    /// were never seen are left blank.
+    pub fn render(
+        &self,
+        droid: Option<Point2>,
+        filled: &HashSet<Point2>,
+        front: &[Point2],
+    ) -> String {
+        let (min, max) = match self.tiles.bounds() {
+            Some(bounds) => bounds,
+            None => return String::new(),
+        };
+        let mut text = String::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let point = Point2::new(x, y);
+                let c = if droid == Some(point) {
+                    'D'
+                } else if front.contains(&point) {
+                    'O'
+                } else if filled.contains(&point) {
+                    '~'
+                } else {
+                    match self.tiles.get(point) {
+                        Some(Tile::Wall) => '#',
+                        Some(Tile::Open) => '.',
+                        Some(Tile::Oxygen) => 'S',
+                        None => ' ',
+                    }
+                };
+                text.push(c);
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn exploring_frame(map: &Map, droid: Point2) -> String {
+    format!(
+        "Exploring\n{}",
+        map.render(Some(droid), &HashSet::new(), &[])
+    )
+}
+
+fn driving_frame(map: &Map, droid: Point2) -> String {
+    format!(
+        "Arrows move, f finishes exploring, q quits\n{}",
+        map.render(Some(droid), &HashSet::new(), &[])
+    )
+}
+
+/// Plays the exploration in the terminal, then the oxygen filling the area
+/// once the droid has mapped all of it.
+pub fn watch_droid(memory: Vec<isize>) -> Result<Map, Box<dyn Error>> {
+    let mut droid = Droid::new(IntCodeMachine::new(memory));
+    play_droid(&mut droid, &mut Terminal::stdout()?)
+}
+
+/// Plays the exploration like [`watch_droid`], with any droid on any frame
+/// sink.
+pub fn play_droid<M: Machine>(
+    droid: &mut Droid<M>,
+    sink: &mut dyn FrameSink,
+) -> Result<Map, Box<dyn Error>> {
+    let mut limiter = FrameLimiter::new(60);
+    let mut result = Ok(());
+    let map = Map::explore(droid, |map, position| {
+        if result.is_ok() {
+            limiter.wait();
+            result = sink.draw(&exploring_frame(map, position));
+        }
+    })?;
+    result?;
+
+    let oxygen = map
+        .oxygen()
+        .ok_or("The droid didn't find the oxygen system")?;
+    let mut filled = HashSet::new();
+    let mut result = Ok(());
+    map.spread(oxygen, |minute, front| {
+        if result.is_ok() {
+            let frame = format!("Minute {:>4}\n{}", minute, map.render(None, &filled, front));
+            filled.extend(front.iter().copied());
+            limiter.wait();
+            result = sink.draw(&frame);
+        }
+    });
+    result?;
+    Ok(map)
+}
+
+/// A key pressed while driving the droid by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Move(Direction),
+    /// Hands the droid back to the explorer to map the rest.
+    AutoFinish,
+    Quit,
+}
+
+/// The keys in what a terminal sends: arrows as escape sequences, `f` to
+/// finish exploring and `q` to quit. Anything else is skipped.
+pub fn keys<R: Read>(reader: R) -> impl Iterator<Item = Key> {
+    let mut bytes = BufReader::new(reader).bytes().map_while(Result::ok);
+    iter::from_fn(move || loop {
+        let key = match bytes.next()? {
+            b'\x1b' => {
+                if bytes.next()? != b'[' {
+                    continue;
+                }
+                match bytes.next()? {
+                    b'A' => Key::Move(Direction::Up),
+                    b'B' => Key::Move(Direction::Down),
+                    b'C' => Key::Move(Direction::Right),
+                    b'D' => Key::Move(Direction::Left),
+                    _ => continue,
+                }
+            }
+            b'f' => Key::AutoFinish,
+            b'q' => Key::Quit,
+            _ => continue,
+        };
+        return Some(key);
+    })
+}
+
+/// Lets the arrow keys drive the droid, with the map drawn live in the
+/// terminal. Stdin can still be the puzzle input, the keys are read from
+/// the terminal itself.
+pub fn drive_droid(memory: Vec<isize>) -> Result<Option<Map>, Box<dyn Error>> {
+    let raw = RawMode::enable()?;
+    let mut droid = Droid::new(IntCodeMachine::new(memory));
+    let mut terminal = Terminal::stdout()?;
+    let map = drive(&mut droid, keys(raw.keys()?), &mut terminal)?;
+    Ok(map)
+}
+
+/// Drives the droid with the keys on any frame sink. Once told to finish,
+/// the explorer maps whatever is left and the whole map is returned.
+/// Quitting, or running out of keys, gives `None`.
+pub fn drive<M, I>(
+    droid: &mut Droid<M>,
+    keys: I,
+    sink: &mut dyn FrameSink,
+) -> Result<Option<Map>, Box<dyn Error>>
+where
+    M: Machine,
+    I: IntoIterator<Item = Key>,
+{
+    let mut map = Map::new(droid.position());
+    sink.draw(&driving_frame(&map, droid.position()))?;
+    for key in keys {
+        match key {
+            Key::Move(direction) => {
+                map.step(droid, direction)?;
+            }
+            Key::AutoFinish => {
+                let mut limiter = FrameLimiter::new(60);
+                let mut result = Ok(());
+                map.finish_exploring(droid, |map, position| {
+                    if result.is_ok() {
+                        limiter.wait();
+                        result = sink.draw(&exploring_frame(map, position));
+                    }
+                })?;
+                result?;
+                return Ok(Some(map));
+            }
+            Key::Quit => return Ok(None),
+        }
+        sink.draw(&driving_frame(&map, droid.position()))?;
+    }
+    Ok(None)
+}
+
+pub struct Day15;
+
+impl Puzzle for Day15 {
+    type Input = Map;
+
+    // The whole area is explored up front, both parts only read the map
+    fn parse(&self, input: &str) -> Result<Map, String> {
+        let memory = read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())?;
+        let mut droid = Droid::new(IntCodeMachine::new(memory));
+        Map::explore(&mut droid, |_, _| {}).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, map: &Map) -> Result<String, String> {
+        Ok(or_no_solution(map.oxygen_distance()))
+    }
+
+    fn part2(&self, map: &Map) -> Result<String, String> {
+        Ok(or_no_solution(map.fill_time()))
+    }
+}
+
+aoc_utils::register_solution!(15, Day15);
+
+#[cfg(test)]
+mod test {
+    use super::{drive, keys, play_droid, Droid, Key, Map, Tile};
+    use aoc_utils::terminal::FrameSink;
+    use aoc_utils::Direction;
+    use aoc_utils::Point2;
+    use intcode::{IntCodeError, Machine};
+    use std::collections::HashSet;
+    use std::io;
+
+    // The example from part 2, with the droid starting at D
+    const AREA: &str = " ##
+#D.##
+#.#..#
+#.O.#
+ ###  ";
+
+    /// A droid program played out on a drawn area, where anything that
+    /// isn't open floor is a wall.
+    struct Area {
+        cells: Vec<Vec<u8>>,
+        position: Point2,
+    }
+
+    impl Area {
+        fn new(text: &str) -> Self {
+            let cells: Vec<Vec<u8>> = text.lines().map(|line| line.bytes().collect()).collect();
+            let position = cells
+                .iter()
+                .enumerate()
+                .find_map(|(y, row)| {
+                    let x = row.iter().position(|cell| *cell == b'D')?;
+                    Some(Point2::new(x as i64, y as i64))
+                })
+                .unwrap();
+            Self { cells, position }
+        }
+
+        fn cell(&self, point: Point2) -> u8 {
+            self.cells
+                .get(point.y as usize)
+                .and_then(|row| row.get(point.x as usize))
+                .copied()
+                .unwrap_or(b'#')
+        }
+    }
+
+    impl Machine for Area {
+        fn execute(&mut self, input: Vec<isize>) -> Result<Vec<isize>, IntCodeError> {
+            let mut output = Vec::new();
+            for command in input {
+                let delta = match command {
+                    1 => Point2::new(0, -1),
+                    2 => Point2::new(0, 1),
+                    3 => Point2::new(-1, 0),
+                    4 => Point2::new(1, 0),
+                    _ => return Err(IntCodeError::InputError),
+                };
+                let target = self.position + delta;
+                output.push(match self.cell(target) {
+                    b'.' | b'D' => 1,
+                    b'O' => 2,
+                    _ => 0,
+                });
+                if output.last() != Some(&0) {
+                    self.position = target;
+                }
+            }
+            Ok(output)
+        }
+
+        fn finished(&self) -> bool {
+            false
+        }
+    }
+
+    #[derive(Default)]
+    struct Frames(Vec<String>);
+
+    impl FrameSink for Frames {
+        fn draw(&mut self, frame: &str) -> io::Result<()> {
+            self.0.push(frame.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_explore() {
+        let mut droid = Droid::new(Area::new(AREA));
+        let map = Map::explore(&mut droid, |_, _| {}).unwrap();
+        assert_eq!(droid.position(), Point2::ORIGIN);
+        assert_eq!(map.oxygen(), Some(Point2::new(1, 2)));
+        assert_eq!(map.oxygen_distance(), Some(3));
+        assert_eq!(map.fill_time(), Some(4));
+    }
+
+    #[test]
+    fn test_step() {
+        let mut droid = Droid::new(Area::new(AREA));
+        assert_eq!(droid.step(aoc_utils::Direction::Up).unwrap(), Tile::Wall);
+        assert_eq!(droid.position(), Point2::ORIGIN);
+        assert_eq!(droid.step(aoc_utils::Direction::Down).unwrap(), Tile::Open);
+        assert_eq!(droid.position(), Point2::new(0, 1));
+    }
+
+    #[test]
+    fn test_render() {
+        let mut droid = Droid::new(Area::new(AREA));
+        let map = Map::explore(&mut droid, |_, _| {}).unwrap();
+        let filled: HashSet<Point2> = [Point2::new(1, 2)].iter().copied().collect();
+        let front = [Point2::new(0, 2), Point2::new(2, 2)];
+        assert_eq!(
+            map.render(None, &filled, &front),
+            " ##   \n#..## \n#.#..#\n#O~O# \n ###  \n"
+        );
+    }
+
+    #[test]
+    fn test_play() {
+        let mut droid = Droid::new(Area::new(AREA));
+        let mut frames = Frames::default();
+        play_droid(&mut droid, &mut frames).unwrap();
+        assert!(frames.0[0].starts_with("Exploring\n"));
+        assert!(frames.0[0].contains('D'));
+        // Filling takes a frame for every minute, the start included
+        let filling: Vec<&String> = frames
+            .0
+            .iter()
+            .filter(|frame| frame.starts_with("Minute"))
+            .collect();
+        assert_eq!(filling.len(), 5);
+        assert!(!filling[4].contains('.'));
+    }
+
+    #[test]
+    fn test_keys() {
+        let pressed: Vec<Key> = keys(&b"\x1b[A\x1b[Dx\x1b[Zfq"[..]).collect();
+        assert_eq!(
+            pressed,
+            [
+                Key::Move(Direction::Up),
+                Key::Move(Direction::Left),
+                Key::AutoFinish,
+                Key::Quit
+            ]
+        );
+    }
+
+    #[test]
+    fn test_drive() {
+        let mut droid = Droid::new(Area::new(AREA));
+        let mut frames = Frames::default();
+        let moves = [Direction::Down, Direction::Down, Direction::Right];
+        let pressed = moves.iter().map(|direction| Key::Move(*direction));
+        let map = drive(&mut droid, pressed.chain(Some(Key::Quit)), &mut frames).unwrap();
+        assert!(map.is_none());
+        assert_eq!(frames.0.len(), 4);
+        assert!(frames.0[3].ends_with("quits\n. \n. \n.D\n"));
+
+        // The explorer has to walk back to the start to map the rest
+        let mut droid = Droid::new(Area::new(AREA));
+        let pressed = moves.iter().map(|direction| Key::Move(*direction));
+        let map = drive(
+            &mut droid,
+            pressed.chain(Some(Key::AutoFinish)),
+            &mut frames,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(map.oxygen_distance(), Some(3));
+        assert_eq!(map.fill_time(), Some(4));
+    }
+}
@@ -0,0 +1,34 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_15::{drive_droid, watch_droid, Day15};
+use intcode::read_intcode_input;
+use std::env;
+
+fn main() {
+    let input = read_stdin();
+    print_answers(&Day15, &input, timing_requested());
+
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--time").collect();
+    let drive = match args.as_slice() {
+        [flag] if flag == "--watch" => false,
+        [flag] if flag == "--drive" => true,
+        _ => return,
+    };
+    let memory = read_intcode_input(input.as_bytes()).expect("Invalid puzzle input");
+    if !drive {
+        if let Err(error) = watch_droid(memory) {
+            eprintln!("Could not play the exploration: {}", error);
+        }
+        return;
+    }
+    match drive_droid(memory) {
+        Ok(Some(map)) => match (map.oxygen_distance(), map.fill_time()) {
+            (Some(distance), Some(minutes)) => println!(
+                "The oxygen system is {} steps away and fills the area in {} minutes",
+                distance, minutes
+            ),
+            _ => println!("The droid didn't find the oxygen system"),
+        },
+        Ok(None) => {}
+        Err(error) => eprintln!("Could not drive the droid: {}", error),
+    }
+}
@@ -0,0 +1,32 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day_16::{parse_signal, real_signal_message};
+
+// A made up 650 digit signal, the length of a puzzle input, repeated to the
+// full 6.5 million digits by part 2
+fn signal(offset: &str) -> Vec<i32> {
+    let mut state: u32 = 2019;
+    let digits: String = (0..650 - offset.len())
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            std::char::from_digit((state >> 16) % 10, 10).unwrap()
+        })
+        .collect();
+    parse_signal(&format!("{}{}", offset, digits)).unwrap()
+}
+
+fn real_signal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("real signal");
+    group.sample_size(10);
+    // Where puzzle inputs put the message, and the earliest offset that
+    // works, which leaves half the signal to go through
+    for (name, offset) in [("usual offset", "5975483"), ("half signal", "3250000")] {
+        let signal = signal(offset);
+        group.bench_function(name, |b| {
+            b.iter(|| real_signal_message(black_box(&signal)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, real_signal);
+criterion_main!(benches);
@@ -0,0 +1,218 @@
+use aoc_utils::Puzzle;
+use rayon::prelude::*;
+
+const BASE_PATTERN: [i32; 4] = [0, 1, 0, -1];
+
+// Digits every thread sums at once in the second half of the signal
+const CHUNK_SIZE: usize = 1 << 16;
+
+pub fn parse_signal(input: &str) -> Result<Vec<i32>, &'static str> {
+    input
+        .trim()
+        .chars()
+        .map(|c| {
+            c.to_digit(10)
+                .map(|d| d as i32)
+                .ok_or("Invalid digit in signal")
+        })
+        .collect()
+}
+
+fn pattern_value(position: usize, index: usize) -> i32 {
+    // The pattern is shifted left by one, so the first value is skipped
+    BASE_PATTERN[((index + 1) / (position + 1)) % BASE_PATTERN.len()]
+}
+
+fn phase(signal: &[i32]) -> Vec<i32> {
+    (0..signal.len())
+        .map(|position| {
+            let sum: i32 = signal
+                .iter()
+                .enumerate()
+                .skip(position)
+                .map(|(index, value)| value * pattern_value(position, index))
+                .sum();
+            sum.abs() % 10
+        })
+        .collect()
+}
+
+fn to_string(digits: &[i32]) -> String {
+    digits
+        .iter()
+        .map(|digit| std::char::from_digit(*digit as u32, 10).unwrap())
+        .collect()
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip(signal)))]
+fn run_phases(mut signal: Vec<i32>, phases: usize) -> Vec<i32> {
+    for _ in 0..phases {
+        signal = phase(&signal);
+    }
+    signal
+}
+
+fn first_eight_after_phases(signal: &[i32], phases: usize) -> Result<String, &'static str> {
+    if signal.len() < 8 {
+        return Err("signal is too short");
+    }
+    Ok(to_string(&run_phases(signal.to_vec(), phases)[..8]))
+}
+
+// Replaces every digit with the sum of it and all the digits after it. The
+// chunks are summed in parallel first, so each one knows what the chunks
+// after it add, and then filled in parallel.
+fn suffix_sums(digits: &mut [i32]) {
+    let totals: Vec<i32> = digits
+        .par_chunks(CHUNK_SIZE)
+        .map(|chunk| chunk.iter().sum::<i32>() % 10)
+        .collect();
+    let mut after = vec![0; totals.len()];
+    let mut sum = 0;
+    for (index, total) in totals.iter().enumerate().rev() {
+        after[index] = sum;
+        sum = (sum + total) % 10;
+    }
+
+    digits
+        .par_chunks_mut(CHUNK_SIZE)
+        .zip(after)
+        .for_each(|(chunk, mut sum)| {
+            // Both are single digits, so a subtraction is all the modulo
+            // needs, and is a lot cheaper than a division
+            for value in chunk.iter_mut().rev() {
+                sum += *value;
+                if sum >= 10 {
+                    sum -= 10;
+                }
+                *value = sum;
+            }
+        });
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn real_signal_message(signal: &[i32]) -> Result<String, &'static str> {
+    // The offset takes the first seven digits
+    if signal.len() < 7 {
+        return Err("signal is too short");
+    }
+    let offset = signal[..7]
+        .iter()
+        .fold(0, |acc, digit| acc * 10 + *digit as usize);
+    let total_length = signal.len() * 10_000;
+
+    // The trick only works when the message sits in the second half of the
+    // signal, where every pattern value is 1 from the position onwards. Each
+    // digit is then the sum of all digits after it.
+    if offset < total_length / 2 || offset + 8 > total_length {
+        return Err("Message offset is not in the second half of the signal");
+    }
+
+    let mut tail: Vec<i32> = (offset..total_length)
+        .map(|index| signal[index % signal.len()])
+        .collect();
+
+    for _ in 0..100 {
+        suffix_sums(&mut tail);
+    }
+
+    Ok(to_string(&tail[..8]))
+}
+
+pub struct Day16;
+
+impl Puzzle for Day16 {
+    type Input = Vec<i32>;
+
+    fn parse(&self, input: &str) -> Result<Vec<i32>, String> {
+        parse_signal(input).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, signal: &Vec<i32>) -> Result<String, String> {
+        first_eight_after_phases(signal, 100).map_err(|error| error.to_string())
+    }
+
+    fn part2(&self, signal: &Vec<i32>) -> Result<String, String> {
+        real_signal_message(signal).map_err(|error| error.to_string())
+    }
+}
+
+aoc_utils::register_solution!(16, Day16);
+
+#[cfg(test)]
+mod test {
+    use super::{first_eight_after_phases, parse_signal, real_signal_message, suffix_sums, Day16};
+    use aoc_utils::Solution;
+
+    #[test]
+    fn test_suffix_sums() {
+        // Long enough to span a few chunks
+        let mut digits: Vec<i32> = (0..200_000).map(|index| index * 7 % 10).collect();
+        let mut expected = digits.clone();
+        let mut sum = 0;
+        for value in expected.iter_mut().rev() {
+            sum = (sum + *value) % 10;
+            *value = sum;
+        }
+        suffix_sums(&mut digits);
+        assert_eq!(digits, expected);
+    }
+
+    #[test]
+    fn test_phases_case_1() {
+        let signal = parse_signal("12345678").unwrap();
+        assert_eq!(first_eight_after_phases(&signal, 1).unwrap(), "48226158");
+        assert_eq!(first_eight_after_phases(&signal, 4).unwrap(), "01029498");
+    }
+
+    #[test]
+    fn test_phases_case_2() {
+        let signal = parse_signal("80871224585914546619083218645595").unwrap();
+        assert_eq!(first_eight_after_phases(&signal, 100).unwrap(), "24176176");
+    }
+
+    #[test]
+    fn test_phases_case_3() {
+        let signal = parse_signal("19617804207202209144916044189917").unwrap();
+        assert_eq!(first_eight_after_phases(&signal, 100).unwrap(), "73745418");
+    }
+
+    #[test]
+    fn test_short_signal() {
+        let signal = parse_signal("1234567").unwrap();
+        assert_eq!(
+            first_eight_after_phases(&signal, 1),
+            Err("signal is too short")
+        );
+        let signal = parse_signal("123456").unwrap();
+        assert_eq!(real_signal_message(&signal), Err("signal is too short"));
+    }
+
+    #[test]
+    fn test_offset_outside_signal() {
+        // Part 1 works on any eight digits, the offset of part 2 doesn't
+        assert!(Day16.part1("12345678").is_ok());
+        assert_eq!(
+            Day16.part2("12345678"),
+            Err("Message offset is not in the second half of the signal".to_string())
+        );
+    }
+
+    #[test]
+    fn test_real_signal_case_1() {
+        let signal = parse_signal("03036732577212944063491565474664").unwrap();
+        assert_eq!(real_signal_message(&signal), Ok("84462026".to_string()));
+    }
+
+    #[test]
+    fn test_real_signal_case_2() {
+        let signal = parse_signal("02935109699940807407585447034323").unwrap();
+        assert_eq!(real_signal_message(&signal), Ok("78725270".to_string()));
+    }
+
+    #[test]
+    fn test_real_signal_case_3() {
+        let signal = parse_signal("03081770884921959731165446850517").unwrap();
+        assert_eq!(real_signal_message(&signal), Ok("53553731".to_string()));
+    }
+}
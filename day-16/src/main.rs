@@ -0,0 +1,6 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_16::Day16;
+
+fn main() {
+    print_answers(&Day16, &read_stdin(), timing_requested());
+}
@@ -0,0 +1,670 @@
+use aoc_utils::terminal::{FrameLimiter, FrameSink, Terminal};
+use aoc_utils::{Direction, Grid, Point2, Puzzle};
+use intcode::{read_intcode_input, IntCodeError, IntCodeMachine};
+use recorder::Recorder;
+use std::collections::HashSet;
+use std::fmt;
+
+const MAX_ROUTINE_LENGTH: usize = 20;
+const FUNCTION_NAMES: [char; 3] = ['A', 'B', 'C'];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Move {
+    Left(usize),
+    Right(usize),
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::Left(distance) => write!(f, "L,{}", distance),
+            Move::Right(distance) => write!(f, "R,{}", distance),
+        }
+    }
+}
+
+fn routine_to_string(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(Move::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+struct Scaffold {
+    cells: Grid<bool>,
+    robot: Point2,
+    direction: Direction,
+}
+
+impl Scaffold {
+    fn parse(view: &str) -> Result<Self, &'static str> {
+        let view: Grid<char> = Grid::parse(view, |c| match c {
+            '#' | '.' | 'X' | '^' | 'v' | '<' | '>' => Ok(c),
+            _ => Err("Invalid character in camera view"),
+        })?;
+        let robot = view
+            .find(|c| Direction::from_char(*c).is_some())
+            .ok_or("Robot not found in camera view")?;
+        Ok(Self {
+            cells: view.map(|c| *c != '.' && *c != 'X'),
+            robot,
+            direction: Direction::from_char(view[robot]).unwrap(),
+        })
+    }
+
+    fn is_scaffold(&self, point: Point2) -> bool {
+        self.cells.get(point).copied().unwrap_or(false)
+    }
+
+    fn alignment_sum(&self) -> i64 {
+        self.cells
+            .iter()
+            .filter(|(point, cell)| {
+                **cell
+                    && point
+                        .neighbours()
+                        .iter()
+                        .all(|point| self.is_scaffold(*point))
+            })
+            .map(|(point, _)| point.x * point.y)
+            .sum()
+    }
+
+    fn path(&self) -> Vec<Move> {
+        let step = |position: Point2, direction: Direction| position + direction.screen_delta();
+
+        let mut moves = Vec::new();
+        let mut position = self.robot;
+        let mut direction = self.direction;
+        loop {
+            let left = direction.turn_left();
+            let right = direction.turn_right();
+            let turn: fn(usize) -> Move = if self.is_scaffold(step(position, left)) {
+                direction = left;
+                Move::Left
+            } else if self.is_scaffold(step(position, right)) {
+                direction = right;
+                Move::Right
+            } else {
+                return moves;
+            };
+
+            let mut distance = 0;
+            while self.is_scaffold(step(position, direction)) {
+                position = step(position, direction);
+                distance += 1;
+            }
+            moves.push(turn(distance));
+        }
+    }
+
+    /// Moves the robot as the routines tell it to, checking it never steps
+    /// off the scaffold and visits all of it.
+    fn follow(&self, routines: &Routines) -> Result<(), RoutineError> {
+        let mut position = self.robot;
+        let mut direction = self.direction;
+        let mut visited = HashSet::new();
+        visited.insert(position);
+        for (call, index) in routines.main.iter().enumerate() {
+            for (number, movement) in routines.functions[*index].iter().enumerate() {
+                let distance = match *movement {
+                    Move::Left(distance) => {
+                        direction = direction.turn_left();
+                        distance
+                    }
+                    Move::Right(distance) => {
+                        direction = direction.turn_right();
+                        distance
+                    }
+                };
+                for step in 0..distance {
+                    let next = position + direction.screen_delta();
+                    if !self.is_scaffold(next) {
+                        return Err(RoutineError::Derailed {
+                            call: call + 1,
+                            function: FUNCTION_NAMES[*index],
+                            movement: number + 1,
+                            position,
+                            steps: step,
+                        });
+                    }
+                    position = next;
+                    visited.insert(position);
+                }
+            }
+        }
+        let missed = self
+            .cells
+            .iter()
+            .filter(|(point, cell)| **cell && !visited.contains(point))
+            .count();
+        if missed > 0 {
+            return Err(RoutineError::Unfinished { position, missed });
+        }
+        Ok(())
+    }
+}
+
+/// Why a set of movement routines doesn't work.
+#[derive(Debug, PartialEq)]
+pub enum RoutineError {
+    TooLong {
+        routine: String,
+        length: usize,
+    },
+    Invalid {
+        routine: String,
+        token: String,
+    },
+    UndefinedFunction(char),
+    /// More functions were given than the robot has names for.
+    TooManyFunctions(usize),
+    /// The robot would leave the scaffold during the `call`th call in main,
+    /// after taking `steps` steps of the `movement`th move of the function.
+    Derailed {
+        call: usize,
+        function: char,
+        movement: usize,
+        position: Point2,
+        steps: usize,
+    },
+    /// The robot stayed on the scaffold but didn't visit all of it.
+    Unfinished {
+        position: Point2,
+        missed: usize,
+    },
+}
+
+impl fmt::Display for RoutineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoutineError::TooLong { routine, length } => write!(
+                f,
+                "Routine {} is {} characters long, the limit is {}",
+                routine, length, MAX_ROUTINE_LENGTH
+            ),
+            RoutineError::Invalid { routine, token } => {
+                write!(f, "Routine {} has an invalid move: {:?}", routine, token)
+            }
+            RoutineError::UndefinedFunction(name) => {
+                write!(f, "Main calls function {}, which is empty", name)
+            }
+            RoutineError::TooManyFunctions(count) => write!(
+                f,
+                "{} functions were given, the robot only has {}",
+                count,
+                FUNCTION_NAMES.len()
+            ),
+            RoutineError::Derailed {
+                call,
+                function,
+                movement,
+                position,
+                steps,
+            } => write!(
+                f,
+                "Call {} in main (function {}) leaves the scaffold on move {}, at {} after {} steps",
+                call, function, movement, position, steps
+            ),
+            RoutineError::Unfinished { position, missed } => write!(
+                f,
+                "The robot stops at {} without visiting {} scaffold cells",
+                position, missed
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RoutineError {}
+
+#[derive(Debug)]
+pub struct Routines {
+    main: Vec<usize>,
+    functions: Vec<Vec<Move>>,
+}
+
+impl Routines {
+    /// Reads routines as they would be typed into the robot: the main
+    /// routine as calls to `A`, `B` and `C`, and every function as turns
+    /// followed by distances, all separated by commas. Unused functions can
+    /// be left empty.
+    pub fn parse(main: &str, functions: &[&str]) -> Result<Self, RoutineError> {
+        let check_length = |routine: &str, text: &str| {
+            if text.len() > MAX_ROUTINE_LENGTH {
+                Err(RoutineError::TooLong {
+                    routine: routine.to_string(),
+                    length: text.len(),
+                })
+            } else {
+                Ok(())
+            }
+        };
+        let invalid = |routine: &str, token: &str| RoutineError::Invalid {
+            routine: routine.to_string(),
+            token: token.to_string(),
+        };
+
+        let main = main.trim();
+        check_length("main", main)?;
+        let main = main
+            .split(',')
+            .map(|call| match call.trim() {
+                "A" => Ok(0),
+                "B" => Ok(1),
+                "C" => Ok(2),
+                call => Err(invalid("main", call)),
+            })
+            .collect::<Result<Vec<usize>, _>>()?;
+
+        if functions.len() > FUNCTION_NAMES.len() {
+            return Err(RoutineError::TooManyFunctions(functions.len()));
+        }
+        let mut parsed = Vec::new();
+        for (name, text) in FUNCTION_NAMES.iter().zip(functions) {
+            let routine = name.to_string();
+            let text = text.trim();
+            check_length(&routine, text)?;
+            let mut moves = Vec::new();
+            let mut tokens = text
+                .split(',')
+                .map(str::trim)
+                .filter(|token| !token.is_empty());
+            while let Some(turn) = tokens.next() {
+                let turn: fn(usize) -> Move = match turn {
+                    "L" => Move::Left,
+                    "R" => Move::Right,
+                    _ => return Err(invalid(&routine, turn)),
+                };
+                let distance = tokens.next().ok_or_else(|| invalid(&routine, ""))?;
+                let distance = distance.parse().map_err(|_| invalid(&routine, distance))?;
+                moves.push(turn(distance));
+            }
+            parsed.push(moves);
+        }
+        for index in main.iter() {
+            if parsed.get(*index).is_none_or(Vec::is_empty) {
+                return Err(RoutineError::UndefinedFunction(FUNCTION_NAMES[*index]));
+            }
+        }
+        Ok(Self {
+            main,
+            functions: parsed,
+        })
+    }
+
+    /// Reads the main routine and the functions from one line each.
+    pub fn parse_lines(text: &str) -> Result<Self, RoutineError> {
+        let mut lines = text.lines();
+        let main = lines.next().unwrap_or("");
+        let functions: Vec<&str> = lines.take(FUNCTION_NAMES.len()).collect();
+        Self::parse(main, &functions)
+    }
+
+    fn main_to_string(&self) -> String {
+        self.main
+            .iter()
+            .map(|index| ((b'A' + *index as u8) as char).to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn to_input(&self, video_feed: bool) -> String {
+        let mut input = self.main_to_string();
+        input.push('\n');
+        for index in 0..3 {
+            let function = self.functions.get(index).map_or(&[][..], Vec::as_slice);
+            input.push_str(&routine_to_string(function));
+            input.push('\n');
+        }
+        input.push_str(if video_feed { "y\n" } else { "n\n" });
+        input
+    }
+}
+
+fn compress_from<'a>(
+    path: &'a [Move],
+    position: usize,
+    functions: &mut Vec<&'a [Move]>,
+    main: &mut Vec<usize>,
+) -> bool {
+    if position == path.len() {
+        return true;
+    }
+    // Every call in main takes one character plus a comma
+    if 2 * (main.len() + 1) - 1 > MAX_ROUTINE_LENGTH {
+        return false;
+    }
+
+    for index in 0..functions.len() {
+        let function = functions[index];
+        if path[position..].starts_with(function) {
+            main.push(index);
+            if compress_from(path, position + function.len(), functions, main) {
+                return true;
+            }
+            main.pop();
+        }
+    }
+
+    if functions.len() < 3 {
+        for end in position + 1..=path.len() {
+            let function = &path[position..end];
+            if routine_to_string(function).len() > MAX_ROUTINE_LENGTH {
+                break;
+            }
+            main.push(functions.len());
+            functions.push(function);
+            if compress_from(path, end, functions, main) {
+                return true;
+            }
+            functions.pop();
+            main.pop();
+        }
+    }
+    false
+}
+
+fn compress(path: &[Move]) -> Option<Routines> {
+    let mut functions = Vec::new();
+    let mut main = Vec::new();
+    if compress_from(path, 0, &mut functions, &mut main) {
+        Some(Routines {
+            main,
+            functions: functions.into_iter().map(<[Move]>::to_vec).collect(),
+        })
+    } else {
+        None
+    }
+}
+
+fn read_camera(memory: &[isize]) -> Result<String, IntCodeError> {
+    let mut machine = IntCodeMachine::new(memory.to_vec());
+    let output = machine.execute([])?;
+    Ok(output
+        .into_iter()
+        .map(|value| value as u8 as char)
+        .collect())
+}
+
+// Setting the first address wakes the robot up to take routines
+fn wake_robot(memory: &[isize]) -> IntCodeMachine {
+    let mut memory = memory.to_vec();
+    memory[0] = 2;
+    IntCodeMachine::new(memory)
+}
+
+fn run_robot(
+    memory: &[isize],
+    routines: &Routines,
+    video_feed: bool,
+) -> Result<Vec<isize>, IntCodeError> {
+    let input = routines.to_input(video_feed);
+    wake_robot(memory).execute(input.bytes().map(|value| value as isize))
+}
+
+/// Runs the robot with the routines, returning the dust it collected.
+pub fn collect_dust(memory: &[isize], routines: &Routines) -> Result<isize, IntCodeError> {
+    let output = run_robot(memory, routines, false)?;
+    output
+        .last()
+        .copied()
+        .ok_or(IntCodeError::UnexpectedOutput(output))
+}
+
+// With the video feed on, the robot prints the whole scaffold after every
+// move. Frames are separated by blank lines, mixed with the routine prompts.
+fn video_frames(output: &[isize]) -> Vec<String> {
+    let text: String = output
+        .iter()
+        .filter(|value| **value < 128)
+        .map(|value| *value as u8 as char)
+        .collect();
+    text.split("\n\n")
+        .filter(|frame| !frame.trim().is_empty() && frame.chars().all(|c| "#.^v<>X\n".contains(c)))
+        .map(str::to_string)
+        .collect()
+}
+
+pub fn record_video(
+    memory: &[isize],
+    routines: &Routines,
+    path: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let output = run_robot(memory, routines, true)?;
+    let palette = [[16, 16, 32], [120, 120, 140], [255, 200, 0], [255, 60, 60]];
+    let mut recorder = Recorder::new(&palette).with_scale(6).with_delay(3);
+    for frame in video_frames(&output) {
+        recorder.push_text(&frame, |c| match c {
+            '#' => 1,
+            '^' | 'v' | '<' | '>' => 2,
+            'X' => 3,
+            _ => 0,
+        });
+    }
+    recorder.save(path)?;
+    Ok(recorder.len())
+}
+
+pub fn watch_video(
+    memory: &[isize],
+    routines: &Routines,
+) -> Result<(), Box<dyn std::error::Error>> {
+    play_video(memory, routines, &mut Terminal::stdout()?)
+}
+
+/// Plays the robot's video feed on any frame sink, at 30 frames a second.
+/// The robot runs to the end before the first frame, and the sink is told
+/// how much work that took.
+pub fn play_video(
+    memory: &[isize],
+    routines: &Routines,
+    sink: &mut dyn FrameSink,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut machine = wake_robot(memory);
+    let input = routines.to_input(true);
+    let output = machine.execute(input.bytes().map(|value| value as isize))?;
+    sink.machine_ran(machine.executed(), input.len() as u64, output.len() as u64);
+    let mut limiter = FrameLimiter::new(30);
+    for frame in video_frames(&output) {
+        limiter.wait();
+        sink.draw(&frame)?;
+    }
+    Ok(())
+}
+
+pub fn parse(input: &str) -> Result<Vec<isize>, String> {
+    read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())
+}
+
+/// Splits the path along the scaffold into the movement routines.
+pub fn find_routines(memory: &[isize]) -> Result<Routines, String> {
+    let view = read_camera(memory).map_err(|error| error.to_string())?;
+    let scaffold = Scaffold::parse(&view).map_err(|_| "Invalid camera output")?;
+    let path = scaffold.path();
+    compress(&path).ok_or_else(|| format!("Could not compress path {}", routine_to_string(&path)))
+}
+
+/// Checks the routines walk the robot over the whole scaffold without ever
+/// leaving it.
+pub fn check_routines(
+    memory: &[isize],
+    routines: &Routines,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let view = read_camera(memory)?;
+    let scaffold = Scaffold::parse(&view)?;
+    scaffold.follow(routines)?;
+    Ok(())
+}
+
+pub struct Day17;
+
+impl Puzzle for Day17 {
+    type Input = Vec<isize>;
+
+    fn parse(&self, input: &str) -> Result<Vec<isize>, String> {
+        parse(input)
+    }
+
+    fn part1(&self, memory: &Vec<isize>) -> Result<String, String> {
+        let view = read_camera(memory).map_err(|error| error.to_string())?;
+        let scaffold = Scaffold::parse(&view).map_err(|_| "Invalid camera output")?;
+        Ok(scaffold.alignment_sum().to_string())
+    }
+
+    fn part2(&self, memory: &Vec<isize>) -> Result<String, String> {
+        let routines = find_routines(memory)?;
+        collect_dust(memory, &routines)
+            .map(|dust| dust.to_string())
+            .map_err(|error| error.to_string())
+    }
+}
+
+aoc_utils::register_solution!(17, Day17);
+
+#[cfg(test)]
+mod test {
+    use super::{
+        collect_dust, compress, routine_to_string, video_frames, RoutineError, Routines, Scaffold,
+    };
+    use intcode::IntCodeError;
+
+    const INTERSECTIONS: &str = "\
+..#..........
+..#..........
+#######...###
+#.#...#...#.#
+#############
+..#...#...#..
+..#####...^..
+";
+
+    const MOVEMENT: &str = "\
+#######...#####
+#.....#...#...#
+#.....#...#...#
+......#...#...#
+......#...###.#
+......#.....#.#
+^########...#.#
+......#.#...#.#
+......#########
+........#...#..
+....#########..
+....#...#......
+....#...#......
+....#...#......
+....#####......
+";
+
+    #[test]
+    fn test_alignment_sum() {
+        let scaffold = Scaffold::parse(INTERSECTIONS).unwrap();
+        assert_eq!(scaffold.alignment_sum(), 76);
+    }
+
+    #[test]
+    fn test_path() {
+        let scaffold = Scaffold::parse(MOVEMENT).unwrap();
+        assert_eq!(
+            routine_to_string(&scaffold.path()),
+            "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2"
+        );
+    }
+
+    #[test]
+    fn test_compress() {
+        let path = Scaffold::parse(MOVEMENT).unwrap().path();
+        let routines = compress(&path).unwrap();
+        assert!(routines.functions.len() <= 3);
+        assert!(routines.main_to_string().len() <= 20);
+        let expanded: Vec<_> = routines
+            .main
+            .iter()
+            .flat_map(|index| routines.functions[*index].iter().cloned())
+            .collect();
+        assert_eq!(expanded, path);
+        for function in routines.functions.iter() {
+            assert!(routine_to_string(function).len() <= 20);
+        }
+    }
+
+    #[test]
+    fn test_video_frames() {
+        let text = "Main:\nContinuous video feed?\n\n#^#\n..#\n\n#.#\n..v\n\n";
+        let mut output: Vec<isize> = text.bytes().map(|value| value as isize).collect();
+        output.push(1234);
+        assert_eq!(video_frames(&output), ["#^#\n..#", "#.#\n..v"]);
+    }
+
+    #[test]
+    fn test_parse_routines() {
+        let routines = Routines::parse("A,B,A", &["R,8,R,8", "L,6"]).unwrap();
+        assert_eq!(routines.main, [0, 1, 0]);
+        assert_eq!(routines.to_input(false), "A,B,A\nR,8,R,8\nL,6\n\nn\n");
+
+        let long = "R,10,R,10,R,10,R,10,R";
+        assert_eq!(
+            Routines::parse("A", &[long]).unwrap_err(),
+            RoutineError::TooLong {
+                routine: "A".to_string(),
+                length: 21
+            }
+        );
+        assert_eq!(
+            Routines::parse("A,D", &["R,8"]).unwrap_err(),
+            RoutineError::Invalid {
+                routine: "main".to_string(),
+                token: "D".to_string()
+            }
+        );
+        assert_eq!(
+            Routines::parse("A,C", &["R,8", "L,6"]).unwrap_err(),
+            RoutineError::UndefinedFunction('C')
+        );
+        assert_eq!(
+            Routines::parse("A", &["R,8", "L,6", "R,4", "L,2"]).unwrap_err(),
+            RoutineError::TooManyFunctions(4)
+        );
+        assert!(matches!(
+            Routines::parse("A", &["R,8,L"]),
+            Err(RoutineError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_follow() {
+        let scaffold = Scaffold::parse(MOVEMENT).unwrap();
+        let routines =
+            Routines::parse_lines("A,B,C,B,A,C\nR,8,R,8\nR,4,R,4,R,8\nL,6,L,2\n").unwrap();
+        assert_eq!(scaffold.follow(&routines), Ok(()));
+
+        // B's second move should stop after 4 steps, at the corner
+        let routines = Routines::parse("A,B", &["R,8,R,8", "R,4,R,6"]).unwrap();
+        assert_eq!(
+            scaffold.follow(&routines),
+            Err(RoutineError::Derailed {
+                call: 2,
+                function: 'B',
+                movement: 2,
+                position: (4, 10).into(),
+                steps: 4,
+            })
+        );
+
+        let routines = Routines::parse("A", &["R,8,R,8"]).unwrap();
+        assert!(matches!(
+            scaffold.follow(&routines),
+            Err(RoutineError::Unfinished { missed, .. }) if missed > 0
+        ));
+    }
+
+    #[test]
+    fn test_collect_nothing() {
+        // Woken up, the robot multiplies its first cell and halts silently
+        let routines = Routines::parse("A", &["R,8"]).unwrap();
+        let error = collect_dust(&[0, 0, 0, 0, 99], &routines).unwrap_err();
+        assert!(matches!(error, IntCodeError::UnexpectedOutput(ref output) if output.is_empty()));
+        assert_eq!(error.to_string(), "Expected output, found none");
+    }
+}
@@ -0,0 +1,113 @@
+use aoc_utils::solution::{print_answers, print_solved, read_stdin, timing_requested, Solved};
+use aoc_utils::Puzzle;
+use day_17::{
+    check_routines, collect_dust, find_routines, parse, record_video, watch_video, Day17, Routines,
+};
+use std::time::Instant;
+use std::{env, fs, process};
+
+const USAGE: &str = "Usage: day-17 [--time] [--routines MAIN [A] [B] [C] | --routines-file PATH] \
+                     [--record PATH | --watch]";
+
+// Routines from the command line, either as `--routines MAIN A B C` or as a
+// file with one routine per line. Returns the arguments left over.
+fn given_routines(args: &[String]) -> (Option<Result<Routines, String>>, &[String]) {
+    match args {
+        [flag, main, functions @ ..] if flag == "--routines" && !main.starts_with("--") => {
+            let count = functions
+                .iter()
+                .take_while(|arg| !arg.starts_with("--"))
+                .count();
+            let (functions, rest) = functions.split_at(count);
+            let functions: Vec<&str> = functions.iter().map(String::as_str).collect();
+            let routines = Routines::parse(main, &functions).map_err(|error| error.to_string());
+            (Some(routines), rest)
+        }
+        [flag, path, rest @ ..] if flag == "--routines-file" && !path.starts_with("--") => {
+            let routines = fs::read_to_string(path)
+                .map_err(|error| format!("Could not read {}: {}", path, error))
+                .and_then(|text| Routines::parse_lines(&text).map_err(|error| error.to_string()));
+            (Some(routines), rest)
+        }
+        _ => (None, args),
+    }
+}
+
+enum Video<'a> {
+    Record(&'a str),
+    Watch,
+}
+
+fn video(args: &[String]) -> Option<Option<Video<'_>>> {
+    match args {
+        [] => Some(None),
+        [flag, path] if flag == "--record" && !path.starts_with("--") => {
+            Some(Some(Video::Record(path)))
+        }
+        [flag] if flag == "--watch" => Some(Some(Video::Watch)),
+        _ => None,
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--time").collect();
+    let (given, rest) = given_routines(&args);
+    let video = video(rest).unwrap_or_else(|| {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    });
+    let given = given.map(|routines| routines.unwrap_or_else(|error| fail(&error)));
+
+    let input = read_stdin();
+    let (memory, routines) = match given {
+        // The given routines replace the automatic compression, so part 2
+        // still works when it can't find any.
+        Some(routines) => {
+            let start = Instant::now();
+            let memory = parse(&input).unwrap_or_else(|error| fail(&error));
+            let parsed = start.elapsed();
+            let part1 = Day17.part1(&memory).unwrap_or_else(|error| fail(&error));
+            let part1_time = start.elapsed() - parsed;
+            if let Err(error) = check_routines(&memory, &routines) {
+                fail(&error.to_string());
+            }
+            let part2 = collect_dust(&memory, &routines)
+                .unwrap_or_else(|error| fail(&format!("The robot failed: {}", error)));
+            let solved = Solved {
+                part1,
+                part2: part2.to_string(),
+                parse_time: Some(parsed),
+                part1_time,
+                part2_time: start.elapsed() - parsed - part1_time,
+            };
+            print_solved(&solved, timing_requested());
+            (memory, routines)
+        }
+        None => {
+            print_answers(&Day17, &input, timing_requested());
+            if video.is_none() {
+                return;
+            }
+            let memory = parse(&input).unwrap_or_else(|error| fail(&error));
+            let routines = find_routines(&memory).unwrap_or_else(|error| fail(&error));
+            (memory, routines)
+        }
+    };
+    match video {
+        Some(Video::Record(path)) => match record_video(&memory, &routines, path) {
+            Ok(frames) => println!("Recorded {} frames to {}", frames, path),
+            Err(error) => fail(&format!("Could not record video: {}", error)),
+        },
+        Some(Video::Watch) => {
+            if let Err(error) = watch_video(&memory, &routines) {
+                fail(&format!("Could not play video: {}", error));
+            }
+        }
+        None => {}
+    }
+}
@@ -0,0 +1,376 @@
+use aoc_utils::search::dijkstra;
+use aoc_utils::solution::{or_no_solution, Puzzle};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+type Position = (usize, usize);
+
+// Nodes 0..26 are the keys 'a'..'z', entrances come right after them
+const ENTRANCE_NODE: usize = 26;
+
+// Splitting the entrance makes four, which is the most the search handles
+const MAX_ROBOTS: usize = 4;
+
+// The node every robot stands on, with the unused slots left at the first
+// entrance, and the keys collected so far
+type State = ([usize; MAX_ROBOTS], u32);
+
+struct Edge {
+    target: usize,
+    distance: usize,
+    // Doors that must be open to walk this edge
+    doors: u32,
+    // Keys that get picked up on the way to the target
+    keys: u32,
+}
+
+#[derive(Clone)]
+pub struct Maze {
+    cells: Vec<Vec<u8>>,
+}
+
+fn key_bit(cell: u8) -> u32 {
+    1 << (cell.to_ascii_lowercase() - b'a')
+}
+
+impl Maze {
+    fn parse(input: &str) -> Result<Self, &'static str> {
+        let cells: Vec<Vec<u8>> = input
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| line.bytes().collect())
+            .collect();
+        for cell in cells.iter().flatten() {
+            match cell {
+                b'#' | b'.' | b'@' | b'a'..=b'z' | b'A'..=b'Z' => {}
+                _ => return Err("Invalid character in maze"),
+            }
+        }
+        let maze = Self { cells };
+        match maze.entrances().len() {
+            0 => Err("The maze has no entrance"),
+            1..=MAX_ROBOTS => Ok(maze),
+            _ => Err("The maze has more than four entrances"),
+        }
+    }
+
+    fn get(&self, (x, y): Position) -> u8 {
+        self.cells
+            .get(y)
+            .and_then(|row| row.get(x))
+            .copied()
+            .unwrap_or(b'#')
+    }
+
+    fn entrances(&self) -> Vec<Position> {
+        self.points_of_interest()
+            .filter(|(cell, _)| *cell == b'@')
+            .map(|(_, position)| position)
+            .collect()
+    }
+
+    fn points_of_interest(&self) -> impl Iterator<Item = (u8, Position)> + '_ {
+        self.cells.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, cell)| **cell == b'@' || cell.is_ascii_lowercase())
+                .map(move |(x, cell)| (*cell, (x, y)))
+        })
+    }
+
+    fn all_keys(&self) -> u32 {
+        self.points_of_interest()
+            .filter(|(cell, _)| cell.is_ascii_lowercase())
+            .fold(0, |keys, (cell, _)| keys | key_bit(cell))
+    }
+
+    // Replaces the single entrance and its neighbours with four walled-off
+    // entrances, one on each quadrant
+    fn split_entrance(&mut self) -> Result<(), &'static str> {
+        let entrances = self.entrances();
+        if entrances.len() != 1 {
+            return Err("Expected a single entrance to split");
+        }
+        let (x, y) = entrances[0];
+        if x == 0 || y == 0 {
+            return Err("Entrance is on the edge of the map");
+        }
+        // Keys or doors next to the entrance would be walled over
+        for dy in 0..3 {
+            for dx in 0..3 {
+                match self.get((x + dx - 1, y + dy - 1)) {
+                    b'.' | b'@' => {}
+                    _ => return Err("Entrance is not surrounded by open floor"),
+                }
+            }
+        }
+        let patch = [b"@#@", b"###", b"@#@"];
+        for (dy, row) in patch.iter().enumerate() {
+            for (dx, cell) in row.iter().enumerate() {
+                let target = self
+                    .cells
+                    .get_mut(y + dy - 1)
+                    .and_then(|row| row.get_mut(x + dx - 1))
+                    .ok_or("Entrance is on the edge of the map")?;
+                *target = *cell;
+            }
+        }
+        Ok(())
+    }
+
+    fn edges_from(&self, start: Position) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        let mut visited = HashSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(start);
+        queue.push_back((start, 0, 0, 0));
+
+        while let Some(((x, y), distance, doors, keys)) = queue.pop_front() {
+            let cell = self.get((x, y));
+            let mut doors = doors;
+            let mut keys = keys;
+            if cell.is_ascii_uppercase() {
+                doors |= key_bit(cell);
+            } else if cell.is_ascii_lowercase() && (x, y) != start {
+                edges.push(Edge {
+                    target: (cell - b'a') as usize,
+                    distance,
+                    doors,
+                    keys,
+                });
+                keys |= key_bit(cell);
+            }
+
+            let neighbours = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for next in neighbours.iter() {
+                if self.get(*next) != b'#' && visited.insert(*next) {
+                    queue.push_back((*next, distance + 1, doors, keys));
+                }
+            }
+        }
+        edges
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn graph(&self) -> HashMap<usize, Vec<Edge>> {
+        let mut entrance = ENTRANCE_NODE;
+        self.points_of_interest()
+            .map(|(cell, position)| {
+                let node = if cell == b'@' {
+                    entrance += 1;
+                    entrance - 1
+                } else {
+                    (cell - b'a') as usize
+                };
+                (node, self.edges_from(position))
+            })
+            .collect()
+    }
+
+    /// Finds the fewest steps needed to collect every key, moving one robot
+    /// per entrance. The search state is the node each robot is standing on
+    /// plus the set of keys collected so far.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    fn shortest_path(&self) -> Option<usize> {
+        let graph = self.graph();
+        let all_keys = self.all_keys();
+        let count = self.entrances().len();
+        let mut robots = [ENTRANCE_NODE; MAX_ROBOTS];
+        for (index, robot) in robots.iter_mut().enumerate().take(count) {
+            *robot = ENTRANCE_NODE + index;
+        }
+
+        let successors = |(robots, keys): &State| {
+            let mut next = Vec::new();
+            for (index, node) in robots[..count].iter().enumerate() {
+                for edge in graph[node].iter() {
+                    let target_key = 1 << edge.target;
+                    if keys & target_key != 0 || edge.doors & !keys != 0 || edge.keys & !keys != 0 {
+                        continue;
+                    }
+                    let mut next_robots = *robots;
+                    next_robots[index] = edge.target;
+                    next.push(((next_robots, keys | target_key), edge.distance));
+                }
+            }
+            next
+        };
+        dijkstra((robots, 0), successors, |(_, keys)| *keys == all_keys)
+            .map(|(distance, _)| distance)
+    }
+}
+
+pub struct Day18;
+
+impl Puzzle for Day18 {
+    type Input = Maze;
+
+    fn parse(&self, input: &str) -> Result<Maze, String> {
+        Maze::parse(input).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, maze: &Maze) -> Result<String, String> {
+        Ok(or_no_solution(maze.shortest_path()))
+    }
+
+    fn part2(&self, maze: &Maze) -> Result<String, String> {
+        let mut maze = maze.clone();
+        if maze.entrances().len() == 1 {
+            maze.split_entrance()?;
+        }
+        Ok(or_no_solution(maze.shortest_path()))
+    }
+}
+
+aoc_utils::register_solution!(18, Day18);
+
+#[cfg(test)]
+mod test {
+    use super::{Day18, Maze};
+    use aoc_utils::Puzzle;
+
+    fn shortest_path(input: &str) -> Option<usize> {
+        Maze::parse(input).unwrap().shortest_path()
+    }
+
+    fn split_shortest_path(input: &str) -> Option<usize> {
+        let mut maze = Maze::parse(input).unwrap();
+        maze.split_entrance().unwrap();
+        maze.shortest_path()
+    }
+
+    #[test]
+    fn test_single_robot_case_1() {
+        let input = "\
+#########
+#b.A.@.a#
+#########";
+        assert_eq!(shortest_path(input), Some(8));
+    }
+
+    #[test]
+    fn test_single_robot_case_2() {
+        let input = "\
+########################
+#f.D.E.e.C.b.A.@.a.B.c.#
+######################.#
+#d.....................#
+########################";
+        assert_eq!(shortest_path(input), Some(86));
+    }
+
+    #[test]
+    fn test_single_robot_case_3() {
+        let input = "\
+########################
+#...............b.C.D.f#
+#.######################
+#.....@.a.B.c.d.A.e.F.g#
+########################";
+        assert_eq!(shortest_path(input), Some(132));
+    }
+
+    #[test]
+    fn test_single_robot_case_4() {
+        let input = "\
+#################
+#i.G..c...e..H.p#
+########.########
+#j.A..b...f..D.o#
+########@########
+#k.E..a...g..B.n#
+########.########
+#l.F..d...h..C.m#
+#################";
+        assert_eq!(shortest_path(input), Some(136));
+    }
+
+    #[test]
+    fn test_single_robot_case_5() {
+        let input = "\
+########################
+#@..............ac.GI.b#
+###d#e#f################
+###A#B#C################
+###g#h#i################
+########################";
+        assert_eq!(shortest_path(input), Some(81));
+    }
+
+    #[test]
+    fn test_invalid_mazes() {
+        assert!(Maze::parse("#####\n#a.b#\n#####").is_err());
+        assert!(Maze::parse("#@#@#@#@#@#").is_err());
+
+        // A key right next to the entrance would be walled over
+        let mut maze = Maze::parse("#####\n#.a.#\n#.@.#\n#...#\n#####").unwrap();
+        assert!(maze.split_entrance().is_err());
+        assert_eq!(
+            Day18.part2(&Maze::parse("#####\n#.a.#\n#.@.#\n#...#\n#####").unwrap()),
+            Err("Entrance is not surrounded by open floor".to_string())
+        );
+        let mut maze = Maze::parse("#####\n#...#\n#.@.#\n#.a.#\n#...#\n#####").unwrap();
+        assert!(maze.split_entrance().is_err());
+        let mut maze = Maze::parse("#####\n#...#\n#.@.#\n#...#\n#.a.#\n#####").unwrap();
+        assert_eq!(maze.split_entrance(), Ok(()));
+        assert_eq!(maze.entrances().len(), 4);
+    }
+
+    #[test]
+    fn test_four_robots_case_1() {
+        let input = "\
+#######
+#a.#Cd#
+##...##
+##.@.##
+##...##
+#cB#Ab#
+#######";
+        assert_eq!(split_shortest_path(input), Some(8));
+    }
+
+    #[test]
+    fn test_four_robots_case_2() {
+        let input = "\
+###############
+#d.ABC.#.....a#
+######@#@######
+###############
+######@#@######
+#b.....#.....c#
+###############";
+        assert_eq!(shortest_path(input), Some(24));
+    }
+
+    #[test]
+    fn test_four_robots_case_3() {
+        let input = "\
+#############
+#DcBa.#.GhKl#
+#.###@#@#I###
+#e#d#####j#k#
+###C#@#@###J#
+#fEbA.#.FgHi#
+#############";
+        assert_eq!(shortest_path(input), Some(32));
+    }
+
+    #[test]
+    fn test_four_robots_case_4() {
+        let input = "\
+#############
+#g#f.D#..h#l#
+#F###e#E###.#
+#dCba@#@BcIJ#
+#############
+#nK.L@#@G...#
+#M###N#H###.#
+#o#m..#i#jk.#
+#############";
+        assert_eq!(shortest_path(input), Some(72));
+    }
+}
@@ -0,0 +1,6 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_18::Day18;
+
+fn main() {
+    print_answers(&Day18, &read_stdin(), timing_requested());
+}
@@ -0,0 +1,172 @@
+use aoc_utils::progress::Progress;
+use aoc_utils::solution::{or_no_solution, Puzzle};
+use intcode::{read_intcode_input, IntCodeError, IntCodeMachine};
+use log::debug;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The drone system, asked whether points are pulled by the beam. Answers
+/// are cached, and every query that isn't runs on the same machine, reset
+/// between queries instead of built again.
+pub struct Beam {
+    program: Vec<isize>,
+    machine: RefCell<IntCodeMachine>,
+    cache: RefCell<HashMap<(isize, isize), bool>>,
+}
+
+impl Beam {
+    pub fn new(program: Vec<isize>) -> Self {
+        Self {
+            machine: RefCell::new(IntCodeMachine::new(program.clone())),
+            program,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn contains(&self, x: isize, y: isize) -> Result<bool, IntCodeError> {
+        if let Some(pulled) = self.cache.borrow().get(&(x, y)) {
+            return Ok(*pulled);
+        }
+        let mut machine = self.machine.borrow_mut();
+        machine.reset(&self.program);
+        let output = machine.execute([x, y])?;
+        let pulled = match output.first() {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(IntCodeError::UnexpectedOutput(output)),
+        };
+        self.cache.borrow_mut().insert((x, y), pulled);
+        Ok(pulled)
+    }
+
+    /// The beam as a plain predicate, for searches and drawing. Panics if
+    /// the drone system fails.
+    pub fn predicate(&self) -> impl Fn(isize, isize) -> bool + '_ {
+        move |x, y| self.contains(x, y).expect("Failed to query the drone")
+    }
+
+    /// How many different points were asked about.
+    pub fn queried(&self) -> usize {
+        self.cache.borrow().len()
+    }
+}
+
+fn count_affected<F>(size: isize, beam: F) -> usize
+where
+    F: Fn(isize, isize) -> bool,
+{
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .filter(|(x, y)| beam(*x, *y))
+        .count()
+}
+
+/// Follows the left edge of the beam downwards, row by row. For every row,
+/// the square would have its bottom left corner on the edge, so it fits as
+/// soon as the top right corner is also inside the beam.
+fn find_square<F>(size: isize, max_rows: isize, beam: F) -> Option<(isize, isize)>
+where
+    F: Fn(isize, isize) -> bool,
+{
+    let mut left = 0;
+    let mut progress = Progress::new("Scanning rows", (max_rows - size + 1).max(0) as u64);
+    for y in size - 1..max_rows {
+        progress.inc(1);
+        // Close to the emitter the beam is thin enough to skip some rows
+        // entirely, so the edge search is bounded by the row width
+        match (left..=left + y + 1).find(|x| beam(*x, y)) {
+            Some(x) => left = x,
+            None => continue,
+        }
+        let top = y - (size - 1);
+        if beam(left + size - 1, top) {
+            debug!("The square fits after scanning {} rows", progress.done());
+            return Some((left, top));
+        }
+    }
+    None
+}
+
+pub struct Day19;
+
+impl Puzzle for Day19 {
+    type Input = Beam;
+
+    fn parse(&self, input: &str) -> Result<Beam, String> {
+        read_intcode_input(input.as_bytes())
+            .map(Beam::new)
+            .map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, beam: &Beam) -> Result<String, String> {
+        Ok(count_affected(50, beam.predicate()).to_string())
+    }
+
+    fn part2(&self, beam: &Beam) -> Result<String, String> {
+        let square = find_square(100, 100_000, beam.predicate());
+        debug!("Queried the drone at {} points", beam.queried());
+        Ok(or_no_solution(square.map(|(x, y)| x * 10_000 + y)))
+    }
+}
+
+aoc_utils::register_solution!(19, Day19);
+
+#[cfg(test)]
+mod test {
+    use super::{count_affected, find_square, Beam};
+
+    // A beam that widens as it goes down, similar in shape to the puzzle
+    fn synthetic_beam(x: isize, y: isize) -> bool {
+        x * 10 >= y * 7 && x * 10 <= y * 11
+    }
+
+    fn brute_force_square(size: isize, beam: fn(isize, isize) -> bool) -> (isize, isize) {
+        (0..)
+            .flat_map(|sum: isize| (0..=sum).map(move |y| (sum - y, y)))
+            .find(|(x, y)| {
+                beam(*x, *y)
+                    && beam(x + size - 1, *y)
+                    && beam(*x, y + size - 1)
+                    && beam(x + size - 1, y + size - 1)
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_count_affected() {
+        let count = count_affected(10, |x, y| x == y);
+        assert_eq!(count, 10);
+    }
+
+    #[test]
+    fn test_find_square() {
+        for size in [2, 5, 10].iter() {
+            let expected = brute_force_square(*size, synthetic_beam);
+            assert_eq!(find_square(*size, 10_000, synthetic_beam), Some(expected));
+        }
+        // Fewer rows than the square is tall
+        assert_eq!(find_square(10, 5, synthetic_beam), None);
+    }
+
+    #[test]
+    fn test_cached_beam() {
+        // Reads x and y, and reports whether x < y
+        let mut program = vec![3, 20, 3, 21, 7, 20, 21, 22, 4, 22, 99];
+        program.resize(23, 0);
+        let beam = Beam::new(program);
+        let pulled = beam.predicate();
+        assert!(pulled(2, 3));
+        assert!(!pulled(3, 2));
+        assert!(pulled(2, 3));
+        assert_eq!(beam.queried(), 2);
+    }
+
+    #[test]
+    fn test_broken_drone() {
+        let beam = Beam::new(vec![104, 2, 99]);
+        assert_eq!(
+            beam.contains(0, 0).unwrap_err().to_string(),
+            "Unexpected output [2]"
+        );
+    }
+}
@@ -0,0 +1,6 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_19::Day19;
+
+fn main() {
+    print_answers(&Day19, &read_stdin(), timing_requested());
+}
@@ -0,0 +1,50 @@
+use aoc_utils::solution::{or_no_solution, Puzzle};
+use intcode::read_intcode_input;
+use intcode::search::{find_noun_verb, run_with};
+
+pub struct Day2;
+
+impl Puzzle for Day2 {
+    type Input = Vec<isize>;
+
+    fn parse(&self, input: &str) -> Result<Vec<isize>, String> {
+        let memory = read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())?;
+        if memory.len() < 3 {
+            return Err("The program is too short to have a noun and verb".to_string());
+        }
+        Ok(memory)
+    }
+
+    fn part1(&self, memory: &Vec<isize>) -> Result<String, String> {
+        run_with(memory, memory[1], memory[2])
+            .map(|value| value.to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    fn part2(&self, memory: &Vec<isize>) -> Result<String, String> {
+        let found =
+            find_noun_verb(memory, 19_690_720, 0..=99).map_err(|error| error.to_string())?;
+        Ok(or_no_solution(found.map(|(noun, verb)| 100 * noun + verb)))
+    }
+}
+
+aoc_utils::register_solution!(2, Day2);
+
+#[cfg(test)]
+mod test {
+    use super::Day2;
+    use aoc_utils::Solution;
+
+    aoc_utils::example_tests!(Day2, {
+        part1_program: part1("program.txt") == "3500",
+        part1_small_program: part1("small-program.txt") == "30",
+    });
+
+    #[test]
+    fn test_short_program() {
+        assert_eq!(
+            Day2.part1("99"),
+            Err("The program is too short to have a noun and verb".to_string())
+        );
+    }
+}
@@ -0,0 +1,370 @@
+use aoc_utils::search::bfs;
+use aoc_utils::solution::{or_no_solution, Puzzle};
+use aoc_utils::terminal::{FrameLimiter, FrameSink, Terminal};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+
+type Position = (usize, usize);
+
+struct Portal {
+    target: Position,
+    // Going through an inner portal goes one level deeper, an outer portal
+    // goes back up one level
+    level_change: isize,
+}
+
+pub struct Maze {
+    // The maze as drawn, labels included, to render it back
+    cells: Vec<Vec<u8>>,
+    open: HashSet<Position>,
+    portals: HashMap<Position, Portal>,
+    start: Position,
+    end: Position,
+}
+
+impl Maze {
+    pub fn parse(input: &str) -> Result<Self, &'static str> {
+        let cells: Vec<Vec<u8>> = input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.bytes().collect())
+            .collect();
+        let height = cells.len();
+        let width = cells.iter().map(Vec::len).max().unwrap_or(0);
+        let get = |x: usize, y: usize| {
+            cells
+                .get(y)
+                .and_then(|row| row.get(x))
+                .copied()
+                .unwrap_or(b' ')
+        };
+
+        // The outer edge of the donut is the bounding box of its tiles, which
+        // doesn't depend on whether trailing whitespace was kept
+        let tiles = || {
+            (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .filter(|(x, y)| get(*x, *y) == b'#' || get(*x, *y) == b'.')
+        };
+        let min_x = tiles().map(|(x, _)| x).min().unwrap_or(0);
+        let max_x = tiles().map(|(x, _)| x).max().unwrap_or(0);
+        let min_y = tiles().map(|(_, y)| y).min().unwrap_or(0);
+        let max_y = tiles().map(|(_, y)| y).max().unwrap_or(0);
+
+        let mut open = HashSet::new();
+        let mut labels: HashMap<(u8, u8), Vec<(Position, bool)>> = HashMap::new();
+        for y in 0..height {
+            for x in 0..width {
+                if get(x, y) != b'.' {
+                    continue;
+                }
+                open.insert((x, y));
+
+                // Labels are always read top to bottom or left to right
+                let label = if x >= 2 && get(x - 1, y).is_ascii_uppercase() {
+                    Some((get(x - 2, y), get(x - 1, y)))
+                } else if get(x + 1, y).is_ascii_uppercase() {
+                    Some((get(x + 1, y), get(x + 2, y)))
+                } else if y >= 2 && get(x, y - 1).is_ascii_uppercase() {
+                    Some((get(x, y - 2), get(x, y - 1)))
+                } else if get(x, y + 1).is_ascii_uppercase() {
+                    Some((get(x, y + 1), get(x, y + 2)))
+                } else {
+                    None
+                };
+
+                if let Some(label) = label {
+                    if !label.0.is_ascii_uppercase() || !label.1.is_ascii_uppercase() {
+                        return Err("Found an incomplete portal label");
+                    }
+                    let outer = x == min_x || x == max_x || y == min_y || y == max_y;
+                    labels.entry(label).or_default().push(((x, y), outer));
+                }
+            }
+        }
+
+        let single = |label: (u8, u8)| match labels.get(&label).map(Vec::as_slice) {
+            Some([(position, _)]) => Ok(*position),
+            _ => Err("Expected exactly one AA and one ZZ tile"),
+        };
+        let start = single((b'A', b'A'))?;
+        let end = single((b'Z', b'Z'))?;
+
+        let mut portals = HashMap::new();
+        for (label, ends) in labels.iter() {
+            if *label == (b'A', b'A') || *label == (b'Z', b'Z') {
+                continue;
+            }
+            match ends.as_slice() {
+                [(first, first_outer), (second, second_outer)] => {
+                    if first_outer == second_outer {
+                        return Err("Portal ends must be on opposite sides of the donut");
+                    }
+                    let level_change = |outer: bool| if outer { -1 } else { 1 };
+                    portals.insert(
+                        *first,
+                        Portal {
+                            target: *second,
+                            level_change: level_change(*first_outer),
+                        },
+                    );
+                    portals.insert(
+                        *second,
+                        Portal {
+                            target: *first,
+                            level_change: level_change(*second_outer),
+                        },
+                    );
+                }
+                _ => return Err("Found a portal without exactly two ends"),
+            }
+        }
+
+        Ok(Self {
+            cells,
+            open,
+            portals,
+            start,
+            end,
+        })
+    }
+
+    fn neighbours(
+        &self,
+        (x, y): Position,
+        level: isize,
+        recursive: bool,
+    ) -> Vec<(Position, isize)> {
+        let mut neighbours: Vec<(Position, isize)> = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ]
+        .iter()
+        .filter(|position| self.open.contains(position))
+        .map(|position| (*position, level))
+        .collect();
+
+        if let Some(portal) = self.portals.get(&(x, y)) {
+            if !recursive {
+                neighbours.push((portal.target, level));
+            } else if level + portal.level_change >= 0 {
+                neighbours.push((portal.target, level + portal.level_change));
+            }
+        }
+        neighbours
+    }
+
+    fn successors(
+        &self,
+        (position, level): (Position, isize),
+        recursive: bool,
+    ) -> Vec<(Position, isize)> {
+        // Going deeper than the number of portals can't lead to a shorter
+        // path, and bounds the search when there is no solution
+        let max_level = self.portals.len() as isize;
+        let mut successors = self.neighbours(position, level, recursive);
+        successors.retain(|(_, level)| *level <= max_level);
+        successors
+    }
+
+    /// Breadth first search from AA to ZZ. When `recursive` is set, portals
+    /// move between levels of the maze and the exit only exists on the
+    /// outermost one.
+    fn shortest_path(&self, recursive: bool) -> Option<usize> {
+        let successors = |state: &(Position, isize)| self.successors(*state, recursive);
+        bfs((self.start, 0), successors, |state| *state == (self.end, 0))
+            .map(|(distance, _)| distance)
+    }
+
+    /// The same search as [`Maze::shortest_path`], one distance at a time.
+    /// `visit` gets every distance with the tiles first reached at it, by
+    /// level, until the exit is found.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, visit)))]
+    pub fn explore<F>(&self, recursive: bool, mut visit: F) -> Option<usize>
+    where
+        F: FnMut(usize, &BTreeMap<isize, Vec<Position>>),
+    {
+        let mut seen = HashSet::new();
+        let mut frontier = vec![(self.start, 0)];
+        seen.insert((self.start, 0));
+        let mut distance = 0;
+        while !frontier.is_empty() {
+            let mut levels: BTreeMap<isize, Vec<Position>> = BTreeMap::new();
+            for (position, level) in frontier.iter() {
+                levels.entry(*level).or_default().push(*position);
+            }
+            visit(distance, &levels);
+            if seen.contains(&(self.end, 0)) {
+                return Some(distance);
+            }
+
+            frontier = frontier
+                .iter()
+                .flat_map(|state| self.successors(*state, recursive))
+                .filter(|state| seen.insert(*state))
+                .collect();
+            distance += 1;
+        }
+        None
+    }
+
+    /// Draws one level of the maze, with `@` on the tiles just reached and
+    /// `~` on the ones reached before. The entrance and exit only exist on
+    /// the outermost level.
+    pub fn render(
+        &self,
+        level: isize,
+        reached: &HashSet<Position>,
+        frontier: &[Position],
+    ) -> String {
+        let mut text = String::new();
+        for (y, row) in self.cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                let c = if frontier.contains(&(x, y)) {
+                    '@'
+                } else if reached.contains(&(x, y)) {
+                    '~'
+                } else if level > 0 && ((x, y) == self.start || (x, y) == self.end) {
+                    '#'
+                } else {
+                    *cell as char
+                };
+                text.push(c);
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// Plays the search in the terminal, drawing the level of every group of
+/// tiles reached, one step at a time.
+pub fn watch_search(maze: &Maze, recursive: bool) -> io::Result<Option<usize>> {
+    play_search(maze, recursive, &mut Terminal::stdout()?)
+}
+
+/// Plays the search like [`watch_search`], on any frame sink.
+pub fn play_search(
+    maze: &Maze,
+    recursive: bool,
+    sink: &mut dyn FrameSink,
+) -> io::Result<Option<usize>> {
+    let mut limiter = FrameLimiter::new(60);
+    let mut reached: HashMap<isize, HashSet<Position>> = HashMap::new();
+    let mut result = Ok(());
+    let distance = maze.explore(recursive, |distance, levels| {
+        for (level, frontier) in levels.iter() {
+            if result.is_err() {
+                return;
+            }
+            let reached = reached.entry(*level).or_default();
+            let frame = format!(
+                "Step {:>5}  level {:>3}\n{}",
+                distance,
+                level,
+                maze.render(*level, reached, frontier)
+            );
+            reached.extend(frontier.iter().copied());
+            limiter.wait();
+            result = sink.draw(&frame);
+        }
+    });
+    result.map(|_| distance)
+}
+
+pub struct Day20;
+
+impl Puzzle for Day20 {
+    type Input = Maze;
+
+    fn parse(&self, input: &str) -> Result<Maze, String> {
+        Maze::parse(input).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, maze: &Maze) -> Result<String, String> {
+        Ok(or_no_solution(maze.shortest_path(false)))
+    }
+
+    fn part2(&self, maze: &Maze) -> Result<String, String> {
+        Ok(or_no_solution(maze.shortest_path(true)))
+    }
+}
+
+aoc_utils::register_solution!(20, Day20);
+
+#[cfg(test)]
+mod test {
+    use super::Maze;
+    use std::collections::HashSet;
+
+    const SMALL: &str = "         A
+         A
+  #######.#########
+  #######.........#
+  #######.#######.#
+  #######.#######.#
+  #######.#######.#
+  #####  B    ###.#
+BC...##  C    ###.#
+  ##.##       ###.#
+  ##...DE  F  ###.#
+  #####    G  ###.#
+  #########.#####.#
+DE..#######...###.#
+  #.#########.###.#
+FG..#########.....#
+  ###########.#####
+             Z
+             Z
+";
+
+    #[test]
+    fn test_flat_maze() {
+        let maze = Maze::parse(SMALL).unwrap();
+        assert_eq!(maze.shortest_path(false), Some(23));
+    }
+
+    #[test]
+    fn test_recursive_maze() {
+        let maze = Maze::parse(SMALL).unwrap();
+        assert_eq!(maze.shortest_path(true), Some(26));
+    }
+
+    #[test]
+    fn test_explore() {
+        let maze = Maze::parse(SMALL).unwrap();
+        let mut deepest = 0;
+        let mut steps = 0;
+        let distance = maze.explore(true, |distance, levels| {
+            steps = distance;
+            deepest = deepest.max(*levels.keys().last().unwrap());
+        });
+        assert_eq!(distance, Some(26));
+        assert_eq!(steps, 26);
+        assert!(deepest > 0);
+        assert_eq!(maze.explore(false, |_, _| {}), Some(23));
+    }
+
+    #[test]
+    fn test_render() {
+        let maze = Maze::parse(SMALL).unwrap();
+        let reached: HashSet<_> = [(9, 2)].iter().copied().collect();
+        let text = maze.render(1, &reached, &[(9, 3)]);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[2], "  #######~#########");
+        assert_eq!(lines[3], "  #######@........#");
+        // The exit is walled off below the outermost level
+        assert_eq!(lines[16], "  #################");
+
+        let outermost = maze.render(0, &reached, &[]);
+        assert_eq!(outermost, SMALL.replacen("#######.###", "#######~###", 1));
+    }
+
+    #[test]
+    fn test_missing_portal_end() {
+        let input = SMALL.replace("FG..", "FH..");
+        assert!(Maze::parse(&input).is_err());
+    }
+}
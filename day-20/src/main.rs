@@ -0,0 +1,21 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_20::{watch_search, Day20, Maze};
+use std::env;
+
+fn main() {
+    let input = read_stdin();
+    print_answers(&Day20, &input, timing_requested());
+
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--time").collect();
+    let recursive = match args.as_slice() {
+        [flag] if flag == "--watch" => true,
+        [flag] if flag == "--watch-flat" => false,
+        _ => return,
+    };
+    let maze = Maze::parse(&input).expect("Invalid puzzle input");
+    match watch_search(&maze, recursive) {
+        Ok(Some(distance)) => println!("Reached ZZ in {} steps", distance),
+        Ok(None) => println!("ZZ can't be reached"),
+        Err(error) => eprintln!("Could not play the search: {}", error),
+    }
+}
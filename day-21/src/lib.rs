@@ -0,0 +1,185 @@
+use aoc_utils::Puzzle;
+use intcode::{read_intcode_input, IntCodeMachine};
+use std::fmt;
+
+mod simulator;
+mod springscript;
+
+use springscript::Register::*;
+use springscript::Script;
+use springscript::WriteRegister::{J, T};
+
+// Jump whenever there is a hole in the next three tiles and ground to land on
+fn walk_script() -> Script {
+    Script::walk()
+        .not(A, J)
+        .not(B, T)
+        .or(T, J)
+        .not(C, T)
+        .or(T, J)
+        .and(D, J)
+}
+
+// Same as walking, but only jump if after landing the droid can either take
+// a step or immediately jump again
+fn run_script() -> Script {
+    Script::run()
+        .not(A, J)
+        .not(B, T)
+        .or(T, J)
+        .not(C, T)
+        .or(T, J)
+        .and(D, J)
+        .not(E, T)
+        .not(T, T)
+        .or(H, T)
+        .and(T, J)
+}
+
+enum Survey {
+    HullDamage(isize),
+    // The droid fell into space, the output is the rendered last moments
+    Fell(String),
+}
+
+/// The droid halted without printing anything, neither the hull damage nor
+/// its last moments before falling into space.
+#[derive(Debug)]
+struct NoReport;
+
+impl fmt::Display for NoReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The droid printed no hull damage and didn't show a fall")
+    }
+}
+
+impl std::error::Error for NoReport {}
+
+fn survey(memory: &[isize], script: &Script) -> Result<Survey, Box<dyn std::error::Error>> {
+    let text = script.assemble()?;
+    let mut machine = IntCodeMachine::new(memory.to_vec());
+    let output = machine.execute(text.bytes().map(|value| value as isize))?;
+    match output.last() {
+        Some(value) if *value > 127 => Ok(Survey::HullDamage(*value)),
+        Some(_) => Ok(Survey::Fell(
+            output.iter().map(|value| *value as u8 as char).collect(),
+        )),
+        None => Err(Box::new(NoReport)),
+    }
+}
+
+// Runs both scripts against every crossable hull of a given length, and
+// shows a few of the ones each script falls on
+pub fn simulate_all(length: usize) {
+    for script in [walk_script(), run_script()].iter() {
+        let failures: Vec<_> = simulator::Hull::all_crossable(length)
+            .filter_map(|hull| {
+                let outcome = simulator::simulate(script, &hull).unwrap();
+                if outcome.survived() {
+                    None
+                } else {
+                    Some(outcome.render(&hull))
+                }
+            })
+            .collect();
+        println!("{} - fell on {} hulls", script.mode(), failures.len());
+        for failure in failures.iter().take(5) {
+            println!("{}\n", failure);
+        }
+    }
+}
+
+// Runs both scripts against the given hull patterns without the droid
+// program, drawing where each of them jumps or falls
+pub fn simulate_patterns(patterns: &[String]) {
+    for pattern in patterns {
+        let hull: simulator::Hull = match pattern.parse() {
+            Ok(hull) => hull,
+            Err(error) => {
+                eprintln!("{}: {}", pattern, error);
+                continue;
+            }
+        };
+        for script in [walk_script(), run_script()].iter() {
+            let outcome = simulator::simulate(script, &hull).unwrap();
+            let result = if outcome.survived() {
+                "survived"
+            } else {
+                "fell"
+            };
+            println!("{} - {}", script.mode(), result);
+            println!("{}\n", outcome.render(&hull));
+        }
+    }
+}
+
+fn answer(memory: &[isize], script: &Script) -> Result<String, String> {
+    match survey(memory, script).map_err(|error| error.to_string())? {
+        Survey::HullDamage(value) => Ok(value.to_string()),
+        Survey::Fell(view) => Ok(format!("droid fell:\n{}", view)),
+    }
+}
+
+pub struct Day21;
+
+impl Puzzle for Day21 {
+    type Input = Vec<isize>;
+
+    fn parse(&self, input: &str) -> Result<Vec<isize>, String> {
+        read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, memory: &Vec<isize>) -> Result<String, String> {
+        answer(memory, &walk_script())
+    }
+
+    fn part2(&self, memory: &Vec<isize>) -> Result<String, String> {
+        answer(memory, &run_script())
+    }
+}
+
+aoc_utils::register_solution!(21, Day21);
+
+#[cfg(test)]
+mod test {
+    use super::simulator::{simulate, Hull};
+    use super::{run_script, survey, walk_script, Day21};
+    use aoc_utils::Solution;
+
+    fn survives(script: &super::Script, pattern: &str) -> bool {
+        let hull: Hull = pattern.parse().unwrap();
+        simulate(script, &hull).unwrap().survived()
+    }
+
+    #[test]
+    fn test_walk_script() {
+        let script = walk_script();
+        assert!(survives(&script, "#####.###########"));
+        assert!(survives(&script, "#####...#########"));
+        assert!(survives(&script, "#####..#.########"));
+    }
+
+    #[test]
+    fn test_run_script() {
+        let script = run_script();
+        assert!(survives(&script, "#####.###########"));
+        assert!(survives(&script, "#####...#########"));
+        assert!(survives(&script, "#####..#.########"));
+        assert!(survives(&script, "#####.#.##.#.####"));
+        assert!(!survives(&walk_script(), "#####.#.##.#.####"));
+    }
+
+    #[test]
+    fn test_silent_droid() {
+        // Reads nothing and halts straight away
+        let error = survey(&[99], &walk_script()).err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "The droid printed no hull damage and didn't show a fall"
+        );
+        assert_eq!(
+            Day21.part2("99"),
+            Err("The droid printed no hull damage and didn't show a fall".to_string())
+        );
+    }
+}
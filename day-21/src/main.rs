@@ -0,0 +1,17 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_21::{simulate_all, simulate_patterns, Day21};
+use std::env;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--time").collect();
+    if args.first().map(String::as_str) == Some("--simulate") {
+        if args.len() > 1 {
+            simulate_patterns(&args[1..]);
+        } else {
+            simulate_all(13);
+        }
+        return;
+    }
+
+    print_answers(&Day21, &read_stdin(), timing_requested());
+}
@@ -0,0 +1,190 @@
+use super::springscript::{Instruction, Register, Script, ScriptError, WriteRegister};
+use std::fmt;
+use std::str::FromStr;
+
+// The sensors see up to nine tiles ahead, and a jump lands four tiles away
+const JUMP_DISTANCE: usize = 4;
+
+/// A section of hull, as drawn by the droid. Tiles past the end are ground.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hull {
+    ground: Vec<bool>,
+}
+
+impl FromStr for Hull {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let ground = s
+            .trim()
+            .chars()
+            .map(|c| match c {
+                '#' | '@' => Ok(true),
+                '.' => Ok(false),
+                _ => Err("Invalid hull tile"),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if ground.first() != Some(&true) {
+            return Err("The droid must start on ground");
+        }
+        Ok(Self { ground })
+    }
+}
+
+impl fmt::Display for Hull {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for ground in self.ground.iter() {
+            write!(f, "{}", if *ground { '#' } else { '.' })?;
+        }
+        Ok(())
+    }
+}
+
+impl Hull {
+    fn is_ground(&self, position: usize) -> bool {
+        self.ground.get(position).copied().unwrap_or(true)
+    }
+
+    /// Every hull of the given length that a droid making perfect jumps
+    /// could cross.
+    pub fn all_crossable(length: usize) -> impl Iterator<Item = Hull> {
+        (0..1u64 << length)
+            .map(move |mask| Hull {
+                ground: (0..length).map(|index| mask & (1 << index) != 0).collect(),
+            })
+            .filter(Hull::is_crossable)
+    }
+
+    fn is_crossable(&self) -> bool {
+        // A tile can be reached by stepping from the previous one or by
+        // jumping from four tiles behind
+        let end = self.ground.len() + JUMP_DISTANCE;
+        let mut reachable = vec![false; end];
+        reachable[0] = self.is_ground(0);
+        for position in 1..end {
+            let from_step = reachable[position - 1];
+            let from_jump = position >= JUMP_DISTANCE && reachable[position - JUMP_DISTANCE];
+            reachable[position] = self.is_ground(position) && (from_step || from_jump);
+        }
+        reachable[self.ground.len()..].iter().any(|value| *value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Survived { jumps: Vec<usize> },
+    Fell { position: usize, jumps: Vec<usize> },
+}
+
+impl Outcome {
+    pub fn survived(&self) -> bool {
+        matches!(self, Outcome::Survived { .. })
+    }
+
+    /// Draws the hull with the positions the droid jumped from marked with
+    /// `J`, and the hole it fell into marked with `X`.
+    pub fn render(&self, hull: &Hull) -> String {
+        let (jumps, fell) = match self {
+            Outcome::Survived { jumps } => (jumps, None),
+            Outcome::Fell { position, jumps } => (jumps, Some(*position)),
+        };
+        let markers: String = (0..hull.ground.len())
+            .map(|position| {
+                if fell == Some(position) {
+                    'X'
+                } else if jumps.contains(&position) {
+                    'J'
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        format!("{}\n{}", hull, markers.trim_end())
+    }
+}
+
+fn run_instructions(instructions: &[Instruction], sensors: &[bool]) -> bool {
+    let mut t = false;
+    let mut j = false;
+    for instruction in instructions {
+        let read = |register: Register| match register {
+            Register::T => t,
+            Register::J => j,
+            sensor => sensors[sensor as usize],
+        };
+        let (value, target) = match *instruction {
+            Instruction::And(x, y) => (read(x) && read(y.into()), y),
+            Instruction::Or(x, y) => (read(x) || read(y.into()), y),
+            Instruction::Not(x, y) => (!read(x), y),
+        };
+        match target {
+            WriteRegister::T => t = value,
+            WriteRegister::J => j = value,
+        }
+    }
+    j
+}
+
+/// Walks a droid running the script across the hull, the same way the
+/// springdroid program does: every step the script runs with fresh
+/// registers, and the droid jumps if J ends up true.
+pub fn simulate(script: &Script, hull: &Hull) -> Result<Outcome, ScriptError> {
+    script.validate()?;
+    let mut jumps = Vec::new();
+    let mut position = 0;
+    while position < hull.ground.len() {
+        if !hull.is_ground(position) {
+            return Ok(Outcome::Fell { position, jumps });
+        }
+        let sensors: Vec<bool> = (1..=9)
+            .map(|distance| hull.is_ground(position + distance))
+            .collect();
+        if run_instructions(script.instructions(), &sensors) {
+            jumps.push(position);
+            position += JUMP_DISTANCE;
+        } else {
+            position += 1;
+        }
+    }
+    Ok(Outcome::Survived { jumps })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::springscript::Register::*;
+    use super::super::springscript::Script;
+    use super::super::springscript::WriteRegister::J;
+    use super::{simulate, Hull, Outcome};
+
+    #[test]
+    fn test_walk_into_hole() {
+        let hull: Hull = "#####.###".parse().unwrap();
+        let outcome = simulate(&Script::walk(), &hull).unwrap();
+        assert_eq!(
+            outcome,
+            Outcome::Fell {
+                position: 5,
+                jumps: vec![]
+            }
+        );
+        assert_eq!(outcome.render(&hull), "#####.###\n     X");
+    }
+
+    #[test]
+    fn test_jump_over_hole() {
+        let hull: Hull = "#####.###".parse().unwrap();
+        let script = Script::walk().not(A, J);
+        let outcome = simulate(&script, &hull).unwrap();
+        assert_eq!(outcome, Outcome::Survived { jumps: vec![4] });
+        assert_eq!(outcome.render(&hull), "#####.###\n    J");
+    }
+
+    #[test]
+    fn test_all_crossable() {
+        let hulls: Vec<String> = Hull::all_crossable(5).map(|h| h.to_string()).collect();
+        assert!(hulls.contains(&"#...#".to_string()));
+        assert!(!hulls.contains(&"#....".to_string()));
+        assert!(!hulls.contains(&"#.#..".to_string()));
+        assert!(hulls.iter().all(|hull| hull.starts_with('#')));
+    }
+}
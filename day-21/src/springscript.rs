@@ -0,0 +1,235 @@
+use std::fmt;
+
+const MAX_INSTRUCTIONS: usize = 15;
+
+// Not every sensor is read by the scripts in this crate, but they are all
+// part of the language
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Register {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    T,
+    J,
+}
+
+/// Registers that instructions are allowed to write to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WriteRegister {
+    T,
+    J,
+}
+
+impl From<WriteRegister> for Register {
+    fn from(register: WriteRegister) -> Self {
+        match register {
+            WriteRegister::T => Register::T,
+            WriteRegister::J => Register::J,
+        }
+    }
+}
+
+impl Register {
+    // Sensors past D are only available when running
+    fn available_in(self, mode: Mode) -> bool {
+        match self {
+            Register::E | Register::F | Register::G | Register::H | Register::I => {
+                mode == Mode::Run
+            }
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl fmt::Display for WriteRegister {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    And(Register, WriteRegister),
+    Or(Register, WriteRegister),
+    Not(Register, WriteRegister),
+}
+
+impl Instruction {
+    fn source(self) -> Register {
+        match self {
+            Instruction::And(source, _)
+            | Instruction::Or(source, _)
+            | Instruction::Not(source, _) => source,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Instruction::And(x, y) => write!(f, "AND {} {}", x, y),
+            Instruction::Or(x, y) => write!(f, "OR {} {}", x, y),
+            Instruction::Not(x, y) => write!(f, "NOT {} {}", x, y),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Walk,
+    Run,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Walk => write!(f, "WALK"),
+            Mode::Run => write!(f, "RUN"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ScriptError {
+    TooManyInstructions(usize),
+    RegisterNotAvailable(Register, Mode),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptError::TooManyInstructions(count) => write!(
+                f,
+                "Script has {} instructions, the droid only accepts {}",
+                count, MAX_INSTRUCTIONS
+            ),
+            ScriptError::RegisterNotAvailable(register, mode) => {
+                write!(f, "Register {} is not available in {} mode", register, mode)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+/// A springscript program, built one instruction at a time and validated
+/// before being assembled into the droid's ASCII input.
+#[derive(Debug, Clone)]
+pub struct Script {
+    mode: Mode,
+    instructions: Vec<Instruction>,
+}
+
+impl Script {
+    pub fn walk() -> Self {
+        Self::new(Mode::Walk)
+    }
+
+    pub fn run() -> Self {
+        Self::new(Mode::Run)
+    }
+
+    pub fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            instructions: Vec::new(),
+        }
+    }
+
+    pub fn and(self, x: impl Into<Register>, y: WriteRegister) -> Self {
+        self.push(Instruction::And(x.into(), y))
+    }
+
+    pub fn or(self, x: impl Into<Register>, y: WriteRegister) -> Self {
+        self.push(Instruction::Or(x.into(), y))
+    }
+
+    pub fn not(self, x: impl Into<Register>, y: WriteRegister) -> Self {
+        self.push(Instruction::Not(x.into(), y))
+    }
+
+    pub fn push(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn validate(&self) -> Result<(), ScriptError> {
+        if self.instructions.len() > MAX_INSTRUCTIONS {
+            return Err(ScriptError::TooManyInstructions(self.instructions.len()));
+        }
+        for instruction in self.instructions.iter() {
+            let source = instruction.source();
+            if !source.available_in(self.mode) {
+                return Err(ScriptError::RegisterNotAvailable(source, self.mode));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the script and converts it to the text the droid expects,
+    /// one instruction per line followed by the mode command.
+    pub fn assemble(&self) -> Result<String, ScriptError> {
+        self.validate()?;
+        let mut text = String::new();
+        for instruction in self.instructions.iter() {
+            text.push_str(&instruction.to_string());
+            text.push('\n');
+        }
+        text.push_str(&self.mode.to_string());
+        text.push('\n');
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Register::*;
+    use super::WriteRegister::{J, T};
+    use super::{Mode, Register, Script, ScriptError};
+
+    #[test]
+    fn test_assemble() {
+        let script = Script::walk().not(A, J).not(B, T).or(T, J).and(D, J);
+        assert_eq!(
+            script.assemble(),
+            Ok("NOT A J\nNOT B T\nOR T J\nAND D J\nWALK\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_register_not_available() {
+        let script = Script::walk().not(E, J);
+        assert_eq!(
+            script.assemble(),
+            Err(ScriptError::RegisterNotAvailable(E, Mode::Walk))
+        );
+        assert!(Script::run().not(E, J).assemble().is_ok());
+    }
+
+    #[test]
+    fn test_too_many_instructions() {
+        let script = (0..16).fold(Script::run(), |script, _| script.or(Register::A, J));
+        assert_eq!(script.assemble(), Err(ScriptError::TooManyInstructions(16)));
+    }
+}
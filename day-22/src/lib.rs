@@ -0,0 +1,157 @@
+use aoc_utils::math::Affine;
+use aoc_utils::Puzzle;
+use std::str::FromStr;
+
+pub enum Technique {
+    NewStack,
+    Cut(i64),
+    Increment(i64),
+}
+
+impl FromStr for Technique {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "deal into new stack" {
+            Ok(Technique::NewStack)
+        } else if let Some(value) = s.strip_prefix("cut ") {
+            Ok(Technique::Cut(value.parse().map_err(|_| "Invalid cut")?))
+        } else if let Some(value) = s.strip_prefix("deal with increment ") {
+            Ok(Technique::Increment(
+                value.parse().map_err(|_| "Invalid increment")?,
+            ))
+        } else {
+            Err("Unknown shuffle technique")
+        }
+    }
+}
+
+/// The map from a card's position before the technique to its position
+/// after it, in a deck of `size` cards.
+fn technique_map(technique: &Technique, size: i64) -> Affine {
+    match technique {
+        Technique::NewStack => Affine::new(-1, -1, size),
+        Technique::Cut(n) => Affine::new(1, -n, size),
+        Technique::Increment(n) => Affine::new(*n, 0, size),
+    }
+}
+
+/// The map from a card's position before the whole shuffle to its position
+/// after it. Every technique is affine, and so is any chain of them.
+fn shuffle_map(techniques: &[Technique], size: i64) -> Affine {
+    techniques
+        .iter()
+        .fold(Affine::identity(size), |shuffle, technique| {
+            shuffle.then(&technique_map(technique, size))
+        })
+}
+
+pub struct Day22;
+
+impl Puzzle for Day22 {
+    type Input = Vec<Technique>;
+
+    fn parse(&self, input: &str) -> Result<Vec<Technique>, String> {
+        input
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.parse::<Technique>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, techniques: &Vec<Technique>) -> Result<String, String> {
+        let shuffle = shuffle_map(techniques, 10_007);
+        Ok(shuffle.apply(2019).to_string())
+    }
+
+    fn part2(&self, techniques: &Vec<Technique>) -> Result<String, String> {
+        let shuffle = shuffle_map(techniques, 119_315_717_514_047);
+        let card = shuffle
+            .pow(101_741_582_076_661)
+            .inverse()
+            .ok_or("Shuffle can't be undone")?
+            .apply(2020);
+        Ok(card.to_string())
+    }
+}
+
+aoc_utils::register_solution!(22, Day22);
+
+#[cfg(test)]
+mod test {
+    use super::{shuffle_map, Day22, Technique};
+    use aoc_utils::Puzzle;
+
+    // Deck sizes in the examples aren't prime, so the final order is built
+    // by moving every card instead of inverting the shuffle
+    fn deck_after(input: &str) -> Vec<i64> {
+        let techniques: Vec<Technique> = input.lines().map(|l| l.parse().unwrap()).collect();
+        let shuffle = shuffle_map(&techniques, 10);
+        let mut deck = vec![0; 10];
+        for card in 0..10 {
+            deck[shuffle.apply(card) as usize] = card;
+        }
+        deck
+    }
+
+    #[test]
+    fn test_case_1() {
+        let input = "deal with increment 7\ndeal into new stack\ndeal into new stack";
+        assert_eq!(deck_after(input), [0, 3, 6, 9, 2, 5, 8, 1, 4, 7]);
+    }
+
+    #[test]
+    fn test_case_2() {
+        let input = "cut 6\ndeal with increment 7\ndeal into new stack";
+        assert_eq!(deck_after(input), [3, 0, 7, 4, 1, 8, 5, 2, 9, 6]);
+    }
+
+    #[test]
+    fn test_case_3() {
+        let input = "deal with increment 7\ndeal with increment 9\ncut -2";
+        assert_eq!(deck_after(input), [6, 3, 0, 7, 4, 1, 8, 5, 2, 9]);
+    }
+
+    #[test]
+    fn test_case_4() {
+        let input = "deal into new stack
+cut -2
+deal with increment 7
+cut 8
+cut -4
+deal with increment 7
+cut 3
+deal with increment 9
+deal with increment 3
+cut -1";
+        assert_eq!(deck_after(input), [9, 2, 5, 8, 1, 4, 7, 0, 3, 6]);
+    }
+
+    #[test]
+    fn test_repeat_and_inverse() {
+        let techniques: Vec<Technique> = ["cut 3", "deal with increment 7", "deal into new stack"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        let shuffle = shuffle_map(&techniques, 10_007);
+
+        let mut position = 2019;
+        for _ in 0..25 {
+            position = shuffle.apply(position);
+        }
+        let repeated = shuffle.pow(25);
+        assert_eq!(repeated.apply(2019), position);
+        assert_eq!(repeated.inverse().unwrap().apply(position), 2019);
+    }
+
+    #[test]
+    fn test_shuffle_without_inverse() {
+        let techniques = vec![Technique::Increment(0)];
+        assert_eq!(
+            Day22.part2(&techniques),
+            Err("Shuffle can't be undone".to_string())
+        );
+    }
+}
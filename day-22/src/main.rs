@@ -0,0 +1,6 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_22::Day22;
+
+fn main() {
+    print_answers(&Day22, &read_stdin(), timing_requested());
+}
@@ -0,0 +1,344 @@
+use aoc_utils::Puzzle;
+use intcode::schedule::{Policy, Schedule, Scheduler};
+use intcode::{read_intcode_input, IntCodeError, IntCodeMachine, Machine};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
+
+const NAT_ADDRESS: isize = 255;
+
+// The puzzle's network repeats itself within a few hundred rounds
+const ROUND_LIMIT: usize = 100_000;
+
+type Packet = (isize, isize);
+
+struct Network {
+    machines: Vec<IntCodeMachine>,
+    // Each computer starts with its network address as the only input
+    queues: Vec<VecDeque<isize>>,
+    nat: Option<Packet>,
+    scheduler: Scheduler,
+}
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    // A computer sent a packet to the NAT
+    NatReceived(Packet),
+    // The network was idle and the NAT woke up computer 0
+    NatSent(Packet),
+}
+
+/// Why the network stopped before the NAT repeated itself.
+#[derive(Debug)]
+pub enum NetworkError {
+    Computer(IntCodeError),
+    /// A computer stopped partway through sending a packet, with the values
+    /// it did send.
+    PartialPacket {
+        computer: usize,
+        values: Vec<isize>,
+    },
+    /// Every computer halted, so nothing will ever reach the NAT again.
+    Halted,
+    /// The NAT still hadn't repeated itself after this many rounds.
+    RoundLimit(usize),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NetworkError::Computer(error) => write!(f, "{}", error),
+            NetworkError::PartialPacket { computer, values } => {
+                write!(
+                    f,
+                    "Computer {} sent a partial packet {:?}",
+                    computer, values
+                )
+            }
+            NetworkError::Halted => write!(f, "Every computer halted"),
+            NetworkError::RoundLimit(rounds) => {
+                write!(f, "The NAT didn't repeat itself in {} rounds", rounds)
+            }
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+impl From<IntCodeError> for NetworkError {
+    fn from(error: IntCodeError) -> Self {
+        NetworkError::Computer(error)
+    }
+}
+
+impl Network {
+    fn new(memory: &[isize], size: usize, policy: Policy) -> Self {
+        Self {
+            machines: vec![IntCodeMachine::new(memory.to_vec()); size],
+            queues: (0..size)
+                .map(|address| vec![address as isize].into())
+                .collect(),
+            nat: None,
+            scheduler: Scheduler::new(policy),
+        }
+    }
+
+    /// Gives every computer a chance to run, in the order picked by the
+    /// scheduler, delivering its queued packets or -1 when there are none,
+    /// and routes everything they send.
+    fn round(&mut self) -> Result<Vec<Event>, NetworkError> {
+        let mut events = Vec::new();
+        let mut idle = true;
+        for address in self.scheduler.next_round(self.machines.len()) {
+            let input = if self.queues[address].is_empty() {
+                vec![-1]
+            } else {
+                idle = false;
+                self.queues[address].drain(..).collect()
+            };
+
+            let output = self.machines[address].execute(input)?;
+            if !output.is_empty() {
+                idle = false;
+            }
+            for packet in output.chunks(3) {
+                match *packet {
+                    [NAT_ADDRESS, x, y] => {
+                        self.nat = Some((x, y));
+                        events.push(Event::NatReceived((x, y)));
+                    }
+                    [destination, x, y] => {
+                        let queue = usize::try_from(destination)
+                            .ok()
+                            .and_then(|destination| self.queues.get_mut(destination))
+                            .ok_or(IntCodeError::InvalidAddress)?;
+                        queue.push_back(x);
+                        queue.push_back(y);
+                    }
+                    _ => {
+                        return Err(NetworkError::PartialPacket {
+                            computer: address,
+                            values: packet.to_vec(),
+                        })
+                    }
+                }
+            }
+        }
+
+        if idle {
+            if let Some(packet) = self.nat {
+                self.queues[0].push_back(packet.0);
+                self.queues[0].push_back(packet.1);
+                events.push(Event::NatSent(packet));
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// What running the network found, and the order the computers ran in to
+/// get there.
+pub struct Run {
+    /// The first Y value sent to the NAT.
+    pub first: isize,
+    /// The Y value the NAT delivered to computer 0 twice in a row.
+    pub repeated: isize,
+    pub schedule: Schedule,
+}
+
+/// A network run a round at a time, only as far as the answer asked for
+/// needs. Asking for the repeated value after the first one carries on from
+/// where the first one stopped, so both come from a single run.
+pub struct Simulation {
+    network: Network,
+    round_limit: usize,
+    first: Option<isize>,
+    last_sent: Option<isize>,
+    repeated: Option<isize>,
+}
+
+impl Simulation {
+    /// The puzzle's network of 50 computers, taking turns as `policy` says.
+    pub fn new(memory: &[isize], policy: Policy) -> Self {
+        Self::sized(memory, 50, policy)
+    }
+
+    fn sized(memory: &[isize], size: usize, policy: Policy) -> Self {
+        Self {
+            network: Network::new(memory, size, policy),
+            round_limit: ROUND_LIMIT,
+            first: None,
+            last_sent: None,
+            repeated: None,
+        }
+    }
+
+    fn step(&mut self) -> Result<(), NetworkError> {
+        let rounds = self.schedule().rounds.len();
+        if rounds >= self.round_limit {
+            return Err(NetworkError::RoundLimit(rounds));
+        }
+        for event in self.network.round()? {
+            match event {
+                Event::NatReceived((_, y)) => {
+                    self.first.get_or_insert(y);
+                }
+                Event::NatSent((_, y)) => {
+                    if self.last_sent == Some(y) {
+                        self.repeated.get_or_insert(y);
+                    }
+                    self.last_sent = Some(y);
+                }
+            }
+        }
+        if self
+            .network
+            .machines
+            .iter()
+            .all(|machine| machine.finished())
+        {
+            return Err(NetworkError::Halted);
+        }
+        Ok(())
+    }
+
+    /// The first Y value sent to the NAT.
+    pub fn first(&mut self) -> Result<isize, NetworkError> {
+        loop {
+            if let Some(first) = self.first {
+                return Ok(first);
+            }
+            self.step()?;
+        }
+    }
+
+    /// The Y value the NAT delivered to computer 0 twice in a row.
+    pub fn repeated(&mut self) -> Result<isize, NetworkError> {
+        loop {
+            if let Some(repeated) = self.repeated {
+                return Ok(repeated);
+            }
+            self.step()?;
+        }
+    }
+
+    pub fn schedule(&self) -> &Schedule {
+        self.network.scheduler.schedule()
+    }
+}
+
+/// Runs the network until the NAT delivers the same Y value to computer 0
+/// twice in a row, with the computers taking turns as `policy` says. The
+/// puzzle runs them round-robin.
+pub fn run_network(memory: &[isize], policy: Policy) -> Result<Run, NetworkError> {
+    let mut simulation = Simulation::new(memory, policy);
+    let repeated = simulation.repeated()?;
+    Ok(Run {
+        // The NAT only delivers what it received, so this doesn't run again
+        first: simulation.first()?,
+        repeated,
+        schedule: simulation.schedule().clone(),
+    })
+}
+
+pub struct Day23;
+
+// Part 2 picks the simulation up where part 1 left it
+impl Puzzle for Day23 {
+    type Input = RefCell<Simulation>;
+
+    fn parse(&self, input: &str) -> Result<RefCell<Simulation>, String> {
+        let memory = read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())?;
+        Ok(RefCell::new(Simulation::new(&memory, Policy::RoundRobin)))
+    }
+
+    fn part1(&self, simulation: &RefCell<Simulation>) -> Result<String, String> {
+        let first = simulation.borrow_mut().first();
+        first
+            .map(|first| first.to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    fn part2(&self, simulation: &RefCell<Simulation>) -> Result<String, String> {
+        let repeated = simulation.borrow_mut().repeated();
+        repeated
+            .map(|repeated| repeated.to_string())
+            .map_err(|error| error.to_string())
+    }
+}
+
+aoc_utils::register_solution!(23, Day23);
+
+#[cfg(test)]
+mod test {
+    use super::{run_network, Event, Network, NetworkError, Simulation};
+    use intcode::schedule::{parse_schedule, Policy};
+
+    // Computer 0 sends (1, 42) to the NAT on boot, then every computer just
+    // keeps reading input forever
+    const PROGRAM: [isize; 16] = [
+        3, 100, 1005, 100, 11, 104, 255, 104, 1, 104, 42, 3, 101, 1105, 1, 11,
+    ];
+
+    #[test]
+    fn test_nat_wakes_computer_zero() {
+        let mut network = Network::new(&PROGRAM, 3, Policy::RoundRobin);
+        assert_eq!(network.round().unwrap(), [Event::NatReceived((1, 42))]);
+        assert_eq!(network.round().unwrap(), [Event::NatSent((1, 42))]);
+    }
+
+    #[test]
+    fn test_partial_packet() {
+        // Sends the address and x of a packet, then halts
+        let mut network = Network::new(&[104, 1, 104, 7, 99], 2, Policy::RoundRobin);
+        assert!(matches!(
+            network.round(),
+            Err(NetworkError::PartialPacket { computer: 0, values }) if values == [1, 7]
+        ));
+    }
+
+    #[test]
+    fn test_stuck_network() {
+        let mut simulation = Simulation::new(&[99], Policy::RoundRobin);
+        assert!(matches!(simulation.first(), Err(NetworkError::Halted)));
+
+        // Every computer sends the NAT a packet every round, so the network
+        // is never idle and the NAT never delivers anything
+        let program = [3, 100, 104, 255, 104, 1, 104, 2, 1105, 1, 0];
+        let mut simulation = Simulation::sized(&program, 3, Policy::RoundRobin);
+        simulation.round_limit = 10;
+        assert_eq!(simulation.first().unwrap(), 2);
+        assert!(matches!(
+            simulation.repeated(),
+            Err(NetworkError::RoundLimit(10))
+        ));
+    }
+
+    #[test]
+    fn test_one_run_for_both_parts() {
+        let mut simulation = Simulation::new(&PROGRAM, Policy::RoundRobin);
+        assert_eq!(simulation.first().unwrap(), 42);
+        assert_eq!(simulation.schedule().rounds.len(), 1);
+        assert_eq!(simulation.repeated().unwrap(), 42);
+        // No more rounds than a run straight to the repeated value
+        let run = run_network(&PROGRAM, Policy::RoundRobin).unwrap();
+        assert_eq!(simulation.schedule(), &run.schedule);
+    }
+
+    #[test]
+    fn test_run_network() {
+        let run = run_network(&PROGRAM, Policy::RoundRobin).unwrap();
+        assert_eq!((run.first, run.repeated), (42, 42));
+        assert_eq!(run.schedule.rounds[0], (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_replay_schedule() {
+        let run = run_network(&PROGRAM, Policy::Random { seed: 23 }).unwrap();
+        let replay = parse_schedule(&run.schedule.to_string()).unwrap();
+        let replayed = run_network(&PROGRAM, replay).unwrap();
+        assert_eq!(replayed.schedule, run.schedule);
+        assert_eq!((replayed.first, replayed.repeated), (42, 42));
+    }
+}
@@ -0,0 +1,60 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_23::{run_network, Day23};
+use intcode::read_intcode_input;
+use intcode::schedule::{parse_schedule, Policy};
+use std::{env, fs};
+
+// Runs the network again with another schedule, to reproduce or look for
+// answers that depend on the order the computers run in
+fn run_scheduled(input: &str, args: &[String]) -> Result<(), String> {
+    let mut policy = None;
+    let mut save = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--schedule" => policy = Some(value()?.parse::<Policy>()?),
+            "--replay" => {
+                let path = value()?;
+                let text = fs::read_to_string(path)
+                    .map_err(|error| format!("Could not read {}: {}", path, error))?;
+                policy = Some(parse_schedule(&text)?);
+            }
+            "--save-schedule" => save = Some(value()?),
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+    let policy = match policy {
+        Some(policy) => policy,
+        None if save.is_some() => Policy::RoundRobin,
+        None => return Ok(()),
+    };
+
+    let memory = read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())?;
+    let run = run_network(&memory, policy).map_err(|error| error.to_string())?;
+    println!(
+        "Scheduled - {} and {}, after {} rounds",
+        run.first,
+        run.repeated,
+        run.schedule.rounds.len()
+    );
+    if let Some(path) = save {
+        fs::write(path, run.schedule.to_string())
+            .map_err(|error| format!("Could not write {}: {}", path, error))?;
+        println!("Saved the schedule to {}", path);
+    }
+    Ok(())
+}
+
+fn main() {
+    let input = read_stdin();
+    print_answers(&Day23, &input, timing_requested());
+
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--time").collect();
+    if let Err(error) = run_scheduled(&input, &args) {
+        eprintln!("Could not run the network: {}", error);
+    }
+}
@@ -0,0 +1,261 @@
+use aoc_utils::cycle::find_repeat;
+use aoc_utils::image::{self, ImageError};
+use aoc_utils::solution::or_no_solution;
+use aoc_utils::{Grid, Point2, Puzzle};
+use std::collections::HashMap;
+
+/// Which tiles have a bug. The puzzle's grids are 5x5, but any rectangle
+/// works, and any with odd sides can be nested.
+pub type Layout = Grid<bool>;
+
+pub fn parse_layout(input: &str) -> Result<Layout, &'static str> {
+    let layout = Grid::parse(input.trim(), |c| match c {
+        '#' => Ok(true),
+        '.' => Ok(false),
+        _ => Err("Invalid character in layout"),
+    })?;
+    if layout.width() == 0 {
+        return Err("Layout is empty");
+    }
+    Ok(layout)
+}
+
+/// The puzzle's rule: a bug survives with exactly one bug next to it, and
+/// an empty tile gets infested with one or two.
+pub fn bug_rule(alive: bool, neighbours: usize) -> bool {
+    if alive {
+        neighbours == 1
+    } else {
+        neighbours == 1 || neighbours == 2
+    }
+}
+
+fn has_bug(layout: &Layout, x: i64, y: i64) -> bool {
+    layout.get(Point2::new(x, y)) == Some(&true)
+}
+
+/// A minute of a single grid, where `rule` decides every tile from whether
+/// it has a bug and how many of its four neighbours do.
+pub fn step<R: Fn(bool, usize) -> bool>(layout: &Layout, rule: R) -> Layout {
+    let mut next = layout.clone();
+    for point in layout.points() {
+        let neighbours = layout
+            .neighbours4(point)
+            .filter(|neighbour| has_bug(layout, neighbour.x, neighbour.y))
+            .count();
+        *next.get_mut(point).unwrap() = rule(has_bug(layout, point.x, point.y), neighbours);
+    }
+    next
+}
+
+/// The first layout that shows up twice.
+pub fn first_repeat<R: Fn(bool, usize) -> bool>(layout: &Layout, rule: R) -> Layout {
+    find_repeat(layout.clone(), |layout| step(layout, &rule)).state
+}
+
+/// Every bug is worth a power of two, by its position in reading order.
+/// Grids of more than 128 tiles don't have one.
+pub fn biodiversity(layout: &Layout) -> Option<u128> {
+    if layout.width() * layout.height() > 128 {
+        return None;
+    }
+    Some(
+        layout
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, bug))| **bug)
+            .map(|(index, _)| 1 << index)
+            .sum(),
+    )
+}
+
+pub fn first_repeated_rating(layout: &Layout) -> Option<u128> {
+    biodiversity(&first_repeat(layout, bug_rule))
+}
+
+/// Counts the bugs next to a tile when every level's center tile holds the
+/// next level down. Level `depth + 1` is the one inside the center.
+fn recursive_neighbours(levels: &HashMap<i32, Layout>, depth: i32, x: i64, y: i64) -> usize {
+    let empty = Grid::new(0, 0, false);
+    let level = |depth| levels.get(&depth).unwrap_or(&empty);
+    let (width, height) = (levels[&0].width() as i64, levels[&0].height() as i64);
+    let (center_x, center_y) = (width / 2, height / 2);
+    let mut count = 0;
+    for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+        let (nx, ny) = (x + dx, y + dy);
+        if nx < 0 || ny < 0 || nx >= width || ny >= height {
+            // Off the edge, into the tile around the center of the outer level
+            if has_bug(level(depth - 1), center_x + dx, center_y + dy) {
+                count += 1;
+            }
+        } else if nx == center_x && ny == center_y {
+            // Into the center, which touches a whole edge of the inner level
+            let inner = level(depth + 1);
+            let edge: Vec<(i64, i64)> = match (dx, dy) {
+                (1, _) => (0..height).map(|i| (0, i)).collect(),
+                (-1, _) => (0..height).map(|i| (width - 1, i)).collect(),
+                (_, 1) => (0..width).map(|i| (i, 0)).collect(),
+                _ => (0..width).map(|i| (i, height - 1)).collect(),
+            };
+            count += edge
+                .into_iter()
+                .filter(|(x, y)| has_bug(inner, *x, *y))
+                .count();
+        } else if has_bug(level(depth), nx, ny) {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn recursive_step<R>(levels: &HashMap<i32, Layout>, rule: &R) -> HashMap<i32, Layout>
+where
+    R: Fn(bool, usize) -> bool,
+{
+    let min = levels.keys().min().copied().unwrap_or(0);
+    let max = levels.keys().max().copied().unwrap_or(0);
+    let blank = levels[&0].map(|_| false);
+    let center = Point2::new(blank.width() as i64 / 2, blank.height() as i64 / 2);
+    let mut next: HashMap<i32, Layout> = (min - 1..=max + 1)
+        .map(|depth| {
+            let current = levels.get(&depth).unwrap_or(&blank);
+            let mut next = blank.clone();
+            for point in blank.points().filter(|point| *point != center) {
+                let neighbours = recursive_neighbours(levels, depth, point.x, point.y);
+                *next.get_mut(point).unwrap() =
+                    rule(has_bug(current, point.x, point.y), neighbours);
+            }
+            (depth, next)
+        })
+        .filter(|(depth, layout)| *depth == 0 || layout.iter().any(|(_, bug)| *bug))
+        .collect();
+    // The outermost level stays around, even when empty, to know the size
+    next.entry(0).or_insert(blank);
+    next
+}
+
+/// Counts the bugs after some minutes of nested grids, each one sitting in
+/// the center tile of the one around it. The grid needs odd sides for it
+/// to have a center.
+pub fn recursive_bug_count_with<R>(
+    layout: &Layout,
+    minutes: usize,
+    rule: R,
+) -> Result<usize, &'static str>
+where
+    R: Fn(bool, usize) -> bool,
+{
+    let odd = |side: usize| side >= 3 && !side.is_multiple_of(2);
+    if !odd(layout.width()) || !odd(layout.height()) {
+        return Err("Nested grids need odd sides of at least 3 tiles");
+    }
+    let mut first = layout.clone();
+    // The center tile is the next level down, so it never has bugs itself
+    let center = Point2::new(layout.width() as i64 / 2, layout.height() as i64 / 2);
+    *first.get_mut(center).unwrap() = false;
+    let mut levels = HashMap::new();
+    levels.insert(0, first);
+    for _ in 0..minutes {
+        levels = recursive_step(&levels, &rule);
+    }
+    Ok(levels
+        .values()
+        .map(|layout| layout.iter().filter(|(_, bug)| **bug).count())
+        .sum())
+}
+
+pub fn recursive_bug_count(layout: &Layout, minutes: usize) -> Result<usize, &'static str> {
+    recursive_bug_count_with(layout, minutes, bug_rule)
+}
+
+pub fn export_layout(layout: &Layout, path: &str) -> Result<(), ImageError> {
+    let image = layout.map(|bug| if *bug { [120, 200, 60] } else { [30, 30, 40] });
+    image::save(&image, 40, path)
+}
+
+pub struct Day24;
+
+impl Puzzle for Day24 {
+    type Input = Layout;
+
+    fn parse(&self, input: &str) -> Result<Layout, String> {
+        parse_layout(input).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, layout: &Layout) -> Result<String, String> {
+        Ok(or_no_solution(first_repeated_rating(layout)))
+    }
+
+    fn part2(&self, layout: &Layout) -> Result<String, String> {
+        Ok(or_no_solution(recursive_bug_count(layout, 200).ok()))
+    }
+}
+
+aoc_utils::register_solution!(24, Day24);
+
+#[cfg(test)]
+mod test {
+    use super::{
+        biodiversity, bug_rule, first_repeat, first_repeated_rating, parse_layout,
+        recursive_bug_count, recursive_bug_count_with, step,
+    };
+
+    const EXAMPLE: &str = "\
+....#
+#..#.
+#..##
+..#..
+#....";
+
+    #[test]
+    fn test_step() {
+        let layout = parse_layout(EXAMPLE).unwrap();
+        let expected = parse_layout(
+            "\
+#..#.
+####.
+###.#
+##.##
+.##..",
+        )
+        .unwrap();
+        assert_eq!(step(&layout, bug_rule), expected);
+    }
+
+    #[test]
+    fn test_first_repeated_rating() {
+        let layout = parse_layout(EXAMPLE).unwrap();
+        assert_eq!(first_repeated_rating(&layout), Some(2_129_920));
+    }
+
+    #[test]
+    fn test_recursive_bug_count() {
+        let layout = parse_layout(EXAMPLE).unwrap();
+        assert_eq!(recursive_bug_count(&layout, 10), Ok(99));
+    }
+
+    #[test]
+    fn test_other_grids() {
+        assert!(parse_layout("#.#\n##").is_err());
+        assert!(parse_layout("\n").is_err());
+
+        // Rules that only look at the tile, or only at its neighbours, on a
+        // wider grid
+        let toggle = |alive: bool, _| !alive;
+        let spread = |alive, neighbours| alive || neighbours > 0;
+        let layout = parse_layout(".......\n.......\n...#...\n.......\n.......").unwrap();
+        let toggled = step(&layout, toggle);
+        assert_eq!(toggled.iter().filter(|(_, bug)| **bug).count(), 34);
+        assert_eq!(step(&toggled, toggle), layout);
+        assert_eq!(first_repeat(&layout, toggle), layout);
+        assert_eq!(
+            step(&layout, spread),
+            parse_layout(".......\n...#...\n..###..\n...#...\n.......").unwrap()
+        );
+        assert_eq!(biodiversity(&layout), Some(1 << 17));
+
+        let wide = parse_layout("#...#\n.#.#.\n#...#").unwrap();
+        assert!(recursive_bug_count_with(&wide, 3, bug_rule).is_ok());
+        assert!(recursive_bug_count(&parse_layout("##\n##").unwrap(), 3).is_err());
+    }
+}
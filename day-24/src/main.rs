@@ -0,0 +1,20 @@
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_24::{bug_rule, export_layout, first_repeat, parse_layout, Day24};
+use std::env;
+
+fn main() {
+    let input = read_stdin();
+    print_answers(&Day24, &input, timing_requested());
+
+    let args: Vec<String> = env::args().skip(1).filter(|arg| arg != "--time").collect();
+    if let [flag, path] = args.as_slice() {
+        if flag == "--export" {
+            let layout = parse_layout(&input).expect("Invalid puzzle input");
+            let repeated = first_repeat(&layout, bug_rule);
+            match export_layout(&repeated, path) {
+                Ok(()) => println!("Saved the repeated layout to {}", path),
+                Err(error) => eprintln!("Could not export layout: {}", error),
+            }
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use super::Droid;
+use aoc_utils::combinatorics::combinations;
+use aoc_utils::progress::Progress;
+use intcode::IntCodeError;
+use log::debug;
+use std::collections::HashSet;
+use std::fmt;
+
+const CHECKPOINT: &str = "Security Checkpoint";
+
+// Picking any of these up ends the game or gets the droid stuck
+const UNSAFE_ITEMS: [&str; 5] = [
+    "escape pod",
+    "giant electromagnet",
+    "infinite loop",
+    "molten lava",
+    "photons",
+];
+
+#[derive(Debug)]
+pub enum BotError {
+    Machine(IntCodeError),
+    UnexpectedOutput(String),
+    CheckpointNotFound,
+    NoValidCombination,
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BotError::Machine(error) => write!(f, "Droid program failed: {}", error),
+            BotError::UnexpectedOutput(output) => write!(f, "Unexpected output:\n{}", output),
+            BotError::CheckpointNotFound => write!(f, "Could not find the security checkpoint"),
+            BotError::NoValidCombination => write!(f, "No item combination passes the floor"),
+        }
+    }
+}
+
+impl From<IntCodeError> for BotError {
+    fn from(error: IntCodeError) -> Self {
+        BotError::Machine(error)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Room {
+    name: String,
+    doors: Vec<String>,
+    items: Vec<String>,
+}
+
+impl Room {
+    // The output may contain several rooms when the droid gets ejected, the
+    // last one is where it is standing
+    fn parse(output: &str) -> Option<Self> {
+        let start = output.rfind("== ")?;
+        let mut lines = output[start..].lines();
+        let name = lines.next()?.trim_matches(|c| c == '=' || c == ' ');
+
+        let mut room = Room {
+            name: name.to_string(),
+            doors: Vec::new(),
+            items: Vec::new(),
+        };
+        let mut list = None;
+        for line in lines {
+            if line.starts_with("Doors here lead:") {
+                list = Some(&mut room.doors);
+            } else if line.starts_with("Items here:") {
+                list = Some(&mut room.items);
+            } else if let Some(entry) = line.strip_prefix("- ") {
+                if let Some(list) = list.as_mut() {
+                    list.push(entry.to_string());
+                }
+            } else {
+                list = None;
+            }
+        }
+        Some(room)
+    }
+}
+
+fn opposite(direction: &str) -> &'static str {
+    match direction {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        _ => "east",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    TooLight,
+    TooHeavy,
+    Passed,
+}
+
+/// Finds a set of items, as a bitmask, that the floor accepts. Every answer
+/// from the floor prunes the remaining candidates: subsets of a set that is
+/// too light are also too light, and supersets of a set that is too heavy
+/// are also too heavy.
+pub fn search_combination<F>(items: usize, mut check: F) -> Result<u32, BotError>
+where
+    F: FnMut(u32) -> Result<Verdict, BotError>,
+{
+    let mut too_light: Vec<u32> = Vec::new();
+    let mut too_heavy: Vec<u32> = Vec::new();
+
+    // Trying sets with about half of the items first cuts the most
+    // candidates with each answer
+    let indices: Vec<usize> = (0..items).collect();
+    let mut sizes: Vec<usize> = (0..=items).collect();
+    sizes.sort_by_key(|size| (*size as isize - (items / 2) as isize).abs());
+    let candidates = sizes.into_iter().flat_map(|size| {
+        combinations(&indices, size)
+            .map(|chosen| chosen.iter().fold(0, |mask, index| mask | 1 << index))
+    });
+
+    let mut progress = Progress::new("Trying item combinations", 1 << items);
+    for mask in candidates {
+        progress.inc(1);
+        if too_light.iter().any(|light| mask & !light == 0)
+            || too_heavy.iter().any(|heavy| heavy & !mask == 0)
+        {
+            continue;
+        }
+        let verdict = check(mask)?;
+        debug!("Items {:0width$b} are {:?}", mask, verdict, width = items);
+        match verdict {
+            Verdict::Passed => return Ok(mask),
+            Verdict::TooLight => too_light.push(mask),
+            Verdict::TooHeavy => too_heavy.push(mask),
+        }
+    }
+    Err(BotError::NoValidCombination)
+}
+
+struct Bot<'a> {
+    droid: &'a mut Droid,
+    visited: HashSet<String>,
+    items: Vec<String>,
+    // Path from the start to the checkpoint, and the door onto the floor
+    checkpoint: Option<(Vec<String>, String)>,
+    // What the droid printed when it got through the floor
+    passed: Option<String>,
+    log: bool,
+}
+
+impl<'a> Bot<'a> {
+    fn send(&mut self, command: &str) -> Result<String, BotError> {
+        let output = self.droid.send(command)?;
+        if self.log {
+            print!("{}{}", command, output);
+        }
+        if self.droid.finished() {
+            return Err(BotError::UnexpectedOutput(output));
+        }
+        Ok(output)
+    }
+
+    fn explore(
+        &mut self,
+        room: Room,
+        came_from: Option<&str>,
+        path: &mut Vec<String>,
+    ) -> Result<(), BotError> {
+        self.visited.insert(room.name.clone());
+        for item in room.items.iter() {
+            if !UNSAFE_ITEMS.contains(&item.as_str()) {
+                self.send(&format!("take {}", item))?;
+                self.items.push(item.clone());
+            }
+        }
+
+        let back = came_from.map(opposite);
+        if room.name == CHECKPOINT {
+            // Any other door leads onto the pressure-sensitive floor, which
+            // just throws the droid back here
+            let floor = room
+                .doors
+                .iter()
+                .find(|door| Some(door.as_str()) != back)
+                .ok_or(BotError::CheckpointNotFound)?;
+            self.checkpoint = Some((path.clone(), floor.clone()));
+            return Ok(());
+        }
+
+        for door in room.doors.iter() {
+            if Some(door.as_str()) == back {
+                continue;
+            }
+            let output = self.send(door)?;
+            let next = Room::parse(&output).ok_or(BotError::UnexpectedOutput(output))?;
+            if !self.visited.contains(&next.name) {
+                path.push(door.clone());
+                self.explore(next, Some(door), path)?;
+                path.pop();
+            }
+            self.send(opposite(door))?;
+        }
+        Ok(())
+    }
+
+    fn check(&mut self, holding: &mut u32, mask: u32, floor: &str) -> Result<Verdict, BotError> {
+        for (index, item) in self.items.clone().iter().enumerate() {
+            let bit = 1 << index;
+            if *holding & bit != 0 && mask & bit == 0 {
+                self.send(&format!("drop {}", item))?;
+            } else if *holding & bit == 0 && mask & bit != 0 {
+                self.send(&format!("take {}", item))?;
+            }
+        }
+        *holding = mask;
+
+        let output = self.droid.send(floor)?;
+        if self.log {
+            print!("{}{}", floor, output);
+        }
+        if output.contains("heavier than the detected") {
+            Ok(Verdict::TooLight)
+        } else if output.contains("lighter than the detected") {
+            Ok(Verdict::TooHeavy)
+        } else {
+            self.passed = Some(output);
+            Ok(Verdict::Passed)
+        }
+    }
+}
+
+fn find_password(output: &str) -> Option<String> {
+    let start = output.find("typing ")? + "typing ".len();
+    let password: String = output[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    if password.is_empty() {
+        None
+    } else {
+        Some(password)
+    }
+}
+
+/// Explores the ship picking up every safe item, walks to the security
+/// checkpoint and searches for the items that get the droid past the
+/// pressure-sensitive floor. Returns the airlock password.
+pub fn solve(droid: &mut Droid, log: bool) -> Result<String, BotError> {
+    let mut bot = Bot {
+        droid,
+        visited: HashSet::new(),
+        items: Vec::new(),
+        checkpoint: None,
+        passed: None,
+        log,
+    };
+    let output = bot.send("")?;
+    let room = Room::parse(&output).ok_or(BotError::UnexpectedOutput(output))?;
+    bot.explore(room, None, &mut Vec::new())?;
+
+    let (path, floor) = bot.checkpoint.take().ok_or(BotError::CheckpointNotFound)?;
+    for door in path.iter() {
+        bot.send(door)?;
+    }
+
+    let mut holding = (1 << bot.items.len()) - 1;
+    let items = bot.items.len();
+    search_combination(items, |mask| bot.check(&mut holding, mask, &floor))?;
+
+    let output = bot.passed.take().unwrap_or_default();
+    find_password(&output).ok_or(BotError::UnexpectedOutput(output))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_password, search_combination, Room, Verdict};
+
+    #[test]
+    fn test_parse_room() {
+        let output = "
+
+== Hull Breach ==
+You got in through a hole in the floor here. To keep your ship from also freezing, the hole has been sealed.
+
+Doors here lead:
+- north
+- east
+
+Items here:
+- mug
+
+Command?
+";
+        assert_eq!(
+            Room::parse(output),
+            Some(Room {
+                name: "Hull Breach".to_string(),
+                doors: vec!["north".to_string(), "east".to_string()],
+                items: vec!["mug".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_password() {
+        let output = "Oh, hello! You should be able to get in by typing 2622472 on the keypad at the main airlock.";
+        assert_eq!(find_password(output), Some("2622472".to_string()));
+    }
+
+    #[test]
+    fn test_search_combination() {
+        let weights = [1, 2, 4, 8, 16, 32, 64, 128];
+        let target = weights[1] + weights[4] + weights[6];
+        let mut checks = 0;
+        let result = search_combination(weights.len(), |mask| {
+            checks += 1;
+            let weight: u32 = (0..weights.len())
+                .filter(|index| mask & (1 << index) != 0)
+                .map(|index| weights[index])
+                .sum();
+            Ok(match weight {
+                w if w < target => Verdict::TooLight,
+                w if w > target => Verdict::TooHeavy,
+                _ => Verdict::Passed,
+            })
+        })
+        .unwrap();
+        assert_eq!(result, 0b0101_0010);
+        assert!(checks < 256);
+    }
+}
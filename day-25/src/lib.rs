@@ -0,0 +1,153 @@
+use aoc_utils::Puzzle;
+use intcode::ascii::PromptReader;
+use intcode::{read_intcode_input, IntCodeError, IntCodeMachine, Machine, Snapshot};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+pub mod bot;
+
+const PROMPT: &str = "Command?\n";
+
+pub struct Droid {
+    machine: IntCodeMachine,
+    output: PromptReader,
+    // What the droid printed last, shown again when a game is loaded
+    last_output: String,
+}
+
+/// A game saved between commands, with the text the droid printed last.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedGame {
+    machine: Snapshot,
+    last_output: String,
+}
+
+impl SavedGame {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let file = BufReader::new(File::open(path)?);
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        if let Some(directory) = path.as_ref().parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, self)?;
+        Ok(())
+    }
+}
+
+impl Droid {
+    pub fn new(memory: Vec<isize>) -> Self {
+        Self {
+            machine: IntCodeMachine::new(memory),
+            output: PromptReader::new(PROMPT),
+            last_output: String::new(),
+        }
+    }
+
+    /// Sends a command to the droid, returning everything it printed until
+    /// it asks for the next command, or until it halts.
+    pub fn send(&mut self, command: &str) -> Result<String, IntCodeError> {
+        let text = match self.output.send(&mut self.machine, command)? {
+            Some(text) => text,
+            None => self.output.flush(),
+        };
+        self.last_output.clone_from(&text);
+        Ok(text)
+    }
+
+    pub fn save(&self) -> SavedGame {
+        SavedGame {
+            machine: self.machine.snapshot(),
+            last_output: self.last_output.clone(),
+        }
+    }
+
+    /// Puts the droid back where the game was saved, returning what it
+    /// printed then.
+    pub fn load(&mut self, saved: SavedGame) -> &str {
+        self.machine = IntCodeMachine::from_snapshot(saved.machine);
+        self.output = PromptReader::new(PROMPT);
+        self.last_output = saved.last_output;
+        &self.last_output
+    }
+
+    pub fn finished(&self) -> bool {
+        self.machine.finished()
+    }
+}
+
+pub fn expand_shortcut(command: &str) -> &str {
+    match command {
+        "n" => "north",
+        "s" => "south",
+        "e" => "east",
+        "w" => "west",
+        "i" => "inv",
+        _ => command,
+    }
+}
+
+pub struct Day25;
+
+impl Puzzle for Day25 {
+    type Input = Vec<isize>;
+
+    fn parse(&self, input: &str) -> Result<Vec<isize>, String> {
+        read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, memory: &Vec<isize>) -> Result<String, String> {
+        let mut droid = Droid::new(memory.clone());
+        bot::solve(&mut droid, false).map_err(|error| error.to_string())
+    }
+
+    // Day 25 only has one puzzle
+    fn part2(&self, _memory: &Vec<isize>) -> Result<String, String> {
+        Ok("no second puzzle".to_string())
+    }
+}
+
+aoc_utils::register_solution!(25, Day25);
+
+#[cfg(test)]
+mod test {
+    use super::{Day25, Droid, SavedGame};
+    use aoc_utils::Puzzle;
+    use intcode::compiler::compile;
+
+    // Prints how many commands it was sent, as a digit, before every prompt
+    const COUNTER: &str = "
+        count = 48;
+        while 1 {
+            out count; out 10;
+            out 67; out 111; out 109; out 109; out 97; out 110; out 100; out 63; out 10;
+            in c;
+            while c != 10 { in c; }
+            count = count + 1;
+        }";
+
+    #[test]
+    fn test_save_and_load() {
+        let mut droid = Droid::new(compile(COUNTER).unwrap());
+        assert_eq!(droid.send("").unwrap(), "0\nCommand?\n");
+        assert_eq!(droid.send("north").unwrap(), "1\nCommand?\n");
+
+        let saved = serde_json::to_string(&droid.save()).unwrap();
+        assert_eq!(droid.send("take mug").unwrap(), "2\nCommand?\n");
+
+        let saved: SavedGame = serde_json::from_str(&saved).unwrap();
+        assert_eq!(droid.load(saved), "1\nCommand?\n");
+        assert_eq!(droid.send("south").unwrap(), "2\nCommand?\n");
+    }
+
+    #[test]
+    fn test_halted_droid() {
+        assert!(Day25.part1(&vec![99]).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use day_25::{bot, expand_shortcut, Droid, SavedGame};
+use intcode::read_intcode_input;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{stdin, stdout, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+struct Options {
+    program: String,
+    replay: Option<String>,
+    record: Option<String>,
+    saves: String,
+    bot: bool,
+    verbose: bool,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        program: "./input.txt".to_string(),
+        replay: None,
+        record: None,
+        saves: "saves".to_string(),
+        bot: false,
+        verbose: false,
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--replay" => options.replay = Some(args.next().ok_or("Missing replay file")?),
+            "--record" => options.record = Some(args.next().ok_or("Missing record file")?),
+            "--saves" => options.saves = args.next().ok_or("Missing saves directory")?,
+            "--bot" => options.bot = true,
+            "--verbose" => options.verbose = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+            _ => options.program = arg,
+        }
+    }
+    Ok(options)
+}
+
+fn save_path(directory: &str, name: &str) -> PathBuf {
+    Path::new(directory).join(format!("{}.json", name))
+}
+
+// Handles `save <name>` and `load <name>`, which snapshot the game instead
+// of being sent to the droid. Returns whether the command was one of them.
+fn meta_command(droid: &mut Droid, command: &str, saves: &str) -> bool {
+    if let Some(name) = command.strip_prefix("save ") {
+        let path = save_path(saves, name.trim());
+        match droid.save().save(&path) {
+            Ok(()) => println!("Saved the game to {}\n", path.display()),
+            Err(error) => eprintln!("Could not save the game: {}", error),
+        }
+    } else if let Some(name) = command.strip_prefix("load ") {
+        let path = save_path(saves, name.trim());
+        match SavedGame::load(&path) {
+            Ok(saved) => print!("{}", droid.load(saved)),
+            Err(error) => eprintln!("Could not load {}: {}", path.display(), error),
+        }
+    } else {
+        return false;
+    }
+    true
+}
+
+fn main() {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!(
+                "Usage: day-25 [program] [--replay commands] [--record commands] [--saves directory]"
+            );
+            eprintln!("       day-25 [program] --bot [--verbose]");
+            return;
+        }
+    };
+
+    let file = BufReader::new(File::open(&options.program).expect("Could not open program"));
+    let memory = read_intcode_input(file).expect("Invalid puzzle input");
+    let mut droid = Droid::new(memory);
+
+    if options.bot {
+        match bot::solve(&mut droid, options.verbose) {
+            Ok(password) => println!("Airlock password - {}", password),
+            Err(error) => eprintln!("{}", error),
+        }
+        return;
+    }
+
+    print!("{}", droid.send("").unwrap());
+
+    // Commands from a previous session are played back before handing
+    // control over to the keyboard
+    let replayed: Vec<String> = match &options.replay {
+        Some(path) => BufReader::new(File::open(path).expect("Could not open replay file"))
+            .lines()
+            .collect::<Result<_, _>>()
+            .expect("Could not read replay file"),
+        None => Vec::new(),
+    };
+    let mut record = options.record.as_ref().map(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .expect("Could not open record file")
+    });
+
+    // Only typed commands are recorded, so the same file can be used to
+    // replay and record, picking the game up where it was left. Saves and
+    // loads are recorded too, so a replay goes through the same saves
+    let stdin = stdin();
+    let typed = stdin.lock().lines().map(Result::unwrap);
+    let commands = replayed
+        .into_iter()
+        .map(|line| (line, true))
+        .chain(typed.map(|line| (line, false)));
+    for (line, is_replay) in commands {
+        if droid.finished() {
+            break;
+        }
+        let command = expand_shortcut(line.trim());
+        if command.is_empty() {
+            continue;
+        }
+        if is_replay {
+            println!("{}", command);
+        } else if let Some(file) = record.as_mut() {
+            writeln!(file, "{}", command).expect("Could not record command");
+        }
+        if !meta_command(&mut droid, command, &options.saves) {
+            print!("{}", droid.send(command).unwrap());
+        }
+        stdout().flush().unwrap();
+    }
+}
@@ -0,0 +1,228 @@
+use aoc_utils::image::{self, render_sparse};
+use aoc_utils::input::{comma_separated, ParseError};
+use aoc_utils::{Direction, Point2, Puzzle, SparseGrid};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+/// A straight stretch of wire.
+#[derive(Debug, Clone, Copy)]
+pub struct Segment {
+    direction: Direction,
+    distance: i32,
+}
+
+impl FromStr for Segment {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s.char_indices().nth(1).map_or(s.len(), |(index, _)| index);
+        let direction = s[..split].parse()?;
+        let distance = s[split..].parse().map_err(|_| "invalid number")?;
+        Ok(Self {
+            direction,
+            distance,
+        })
+    }
+}
+
+struct Points<I> {
+    segments: I,
+    position: Point2,
+    direction: Option<Direction>,
+    remaining: i32,
+}
+
+impl<I> Iterator for Points<I>
+where
+    I: Iterator<Item = Segment>,
+{
+    type Item = Point2;
+
+    fn next(&mut self) -> Option<Point2> {
+        let direction = match self.direction.take() {
+            Some(d) => d,
+            None => match self.segments.next() {
+                Some(segment) => {
+                    self.remaining = segment.distance;
+                    segment.direction
+                }
+                None => return None,
+            },
+        };
+        self.position += direction.delta();
+
+        self.remaining -= 1;
+        if self.remaining > 0 {
+            self.direction = Some(direction);
+        }
+
+        Some(self.position)
+    }
+}
+
+impl<I: Iterator<Item = Segment>> Points<I> {
+    fn new(segments: I) -> Self {
+        Self {
+            segments,
+            position: Point2::ORIGIN,
+            direction: None,
+            remaining: 0,
+        }
+    }
+}
+
+fn find_minimum_distance(first: &[Segment], second: &[Segment]) -> u32 {
+    let first_set = Points::new(first.iter().copied()).collect::<HashSet<Point2>>();
+
+    Points::new(second.iter().copied())
+        .filter(|point| first_set.contains(point))
+        .map(|point| point.manhattan() as u32)
+        .min()
+        .unwrap_or(0)
+}
+
+fn find_minimum_combined_steps(first: &[Segment], second: &[Segment]) -> u32 {
+    // We aren't emitting the point (0, 0), so a step index is one
+    // lower than expected
+    let first_set = Points::new(first.iter().copied()).enumerate().fold(
+        HashMap::new(),
+        |mut map, (index, point)| {
+            map.entry(point).or_insert(index + 1);
+            map
+        },
+    );
+
+    Points::new(second.iter().copied())
+        .enumerate()
+        .filter(|(_, point)| first_set.contains_key(point))
+        .map(|(index, point)| 1 + index as u32 + *first_set.get(&point).unwrap() as u32)
+        .min()
+        .unwrap_or(0)
+}
+
+// Cells crossed by the first wire get bit 1, by the second wire bit 2. The
+// map is flipped so up is towards the top of the picture.
+fn wire_map(first: &[Segment], second: &[Segment]) -> SparseGrid<u8> {
+    let mut map = SparseGrid::new();
+    for (segments, bit) in [(first, 1), (second, 2)] {
+        for point in Points::new(segments.iter().copied()) {
+            *map.entry(Point2::new(point.x, -point.y)).or_insert(0) |= bit;
+        }
+    }
+    map
+}
+
+pub fn export_wires(
+    first: &[Segment],
+    second: &[Segment],
+    path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let map = wire_map(first, second);
+    let image = render_sparse(&map, |cell| match cell {
+        Some(1) => [255, 120, 40],
+        Some(2) => [40, 160, 255],
+        Some(_) => [255, 255, 255],
+        None => [16, 16, 32],
+    });
+    image::save(&image, 1, path)?;
+    Ok(())
+}
+
+/// The two wires of the puzzle input, one per line.
+pub fn parse_wires(input: &str) -> Result<(Vec<Segment>, Vec<Segment>), ParseError> {
+    let lines: Vec<&str> = input.lines().collect();
+    match lines.as_slice() {
+        [first, second] => Ok((
+            comma_separated(first)?,
+            comma_separated(second).map_err(|error| error.on_line(2))?,
+        )),
+        [_] | [] => Err(ParseError::new(lines.len() + 1, 1, "missing a wire")),
+        _ => Err(ParseError::new(3, 1, "expected only two wires")),
+    }
+}
+
+pub struct Day3;
+
+impl Puzzle for Day3 {
+    type Input = (Vec<Segment>, Vec<Segment>);
+
+    fn parse(&self, input: &str) -> Result<(Vec<Segment>, Vec<Segment>), String> {
+        parse_wires(input).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, (first, second): &(Vec<Segment>, Vec<Segment>)) -> Result<String, String> {
+        Ok(find_minimum_distance(first, second).to_string())
+    }
+
+    fn part2(&self, (first, second): &(Vec<Segment>, Vec<Segment>)) -> Result<String, String> {
+        Ok(find_minimum_combined_steps(first, second).to_string())
+    }
+}
+
+aoc_utils::register_solution!(3, Day3);
+
+#[cfg(test)]
+mod test {
+    use super::{find_minimum_combined_steps, find_minimum_distance, parse_wires, Segment};
+
+    fn wires(first: &str, second: &str) -> (Vec<Segment>, Vec<Segment>) {
+        parse_wires(&format!("{}\n{}", first, second)).unwrap()
+    }
+
+    #[test]
+    fn test_minimum_distance_case_1() {
+        let (first, second) = wires("R8,U5,L5,D3", "U7,R6,D4,L4");
+        assert_eq!(find_minimum_distance(&first, &second), 6);
+    }
+
+    #[test]
+    fn test_minimum_distance_case_2() {
+        let (first, second) = wires(
+            "R75,D30,R83,U83,L12,D49,R71,U7,L72",
+            "U62,R66,U55,R34,D71,R55,D58,R83",
+        );
+        assert_eq!(find_minimum_distance(&first, &second), 159);
+    }
+
+    #[test]
+    fn test_minimum_distance_case_3() {
+        let (first, second) = wires(
+            "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51",
+            "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+        );
+        assert_eq!(find_minimum_distance(&first, &second), 135);
+    }
+
+    #[test]
+    fn test_minimum_combined_steps_case_1() {
+        let (first, second) = wires("R8,U5,L5,D3", "U7,R6,D4,L4");
+        assert_eq!(find_minimum_combined_steps(&first, &second), 30);
+    }
+
+    #[test]
+    fn test_minimum_combined_steps_case_2() {
+        let (first, second) = wires(
+            "R75,D30,R83,U83,L12,D49,R71,U7,L72",
+            "U62,R66,U55,R34,D71,R55,D58,R83",
+        );
+        assert_eq!(find_minimum_combined_steps(&first, &second), 610);
+    }
+
+    #[test]
+    fn test_minimum_combined_steps_case_3() {
+        let (first, second) = wires(
+            "R98,U47,R26,D63,R33,U87,L62,D20,R33,U53,R51",
+            "U98,R91,D20,R16,D67,R40,U7,R15,U6,R7",
+        );
+        assert_eq!(find_minimum_combined_steps(&first, &second), 410);
+    }
+
+    #[test]
+    fn test_parse_wires_errors() {
+        assert_eq!(parse_wires("R8,U5").unwrap_err().line, 2);
+        assert_eq!(parse_wires("R8\nU7\nL4").unwrap_err().line, 3);
+        let error = parse_wires("R8,U5\nU7,X6").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 4));
+        assert!(parse_wires("R8,U\nU7").is_err());
+    }
+}
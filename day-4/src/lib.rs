@@ -0,0 +1,85 @@
+use aoc_utils::digits::digits;
+use aoc_utils::Puzzle;
+use std::ops::RangeInclusive;
+
+fn is_non_decreasing(digits: &[u8]) -> bool {
+    digits.windows(2).all(|pair| pair[0] <= pair[1])
+}
+
+fn is_valid_puzzle_1(value: u32) -> bool {
+    let digits: Vec<u8> = digits(value as u64).collect();
+    // With non-decreasing digits, any repeated digits are next to each other
+    is_non_decreasing(&digits) && digits.windows(2).any(|pair| pair[0] == pair[1])
+}
+
+fn is_valid_puzzle_2(value: u32) -> bool {
+    let digits: Vec<u8> = digits(value as u64).collect();
+    if !is_non_decreasing(&digits) {
+        return false;
+    }
+    let mut repetitions: [u8; 10] = [0; 10];
+    for digit in digits {
+        repetitions[digit as usize] += 1;
+    }
+    repetitions.contains(&2)
+}
+
+/// The puzzle input is the range of passwords, like `172851-675869`.
+fn parse_range(input: &str) -> Result<RangeInclusive<u32>, String> {
+    let bounds = input.trim().split_once('-').and_then(|(start, end)| {
+        let start = start.parse().ok()?;
+        let end = end.parse().ok()?;
+        Some(start..=end)
+    });
+    bounds.ok_or_else(|| format!("Invalid range {}", input.trim()))
+}
+
+pub struct Day4;
+
+impl Puzzle for Day4 {
+    type Input = RangeInclusive<u32>;
+
+    fn parse(&self, input: &str) -> Result<RangeInclusive<u32>, String> {
+        parse_range(input)
+    }
+
+    fn part1(&self, range: &RangeInclusive<u32>) -> Result<String, String> {
+        let count = range
+            .clone()
+            .filter(|value| is_valid_puzzle_1(*value))
+            .count();
+        Ok(count.to_string())
+    }
+
+    fn part2(&self, range: &RangeInclusive<u32>) -> Result<String, String> {
+        let count = range
+            .clone()
+            .filter(|value| is_valid_puzzle_2(*value))
+            .count();
+        Ok(count.to_string())
+    }
+}
+
+aoc_utils::register_solution!(4, Day4);
+
+#[cfg(test)]
+mod test {
+    use super::{is_valid_puzzle_1, is_valid_puzzle_2};
+
+    #[test]
+    fn test_valid_puzzle_1() {
+        assert!(is_valid_puzzle_1(111111));
+        assert!(!is_valid_puzzle_1(123456));
+        assert!(is_valid_puzzle_1(122345));
+    }
+
+    #[test]
+    fn test_valid_puzzle_2() {
+        assert!(!is_valid_puzzle_2(111111));
+        assert!(!is_valid_puzzle_2(123456));
+        assert!(is_valid_puzzle_2(122345));
+        assert!(!is_valid_puzzle_2(122245));
+        assert!(!is_valid_puzzle_2(123444));
+        assert!(is_valid_puzzle_2(111122));
+    }
+}
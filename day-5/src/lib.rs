@@ -0,0 +1,46 @@
+use aoc_utils::Puzzle;
+use intcode::{read_intcode_input, IntCodeError, IntCodeMachine};
+
+/// Runs the diagnostic program for the system with the given ID, returning
+/// every output it produced.
+fn run_diagnostic(memory: &[isize], system: isize) -> Result<Vec<isize>, IntCodeError> {
+    let mut machine = IntCodeMachine::new(memory.to_vec());
+    machine.execute([system])
+}
+
+pub struct Day5;
+
+impl Puzzle for Day5 {
+    type Input = Vec<isize>;
+
+    fn parse(&self, input: &str) -> Result<Vec<isize>, String> {
+        read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, memory: &Vec<isize>) -> Result<String, String> {
+        run_diagnostic(memory, 1)
+            .map(|output| format!("{:?}", output))
+            .map_err(|error| error.to_string())
+    }
+
+    fn part2(&self, memory: &Vec<isize>) -> Result<String, String> {
+        run_diagnostic(memory, 5)
+            .map(|output| format!("{:?}", output))
+            .map_err(|error| error.to_string())
+    }
+}
+
+aoc_utils::register_solution!(5, Day5);
+
+#[cfg(test)]
+mod test {
+    use super::Day5;
+
+    aoc_utils::example_tests!(Day5, {
+        part1_echo: part1("echo.txt") == "[1]",
+        part2_echo: part2("echo.txt") == "[5]",
+        part1_equal_to_8: part1("equal-to-8.txt") == "[0]",
+        part1_compare_to_8: part1("compare-to-8.txt") == "[999]",
+        part2_compare_to_8: part2("compare-to-8.txt") == "[999]",
+    });
+}
@@ -1,15 +1,6 @@
-use intcode::{read_intcode_input, IntCodeMachine};
-use std::fs::File;
-use std::io::BufReader;
+use aoc_utils::solution::{print_answers, read_stdin, timing_requested};
+use day_5::Day5;
 
 fn main() {
-    let file = BufReader::new(File::open("./input.txt").unwrap());
-    let memory = read_intcode_input(file).expect("Invalid puzzle input");
-    let mut machine = IntCodeMachine::new(memory.clone());
-    let output = machine.execute(vec![1]).unwrap();
-    println!("Puzzle 1 - {:?}", output);
-
-    let mut machine = IntCodeMachine::new(memory.clone());
-    let output = machine.execute(vec![5]).unwrap();
-    println!("Puzzle 2 - {:?}", output);
+    print_answers(&Day5, &read_stdin(), timing_requested());
 }
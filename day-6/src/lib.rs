@@ -0,0 +1,116 @@
+use aoc_utils::input::{lines_of, ParseError};
+use aoc_utils::interner::Interner;
+use aoc_utils::solution::{or_no_solution, Puzzle};
+use graph::{Graph, NodeId, Tree};
+use std::str::FromStr;
+
+struct Orbit {
+    orbited: String,
+    orbits: String,
+}
+
+impl FromStr for Orbit {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut split = s.split(')');
+        match (split.next(), split.next(), split.next()) {
+            (Some(orbited), Some(orbits), None) if !orbited.is_empty() && !orbits.is_empty() => {
+                Ok(Self {
+                    orbited: orbited.to_owned(),
+                    orbits: orbits.to_owned(),
+                })
+            }
+            _ => Err("expected two objects separated by ')'"),
+        }
+    }
+}
+
+pub struct OrbitMap {
+    names: Interner,
+    // Every object points to the objects orbiting it
+    graph: Graph,
+}
+
+impl OrbitMap {
+    fn id(&self, name: &str) -> Option<NodeId> {
+        self.names.get(name).map(|id| id as NodeId)
+    }
+
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut map = OrbitMap {
+            names: Interner::new(),
+            graph: Graph::directed(),
+        };
+        for orbit in lines_of::<Orbit>(input)? {
+            map.graph
+                .add_named_edge(&mut map.names, &orbit.orbited, &orbit.orbits);
+        }
+        Ok(map)
+    }
+
+    /// The orbits as a tree rooted at COM.
+    pub fn tree(&self) -> Result<Tree, &'static str> {
+        let root = self.id("COM").ok_or("COM is missing from the map")?;
+        Tree::from_graph(&self.graph, root).map_err(|_| "An object orbits more than one object")
+    }
+}
+
+// Every object orbits its parent directly and all of the parent's ancestors
+// indirectly, so its number of orbits is its depth in the tree
+fn orbit_count_checksum(tree: &Tree) -> usize {
+    tree.depths().map(|(_, depth)| depth).sum()
+}
+
+// Transfers go between the objects YOU and SAN are orbiting
+fn jumps_to_santa(map: &OrbitMap, tree: &Tree) -> Option<usize> {
+    let you = tree.parent(map.id("YOU")?)?;
+    let santa = tree.parent(map.id("SAN")?)?;
+    tree.distance(you, santa)
+}
+
+pub struct Day6;
+
+impl Puzzle for Day6 {
+    type Input = (OrbitMap, Tree);
+
+    fn parse(&self, input: &str) -> Result<(OrbitMap, Tree), String> {
+        let map = OrbitMap::parse(input).map_err(|error| error.to_string())?;
+        let tree = map.tree().map_err(|error| error.to_string())?;
+        Ok((map, tree))
+    }
+
+    fn part1(&self, (_, tree): &(OrbitMap, Tree)) -> Result<String, String> {
+        Ok(orbit_count_checksum(tree).to_string())
+    }
+
+    fn part2(&self, (map, tree): &(OrbitMap, Tree)) -> Result<String, String> {
+        Ok(or_no_solution(jumps_to_santa(map, tree)))
+    }
+}
+
+aoc_utils::register_solution!(6, Day6);
+
+#[cfg(test)]
+mod test {
+    use super::{jumps_to_santa, orbit_count_checksum, Day6, OrbitMap};
+
+    aoc_utils::example_tests!(Day6, {
+        part1_orbits: part1("orbits.txt") == "42",
+        part2_transfers: part2("transfers.txt") == "4",
+    });
+
+    #[test]
+    fn test_case_1() {
+        let input = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L";
+        let map = OrbitMap::parse(input).unwrap();
+        assert_eq!(orbit_count_checksum(&map.tree().unwrap()), 42);
+    }
+
+    #[test]
+    fn test_case_2() {
+        let input = "COM)B\nB)C\nC)D\nD)E\nE)F\nB)G\nG)H\nD)I\nE)J\nJ)K\nK)L\nK)YOU\nI)SAN";
+        let map = OrbitMap::parse(input).unwrap();
+        assert_eq!(jumps_to_santa(&map, &map.tree().unwrap()), Some(4));
+    }
+}
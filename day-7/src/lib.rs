@@ -0,0 +1,67 @@
+use aoc_utils::Puzzle;
+use intcode::amplifier::max_thruster_signal;
+use intcode::read_intcode_input;
+
+pub struct Day7;
+
+impl Puzzle for Day7 {
+    type Input = Vec<isize>;
+
+    fn parse(&self, input: &str) -> Result<Vec<isize>, String> {
+        read_intcode_input(input.as_bytes()).map_err(|error| error.to_string())
+    }
+
+    fn part1(&self, memory: &Vec<isize>) -> Result<String, String> {
+        max_thruster_signal(memory, &[0, 1, 2, 3, 4], false)
+            .map(|signal| signal.to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    fn part2(&self, memory: &Vec<isize>) -> Result<String, String> {
+        max_thruster_signal(memory, &[5, 6, 7, 8, 9], true)
+            .map(|signal| signal.to_string())
+            .map_err(|error| error.to_string())
+    }
+}
+
+aoc_utils::register_solution!(7, Day7);
+
+#[cfg(test)]
+mod test {
+    use intcode::amplifier::max_thruster_signal;
+
+    #[test]
+    fn test_case_1() {
+        let program = vec![
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+        assert_eq!(
+            max_thruster_signal(&program, &[0, 1, 2, 3, 4], false).unwrap(),
+            43210
+        );
+    }
+
+    #[test]
+    fn test_case_2() {
+        let program = vec![
+            3, 23, 3, 24, 1002, 24, 10, 24, 1002, 23, -1, 23, 101, 5, 23, 23, 1, 24, 23, 23, 4, 23,
+            99, 0, 0,
+        ];
+        assert_eq!(
+            max_thruster_signal(&program, &[0, 1, 2, 3, 4], false).unwrap(),
+            54321
+        );
+    }
+
+    #[test]
+    fn test_case_3() {
+        let program = vec![
+            3, 31, 3, 32, 1002, 32, 10, 32, 1001, 31, -2, 31, 1007, 31, 0, 33, 1002, 33, 7, 33, 1,
+            33, 31, 31, 1, 32, 31, 31, 4, 31, 99, 0, 0, 0,
+        ];
+        assert_eq!(
+            max_thruster_signal(&program, &[0, 1, 2, 3, 4], false).unwrap(),
+            65210
+        );
+    }
+}
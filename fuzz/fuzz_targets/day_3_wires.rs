@@ -0,0 +1,12 @@
+//! Any input either parses into two wires or fails with an error pointing
+//! inside it, or just past its last line.
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    if let Err(error) = day_3::parse_wires(input) {
+        assert!(error.line >= 1 && error.line <= input.lines().count() + 1);
+        assert!(error.column >= 1);
+    }
+});
@@ -0,0 +1,18 @@
+//! Any input either parses into a map or fails with an error pointing at
+//! one of its lines, and a map either forms a tree or says why not.
+
+#![no_main]
+use day_6::OrbitMap;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    match OrbitMap::parse(input) {
+        Ok(map) => {
+            let _ = map.tree();
+        }
+        Err(error) => {
+            assert!(error.line >= 1 && error.line <= input.lines().count());
+            assert!(error.column >= 1);
+        }
+    }
+});
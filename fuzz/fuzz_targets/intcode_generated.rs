@@ -0,0 +1,40 @@
+//! Generated programs halt within their bound, and print the same thing
+//! whether their input comes all at once or one value per run, and whether
+//! checkpoints are taken or not.
+
+#![no_main]
+use intcode::generate::Generator;
+use intcode::{IntCodeMachine, Machine};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: (u64, Vec<i32>)| {
+    let (seed, input) = data;
+    let generated = Generator::new(seed).generate();
+    let mut input: Vec<isize> = input
+        .into_iter()
+        .map(|value| value as isize % 1_000_000)
+        .collect();
+    input.resize(generated.max_inputs as usize, 0);
+
+    let mut machine = IntCodeMachine::new(generated.program.clone());
+    let output = machine
+        .execute_limited(input.iter().copied(), generated.max_steps)
+        .unwrap();
+    assert!(machine.finished());
+
+    let mut stepped = IntCodeMachine::new(generated.program.clone());
+    let mut stepped_output = stepped.execute(Vec::new()).unwrap();
+    for value in input.iter() {
+        if stepped.finished() {
+            break;
+        }
+        stepped_output.extend(stepped.execute(vec![*value]).unwrap());
+    }
+    assert!(stepped.finished());
+    assert_eq!(stepped_output, output);
+
+    let mut checkpointed = IntCodeMachine::new(generated.program);
+    checkpointed.enable_checkpoints(3, 4);
+    assert_eq!(checkpointed.execute(input).unwrap(), output);
+    assert_eq!(checkpointed.executed(), machine.executed());
+});
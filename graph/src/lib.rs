@@ -0,0 +1,375 @@
+use aoc_utils::interner::Interner;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt;
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphError {
+    Cycle,
+    NotATree,
+    UndirectedGraph,
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::Cycle => write!(f, "Graph has a cycle"),
+            GraphError::NotATree => write!(
+                f,
+                "A node can be reached from the root in more than one way"
+            ),
+            GraphError::UndirectedGraph => write!(f, "Operation needs a directed graph"),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// A graph stored as adjacency lists. Nodes are identified by their index,
+/// and adding an edge to a node that doesn't exist yet creates every node up
+/// to it.
+#[derive(Debug, Clone)]
+pub struct Graph {
+    edges: Vec<Vec<NodeId>>,
+    directed: bool,
+    edge_count: usize,
+}
+
+impl Graph {
+    pub fn directed() -> Self {
+        Self {
+            edges: Vec::new(),
+            directed: true,
+            edge_count: 0,
+        }
+    }
+
+    pub fn undirected() -> Self {
+        Self {
+            directed: false,
+            ..Self::directed()
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    pub fn add_node(&mut self) -> NodeId {
+        self.edges.push(Vec::new());
+        self.edges.len() - 1
+    }
+
+    fn ensure_node(&mut self, node: NodeId) {
+        if node >= self.edges.len() {
+            self.edges.resize_with(node + 1, Vec::new);
+        }
+    }
+
+    /// Adds an edge, in both directions for undirected graphs.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId) {
+        self.ensure_node(from.max(to));
+        self.edges[from].push(to);
+        if !self.directed && from != to {
+            self.edges[to].push(from);
+        }
+        self.edge_count += 1;
+    }
+
+    /// Adds an edge between two named nodes, using `names` to turn the names
+    /// into node IDs.
+    pub fn add_named_edge(&mut self, names: &mut Interner, from: &str, to: &str) {
+        let from = names.intern(from) as NodeId;
+        let to = names.intern(to) as NodeId;
+        self.add_edge(from, to);
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    pub fn nodes(&self) -> std::ops::Range<NodeId> {
+        0..self.edges.len()
+    }
+
+    pub fn neighbours(&self, node: NodeId) -> &[NodeId] {
+        self.edges.get(node).map_or(&[], Vec::as_slice)
+    }
+
+    /// Number of edges arriving at every node.
+    pub fn in_degrees(&self) -> Vec<usize> {
+        let mut degrees = vec![0; self.len()];
+        for node in self.edges.iter().flatten() {
+            degrees[*node] += 1;
+        }
+        degrees
+    }
+
+    /// The same graph with every edge pointing the other way.
+    pub fn reversed(&self) -> Graph {
+        let mut reversed = Graph {
+            edges: vec![Vec::new(); self.len()],
+            ..self.clone()
+        };
+        for (from, targets) in self.edges.iter().enumerate() {
+            for to in targets {
+                reversed.edges[*to].push(from);
+            }
+        }
+        reversed
+    }
+
+    /// Number of edges on the shortest path from `start` to every node, or
+    /// `None` for the nodes it can't reach.
+    pub fn distances(&self, start: NodeId) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.len()];
+        let mut queue = VecDeque::new();
+        if start < self.len() {
+            distances[start] = Some(0);
+            queue.push_back(start);
+        }
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node].unwrap();
+            for next in self.neighbours(node) {
+                if distances[*next].is_none() {
+                    distances[*next] = Some(distance + 1);
+                    queue.push_back(*next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Orders the nodes so every edge goes from an earlier node to a later
+    /// one. Among the nodes that are free to go next, the lowest ID goes
+    /// first, so the order is always the same.
+    pub fn topological_sort(&self) -> Result<Vec<NodeId>, GraphError> {
+        if !self.directed {
+            return Err(GraphError::UndirectedGraph);
+        }
+        let mut degrees = self.in_degrees();
+        let mut ready: BinaryHeap<Reverse<NodeId>> = self
+            .nodes()
+            .filter(|node| degrees[*node] == 0)
+            .map(Reverse)
+            .collect();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(Reverse(node)) = ready.pop() {
+            order.push(node);
+            for next in self.neighbours(node) {
+                degrees[*next] -= 1;
+                if degrees[*next] == 0 {
+                    ready.push(Reverse(*next));
+                }
+            }
+        }
+        if order.len() == self.len() {
+            Ok(order)
+        } else {
+            Err(GraphError::Cycle)
+        }
+    }
+}
+
+/// A rooted tree, built from the edges of a graph reachable from the root.
+/// Answers depth and common ancestor questions.
+#[derive(Debug, Clone)]
+pub struct Tree {
+    root: NodeId,
+    parents: Vec<Option<NodeId>>,
+    depths: Vec<Option<usize>>,
+}
+
+impl Tree {
+    /// Follows the edges out of `root`. For undirected graphs the edge back
+    /// to the parent is ignored. Fails if any node can be reached twice.
+    pub fn from_graph(graph: &Graph, root: NodeId) -> Result<Tree, GraphError> {
+        let mut parents = vec![None; graph.len()];
+        let mut depths = vec![None; graph.len()];
+        let mut queue = VecDeque::new();
+        if root < graph.len() {
+            depths[root] = Some(0);
+            queue.push_back(root);
+        }
+        while let Some(node) = queue.pop_front() {
+            let depth = depths[node].unwrap();
+            let mut skipped_parent = false;
+            for next in graph.neighbours(node) {
+                if !graph.is_directed() && Some(*next) == parents[node] && !skipped_parent {
+                    skipped_parent = true;
+                    continue;
+                }
+                if depths[*next].is_some() {
+                    return Err(GraphError::NotATree);
+                }
+                parents[*next] = Some(node);
+                depths[*next] = Some(depth + 1);
+                queue.push_back(*next);
+            }
+        }
+        Ok(Tree {
+            root,
+            parents,
+            depths,
+        })
+    }
+
+    pub fn root(&self) -> NodeId {
+        self.root
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.parents.get(node).copied().flatten()
+    }
+
+    /// Edges between the root and the node, `None` if the node isn't part
+    /// of the tree.
+    pub fn depth(&self, node: NodeId) -> Option<usize> {
+        self.depths.get(node).copied().flatten()
+    }
+
+    /// Every node in the tree along with its depth.
+    pub fn depths(&self) -> impl Iterator<Item = (NodeId, usize)> + '_ {
+        self.depths
+            .iter()
+            .enumerate()
+            .filter_map(|(node, depth)| depth.map(|depth| (node, depth)))
+    }
+
+    /// The parent, grandparent and so on up to the root.
+    pub fn ancestors(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(node), move |node| self.parent(*node))
+    }
+
+    /// The deepest node that has both `a` and `b` below it, counting each
+    /// node as being below itself.
+    pub fn lowest_common_ancestor(&self, mut a: NodeId, mut b: NodeId) -> Option<NodeId> {
+        let (mut depth_a, mut depth_b) = (self.depth(a)?, self.depth(b)?);
+        while depth_a > depth_b {
+            a = self.parent(a)?;
+            depth_a -= 1;
+        }
+        while depth_b > depth_a {
+            b = self.parent(b)?;
+            depth_b -= 1;
+        }
+        while a != b {
+            a = self.parent(a)?;
+            b = self.parent(b)?;
+        }
+        Some(a)
+    }
+
+    /// Number of edges on the path between two nodes of the tree.
+    pub fn distance(&self, a: NodeId, b: NodeId) -> Option<usize> {
+        let ancestor = self.lowest_common_ancestor(a, b)?;
+        let ancestor_depth = self.depth(ancestor)?;
+        Some(self.depth(a)? + self.depth(b)? - 2 * ancestor_depth)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Graph, GraphError, Interner, Tree};
+
+    //       0
+    //      / \
+    //     1   2
+    //    / \   \
+    //   3   4   5
+    //           |
+    //           6
+    fn tree_edges() -> Vec<(usize, usize)> {
+        vec![(0, 1), (0, 2), (1, 3), (1, 4), (2, 5), (5, 6)]
+    }
+
+    #[test]
+    fn test_undirected_edges() {
+        let mut graph = Graph::undirected();
+        graph.add_edge(0, 3);
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.neighbours(3), [0]);
+        assert_eq!(graph.distances(3), [Some(1), None, None, Some(0)]);
+    }
+
+    #[test]
+    fn test_named_edges() {
+        let mut names = Interner::new();
+        let mut graph = Graph::directed();
+        graph.add_named_edge(&mut names, "COM", "B");
+        graph.add_named_edge(&mut names, "B", "C");
+        graph.add_named_edge(&mut names, "COM", "D");
+        let com = names.get("COM").unwrap() as usize;
+        assert_eq!(graph.len(), 4);
+        assert_eq!(graph.neighbours(com).len(), 2);
+        assert_eq!(names.name(graph.neighbours(1)[0] as u32), Some("C"));
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let mut graph = Graph::directed();
+        for (from, to) in [(3, 1), (1, 0), (2, 0), (3, 2)].iter() {
+            graph.add_edge(*from, *to);
+        }
+        assert_eq!(graph.topological_sort(), Ok(vec![3, 1, 2, 0]));
+        graph.add_edge(0, 3);
+        assert_eq!(graph.topological_sort(), Err(GraphError::Cycle));
+    }
+
+    #[test]
+    fn test_reversed() {
+        let mut graph = Graph::directed();
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        let reversed = graph.reversed();
+        assert_eq!(reversed.neighbours(0), [] as [usize; 0]);
+        assert_eq!(reversed.neighbours(2), [0]);
+        assert_eq!(reversed.in_degrees(), [2, 0, 0]);
+    }
+
+    #[test]
+    fn test_tree() {
+        for directed in [true, false].iter() {
+            let mut graph = if *directed {
+                Graph::directed()
+            } else {
+                Graph::undirected()
+            };
+            for (from, to) in tree_edges() {
+                graph.add_edge(from, to);
+            }
+            let tree = Tree::from_graph(&graph, 0).unwrap();
+            assert_eq!(tree.depth(6), Some(3));
+            assert_eq!(tree.depths().map(|(_, depth)| depth).sum::<usize>(), 11);
+            assert_eq!(tree.ancestors(6).collect::<Vec<_>>(), [5, 2, 0]);
+            assert_eq!(tree.lowest_common_ancestor(3, 4), Some(1));
+            assert_eq!(tree.lowest_common_ancestor(3, 6), Some(0));
+            assert_eq!(tree.lowest_common_ancestor(5, 6), Some(5));
+            assert_eq!(tree.distance(4, 6), Some(5));
+        }
+    }
+
+    #[test]
+    fn test_not_a_tree() {
+        let mut graph = Graph::directed();
+        for (from, to) in tree_edges() {
+            graph.add_edge(from, to);
+        }
+        graph.add_edge(4, 6);
+        assert_eq!(
+            Tree::from_graph(&graph, 0).unwrap_err(),
+            GraphError::NotATree
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Programs with known inputs, outputs and final memory, to check that a
+//! machine runs intcode the way the puzzles expect. Anything implementing
+//! [`Machine`] can be checked, so other backends can prove they behave like
+//! the interpreter:
+//!
+//! ```ignore
+//! #[test]
+//! fn test_conformance() {
+//!     intcode_conformance::assert_conforms::<MyMachine>();
+//! }
+//! ```
+
+use intcode::programs::{
+    COMPARE_TO_8, COUNTDOWN, EQUAL_TO_8_IMMEDIATE, EQUAL_TO_8_POSITION, IS_NONZERO_IMMEDIATE,
+    IS_NONZERO_POSITION, LARGE_NUMBER, LESS_THAN_8_IMMEDIATE, LESS_THAN_8_POSITION, QUINE,
+    SIXTEEN_DIGITS,
+};
+use intcode::{IntCodeMachine, Machine};
+
+type Value = isize;
+
+/// A machine that can be checked.
+pub trait Subject: Machine + Sized {
+    fn load(program: &[Value]) -> Self;
+
+    /// The machine's memory, for the cases that check it. Backends that
+    /// can't show their memory skip those checks.
+    fn memory(&self) -> Option<Vec<Value>> {
+        None
+    }
+}
+
+impl Subject for IntCodeMachine {
+    fn load(program: &[Value]) -> Self {
+        IntCodeMachine::new(program.to_vec())
+    }
+
+    fn memory(&self) -> Option<Vec<Value>> {
+        Some(IntCodeMachine::memory(self).to_vec())
+    }
+}
+
+/// How the last run of a case ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    Halted,
+    WaitingForInput,
+    /// With an error, whichever it is.
+    Failed,
+}
+
+pub struct Case {
+    pub name: &'static str,
+    pub program: &'static [Value],
+    /// The input of every call to `execute`, with what it should print.
+    /// The output of a run that fails isn't checked.
+    pub runs: &'static [(&'static [Value], &'static [Value])],
+    pub end: End,
+    /// Cells expected in memory at the end. Memory past the end of the
+    /// program is zero until written.
+    pub memory: &'static [(usize, Value)],
+}
+
+pub const CASES: &[Case] = &[
+    Case {
+        name: "add",
+        program: &[1, 0, 0, 0, 99],
+        runs: &[(&[], &[])],
+        end: End::Halted,
+        memory: &[(0, 2)],
+    },
+    Case {
+        name: "multiply",
+        program: &[2, 3, 0, 3, 99],
+        runs: &[(&[], &[])],
+        end: End::Halted,
+        memory: &[(3, 6)],
+    },
+    Case {
+        name: "multiply past the halt",
+        program: &[2, 4, 4, 5, 99, 0],
+        runs: &[(&[], &[])],
+        end: End::Halted,
+        memory: &[(5, 9801)],
+    },
+    Case {
+        name: "overwrite the halt",
+        program: &[1, 1, 1, 4, 99, 5, 6, 0, 99],
+        runs: &[(&[], &[])],
+        end: End::Halted,
+        memory: &[(0, 30), (4, 2)],
+    },
+    Case {
+        name: "immediate mode",
+        program: &[1002, 4, 3, 4, 33],
+        runs: &[(&[], &[])],
+        end: End::Halted,
+        memory: &[(4, 99)],
+    },
+    Case {
+        name: "negative immediate",
+        program: &[1101, 100, -1, 4, 0],
+        runs: &[(&[], &[])],
+        end: End::Halted,
+        memory: &[(4, 99)],
+    },
+    Case {
+        name: "echo",
+        program: &[3, 0, 4, 0, 99],
+        runs: &[(&[42], &[42])],
+        end: End::Halted,
+        memory: &[(0, 42)],
+    },
+    Case {
+        name: "equal, position mode",
+        program: &EQUAL_TO_8_POSITION,
+        runs: &[(&[8], &[1])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "less than, position mode",
+        program: &LESS_THAN_8_POSITION,
+        runs: &[(&[9], &[0])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "equal, immediate mode",
+        program: &EQUAL_TO_8_IMMEDIATE,
+        runs: &[(&[7], &[0])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "less than, immediate mode",
+        program: &LESS_THAN_8_IMMEDIATE,
+        runs: &[(&[-3], &[1])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "jump, position mode",
+        program: &IS_NONZERO_POSITION,
+        runs: &[(&[0], &[0])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "jump, immediate mode",
+        program: &IS_NONZERO_IMMEDIATE,
+        runs: &[(&[5], &[1])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "compare below",
+        program: &COMPARE_TO_8,
+        runs: &[(&[7], &[999])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "compare equal",
+        program: &COMPARE_TO_8,
+        runs: &[(&[8], &[1000])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "compare above",
+        program: &COMPARE_TO_8,
+        runs: &[(&[9], &[1001])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "quine",
+        program: &QUINE,
+        runs: &[(&[], &QUINE)],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "sixteen digits",
+        program: &SIXTEEN_DIGITS,
+        runs: &[(&[], &[1_219_070_632_396_864])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "large number",
+        program: &LARGE_NUMBER,
+        runs: &[(&[], &[1_125_899_906_842_624])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "relative write past the program",
+        program: &[109, 7, 203, 0, 204, 0, 99],
+        runs: &[(&[5], &[5])],
+        end: End::Halted,
+        memory: &[(7, 5)],
+    },
+    Case {
+        name: "wait for input",
+        program: &[3, 0, 99],
+        runs: &[(&[], &[])],
+        end: End::WaitingForInput,
+        memory: &[],
+    },
+    Case {
+        name: "resume with input",
+        program: &COUNTDOWN,
+        runs: &[(&[], &[]), (&[3], &[6])],
+        end: End::Halted,
+        memory: &[(19, 6), (20, 0)],
+    },
+    Case {
+        name: "input split between runs",
+        program: &[3, 0, 3, 1, 1, 0, 1, 2, 4, 2, 99],
+        runs: &[(&[2], &[]), (&[3], &[5])],
+        end: End::Halted,
+        memory: &[],
+    },
+    Case {
+        name: "invalid op code",
+        program: &[98, 0, 0, 0, 99],
+        runs: &[(&[], &[])],
+        end: End::Failed,
+        memory: &[],
+    },
+    Case {
+        name: "invalid parameter mode",
+        program: &[301, 0, 0, 0, 99],
+        runs: &[(&[], &[])],
+        end: End::Failed,
+        memory: &[],
+    },
+    Case {
+        name: "write in immediate mode",
+        program: &[11101, 1, 1, 3, 99],
+        runs: &[(&[], &[])],
+        end: End::Failed,
+        memory: &[],
+    },
+    Case {
+        name: "negative address",
+        program: &[1, -1, 0, 0, 99],
+        runs: &[(&[], &[])],
+        end: End::Failed,
+        memory: &[],
+    },
+];
+
+/// Runs a case on a fresh machine, describing the first thing that didn't
+/// go as expected.
+pub fn check<S: Subject>(case: &Case) -> Result<(), String> {
+    let mut machine = S::load(case.program);
+    let mut failed = None;
+    for (index, (input, expected)) in case.runs.iter().enumerate() {
+        let last = index + 1 == case.runs.len();
+        if index > 0 && machine.finished() {
+            return Err(format!("halted before run {}", index + 1));
+        }
+        match machine.execute(input.to_vec()) {
+            Ok(output) if output != *expected => {
+                return Err(format!(
+                    "run {} printed {:?}, expected {:?}",
+                    index + 1,
+                    output,
+                    expected
+                ))
+            }
+            Ok(_) => {}
+            Err(error) if last && case.end == End::Failed => failed = Some(error),
+            Err(error) => return Err(format!("run {} failed: {}", index + 1, error)),
+        }
+    }
+
+    let end = match failed {
+        Some(_) => End::Failed,
+        None if machine.finished() => End::Halted,
+        None => End::WaitingForInput,
+    };
+    if end != case.end {
+        return Err(format!("ended {:?}, expected {:?}", end, case.end));
+    }
+    if let Some(memory) = machine.memory() {
+        for &(address, expected) in case.memory {
+            let value = memory.get(address).copied().unwrap_or(0);
+            if value != expected {
+                return Err(format!(
+                    "memory at {} is {}, expected {}",
+                    address, value, expected
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The cases a machine fails, with what went wrong.
+pub fn failures<S: Subject>() -> Vec<(&'static str, String)> {
+    CASES
+        .iter()
+        .filter_map(|case| check::<S>(case).err().map(|error| (case.name, error)))
+        .collect()
+}
+
+/// Panics with every case the machine fails, for a backend's tests.
+pub fn assert_conforms<S: Subject>() {
+    let failures = failures::<S>();
+    if !failures.is_empty() {
+        let lines: Vec<String> = failures
+            .iter()
+            .map(|(name, error)| format!("{}: {}", name, error))
+            .collect();
+        panic!(
+            "{} of {} cases failed:\n{}",
+            failures.len(),
+            CASES.len(),
+            lines.join("\n")
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{assert_conforms, check, failures, Subject, CASES};
+    use intcode::{IntCodeError, IntCodeMachine, Machine};
+
+    #[test]
+    fn test_interpreter_conforms() {
+        assert_conforms::<IntCodeMachine>();
+    }
+
+    // Loses the last value of every run, and can't show its memory
+    struct Lossy(IntCodeMachine);
+
+    impl Machine for Lossy {
+        fn execute(&mut self, input: Vec<isize>) -> Result<Vec<isize>, IntCodeError> {
+            let mut output = self.0.execute(input)?;
+            output.pop();
+            Ok(output)
+        }
+
+        fn finished(&self) -> bool {
+            self.0.finished()
+        }
+    }
+
+    impl Subject for Lossy {
+        fn load(program: &[isize]) -> Self {
+            Lossy(IntCodeMachine::new(program.to_vec()))
+        }
+    }
+
+    #[test]
+    fn test_failures() {
+        let failed: Vec<&str> = failures::<Lossy>().iter().map(|(name, _)| *name).collect();
+        let printing: Vec<&str> = CASES
+            .iter()
+            .filter(|case| case.runs.iter().any(|(_, output)| !output.is_empty()))
+            .map(|case| case.name)
+            .collect();
+        assert_eq!(failed, printing);
+
+        let echo = CASES.iter().find(|case| case.name == "echo").unwrap();
+        assert_eq!(
+            check::<Lossy>(echo),
+            Err("run 1 printed [], expected [42]".to_string())
+        );
+    }
+}
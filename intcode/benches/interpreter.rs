@@ -0,0 +1,27 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use intcode::amplifier::max_thruster_signal;
+use intcode::programs::{run, COUNTDOWN, QUINE};
+
+// Day 7's feedback loop example
+const FEEDBACK_LOOP: [isize; 29] = [
+    3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28, 1005,
+    28, 6, 99, 0, 0, 5,
+];
+
+fn interpreter(c: &mut Criterion) {
+    assert_eq!(run(&QUINE, vec![]).unwrap(), QUINE);
+    assert_eq!(run(&COUNTDOWN, vec![100]).unwrap(), [5050]);
+
+    c.bench_function("quine", |b| {
+        b.iter(|| run(black_box(&QUINE), vec![]).unwrap())
+    });
+    c.bench_function("countdown 100k", |b| {
+        b.iter(|| run(black_box(&COUNTDOWN), vec![100_000]).unwrap())
+    });
+    c.bench_function("amplifier feedback loop", |b| {
+        b.iter(|| max_thruster_signal(black_box(&FEEDBACK_LOOP), &[5, 6, 7, 8, 9], true).unwrap())
+    });
+}
+
+criterion_group!(benches, interpreter);
+criterion_main!(benches);
@@ -0,0 +1,81 @@
+//! Day 7's amplifiers: copies of one program chained together, each started
+//! with a phase setting and fed the output of the one before it.
+
+use crate::{IntCodeError, IntCodeMachine, Machine, Result, Value};
+use aoc_utils::combinatorics::permutations;
+
+/// The signal that reaches the thrusters with the amplifiers set to
+/// `phases`, in order, starting from a signal of 0. With `feedback`, the
+/// last amplifier's output goes back into the first one until they halt,
+/// otherwise the signal goes through the chain once.
+pub fn thruster_signal(program: &[Value], phases: &[Value], feedback: bool) -> Result<Value> {
+    let mut amplifiers = phases
+        .iter()
+        .map(|&phase| {
+            let mut amplifier = IntCodeMachine::new(program.to_vec());
+            amplifier.execute([phase])?;
+            Ok(amplifier)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut signal = vec![0];
+    loop {
+        for amplifier in amplifiers.iter_mut() {
+            signal = amplifier.execute(signal)?;
+        }
+        if !feedback || amplifiers.last().is_none_or(|last| last.finished()) {
+            break;
+        }
+    }
+    signal.last().copied().ok_or(IntCodeError::InputError)
+}
+
+/// The highest signal [`thruster_signal`] gets out of any ordering of
+/// `phases`.
+pub fn max_thruster_signal(program: &[Value], phases: &[Value], feedback: bool) -> Result<Value> {
+    let mut max = None;
+    for permutation in permutations(phases) {
+        let signal = thruster_signal(program, &permutation, feedback)?;
+        max = Some(max.map_or(signal, |max: Value| max.max(signal)));
+    }
+    max.ok_or(IntCodeError::InputError)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{max_thruster_signal, thruster_signal};
+
+    const FEEDBACK_LOOP: [isize; 29] = [
+        3, 26, 1001, 26, -4, 26, 3, 27, 1002, 27, 2, 27, 1, 27, 26, 27, 4, 27, 1001, 28, -1, 28,
+        1005, 28, 6, 99, 0, 0, 5,
+    ];
+
+    #[test]
+    fn test_serial() {
+        let program = [
+            3, 15, 3, 16, 1002, 16, 10, 16, 1, 16, 15, 15, 4, 15, 99, 0, 0,
+        ];
+        assert_eq!(
+            thruster_signal(&program, &[4, 3, 2, 1, 0], false).unwrap(),
+            43210
+        );
+        assert_eq!(
+            max_thruster_signal(&program, &[0, 1, 2, 3, 4], false).unwrap(),
+            43210
+        );
+    }
+
+    #[test]
+    fn test_feedback() {
+        assert_eq!(
+            thruster_signal(&FEEDBACK_LOOP, &[9, 8, 7, 6, 5], true).unwrap(),
+            139629729
+        );
+        assert_eq!(
+            max_thruster_signal(&FEEDBACK_LOOP, &[5, 6, 7, 8, 9], true).unwrap(),
+            139629729
+        );
+        // Without feedback the signal only goes around once
+        assert!(thruster_signal(&FEEDBACK_LOOP, &[9, 8, 7, 6, 5], false).unwrap() < 139629729);
+    }
+}
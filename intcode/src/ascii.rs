@@ -0,0 +1,107 @@
+//! Talking to programs that read and print ASCII text, like the springdroid
+//! of day 21 and the droid of day 25. Output is buffered until the program
+//! prints its prompt, so a driver knows when it's being asked for input.
+
+use crate::{Machine, Result, Value};
+
+/// Encodes a line of text as input, ending it with a newline.
+pub fn encode_line(line: &str) -> Vec<Value> {
+    line.bytes().chain(Some(b'\n')).map(Value::from).collect()
+}
+
+/// Buffers the text a program prints until it prints `prompt`. Values that
+/// aren't ASCII, like day 21's hull damage, are kept apart.
+#[derive(Debug, Clone)]
+pub struct PromptReader {
+    prompt: String,
+    text: String,
+    other: Vec<Value>,
+}
+
+impl PromptReader {
+    pub fn new(prompt: &str) -> Self {
+        Self {
+            prompt: prompt.to_string(),
+            text: String::new(),
+            other: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, output: &[Value]) {
+        for &value in output {
+            match value {
+                0..=127 => self.text.push(value as u8 as char),
+                _ => self.other.push(value),
+            }
+        }
+    }
+
+    /// Whether the prompt was printed since the text was last taken.
+    pub fn prompted(&self) -> bool {
+        self.text.contains(&self.prompt)
+    }
+
+    /// The text up to and including the first prompt, if it was printed.
+    /// Anything printed after the prompt stays buffered.
+    pub fn take(&mut self) -> Option<String> {
+        let end = self.text.find(&self.prompt)? + self.prompt.len();
+        Some(self.text.drain(..end).collect())
+    }
+
+    /// All the buffered text, prompt or not.
+    pub fn flush(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+
+    /// The values that weren't ASCII, in the order they were printed.
+    pub fn take_other(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.other)
+    }
+
+    /// Sends `line` to the machine, or nothing when it's empty, and runs it
+    /// until it stops. Returns the text through the prompt, or `None` if the
+    /// machine stopped without prompting.
+    pub fn send<M: Machine>(&mut self, machine: &mut M, line: &str) -> Result<Option<String>> {
+        let input = if line.is_empty() {
+            Vec::new()
+        } else {
+            encode_line(line)
+        };
+        let output = machine.execute(input)?;
+        self.push(&output);
+        Ok(self.take())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode_line, PromptReader};
+    use crate::IntCodeMachine;
+
+    #[test]
+    fn test_take() {
+        let mut reader = PromptReader::new("Command?\n");
+        reader.push(&encode_line("You see a door"));
+        assert!(!reader.prompted());
+        assert_eq!(reader.take(), None);
+
+        reader.push(&encode_line("Command?"));
+        reader.push(&[9000, 62]);
+        assert!(reader.prompted());
+        assert_eq!(reader.take().as_deref(), Some("You see a door\nCommand?\n"));
+        assert!(!reader.prompted());
+        assert_eq!(reader.flush(), ">");
+        assert_eq!(reader.take_other(), [9000]);
+    }
+
+    #[test]
+    fn test_send() {
+        // Prints "?" and echoes one character, then halts
+        let program = vec![104, 63, 3, 100, 4, 100, 99];
+        let mut machine = IntCodeMachine::new(program);
+        let mut reader = PromptReader::new("?");
+        assert_eq!(reader.send(&mut machine, "").unwrap().as_deref(), Some("?"));
+        assert_eq!(reader.send(&mut machine, "x").unwrap(), None);
+        assert_eq!(reader.flush(), "x");
+    }
+}
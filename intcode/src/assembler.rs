@@ -0,0 +1,426 @@
+//! Assembles programs written the way the [disassembler](crate::disassemble)
+//! lists them, with labels, into modules the [linker](crate::linker) puts
+//! together.
+//!
+//! ```text
+//! # Prints the input plus one
+//! global main
+//! main:   in [value]
+//!         add [value], 1, [value]
+//!         out [value]
+//!         halt
+//! value:  data 0
+//! ```
+//!
+//! A label stands for its address, either as an immediate value or, in
+//! brackets, as the cell to read or write, and can have an offset like
+//! `table+2`. Relative parameters are written `[rb+3]`. `data` puts values
+//! straight into the program. Comments start with `#`.
+//!
+//! Labels are local to their module unless they are made `global`. Any name
+//! that the module doesn't define is taken from the global labels of the
+//! other modules when linking.
+
+use crate::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl AssembleError {
+    fn new(line: usize, message: impl fmt::Display) -> Self {
+        Self {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+/// A cell that holds the address of a label defined in another module,
+/// plus the offset already in the cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub cell: usize,
+    pub name: String,
+}
+
+/// An assembled source file. Addresses start at 0, and move to wherever
+/// the linker puts the module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub name: String,
+    pub code: Vec<Value>,
+    /// Every label defined in the module.
+    pub labels: BTreeMap<String, usize>,
+    /// The labels other modules can use.
+    pub globals: BTreeSet<String>,
+    /// Cells holding an address in the module, which have to move with it.
+    pub relocations: Vec<usize>,
+    pub imports: Vec<Import>,
+}
+
+// (op code, how many parameters, which parameter is written to)
+fn operation(mnemonic: &str) -> Option<(Value, usize, Option<usize>)> {
+    Some(match mnemonic {
+        "add" => (1, 3, Some(2)),
+        "mul" => (2, 3, Some(2)),
+        "in" => (3, 1, Some(0)),
+        "out" => (4, 1, None),
+        "jnz" => (5, 2, None),
+        "jz" => (6, 2, None),
+        "lt" => (7, 3, Some(2)),
+        "eq" => (8, 3, Some(2)),
+        "arb" => (9, 1, None),
+        "halt" => (99, 0, None),
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Word {
+    Number(Value),
+    Label(String, Value),
+}
+
+// The mode, as in the op code, and the value of a parameter
+fn parse_operand(text: &str) -> Result<(Value, Word), String> {
+    let text = text.trim();
+    match text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+    {
+        Some(inner) => {
+            let inner = inner.trim();
+            match inner.strip_prefix("rb") {
+                Some(offset) if !offset.starts_with(is_name_char) => {
+                    let offset = offset.trim();
+                    let offset = if offset.is_empty() {
+                        0
+                    } else if let Some(offset) = offset.strip_prefix('+') {
+                        parse_number(offset)?
+                    } else if let Some(offset) = offset.strip_prefix('-') {
+                        -parse_number(offset)?
+                    } else {
+                        return Err(format!("expected + or - after rb in {:?}", text));
+                    };
+                    Ok((2, Word::Number(offset)))
+                }
+                _ => Ok((0, parse_word(inner)?)),
+            }
+        }
+        None => Ok((1, parse_word(text)?)),
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn is_name(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && text.chars().all(is_name_char)
+}
+
+fn parse_number(text: &str) -> Result<Value, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("invalid number {:?}", text.trim()))
+}
+
+// A number, or a label with an optional offset
+fn parse_word(text: &str) -> Result<Word, String> {
+    let text = text.trim();
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return parse_number(text).map(Word::Number);
+    }
+    let (name, offset) = match text.find(['+', '-']) {
+        Some(sign) => {
+            let offset = parse_number(text[sign + 1..].trim())?;
+            let offset = if text[sign..].starts_with('-') {
+                -offset
+            } else {
+                offset
+            };
+            (text[..sign].trim(), offset)
+        }
+        None => (text, 0),
+    };
+    if !is_name(name) || name == "rb" {
+        return Err(format!("invalid label {:?}", name));
+    }
+    Ok(Word::Label(name.to_string(), offset))
+}
+
+/// Assembles a source file into a module to link.
+pub fn assemble_module(name: &str, source: &str) -> Result<Module, AssembleError> {
+    let mut code = Vec::new();
+    let mut labels = BTreeMap::new();
+    let mut globals = Vec::new();
+    // Cells to fill in once every label is known
+    let mut references: Vec<(usize, String, Value, usize)> = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        let error = |message: String| AssembleError::new(number, message);
+        let mut rest = line.split('#').next().unwrap_or("").trim();
+
+        while let Some(colon) = rest.find(':') {
+            let label = rest[..colon].trim();
+            if !is_name(label) || label == "rb" {
+                return Err(error(format!("invalid label {:?}", label)));
+            }
+            if labels.insert(label.to_string(), code.len()).is_some() {
+                return Err(error(format!("{} is already defined", label)));
+            }
+            rest = rest[colon + 1..].trim();
+        }
+        if rest.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operands) = match rest.split_once(char::is_whitespace) {
+            Some((mnemonic, operands)) => (mnemonic, operands.trim()),
+            None => (rest, ""),
+        };
+        let operands: Vec<&str> = if operands.is_empty() {
+            Vec::new()
+        } else {
+            operands.split(',').collect()
+        };
+        let mut words = Vec::new();
+        match mnemonic {
+            "global" => {
+                for name in operands {
+                    let name = name.trim();
+                    if !is_name(name) {
+                        return Err(error(format!("invalid label {:?}", name)));
+                    }
+                    globals.push((name.to_string(), number));
+                }
+                continue;
+            }
+            "data" => {
+                if operands.is_empty() {
+                    return Err(error("data needs at least one value".to_string()));
+                }
+                for operand in operands {
+                    words.push(parse_word(operand).map_err(error)?);
+                }
+            }
+            _ => {
+                let (op_code, count, written) = operation(mnemonic)
+                    .ok_or_else(|| error(format!("unknown instruction {}", mnemonic)))?;
+                if operands.len() != count {
+                    return Err(error(format!(
+                        "{} takes {} parameters, found {}",
+                        mnemonic,
+                        count,
+                        operands.len()
+                    )));
+                }
+                let mut modes = 0;
+                let mut parameters = Vec::new();
+                for (position, operand) in operands.iter().enumerate() {
+                    let (mode, word) = parse_operand(operand).map_err(error)?;
+                    if mode == 1 && written == Some(position) {
+                        return Err(error(format!(
+                            "{} can't write to the immediate value {}",
+                            mnemonic,
+                            operand.trim()
+                        )));
+                    }
+                    modes += mode * 10_isize.pow(position as u32 + 2);
+                    parameters.push(word);
+                }
+                words.push(Word::Number(op_code + modes));
+                words.extend(parameters);
+            }
+        }
+        for word in words {
+            match word {
+                Word::Number(value) => code.push(value),
+                Word::Label(label, offset) => {
+                    references.push((code.len(), label, offset, number));
+                    code.push(offset);
+                }
+            }
+        }
+    }
+
+    let mut relocations = Vec::new();
+    let mut imports = Vec::new();
+    for (cell, label, offset, _) in references {
+        match labels.get(&label) {
+            Some(address) => {
+                code[cell] = *address as Value + offset;
+                relocations.push(cell);
+            }
+            None => imports.push(Import { cell, name: label }),
+        }
+    }
+    let mut exported = BTreeSet::new();
+    for (name, line) in globals {
+        if !labels.contains_key(&name) {
+            return Err(AssembleError::new(
+                line,
+                format!("{} is global but never defined", name),
+            ));
+        }
+        exported.insert(name);
+    }
+
+    Ok(Module {
+        name: name.to_string(),
+        code,
+        labels,
+        globals: exported,
+        relocations,
+        imports,
+    })
+}
+
+/// Assembles a program that stands on its own, with no labels from other
+/// modules.
+pub fn assemble(source: &str) -> Result<Vec<Value>, AssembleError> {
+    let module = assemble_module("main", source)?;
+    match module.imports.first() {
+        Some(import) => {
+            let line = source
+                .lines()
+                .position(|line| line.split('#').next().unwrap_or("").contains(&import.name))
+                .map_or(1, |index| index + 1);
+            Err(AssembleError::new(
+                line,
+                format!("{} is never defined", import.name),
+            ))
+        }
+        None => Ok(module.code),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{assemble, assemble_module, AssembleError, Import};
+    use crate::disassemble::disassemble;
+    use crate::programs::COUNTDOWN;
+
+    #[test]
+    fn test_disassembly_round_trip() {
+        let source: Vec<String> = disassemble(&COUNTDOWN)
+            .iter()
+            .map(|instruction| instruction.text.clone())
+            .collect();
+        assert_eq!(assemble(&source.join("\n")).unwrap(), COUNTDOWN);
+    }
+
+    #[test]
+    fn test_labels() {
+        let source = "
+            # Adds one to the input
+            global main
+            main:   in [value]
+                    add [value], 1, [value]
+                    out [value]     # the answer
+                    jz 0, done
+            done:   halt
+            value:  data 0, value+1, [rb-2] # not an operand
+        ";
+        let error = assemble_module("plus_one", source).unwrap_err();
+        assert_eq!(error.line, 9);
+
+        let source = source.replace(", [rb-2] # not an operand", "");
+        let module = assemble_module("plus_one", &source).unwrap();
+        assert_eq!(
+            module.code,
+            [3, 12, 1001, 12, 1, 12, 4, 12, 1106, 0, 11, 99, 0, 13]
+        );
+        assert_eq!(module.labels["value"], 12);
+        assert!(module.globals.contains("main"));
+        assert_eq!(module.relocations, [1, 3, 5, 7, 10, 13]);
+        assert!(module.imports.is_empty());
+    }
+
+    #[test]
+    fn test_relative() {
+        let program = assemble(
+            "arb 10\nout [rb]\nout [rb+3]\nadd [rb-1], rb_value, [rb - 2]\nhalt\nrb_value: data 7",
+        )
+        .unwrap();
+        assert_eq!(program, [109, 10, 204, 0, 204, 3, 21201, -1, 11, -2, 99, 7]);
+        assert_eq!(
+            assemble("out [rb 3]"),
+            Err(AssembleError::new(
+                1,
+                "expected + or - after rb in \"[rb 3]\""
+            ))
+        );
+    }
+
+    #[test]
+    fn test_imports() {
+        let module = assemble_module("caller", "jz 0, print+1\nout [count]\nhalt").unwrap();
+        assert_eq!(module.code, [1106, 0, 1, 4, 0, 99]);
+        assert_eq!(
+            module.imports,
+            [
+                Import {
+                    cell: 2,
+                    name: "print".to_string()
+                },
+                Import {
+                    cell: 4,
+                    name: "count".to_string()
+                }
+            ]
+        );
+        assert_eq!(
+            assemble("jz 0, print\nhalt"),
+            Err(AssembleError::new(1, "print is never defined"))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source: &str| assemble(source).unwrap_err();
+        assert_eq!(
+            error("halt\njump 3"),
+            AssembleError::new(2, "unknown instruction jump")
+        );
+        assert_eq!(
+            error("add 1, 2"),
+            AssembleError::new(1, "add takes 3 parameters, found 2")
+        );
+        assert_eq!(
+            error("in 5"),
+            AssembleError::new(1, "in can't write to the immediate value 5")
+        );
+        assert_eq!(
+            error("a: halt\na: halt"),
+            AssembleError::new(2, "a is already defined")
+        );
+        assert_eq!(
+            error("global main\nhalt"),
+            AssembleError::new(1, "main is global but never defined")
+        );
+        assert_eq!(
+            error("out [rb+x]"),
+            AssembleError::new(1, "invalid number \"x\"")
+        );
+        assert_eq!(
+            error("data"),
+            AssembleError::new(1, "data needs at least one value")
+        );
+    }
+}
@@ -0,0 +1,63 @@
+//! Assembles source files and links them into one program, printed the way
+//! puzzle inputs are written:
+//!
+//! ```text
+//! cargo run -p intcode --bin icasm -- [--symbols symbols.txt] main.s lib/print.s
+//! ```
+//!
+//! The modules are laid out in the order given, so the first one starts the
+//! program. The symbols file can be handed to `icdb` and `icdecompile`.
+
+use intcode::assembler::{assemble_module, Module};
+use intcode::linker::link;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+
+fn read_module(path: &str) -> Result<Module, String> {
+    let source =
+        fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    let name = Path::new(path)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or(path);
+    assemble_module(name, &source).map_err(|error| format!("{}: {}", path, error))
+}
+
+fn main() {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let symbols = match args.iter().position(|arg| arg == "--symbols") {
+        Some(index) if index + 1 < args.len() => {
+            let path = args.remove(index + 1);
+            args.remove(index);
+            Some(path)
+        }
+        _ => None,
+    };
+    if args.is_empty() || args.iter().any(|arg| arg.starts_with("--")) {
+        eprintln!("Usage: icasm [--symbols <output>] <source>...");
+        exit(2);
+    }
+
+    let result = args
+        .iter()
+        .map(|path| read_module(path))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|modules| link(&modules).map_err(|error| error.to_string()));
+    let linked = match result {
+        Ok(linked) => linked,
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(1);
+        }
+    };
+    if let Some(path) = symbols {
+        if let Err(error) = fs::write(&path, linked.symbols.to_string()) {
+            eprintln!("Could not write {}: {}", path, error);
+            exit(1);
+        }
+    }
+    let cells: Vec<String> = linked.program.iter().map(isize::to_string).collect();
+    println!("{}", cells.join(","));
+}
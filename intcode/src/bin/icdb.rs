@@ -0,0 +1,148 @@
+//! Steps through a program, reading commands from stdin:
+//!
+//! ```text
+//! cargo run -p intcode --bin icdb -- day-25/input.txt [symbols.txt]
+//! ```
+//!
+//! `s [count]` steps, `c` continues to the next breakpoint, `b <address>`
+//! and `d <address>` set and delete breakpoints, `w <expression>` and
+//! `u <index>` add and remove watch expressions, `i <values>` gives the
+//! program input, separated by commas, and `q` quits. Watch expressions are
+//! shown every time the program stops.
+
+use intcode::debugger::{Debugger, Report};
+use intcode::disassemble::instruction_at;
+use intcode::symbols::Symbols;
+use intcode::{read_intcode_input, IntCodeMachine};
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::exit;
+
+fn load(path: &str, symbols: Option<&String>) -> Result<IntCodeMachine, String> {
+    let file = File::open(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    let program = read_intcode_input(BufReader::new(file))
+        .map_err(|error| format!("Invalid program {}: {}", path, error))?;
+    let mut machine = IntCodeMachine::new(program);
+    if let Some(path) = symbols {
+        let text = fs::read_to_string(path)
+            .map_err(|error| format!("Could not read {}: {}", path, error))?;
+        let symbols =
+            Symbols::parse(&text).map_err(|error| format!("Invalid {}: {}", path, error))?;
+        machine.set_symbols(symbols);
+    }
+    Ok(machine)
+}
+
+fn show(debugger: &mut Debugger, report: &Report) {
+    let output = debugger.take_output();
+    if !output.is_empty() {
+        println!("Output: {:?}", output);
+    }
+    print!("{}", report);
+    let memory = debugger.machine().memory();
+    if report.instruction_pointer < memory.len() {
+        println!("{}", instruction_at(memory, report.instruction_pointer));
+    }
+}
+
+fn address(argument: &str) -> Result<usize, String> {
+    argument
+        .parse()
+        .map_err(|_| format!("Invalid address {}", argument))
+}
+
+// Runs one command, returning whether to keep going
+fn command(debugger: &mut Debugger, line: &str) -> Result<bool, String> {
+    let (name, argument) = match line.trim().split_once(' ') {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (line.trim(), ""),
+    };
+    match name {
+        "s" | "step" => {
+            let count = match argument {
+                "" => 1,
+                count => count
+                    .parse()
+                    .map_err(|_| format!("Invalid count {}", count))?,
+            };
+            let mut report = debugger.step().map_err(|error| error.to_string())?;
+            for _ in 1..count {
+                report = debugger.step().map_err(|error| error.to_string())?;
+            }
+            show(debugger, &report);
+        }
+        "c" | "continue" => {
+            let report = debugger.resume().map_err(|error| error.to_string())?;
+            show(debugger, &report);
+        }
+        "b" | "break" => {
+            debugger.add_breakpoint(address(argument)?);
+        }
+        "d" | "delete" => {
+            if !debugger.remove_breakpoint(address(argument)?) {
+                return Err(format!("No breakpoint at {}", argument));
+            }
+        }
+        "w" | "watch" => {
+            let index = debugger
+                .watch(argument)
+                .map_err(|error| error.to_string())?;
+            println!("Watch {}: {}", index, argument);
+        }
+        "u" | "unwatch" => {
+            let index = argument
+                .parse()
+                .map_err(|_| format!("Invalid index {}", argument))?;
+            debugger
+                .unwatch(index)
+                .ok_or_else(|| format!("No watch {}", index))?;
+        }
+        "i" | "input" => {
+            let values = argument
+                .split(',')
+                .map(|value| value.trim().parse())
+                .collect::<Result<Vec<isize>, _>>()
+                .map_err(|_| format!("Invalid input {}", argument))?;
+            debugger.push_input(values);
+        }
+        "q" | "quit" => return Ok(false),
+        "" => {}
+        _ => return Err(format!("Unknown command {}", name)),
+    }
+    Ok(true)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let machine = match args.as_slice() {
+        [program] => load(program, None),
+        [program, symbols] => load(program, Some(symbols)),
+        _ => {
+            eprintln!("Usage: icdb <program> [symbols]");
+            exit(2);
+        }
+    };
+    let mut debugger = match machine {
+        Ok(machine) => Debugger::new(machine),
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(2);
+        }
+    };
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => break,
+        };
+        match command(&mut debugger, &line) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(error) => eprintln!("{}", error),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Prints a program as pseudo-code, one function after the other:
+//!
+//! ```text
+//! cargo run -p intcode --bin icdecompile -- day-25/input.txt [symbols.txt]
+//! ```
+//!
+//! Addresses with a name in the symbols file show up by that name.
+
+use intcode::decompile::decompile;
+use intcode::read_intcode_input;
+use intcode::symbols::Symbols;
+use std::env;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::process::exit;
+
+fn read_program(path: &str) -> Result<Vec<isize>, String> {
+    let file = File::open(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    read_intcode_input(BufReader::new(file))
+        .map_err(|error| format!("Invalid program {}: {}", path, error))
+}
+
+fn read_symbols(path: &str) -> Result<Symbols, String> {
+    let text =
+        fs::read_to_string(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    Symbols::parse(&text).map_err(|error| format!("Invalid {}: {}", path, error))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (program, symbols) = match args.as_slice() {
+        [program] => (read_program(program), None),
+        [program, symbols] => (read_program(program), Some(read_symbols(symbols))),
+        _ => {
+            eprintln!("Usage: icdecompile <program> [symbols]");
+            exit(2);
+        }
+    };
+    let result = program.and_then(|program| Ok((program, symbols.transpose()?)));
+    match result {
+        Ok((program, symbols)) => print!("{}", decompile(&program, symbols.as_ref())),
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(2);
+        }
+    }
+}
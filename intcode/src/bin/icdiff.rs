@@ -0,0 +1,43 @@
+//! Compares two programs cell by cell, showing the instructions that
+//! changed:
+//!
+//! ```text
+//! cargo run -p intcode --bin icdiff -- day-19/input.txt other/day-19.txt
+//! ```
+//!
+//! Exits with 1 when the programs differ, like `diff`.
+
+use intcode::diff::{differences, report};
+use intcode::read_intcode_input;
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::process::exit;
+
+fn read_program(path: &str) -> Result<Vec<isize>, String> {
+    let file = File::open(path).map_err(|error| format!("Could not read {}: {}", path, error))?;
+    read_intcode_input(BufReader::new(file))
+        .map_err(|error| format!("Invalid program {}: {}", path, error))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (first, second) = match args.as_slice() {
+        [first, second] => (first, second),
+        _ => {
+            eprintln!("Usage: icdiff <first program> <second program>");
+            exit(2);
+        }
+    };
+    let (first, second) = match (read_program(first), read_program(second)) {
+        (Ok(first), Ok(second)) => (first, second),
+        (Err(error), _) | (_, Err(error)) => {
+            eprintln!("{}", error);
+            exit(2);
+        }
+    };
+    print!("{}", report(&first, &second));
+    if !differences(&first, &second).is_empty() {
+        exit(1);
+    }
+}
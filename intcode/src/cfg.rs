@@ -0,0 +1,401 @@
+//! The control flow graph of a program, built by following jumps from the
+//! first instruction instead of reading the program from start to end, so
+//! data between instructions can't throw it off.
+//!
+//! A jump that comes right after storing its own return address, in the
+//! same stretch of code, is taken as a call: the target starts a function,
+//! and the code after the jump carries on the caller. Jumps to addresses
+//! read from memory, like returns, can't be followed. Code that is only
+//! reached through them, or that a program writes while running, is left
+//! out.
+
+use crate::{OpCode, ParameterMode, Value};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::convert::TryFrom;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Immediate(Value),
+    Position(Value),
+    Relative(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Add(Operand, Operand, Operand),
+    Multiply(Operand, Operand, Operand),
+    Input(Operand),
+    Output(Operand),
+    JumpIfTrue(Operand, Operand),
+    JumpIfFalse(Operand, Operand),
+    LessThan(Operand, Operand, Operand),
+    Equals(Operand, Operand, Operand),
+    AdjustRelativeBase(Operand),
+    Halt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decoded {
+    pub address: usize,
+    /// How many cells the instruction takes, including its op code.
+    pub length: usize,
+    pub op: Op,
+}
+
+/// The instruction at `address`, if there is a valid one there.
+pub fn decode(program: &[Value], address: usize) -> Option<Decoded> {
+    let code = OpCode::try_from(*program.get(address)?).ok()?;
+    let operand = |index: usize, mode: ParameterMode| {
+        let value = *program.get(address + index)?;
+        Some(match mode {
+            ParameterMode::Immediate => Operand::Immediate(value),
+            ParameterMode::Reference => Operand::Position(value),
+            ParameterMode::Relative => Operand::Relative(value),
+        })
+    };
+    let (op, length) = match code {
+        OpCode::Add(a, b, c) => (Op::Add(operand(1, a)?, operand(2, b)?, operand(3, c)?), 4),
+        OpCode::Multiply(a, b, c) => (
+            Op::Multiply(operand(1, a)?, operand(2, b)?, operand(3, c)?),
+            4,
+        ),
+        OpCode::Input(a) => (Op::Input(operand(1, a)?), 2),
+        OpCode::Output(a) => (Op::Output(operand(1, a)?), 2),
+        OpCode::JumpIfTrue(a, b) => (Op::JumpIfTrue(operand(1, a)?, operand(2, b)?), 3),
+        OpCode::JumpIfFalse(a, b) => (Op::JumpIfFalse(operand(1, a)?, operand(2, b)?), 3),
+        OpCode::LessThan(a, b, c) => (
+            Op::LessThan(operand(1, a)?, operand(2, b)?, operand(3, c)?),
+            4,
+        ),
+        OpCode::Equals(a, b, c) => (
+            Op::Equals(operand(1, a)?, operand(2, b)?, operand(3, c)?),
+            4,
+        ),
+        OpCode::AdjustRelativeBase(a) => (Op::AdjustRelativeBase(operand(1, a)?), 2),
+        OpCode::Exit => (Op::Halt, 1),
+    };
+    Some(Decoded {
+        address,
+        length,
+        op,
+    })
+}
+
+/// When a conditional jump is taken: when the operand is 0 for `jz`, and
+/// when it isn't for `jnz`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub operand: Operand,
+    pub if_zero: bool,
+}
+
+/// How a block ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// Runs on into the block at the address.
+    Next(usize),
+    Jump(usize),
+    Branch {
+        condition: Condition,
+        target: usize,
+        next: usize,
+    },
+    Call {
+        target: usize,
+        next: usize,
+    },
+    /// A jump to an address read from memory, which goes on at `next` when
+    /// it isn't taken.
+    Computed {
+        condition: Option<Condition>,
+        target: Operand,
+        next: usize,
+    },
+    Halt,
+    /// Runs into something that isn't an instruction at the address.
+    Invalid(usize),
+}
+
+/// Instructions that always run one after the other. The jump or halt that
+/// ends the block is its exit, not one of its instructions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    /// The address right after the block.
+    pub end: usize,
+    pub instructions: Vec<Decoded>,
+    pub exit: Exit,
+}
+
+impl Block {
+    pub fn successors(&self) -> Vec<usize> {
+        match self.exit {
+            Exit::Next(next) | Exit::Call { next, .. } => vec![next],
+            Exit::Jump(target) => vec![target],
+            Exit::Branch { target, next, .. } => vec![target, next],
+            Exit::Computed {
+                condition: Some(_),
+                next,
+                ..
+            } => vec![next],
+            Exit::Computed {
+                condition: None, ..
+            }
+            | Exit::Halt
+            | Exit::Invalid(_) => Vec::new(),
+        }
+    }
+}
+
+fn target(operand: Operand) -> Option<usize> {
+    match operand {
+        Operand::Immediate(value) => usize::try_from(value).ok(),
+        _ => None,
+    }
+}
+
+// The exit an instruction makes, if it's a jump or a halt. `returns` has
+// the addresses stored since the start of the stretch of code.
+fn exit(instruction: &Decoded, returns: &HashSet<Value>) -> Option<Exit> {
+    let next = instruction.address + instruction.length;
+    let (condition, destination) = match instruction.op {
+        Op::Halt => return Some(Exit::Halt),
+        Op::JumpIfTrue(condition, destination) => (
+            Condition {
+                operand: condition,
+                if_zero: false,
+            },
+            destination,
+        ),
+        Op::JumpIfFalse(condition, destination) => (
+            Condition {
+                operand: condition,
+                if_zero: true,
+            },
+            destination,
+        ),
+        _ => return None,
+    };
+    // A constant condition either always jumps or never does
+    let condition = match condition.operand {
+        Operand::Immediate(value) if (value == 0) == condition.if_zero => None,
+        Operand::Immediate(_) => return Some(Exit::Next(next)),
+        _ => Some(condition),
+    };
+    Some(match (condition, target(destination)) {
+        (None, Some(target)) if returns.contains(&(next as Value)) => Exit::Call { target, next },
+        (None, Some(target)) => Exit::Jump(target),
+        (Some(condition), Some(target)) => Exit::Branch {
+            condition,
+            target,
+            next,
+        },
+        (condition, None) => Exit::Computed {
+            condition,
+            target: destination,
+            next,
+        },
+    })
+}
+
+// A value stored without reading memory, which could be a return address
+fn stored_constant(op: &Op) -> Option<Value> {
+    match *op {
+        Op::Add(Operand::Immediate(a), Operand::Immediate(b), _) => a.checked_add(b),
+        Op::Multiply(Operand::Immediate(a), Operand::Immediate(b), _) => a.checked_mul(b),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Graph {
+    blocks: BTreeMap<usize, Block>,
+    functions: BTreeSet<usize>,
+}
+
+impl Graph {
+    pub fn build(program: &[Value]) -> Self {
+        let mut instructions: BTreeMap<usize, (Decoded, Option<Exit>)> = BTreeMap::new();
+        let mut leaders = BTreeSet::new();
+        let mut functions = BTreeSet::new();
+        let mut pending = vec![0];
+        leaders.insert(0);
+        functions.insert(0);
+
+        while let Some(start) = pending.pop() {
+            let mut address = start;
+            let mut returns = HashSet::new();
+            while !instructions.contains_key(&address) {
+                let instruction = match decode(program, address) {
+                    Some(instruction) => instruction,
+                    None => break,
+                };
+                let exit = exit(&instruction, &returns);
+                instructions.insert(address, (instruction, exit));
+                returns.extend(stored_constant(&instruction.op));
+                address += instruction.length;
+                match exit {
+                    None | Some(Exit::Next(_)) => continue,
+                    Some(Exit::Jump(target)) => {
+                        leaders.insert(target);
+                        pending.push(target);
+                        break;
+                    }
+                    Some(Exit::Branch { target, next, .. }) => {
+                        leaders.extend([target, next]);
+                        pending.push(target);
+                    }
+                    Some(Exit::Call { target, next }) => {
+                        functions.insert(target);
+                        leaders.extend([target, next]);
+                        pending.push(target);
+                    }
+                    Some(Exit::Computed {
+                        condition: Some(_),
+                        next,
+                        ..
+                    }) => {
+                        leaders.insert(next);
+                    }
+                    Some(_) => break,
+                }
+                // Code after a jump is a new stretch
+                returns.clear();
+            }
+        }
+
+        let blocks = leaders
+            .iter()
+            .map(|&start| {
+                let mut block = Block {
+                    start,
+                    end: start,
+                    instructions: Vec::new(),
+                    exit: Exit::Invalid(start),
+                };
+                loop {
+                    let (instruction, exit) = match instructions.get(&block.end) {
+                        Some(found) => *found,
+                        None => {
+                            block.exit = Exit::Invalid(block.end);
+                            break;
+                        }
+                    };
+                    block.end += instruction.length;
+                    match exit {
+                        None => block.instructions.push(instruction),
+                        // A jump that's never taken does nothing
+                        Some(Exit::Next(_)) => {}
+                        Some(exit) => {
+                            block.exit = exit;
+                            break;
+                        }
+                    }
+                    if leaders.contains(&block.end) {
+                        block.exit = Exit::Next(block.end);
+                        break;
+                    }
+                }
+                (start, block)
+            })
+            .collect();
+        Self { blocks, functions }
+    }
+
+    pub fn block(&self, start: usize) -> Option<&Block> {
+        self.blocks.get(&start)
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.values()
+    }
+
+    /// Where the program starts, and every function it calls.
+    pub fn functions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.functions.iter().copied()
+    }
+
+    /// The blocks reached from `entry` without following calls, in order
+    /// of address.
+    pub fn function(&self, entry: usize) -> Vec<&Block> {
+        let mut seen = BTreeSet::new();
+        let mut pending = vec![entry];
+        while let Some(start) = pending.pop() {
+            if let Some(block) = self.blocks.get(&start) {
+                if seen.insert(start) {
+                    pending.extend(block.successors());
+                }
+            }
+        }
+        seen.iter().map(|start| &self.blocks[start]).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Condition, Exit, Graph, Operand};
+    use crate::programs::COUNTDOWN;
+
+    #[test]
+    fn test_countdown() {
+        let graph = Graph::build(&COUNTDOWN);
+        let starts: Vec<usize> = graph.blocks().map(|block| block.start).collect();
+        assert_eq!(starts, [0, 2, 13]);
+        assert_eq!(graph.block(0).unwrap().exit, Exit::Next(2));
+        let body = graph.block(2).unwrap();
+        assert_eq!(
+            body.exit,
+            Exit::Branch {
+                condition: Condition {
+                    operand: Operand::Position(20),
+                    if_zero: false
+                },
+                target: 2,
+                next: 13
+            }
+        );
+        assert_eq!(body.instructions.len(), 2);
+        assert_eq!(graph.block(13).unwrap().exit, Exit::Halt);
+        assert_eq!(graph.functions().collect::<Vec<_>>(), [0]);
+    }
+
+    #[test]
+    fn test_calls() {
+        let program = [
+            109, 50, // arb 50
+            21101, 0, 9, 0, // add 0, 9, [rb+0]
+            1106, 0, 11, // jz 0, 11
+            99, 0, // halt, then data
+            104, 42, // out 42
+            2106, 0, 0, // jz 0, [rb+0]
+        ];
+        let graph = Graph::build(&program);
+        assert_eq!(graph.functions().collect::<Vec<_>>(), [0, 11]);
+        assert_eq!(
+            graph.block(0).unwrap().exit,
+            Exit::Call {
+                target: 11,
+                next: 9
+            }
+        );
+        let function: Vec<usize> = graph.function(11).iter().map(|block| block.start).collect();
+        assert_eq!(function, [11]);
+        assert!(matches!(
+            graph.block(11).unwrap().exit,
+            Exit::Computed {
+                condition: None,
+                target: Operand::Relative(0),
+                ..
+            }
+        ));
+        // Data after the halt is never read as code
+        assert!(graph.block(10).is_none());
+    }
+
+    #[test]
+    fn test_invalid_code() {
+        let graph = Graph::build(&[1105, 1, 4, 99, 12345]);
+        assert_eq!(graph.block(0).unwrap().exit, Exit::Jump(4));
+        assert_eq!(graph.block(4).unwrap().exit, Exit::Invalid(4));
+    }
+}
@@ -0,0 +1,685 @@
+//! A tiny language that compiles to intcode, for writing test programs
+//! without counting addresses by hand.
+//!
+//! ```text
+//! # Prints the sum of 1 to n
+//! in n;
+//! total = 0;
+//! while n > 0 {
+//!     total = total + n;
+//!     n = n - 1;
+//! }
+//! out total;
+//! ```
+//!
+//! Every value is an integer. Expressions have `+`, `-`, `*`, parentheses
+//! and the comparisons `<`, `>`, `<=`, `>=`, `==` and `!=`, which give 1 or
+//! 0. Conditions are true when they aren't 0. A variable has to be assigned
+//! or read with `in` before it's used.
+//!
+//! Variables and temporaries live in cells after the code, which ends with
+//! a halt. The compiler emits the op codes directly, there is no assembler
+//! in between. Expressions made only of constants are worked out while
+//! compiling, and fail to compile when they overflow.
+
+use crate::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompileError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl CompileError {
+    fn new(line: usize, message: impl fmt::Display) -> Self {
+        Self {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+const KEYWORDS: [&str; 5] = ["if", "else", "while", "in", "out"];
+
+const SYMBOLS: [&str; 15] = [
+    "==", "!=", "<=", ">=", "<", ">", "=", "+", "-", "*", "(", ")", "{", "}", ";",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(Value),
+    Name(String),
+    Symbol(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "{}", value),
+            Token::Name(name) => write!(f, "{}", name),
+            Token::Symbol(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, CompileError> {
+    let mut tokens = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let number = index + 1;
+        let line = line.split('#').next().unwrap_or("");
+        let mut rest = line.trim_start();
+        while !rest.is_empty() {
+            let length;
+            if rest.starts_with(|c: char| c.is_ascii_digit()) {
+                length = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let value = rest[..length]
+                    .parse()
+                    .map_err(|_| CompileError::new(number, "number is too large"))?;
+                tokens.push((Token::Number(value), number));
+            } else if rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                length = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                tokens.push((Token::Name(rest[..length].to_string()), number));
+            } else {
+                let symbol = SYMBOLS
+                    .iter()
+                    .find(|symbol| rest.starts_with(*symbol))
+                    .ok_or_else(|| {
+                        let c = rest.chars().next().unwrap();
+                        CompileError::new(number, format!("unexpected character {:?}", c))
+                    })?;
+                length = symbol.len();
+                tokens.push((Token::Symbol(symbol), number));
+            }
+            rest = rest[length..].trim_start();
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Less,
+    Greater,
+    LessOrEqual,
+    GreaterOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Operator {
+    // None when the result doesn't fit in a value
+    fn apply(self, left: Value, right: Value) -> Option<Value> {
+        match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Subtract => left.checked_sub(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Less => Some((left < right) as Value),
+            Operator::Greater => Some((left > right) as Value),
+            Operator::LessOrEqual => Some((left <= right) as Value),
+            Operator::GreaterOrEqual => Some((left >= right) as Value),
+            Operator::Equal => Some((left == right) as Value),
+            Operator::NotEqual => Some((left != right) as Value),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Expr {
+    Number(Value),
+    Variable(usize),
+    Negate(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug)]
+enum Statement {
+    Assign(usize, Expr),
+    Input(usize),
+    Output(Expr),
+    If(Expr, Vec<Statement>, Vec<Statement>),
+    While(Expr, Vec<Statement>),
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    variables: HashMap<String, usize>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        match self.tokens.get(self.position) {
+            Some((_, line)) => *line,
+            None => self.tokens.last().map_or(1, |(_, line)| *line),
+        }
+    }
+
+    fn error(&self, expected: &str) -> CompileError {
+        match self.peek() {
+            Some(token) => CompileError::new(
+                self.line(),
+                format!("expected {}, found {}", expected, token),
+            ),
+            None => CompileError::new(
+                self.line(),
+                format!("expected {}, found the end of the program", expected),
+            ),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, symbol: &'static str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), CompileError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("'{}'", symbol)))
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Name(name)) if name == keyword => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn name(&mut self) -> Result<String, CompileError> {
+        match self.peek() {
+            Some(Token::Name(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(self.error("a variable")),
+        }
+    }
+
+    // Variables are numbered in the order they're first assigned
+    fn declare(&mut self, name: String) -> usize {
+        let next = self.variables.len();
+        *self.variables.entry(name).or_insert(next)
+    }
+
+    fn statements(&mut self) -> Result<Vec<Statement>, CompileError> {
+        let mut statements = Vec::new();
+        while self.peek().is_some() && self.peek() != Some(&Token::Symbol("}")) {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn block(&mut self) -> Result<Vec<Statement>, CompileError> {
+        self.expect("{")?;
+        let statements = self.statements()?;
+        self.expect("}")?;
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement, CompileError> {
+        let statement = if self.keyword("if") {
+            let condition = self.expression()?;
+            let then = self.block()?;
+            let otherwise = if self.keyword("else") {
+                if self.peek() == Some(&Token::Name("if".to_string())) {
+                    vec![self.statement()?]
+                } else {
+                    self.block()?
+                }
+            } else {
+                Vec::new()
+            };
+            return Ok(Statement::If(condition, then, otherwise));
+        } else if self.keyword("while") {
+            let condition = self.expression()?;
+            return Ok(Statement::While(condition, self.block()?));
+        } else if self.keyword("in") {
+            let name = self.name()?;
+            Statement::Input(self.declare(name))
+        } else if self.keyword("out") {
+            Statement::Output(self.expression()?)
+        } else {
+            let name = self.name()?;
+            self.expect("=")?;
+            let value = self.expression()?;
+            Statement::Assign(self.declare(name), value)
+        };
+        self.expect(";")?;
+        Ok(statement)
+    }
+
+    // Works out operations on two constants right away
+    fn binary(
+        &self,
+        operator: Operator,
+        left: Expr,
+        right: Expr,
+        line: usize,
+    ) -> Result<Expr, CompileError> {
+        match (&left, &right) {
+            (Expr::Number(left), Expr::Number(right)) => operator
+                .apply(*left, *right)
+                .map(Expr::Number)
+                .ok_or_else(|| CompileError::new(line, "constant overflows")),
+            _ => Ok(Expr::Binary(operator, Box::new(left), Box::new(right))),
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expr, CompileError> {
+        let left = self.sum()?;
+        let line = self.line();
+        let operator = match self.peek() {
+            Some(Token::Symbol("<")) => Operator::Less,
+            Some(Token::Symbol(">")) => Operator::Greater,
+            Some(Token::Symbol("<=")) => Operator::LessOrEqual,
+            Some(Token::Symbol(">=")) => Operator::GreaterOrEqual,
+            Some(Token::Symbol("==")) => Operator::Equal,
+            Some(Token::Symbol("!=")) => Operator::NotEqual,
+            _ => return Ok(left),
+        };
+        self.position += 1;
+        let right = self.sum()?;
+        self.binary(operator, left, right, line)
+    }
+
+    fn sum(&mut self) -> Result<Expr, CompileError> {
+        let mut left = self.product()?;
+        loop {
+            let line = self.line();
+            let operator = if self.eat("+") {
+                Operator::Add
+            } else if self.eat("-") {
+                Operator::Subtract
+            } else {
+                return Ok(left);
+            };
+            let right = self.product()?;
+            left = self.binary(operator, left, right, line)?;
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, CompileError> {
+        let mut left = self.unary()?;
+        loop {
+            let line = self.line();
+            if !self.eat("*") {
+                break;
+            }
+            let right = self.unary()?;
+            left = self.binary(Operator::Multiply, left, right, line)?;
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, CompileError> {
+        let line = self.line();
+        if self.eat("-") {
+            return match self.unary()? {
+                Expr::Number(value) => value
+                    .checked_neg()
+                    .map(Expr::Number)
+                    .ok_or_else(|| CompileError::new(line, "constant overflows")),
+                inner => Ok(Expr::Negate(Box::new(inner))),
+            };
+        }
+        if self.eat("(") {
+            let expr = self.expression()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        let line = self.line();
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expr::Number(value)),
+            Some(Token::Name(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                match self.variables.get(&name) {
+                    Some(&index) => Ok(Expr::Variable(index)),
+                    None => Err(CompileError::new(
+                        line,
+                        format!("{} is used before it's assigned", name),
+                    )),
+                }
+            }
+            _ => {
+                self.position -= 1;
+                Err(self.error("a value"))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cell {
+    Variable(usize),
+    Temporary(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operand {
+    Immediate(Value),
+    Cell(Cell),
+    // The address of a label, as an immediate
+    Label(usize),
+}
+
+impl Operand {
+    fn mode(self) -> Value {
+        match self {
+            Operand::Cell(_) => 0,
+            _ => 1,
+        }
+    }
+}
+
+const ADD: Value = 1;
+const MULTIPLY: Value = 2;
+const INPUT: Value = 3;
+const OUTPUT: Value = 4;
+const JUMP_IF_TRUE: Value = 5;
+const JUMP_IF_FALSE: Value = 6;
+const LESS_THAN: Value = 7;
+const EQUALS: Value = 8;
+const HALT: Value = 99;
+
+#[derive(Default)]
+struct Generator {
+    code: Vec<Operand>,
+    labels: Vec<Option<usize>>,
+    temporaries: usize,
+    max_temporaries: usize,
+}
+
+impl Generator {
+    fn emit(&mut self, opcode: Value, parameters: &[Operand]) {
+        let modes = parameters
+            .iter()
+            .rev()
+            .fold(0, |modes, parameter| modes * 10 + parameter.mode());
+        self.code.push(Operand::Immediate(opcode + modes * 100));
+        self.code.extend_from_slice(parameters);
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    fn place(&mut self, label: usize) {
+        self.labels[label] = Some(self.code.len());
+    }
+
+    fn temporary(&mut self) -> Operand {
+        self.temporaries += 1;
+        self.max_temporaries = self.max_temporaries.max(self.temporaries);
+        Operand::Cell(Cell::Temporary(self.temporaries - 1))
+    }
+
+    // Leaves 1 in the result when the operand is 0, and 0 otherwise
+    fn not(&mut self, operand: Operand) -> Operand {
+        self.emit(EQUALS, &[operand, Operand::Immediate(0), operand]);
+        operand
+    }
+
+    fn expression(&mut self, expr: &Expr) -> Operand {
+        match expr {
+            Expr::Number(value) => Operand::Immediate(*value),
+            Expr::Variable(index) => Operand::Cell(Cell::Variable(*index)),
+            Expr::Negate(inner) => {
+                let operand = self.expression(inner);
+                let result = self.temporary();
+                self.emit(MULTIPLY, &[operand, Operand::Immediate(-1), result]);
+                result
+            }
+            Expr::Binary(operator, left, right) => {
+                // Constants were worked out by the parser
+                let left = self.expression(left);
+                let right = self.expression(right);
+                let result = self.temporary();
+                match operator {
+                    Operator::Add => self.emit(ADD, &[left, right, result]),
+                    Operator::Multiply => self.emit(MULTIPLY, &[left, right, result]),
+                    Operator::Subtract => {
+                        self.emit(MULTIPLY, &[right, Operand::Immediate(-1), result]);
+                        self.emit(ADD, &[left, result, result]);
+                    }
+                    Operator::Less => self.emit(LESS_THAN, &[left, right, result]),
+                    Operator::Greater => self.emit(LESS_THAN, &[right, left, result]),
+                    Operator::Equal => self.emit(EQUALS, &[left, right, result]),
+                    Operator::GreaterOrEqual => {
+                        self.emit(LESS_THAN, &[left, right, result]);
+                        self.not(result);
+                    }
+                    Operator::LessOrEqual => {
+                        self.emit(LESS_THAN, &[right, left, result]);
+                        self.not(result);
+                    }
+                    Operator::NotEqual => {
+                        self.emit(EQUALS, &[left, right, result]);
+                        self.not(result);
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    fn statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        // Temporaries only live until the end of the statement that needs
+        // them, or the jump that tests them
+        self.temporaries = 0;
+        match statement {
+            Statement::Assign(index, value) => {
+                let value = self.expression(value);
+                let variable = Operand::Cell(Cell::Variable(*index));
+                self.emit(ADD, &[value, Operand::Immediate(0), variable]);
+            }
+            Statement::Input(index) => {
+                self.emit(INPUT, &[Operand::Cell(Cell::Variable(*index))]);
+            }
+            Statement::Output(value) => {
+                let value = self.expression(value);
+                self.emit(OUTPUT, &[value]);
+            }
+            Statement::If(condition, then, otherwise) => {
+                let condition = self.expression(condition);
+                let skip = self.label();
+                self.emit(JUMP_IF_FALSE, &[condition, Operand::Label(skip)]);
+                self.statements(then);
+                if otherwise.is_empty() {
+                    self.place(skip);
+                } else {
+                    let end = self.label();
+                    self.emit(JUMP_IF_TRUE, &[Operand::Immediate(1), Operand::Label(end)]);
+                    self.place(skip);
+                    self.statements(otherwise);
+                    self.place(end);
+                }
+            }
+            Statement::While(condition, body) => {
+                let start = self.label();
+                let end = self.label();
+                self.place(start);
+                let condition = self.expression(condition);
+                self.emit(JUMP_IF_FALSE, &[condition, Operand::Label(end)]);
+                self.statements(body);
+                self.emit(
+                    JUMP_IF_TRUE,
+                    &[Operand::Immediate(1), Operand::Label(start)],
+                );
+                self.place(end);
+            }
+        }
+    }
+
+    fn finish(mut self, variables: usize) -> Vec<Value> {
+        self.code.push(Operand::Immediate(HALT));
+        let data = self.code.len();
+        let address = |cell| match cell {
+            Cell::Variable(index) => data + index,
+            Cell::Temporary(index) => data + variables + index,
+        };
+        let mut program: Vec<Value> = self
+            .code
+            .iter()
+            .map(|operand| match *operand {
+                Operand::Immediate(value) => value,
+                Operand::Cell(cell) => address(cell) as Value,
+                Operand::Label(label) => {
+                    self.labels[label].expect("label was never placed") as Value
+                }
+            })
+            .collect();
+        program.resize(data + variables + self.max_temporaries, 0);
+        program
+    }
+}
+
+/// Compiles a program to intcode.
+pub fn compile(source: &str) -> Result<Vec<Value>, CompileError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+        variables: HashMap::new(),
+    };
+    let statements = parser.statements()?;
+    if parser.peek().is_some() {
+        return Err(parser.error("a statement"));
+    }
+    let mut generator = Generator::default();
+    generator.statements(&statements);
+    Ok(generator.finish(parser.variables.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compile, CompileError};
+    use crate::programs::{run, run_single};
+
+    #[test]
+    fn test_loop() {
+        let program = compile(
+            "# Prints the sum of 1 to n
+            in n;
+            total = 0;
+            while n > 0 {
+                total = total + n;
+                n = n - 1;
+            }
+            out total;",
+        )
+        .unwrap();
+        assert_eq!(run_single(&program, 100).unwrap(), Some(5050));
+        assert_eq!(run_single(&program, -4).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_if_else() {
+        let program = compile(
+            "in x;
+            if x < 8 { out 999; }
+            else if x == 8 { out 1000; }
+            else { out 1001; }",
+        )
+        .unwrap();
+        assert_eq!(run_single(&program, 7).unwrap(), Some(999));
+        assert_eq!(run_single(&program, 8).unwrap(), Some(1000));
+        assert_eq!(run_single(&program, 9).unwrap(), Some(1001));
+    }
+
+    #[test]
+    fn test_expressions() {
+        let program = compile(
+            "in a; in b;
+            out a - b * 2;
+            out -(a + b) * -3;
+            out a - -b;
+            out (a <= b) + (a >= b) * 10 + (a != b) * 100 + (a > b) * 1000;",
+        )
+        .unwrap();
+        assert_eq!(run(&program, [10, 3]).unwrap(), [4, 39, 13, 1110]);
+        assert_eq!(run(&program, [3, 3]).unwrap(), [-3, 18, 6, 11]);
+        // Constants are worked out while compiling
+        assert_eq!(compile("out 2 * -3 + 1;").unwrap(), [104, -5, 99]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |source| compile(source).unwrap_err();
+        assert_eq!(
+            error("x = 1;\nout y;"),
+            CompileError::new(2, "y is used before it's assigned")
+        );
+        assert_eq!(
+            error("in x\nout x;"),
+            CompileError::new(2, "expected ';', found out")
+        );
+        assert_eq!(
+            error("while 1 { out 1;"),
+            CompileError::new(1, "expected '}', found the end of the program")
+        );
+        assert_eq!(
+            error("x = 1 $ 2;"),
+            CompileError::new(1, "unexpected character '$'")
+        );
+        assert_eq!(
+            error("in while;").message,
+            "expected a variable, found while"
+        );
+        assert_eq!(error("}").message, "expected a statement, found }");
+        assert_eq!(
+            error("in x;\nout 9223372036854775807 + 1;"),
+            CompileError::new(2, "constant overflows")
+        );
+        // Reported on the line of the operator
+        assert_eq!(
+            error("out 1 + (3037000500 *\n3037000500);"),
+            CompileError::new(1, "constant overflows")
+        );
+        assert_eq!(
+            error("out -(0 - 9223372036854775807 - 1);"),
+            CompileError::new(1, "constant overflows")
+        );
+    }
+}
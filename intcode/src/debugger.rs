@@ -0,0 +1,522 @@
+//! Running a program one instruction at a time, or until it reaches a
+//! breakpoint, while keeping an eye on the machine through watch
+//! expressions.
+//!
+//! Watch expressions are evaluated after every step and every continue.
+//! They can use `ip` and `rb`, memory cells like `[100]` or `[rb+3]`,
+//! numbers, the names of the machine's symbols, which stand for their
+//! address, and `+`, `-` and `*` with parentheses:
+//!
+//! ```text
+//! [counter] * 2 + 1
+//! [[rb-1]]
+//! ```
+
+use crate::{IntCodeMachine, IntCodeMachineState, Machine, Value};
+use crate::{Result, DEFAULT_MEMORY_LIMIT};
+use std::collections::{BTreeSet, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter;
+
+/// Why the debugger handed control back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// A single step was taken.
+    Step,
+    /// The next instruction is at a breakpoint.
+    Breakpoint(usize),
+    /// The program wants more input than it was given.
+    NeedsInput,
+    Halted,
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Stop::Step => write!(f, "Stepped"),
+            Stop::Breakpoint(address) => write!(f, "Breakpoint at {}", address),
+            Stop::NeedsInput => write!(f, "Waiting for input"),
+            Stop::Halted => write!(f, "Halted"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpressionError {
+    /// Where in the text it went wrong, and why.
+    Syntax(usize, &'static str),
+    UnknownSymbol(String),
+}
+
+impl fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpressionError::Syntax(position, message) => {
+                write!(f, "{} at character {}", message, position + 1)
+            }
+            ExpressionError::UnknownSymbol(name) => write!(f, "Unknown symbol {}", name),
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// Why a watch expression has no value right now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    InvalidAddress(Value),
+    Overflow,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EvalError::InvalidAddress(address) => write!(f, "invalid address {}", address),
+            EvalError::Overflow => write!(f, "overflow"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expression {
+    Number(Value),
+    InstructionPointer,
+    RelativeBase,
+    Cell(Box<Expression>),
+    Add(Box<Expression>, Box<Expression>),
+    Subtract(Box<Expression>, Box<Expression>),
+    Multiply(Box<Expression>, Box<Expression>),
+    Negate(Box<Expression>),
+}
+
+impl Expression {
+    fn evaluate(&self, machine: &IntCodeMachine) -> Result<Value, EvalError> {
+        let binary = |a: &Expression, b: &Expression, op: fn(Value, Value) -> Option<Value>| {
+            op(a.evaluate(machine)?, b.evaluate(machine)?).ok_or(EvalError::Overflow)
+        };
+        match self {
+            Expression::Number(value) => Ok(*value),
+            Expression::InstructionPointer => {
+                Value::try_from(machine.instruction_pointer).map_err(|_| EvalError::Overflow)
+            }
+            Expression::RelativeBase => Ok(machine.relative_base),
+            Expression::Cell(address) => {
+                let address = address.evaluate(machine)?;
+                usize::try_from(address)
+                    .map(|address| machine.read_memory(address))
+                    .map_err(|_| EvalError::InvalidAddress(address))
+            }
+            Expression::Add(a, b) => binary(a, b, Value::checked_add),
+            Expression::Subtract(a, b) => binary(a, b, Value::checked_sub),
+            Expression::Multiply(a, b) => binary(a, b, Value::checked_mul),
+            Expression::Negate(a) => a
+                .evaluate(machine)?
+                .checked_neg()
+                .ok_or(EvalError::Overflow),
+        }
+    }
+}
+
+// Recursive descent over the bytes of the text, symbols are looked up as
+// they're read
+struct Parser<'a> {
+    source: &'a str,
+    text: &'a [u8],
+    position: usize,
+    machine: &'a IntCodeMachine,
+}
+
+type ParseResult = Result<Expression, ExpressionError>;
+
+impl<'a> Parser<'a> {
+    fn skip_spaces(&mut self) {
+        while self.text.get(self.position) == Some(&b' ') {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_spaces();
+        self.text.get(self.position).copied()
+    }
+
+    fn error(&self, message: &'static str) -> ExpressionError {
+        ExpressionError::Syntax(self.position, message)
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), ExpressionError> {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn sum(&mut self) -> ParseResult {
+        let mut left = self.product()?;
+        loop {
+            match self.peek() {
+                Some(b'+') => {
+                    self.position += 1;
+                    left = Expression::Add(Box::new(left), Box::new(self.product()?));
+                }
+                Some(b'-') => {
+                    self.position += 1;
+                    left = Expression::Subtract(Box::new(left), Box::new(self.product()?));
+                }
+                _ => return Ok(left),
+            }
+        }
+    }
+
+    fn product(&mut self) -> ParseResult {
+        let mut left = self.unary()?;
+        while self.peek() == Some(b'*') {
+            self.position += 1;
+            left = Expression::Multiply(Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> ParseResult {
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+            return Ok(Expression::Negate(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> ParseResult {
+        match self.peek() {
+            Some(b'[') => {
+                self.position += 1;
+                let address = self.sum()?;
+                self.expect(b']', "Expected ]")?;
+                Ok(Expression::Cell(Box::new(address)))
+            }
+            Some(b'(') => {
+                self.position += 1;
+                let inner = self.sum()?;
+                self.expect(b')', "Expected )")?;
+                Ok(inner)
+            }
+            Some(byte) if byte.is_ascii_digit() => {
+                let start = self.position;
+                while self.text.get(self.position).is_some_and(u8::is_ascii_digit) {
+                    self.position += 1;
+                }
+                self.source[start..self.position]
+                    .parse()
+                    .map(Expression::Number)
+                    .map_err(|_| ExpressionError::Syntax(start, "Number too large"))
+            }
+            Some(byte) if byte.is_ascii_alphabetic() || byte == b'_' => {
+                let start = self.position;
+                while self
+                    .text
+                    .get(self.position)
+                    .is_some_and(|byte| byte.is_ascii_alphanumeric() || *byte == b'_')
+                {
+                    self.position += 1;
+                }
+                let name = &self.source[start..self.position];
+                match name {
+                    "ip" => Ok(Expression::InstructionPointer),
+                    "rb" => Ok(Expression::RelativeBase),
+                    _ => self
+                        .machine
+                        .symbols()
+                        .and_then(|symbols| symbols.address(name))
+                        .and_then(|address| Value::try_from(address).ok())
+                        .map(Expression::Number)
+                        .ok_or_else(|| ExpressionError::UnknownSymbol(name.to_string())),
+                }
+            }
+            Some(_) => Err(self.error("Unexpected character")),
+            None => Err(self.error("Unexpected end of expression")),
+        }
+    }
+}
+
+/// An expression to show after every stop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watch {
+    pub text: String,
+    expression: Expression,
+}
+
+impl Watch {
+    /// Reads an expression, looking up symbols in the machine's.
+    pub fn parse(text: &str, machine: &IntCodeMachine) -> Result<Self, ExpressionError> {
+        let mut parser = Parser {
+            source: text,
+            text: text.as_bytes(),
+            position: 0,
+            machine,
+        };
+        let expression = parser.sum()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("Unexpected character"));
+        }
+        Ok(Self {
+            text: text.trim().to_string(),
+            expression,
+        })
+    }
+
+    pub fn evaluate(&self, machine: &IntCodeMachine) -> Result<Value, EvalError> {
+        self.expression.evaluate(machine)
+    }
+}
+
+/// Where the debugger stopped, with the value of every watch expression at
+/// that point.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub stop: Stop,
+    pub instruction_pointer: usize,
+    pub watches: Vec<(String, Result<Value, EvalError>)>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} at {}", self.stop, self.instruction_pointer)?;
+        for (text, value) in self.watches.iter() {
+            match value {
+                Ok(value) => writeln!(f, "  {} = {}", text, value)?,
+                Err(error) => writeln!(f, "  {} = <{}>", text, error)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Debugger {
+    machine: IntCodeMachine,
+    input: VecDeque<Value>,
+    output: Vec<Value>,
+    breakpoints: BTreeSet<usize>,
+    watches: Vec<Watch>,
+}
+
+impl Debugger {
+    pub fn new(machine: IntCodeMachine) -> Self {
+        Self {
+            machine,
+            input: VecDeque::new(),
+            output: Vec::new(),
+            breakpoints: BTreeSet::new(),
+            watches: Vec::new(),
+        }
+    }
+
+    pub fn machine(&self) -> &IntCodeMachine {
+        &self.machine
+    }
+
+    /// Queues values for the program to read.
+    pub fn push_input<I: IntoIterator<Item = Value>>(&mut self, input: I) {
+        self.input.extend(input);
+    }
+
+    /// Everything the program printed since the last call.
+    pub fn take_output(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Stops before the instruction at `address` runs. Returns whether
+    /// there wasn't a breakpoint there already.
+    pub fn add_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.insert(address)
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.remove(&address)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Adds an expression to show after every stop, returning its index.
+    pub fn watch(&mut self, text: &str) -> Result<usize, ExpressionError> {
+        self.watches.push(Watch::parse(text, &self.machine)?);
+        Ok(self.watches.len() - 1)
+    }
+
+    pub fn unwatch(&mut self, index: usize) -> Option<Watch> {
+        if index < self.watches.len() {
+            Some(self.watches.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn watches(&self) -> &[Watch] {
+        &self.watches
+    }
+
+    /// Where the machine is now, with the watch expressions evaluated.
+    pub fn report(&self, stop: Stop) -> Report {
+        Report {
+            stop,
+            instruction_pointer: self.machine.instruction_pointer,
+            watches: self
+                .watches
+                .iter()
+                .map(|watch| (watch.text.clone(), watch.evaluate(&self.machine)))
+                .collect(),
+        }
+    }
+
+    // Runs a single instruction, unless the program needs input it doesn't
+    // have or already halted
+    fn step_once(&mut self) -> Result<Stop> {
+        if self.machine.finished() {
+            return Ok(Stop::Halted);
+        }
+        let machine = &mut self.machine;
+        let input = &mut self.input;
+        machine.state = IntCodeMachineState::Running;
+        let code = machine.read_op_code()?;
+        let mut values = iter::from_fn(|| input.pop_front());
+        machine.execute_command(code, &mut values, &mut self.output, DEFAULT_MEMORY_LIMIT)?;
+        Ok(match machine.state {
+            IntCodeMachineState::InputRequired => Stop::NeedsInput,
+            IntCodeMachineState::Finished => {
+                machine.executed += 1;
+                Stop::Halted
+            }
+            IntCodeMachineState::Running => {
+                machine.executed += 1;
+                Stop::Step
+            }
+        })
+    }
+
+    /// Runs the next instruction.
+    pub fn step(&mut self) -> Result<Report> {
+        let stop = self.step_once()?;
+        Ok(self.report(stop))
+    }
+
+    /// Runs until the next instruction is at a breakpoint, or the program
+    /// needs input or halts. A breakpoint where the machine already is
+    /// doesn't count, so continuing from one moves on. Like
+    /// [`IntCodeMachine::execute`], a program that never stops keeps it
+    /// running forever.
+    pub fn resume(&mut self) -> Result<Report> {
+        loop {
+            let stop = self.step_once()?;
+            if stop != Stop::Step {
+                return Ok(self.report(stop));
+            }
+            let address = self.machine.instruction_pointer;
+            if self.breakpoints.contains(&address) {
+                return Ok(self.report(Stop::Breakpoint(address)));
+            }
+        }
+    }
+}
+
+impl From<IntCodeMachine> for Debugger {
+    fn from(machine: IntCodeMachine) -> Self {
+        Self::new(machine)
+    }
+}
+
+impl From<Debugger> for IntCodeMachine {
+    fn from(debugger: Debugger) -> Self {
+        debugger.machine
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Debugger, EvalError, ExpressionError, Stop, Watch};
+    use crate::programs::COUNTDOWN;
+    use crate::symbols::Symbols;
+    use crate::IntCodeMachine;
+
+    #[test]
+    fn test_step() {
+        let mut debugger = Debugger::new(IntCodeMachine::new(COUNTDOWN.to_vec()));
+        let report = debugger.step().unwrap();
+        assert_eq!(report.stop, Stop::NeedsInput);
+        assert_eq!(report.instruction_pointer, 0);
+
+        debugger.push_input(vec![3]);
+        assert_eq!(debugger.step().unwrap().instruction_pointer, 2);
+        assert_eq!(debugger.machine().executed(), 1);
+        assert_eq!(debugger.machine().memory()[20], 3);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = Debugger::new(IntCodeMachine::new(COUNTDOWN.to_vec()));
+        debugger.push_input(vec![3]);
+        assert!(debugger.add_breakpoint(10));
+        assert!(!debugger.add_breakpoint(10));
+        // The loop goes around once for every number counted down
+        for _ in 0..3 {
+            assert_eq!(debugger.resume().unwrap().stop, Stop::Breakpoint(10));
+        }
+        assert!(debugger.remove_breakpoint(10));
+        assert_eq!(debugger.resume().unwrap().stop, Stop::Halted);
+        assert_eq!(debugger.take_output(), [6]);
+        assert_eq!(debugger.step().unwrap().stop, Stop::Halted);
+    }
+
+    #[test]
+    fn test_watches() {
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        let mut symbols = Symbols::new();
+        symbols.insert(19, "sum");
+        machine.set_symbols(symbols);
+        let mut debugger = Debugger::new(machine);
+        debugger.push_input(vec![4]);
+        debugger.add_breakpoint(13);
+        assert_eq!(debugger.watch("[sum]").unwrap(), 0);
+        debugger.watch("[20] * -2 + (ip - 3)").unwrap();
+        debugger.watch("[rb - 1]").unwrap();
+        let report = debugger.resume().unwrap();
+        assert_eq!(report.stop, Stop::Breakpoint(13));
+        assert_eq!(report.watches[0], ("[sum]".to_string(), Ok(10)));
+        assert_eq!(report.watches[1].1, Ok(10));
+        assert_eq!(report.watches[2].1, Err(EvalError::InvalidAddress(-1)));
+        assert_eq!(
+            report.to_string(),
+            "Breakpoint at 13 at 13\n  [sum] = 10\n  [20] * -2 + (ip - 3) = 10\n  \
+             [rb - 1] = <invalid address -1>\n"
+        );
+
+        assert_eq!(debugger.unwatch(2).unwrap().text, "[rb - 1]");
+        assert!(debugger.unwatch(2).is_none());
+        assert_eq!(debugger.step().unwrap().watches.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_expressions() {
+        let machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        assert_eq!(
+            Watch::parse("[1", &machine),
+            Err(ExpressionError::Syntax(2, "Expected ]"))
+        );
+        assert_eq!(
+            Watch::parse("1 +", &machine),
+            Err(ExpressionError::Syntax(3, "Unexpected end of expression"))
+        );
+        assert_eq!(
+            Watch::parse("2 3", &machine),
+            Err(ExpressionError::Syntax(2, "Unexpected character"))
+        );
+        assert_eq!(
+            Watch::parse("counter", &machine),
+            Err(ExpressionError::UnknownSymbol("counter".to_string()))
+        );
+        let overflow = Watch::parse("99999999999 * 99999999999", &machine).unwrap();
+        assert_eq!(overflow.evaluate(&machine), Err(EvalError::Overflow));
+    }
+}
@@ -0,0 +1,454 @@
+//! Pseudo-code for a program, rebuilt from its [control flow
+//! graph](crate::cfg), to read it faster than its disassembly.
+//!
+//! Every function becomes a `fn`, with loops made from the jumps that go
+//! back, and `if` and `else` from the ones that skip forward over code laid
+//! out the way compilers do it. Whatever doesn't fit those shapes is left
+//! as a `goto`. Memory is `mem[12]` and `mem[rb + 3]`, or the name of the
+//! address when the program has symbols:
+//!
+//! ```text
+//! fn main() {
+//!     mem[20] = input();
+//!     do {
+//!         mem[19] += mem[20];
+//!         mem[20] -= 1;
+//!     } while (mem[20]);
+//!     output(mem[19]);
+//!     halt;
+//! }
+//! ```
+
+use crate::cfg::{Block, Condition, Exit, Graph, Op, Operand};
+use crate::symbols::Symbols;
+use crate::Value;
+use std::collections::{BTreeSet, HashSet};
+use std::convert::TryFrom;
+
+const INDENT: &str = "    ";
+
+enum Line {
+    Code(usize, String),
+    Label(usize, usize),
+}
+
+#[derive(Clone, Copy)]
+struct Loop {
+    header: usize,
+    exit: usize,
+}
+
+struct Emitter<'a> {
+    symbols: Option<&'a Symbols>,
+    lines: Vec<Line>,
+    labelled: HashSet<usize>,
+    gotos: BTreeSet<usize>,
+    // Blocks whose exit became part of a loop or an if
+    handled: HashSet<usize>,
+}
+
+impl<'a> Emitter<'a> {
+    fn code(&mut self, depth: usize, text: String) {
+        self.lines.push(Line::Code(depth, text));
+    }
+
+    fn operand(&self, operand: Operand) -> String {
+        match operand {
+            Operand::Immediate(value) => value.to_string(),
+            Operand::Position(address) => usize::try_from(address)
+                .ok()
+                .and_then(|address| self.symbols?.get(address))
+                .map_or_else(|| format!("mem[{}]", address), str::to_string),
+            Operand::Relative(0) => "mem[rb]".to_string(),
+            Operand::Relative(offset) if offset < 0 => format!("mem[rb - {}]", -offset),
+            Operand::Relative(offset) => format!("mem[rb + {}]", offset),
+        }
+    }
+
+    // The condition under which the jump is taken, or isn't
+    fn condition(&self, condition: Condition, taken: bool) -> String {
+        let operand = self.operand(condition.operand);
+        if condition.if_zero == taken {
+            format!("!{}", operand)
+        } else {
+            operand
+        }
+    }
+
+    fn function_name(&self, address: usize) -> String {
+        match self.symbols.and_then(|symbols| symbols.get(address)) {
+            Some(name) => name.to_string(),
+            None if address == 0 => "main".to_string(),
+            None => format!("sub_{}", address),
+        }
+    }
+
+    // `target op= value`, with adding a negative constant written as a
+    // subtraction
+    fn update(&self, target: Operand, op: &str, value: Operand) -> String {
+        match (op, value) {
+            ("+", Operand::Immediate(value)) if value < 0 => {
+                format!("{} -= {};", self.operand(target), -(value as i128))
+            }
+            _ => format!("{} {}= {};", self.operand(target), op, self.operand(value)),
+        }
+    }
+
+    fn statement(&self, op: Op) -> String {
+        let assign =
+            |target: Operand, value: String| format!("{} = {};", self.operand(target), value);
+        let binary = |a: Operand, op: &str, b: Operand| {
+            format!("{} {} {}", self.operand(a), op, self.operand(b))
+        };
+        match op {
+            Op::Add(Operand::Immediate(0), value, target)
+            | Op::Add(value, Operand::Immediate(0), target)
+            | Op::Multiply(Operand::Immediate(1), value, target)
+            | Op::Multiply(value, Operand::Immediate(1), target) => {
+                assign(target, self.operand(value))
+            }
+            Op::Multiply(Operand::Immediate(-1), value, target)
+            | Op::Multiply(value, Operand::Immediate(-1), target) => {
+                assign(target, format!("-{}", self.operand(value)))
+            }
+            Op::Add(a, b, target) if a == target => self.update(target, "+", b),
+            Op::Add(a, b, target) if b == target => self.update(target, "+", a),
+            Op::Multiply(a, b, target) if a == target => self.update(target, "*", b),
+            Op::Multiply(a, b, target) if b == target => self.update(target, "*", a),
+            Op::Add(a, b, target) => assign(target, binary(a, "+", b)),
+            Op::Multiply(a, b, target) => assign(target, binary(a, "*", b)),
+            Op::LessThan(a, b, target) => assign(target, binary(a, "<", b)),
+            Op::Equals(a, b, target) => assign(target, binary(a, "==", b)),
+            Op::Input(target) => assign(target, "input()".to_string()),
+            Op::Output(value) => format!("output({});", self.operand(value)),
+            Op::AdjustRelativeBase(Operand::Immediate(offset)) if offset < 0 => {
+                format!("rb -= {};", -(offset as i128))
+            }
+            Op::AdjustRelativeBase(offset) => format!("rb += {};", self.operand(offset)),
+            // Jumps end blocks, they never show up as statements
+            Op::JumpIfTrue(..) | Op::JumpIfFalse(..) | Op::Halt => String::new(),
+        }
+    }
+
+    fn goto(&mut self, target: usize) -> String {
+        self.gotos.insert(target);
+        format!("goto L{};", target)
+    }
+
+    // What a jump to `target` is called from inside the loop
+    fn jump(&mut self, target: usize, inner: Option<Loop>) -> String {
+        match inner {
+            Some(inner) if inner.exit == target => "break;".to_string(),
+            Some(inner) if inner.header == target => "continue;".to_string(),
+            _ => self.goto(target),
+        }
+    }
+
+    /// Writes `blocks[from..to]`, which are in order of address, at the
+    /// given depth. `inner` is the innermost loop the blocks are in.
+    fn region(
+        &mut self,
+        blocks: &[&Block],
+        from: usize,
+        to: usize,
+        depth: usize,
+        inner: Option<Loop>,
+    ) {
+        let index_of = |address: usize| blocks.iter().position(|block| block.start == address);
+        let mut i = from;
+        while i < to {
+            let block = blocks[i];
+            if self.labelled.insert(block.start) {
+                self.lines.push(Line::Label(depth, block.start));
+            }
+
+            // The last block that jumps back here closes a loop around both
+            let back = (i..to).rev().find(|&j| {
+                !self.handled.contains(&blocks[j].start)
+                    && match blocks[j].exit {
+                        Exit::Jump(target) | Exit::Branch { target, .. } => target == block.start,
+                        _ => false,
+                    }
+            });
+            if let Some(j) = back {
+                self.handled.insert(blocks[j].start);
+                let this = Loop {
+                    header: block.start,
+                    exit: blocks[j].end,
+                };
+                match (blocks[j].exit, block.exit) {
+                    (Exit::Branch { condition, .. }, _) => {
+                        self.code(depth, "do {".to_string());
+                        self.region(blocks, i, j + 1, depth + 1, Some(this));
+                        let condition = self.condition(condition, true);
+                        self.code(depth, format!("}} while ({});", condition));
+                    }
+                    // A loop that only tests its condition at the top
+                    (
+                        _,
+                        Exit::Branch {
+                            condition, target, ..
+                        },
+                    ) if i < j
+                        && block.instructions.is_empty()
+                        && target == this.exit
+                        && !self.handled.contains(&block.start) =>
+                    {
+                        self.handled.insert(block.start);
+                        let condition = self.condition(condition, false);
+                        self.code(depth, format!("while ({}) {{", condition));
+                        self.region(blocks, i + 1, j + 1, depth + 1, Some(this));
+                        self.code(depth, "}".to_string());
+                    }
+                    _ => {
+                        self.code(depth, "loop {".to_string());
+                        self.region(blocks, i, j + 1, depth + 1, Some(this));
+                        self.code(depth, "}".to_string());
+                    }
+                }
+                i = j + 1;
+                continue;
+            }
+
+            for instruction in block.instructions.iter() {
+                let statement = self.statement(instruction.op);
+                self.code(depth, statement);
+            }
+            if self.handled.contains(&block.start) {
+                i += 1;
+                continue;
+            }
+            match block.exit {
+                Exit::Next(_) => {}
+                Exit::Halt => self.code(depth, "halt;".to_string()),
+                Exit::Invalid(address) => self.code(
+                    depth,
+                    format!("invalid(); // no instruction at {}", address),
+                ),
+                Exit::Call { target, .. } => {
+                    let name = self.function_name(target);
+                    self.code(depth, format!("{}();", name));
+                }
+                Exit::Computed {
+                    condition, target, ..
+                } => {
+                    let jump = format!("goto *{};", self.operand(target));
+                    let line = match condition {
+                        Some(condition) => {
+                            format!("if ({}) {}", self.condition(condition, true), jump)
+                        }
+                        None => jump,
+                    };
+                    self.code(depth, line);
+                }
+                Exit::Jump(target) => {
+                    // Running on into the next block needs no jump
+                    let next = blocks.get(i + 1).filter(|_| i + 1 < to);
+                    let is_loop_jump =
+                        inner.is_some_and(|inner| target == inner.exit || target == inner.header);
+                    if is_loop_jump || next.is_none_or(|next| next.start != target) {
+                        let line = self.jump(target, inner);
+                        self.code(depth, line);
+                    }
+                }
+                Exit::Branch {
+                    condition, target, ..
+                } => {
+                    let skip = index_of(target).filter(|&k| i < k && k <= to);
+                    let is_loop_jump =
+                        inner.is_some_and(|inner| target == inner.exit || target == inner.header);
+                    match skip {
+                        Some(k) if !is_loop_jump => {
+                            // The then part ends by jumping over an else part
+                            let otherwise = match blocks[k - 1].exit {
+                                Exit::Jump(end) if k - 1 > i => {
+                                    index_of(end).filter(|&m| k < m && m <= to)
+                                }
+                                _ => None,
+                            };
+                            let condition = self.condition(condition, false);
+                            self.code(depth, format!("if ({}) {{", condition));
+                            match otherwise {
+                                Some(m) => {
+                                    self.handled.insert(blocks[k - 1].start);
+                                    self.region(blocks, i + 1, k, depth + 1, inner);
+                                    self.code(depth, "} else {".to_string());
+                                    self.region(blocks, k, m, depth + 1, inner);
+                                    self.code(depth, "}".to_string());
+                                    i = m;
+                                }
+                                None => {
+                                    self.region(blocks, i + 1, k, depth + 1, inner);
+                                    self.code(depth, "}".to_string());
+                                    i = k;
+                                }
+                            }
+                            continue;
+                        }
+                        _ => {
+                            let condition = self.condition(condition, true);
+                            let jump = self.jump(target, inner);
+                            self.code(depth, format!("if ({}) {}", condition, jump));
+                        }
+                    }
+                }
+            }
+            i += 1;
+        }
+    }
+
+    fn function(&mut self, graph: &Graph, entry: usize) {
+        let blocks = graph.function(entry);
+        self.code(0, format!("fn {}() {{", self.function_name(entry)));
+        // Code before the entry point was jumped back to from after it
+        if blocks.first().is_some_and(|block| block.start != entry) {
+            let line = self.goto(entry);
+            self.code(1, line);
+        }
+        self.region(&blocks, 0, blocks.len(), 1, None);
+        self.code(0, "}".to_string());
+    }
+
+    fn finish(self) -> String {
+        let mut text = String::new();
+        for line in self.lines {
+            match line {
+                Line::Code(depth, code) => {
+                    text.push_str(&INDENT.repeat(depth));
+                    text.push_str(&code);
+                    text.push('\n');
+                }
+                // Labels go half an indent to the left of the code
+                Line::Label(depth, address) if self.gotos.contains(&address) => {
+                    text.push_str(&INDENT.repeat(depth.saturating_sub(1)));
+                    text.push_str(&format!("L{}:\n", address));
+                }
+                Line::Label(..) => {}
+            }
+        }
+        text
+    }
+}
+
+/// Pseudo-code for every function of the program, starting with `main`.
+pub fn decompile(program: &[Value], symbols: Option<&Symbols>) -> String {
+    let graph = Graph::build(program);
+    let mut emitter = Emitter {
+        symbols,
+        lines: Vec::new(),
+        labelled: HashSet::new(),
+        gotos: BTreeSet::new(),
+        handled: HashSet::new(),
+    };
+    for (index, entry) in graph.functions().enumerate() {
+        if index > 0 {
+            emitter.lines.push(Line::Code(0, String::new()));
+        }
+        emitter.function(&graph, entry);
+    }
+    emitter.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::decompile;
+    use crate::compiler::compile;
+    use crate::programs::COUNTDOWN;
+    use crate::symbols::Symbols;
+
+    #[test]
+    fn test_do_while() {
+        assert_eq!(
+            decompile(&COUNTDOWN, None),
+            "fn main() {
+    mem[20] = input();
+    do {
+        mem[19] += mem[20];
+        mem[20] -= 1;
+    } while (mem[20]);
+    output(mem[19]);
+    halt;
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_if_else() {
+        let program = compile("in x; if x < 5 { out 1; } else { out 2; }").unwrap();
+        let mut symbols = Symbols::new();
+        symbols.insert(17, "x");
+        assert_eq!(
+            decompile(&program, Some(&symbols)),
+            "fn main() {
+    x = input();
+    mem[18] = x < 5;
+    if (mem[18]) {
+        output(1);
+    } else {
+        output(2);
+    }
+    halt;
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_while() {
+        let program = compile("in n; while n { out n; n = n - 1; }").unwrap();
+        let text = decompile(&program, None);
+        assert!(text.contains("    while (mem["), "{}", text);
+        assert!(!text.contains("goto"), "{}", text);
+
+        // A condition worked out at the top of the loop is tested inside
+        let program = compile("in n; while n > 0 { n = n - 1; } out n;").unwrap();
+        let text = decompile(&program, None);
+        assert!(text.contains("    loop {\n"), "{}", text);
+        assert!(text.contains("break;"), "{}", text);
+        assert!(!text.contains("goto"), "{}", text);
+    }
+
+    #[test]
+    fn test_calls() {
+        let program = [
+            109, 50, // arb 50
+            21101, 0, 9, 0, // add 0, 9, [rb+0]
+            1106, 0, 11, // jz 0, 11
+            99, 0, // halt, then data
+            104, 42, // out 42
+            2106, 0, 0, // jz 0, [rb+0]
+        ];
+        assert_eq!(
+            decompile(&program, None),
+            "fn main() {
+    rb += 50;
+    mem[rb] = 9;
+    sub_11();
+    halt;
+}
+
+fn sub_11() {
+    output(42);
+    goto *mem[rb];
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_goto() {
+        // Jumps into the middle of a loop from before it
+        let program = [
+            3, 20, // in [20]
+            1006, 20, 7, // jz [20], 7
+            104, 1, // out 1
+            1001, 20, -1, 20, // add [20], -1, [20]
+            1005, 20, 5, // jnz [20], 5
+            99,
+        ];
+        let text = decompile(&program, None);
+        assert!(text.contains("if (mem[20]) goto L5;\n"), "{}", text);
+        assert!(text.contains("L5:\n"), "{}", text);
+    }
+}
@@ -0,0 +1,171 @@
+//! Where two programs differ, cell by cell, with the instructions the cells
+//! belong to. Useful to compare the inputs of two people, or a program
+//! before and after patching it.
+
+use crate::disassemble::{disassemble, Instruction};
+use crate::Value;
+use std::cmp::Ordering;
+
+/// A cell that differs between the programs, `None` where a program is too
+/// short to have it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub address: usize,
+    pub first: Option<Value>,
+    pub second: Option<Value>,
+}
+
+pub fn differences(first: &[Value], second: &[Value]) -> Vec<Difference> {
+    (0..first.len().max(second.len()))
+        .filter_map(|address| {
+            let (a, b) = (first.get(address), second.get(address));
+            if a == b {
+                None
+            } else {
+                Some(Difference {
+                    address,
+                    first: a.copied(),
+                    second: b.copied(),
+                })
+            }
+        })
+        .collect()
+}
+
+// The instruction of a listing that holds the cell at `address`
+fn containing(listing: &[Instruction], address: usize) -> &Instruction {
+    let index = listing.partition_point(|instruction| instruction.address <= address);
+    &listing[index - 1]
+}
+
+// What a cell is to the instruction holding it
+fn role(instruction: &Instruction, address: usize) -> String {
+    match address - instruction.address {
+        _ if instruction.text.starts_with("data") => "data".to_string(),
+        0 => "op code".to_string(),
+        operand => format!("operand {}", operand),
+    }
+}
+
+fn extra_cells(name: &str, longer: usize, shorter: usize) -> String {
+    format!(
+        "The {} program has {} more cells, from address {}\n",
+        name,
+        longer - shorter,
+        shorter
+    )
+}
+
+/// The differences of the cells both programs have, grouped by the
+/// instruction of the first program they are in. Each group shows what the
+/// cells are to the instruction, then the instruction in both programs.
+pub fn report(first: &[Value], second: &[Value]) -> String {
+    let first_listing = disassemble(first);
+    let second_listing = disassemble(second);
+    let mut groups: Vec<(&Instruction, Vec<&Difference>)> = Vec::new();
+    let found = differences(first, second);
+    let shared = found
+        .iter()
+        .filter(|difference| difference.first.is_some() && difference.second.is_some());
+    for difference in shared {
+        let instruction = containing(&first_listing, difference.address);
+        match groups.last_mut() {
+            Some((last, cells)) if *last == instruction => cells.push(difference),
+            _ => groups.push((instruction, vec![difference])),
+        }
+    }
+
+    let mut out = String::new();
+    for (instruction, cells) in &groups {
+        let changes: Vec<String> = cells
+            .iter()
+            .map(|cell| {
+                format!(
+                    "{} {} -> {}",
+                    role(instruction, cell.address),
+                    cell.first.unwrap_or_default(),
+                    cell.second.unwrap_or_default()
+                )
+            })
+            .collect();
+        out.push_str(&format!(
+            "@{}: {}\n",
+            instruction.address,
+            changes.join(", ")
+        ));
+        out.push_str(&format!("- {}\n", instruction));
+        let mut shown = Vec::new();
+        for cell in cells {
+            let other = containing(&second_listing, cell.address);
+            if !shown.contains(&other) {
+                out.push_str(&format!("+ {}\n", other));
+                shown.push(other);
+            }
+        }
+    }
+    match first.len().cmp(&second.len()) {
+        Ordering::Greater => out.push_str(&extra_cells("first", first.len(), second.len())),
+        Ordering::Less => out.push_str(&extra_cells("second", second.len(), first.len())),
+        Ordering::Equal => {}
+    }
+    if found.is_empty() {
+        out.push_str("The programs are the same\n");
+    } else {
+        out.push_str(&format!(
+            "{} cells differ, in {} instructions\n",
+            found.len(),
+            groups.len()
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{differences, report, Difference};
+    use crate::programs::COUNTDOWN;
+
+    #[test]
+    fn test_differences() {
+        assert_eq!(
+            differences(&[1, 2, 3], &[1, 5]),
+            [
+                Difference {
+                    address: 1,
+                    first: Some(2),
+                    second: Some(5),
+                },
+                Difference {
+                    address: 2,
+                    first: Some(3),
+                    second: None,
+                },
+            ]
+        );
+        assert!(differences(&COUNTDOWN, &COUNTDOWN).is_empty());
+    }
+
+    #[test]
+    fn test_report() {
+        assert_eq!(
+            report(&COUNTDOWN, &COUNTDOWN),
+            "The programs are the same\n"
+        );
+        let mut patched = COUNTDOWN.to_vec();
+        // Count down by two, and print from another address
+        patched[8] = -2;
+        patched[13] = 104;
+        patched.push(7);
+        assert_eq!(
+            report(&COUNTDOWN, &patched),
+            "@6: operand 2 -1 -> -2\n\
+             -     6: add [20], -1, [20]\n\
+             +     6: add [20], -2, [20]\n\
+             @13: op code 4 -> 104\n\
+             -    13: out [19]\n\
+             +    13: out 19\n\
+             The second program has 1 more cells, from address 21\n\
+             3 cells differ, in 2 instructions\n"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! A listing of a program's instructions, for reading programs and showing
+//! where two of them differ.
+//!
+//! The program is read from the start, one instruction after the other, so
+//! data between instructions can throw the listing off until an instruction
+//! happens to line up again. Cells that aren't an instruction are listed as
+//! `data`.
+//!
+//! Parameters in position mode are written `[12]`, relative ones `[rb+3]`
+//! and immediate ones as the plain value.
+
+use crate::{OpCode, ParameterMode, Value};
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instruction {
+    pub address: usize,
+    /// How many cells the instruction takes, including its op code.
+    pub length: usize,
+    pub text: String,
+}
+
+impl Instruction {
+    pub fn contains(&self, address: usize) -> bool {
+        (self.address..self.address + self.length).contains(&address)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:>5}: {}", self.address, self.text)
+    }
+}
+
+fn parameter(mode: &ParameterMode, value: Value) -> String {
+    match mode {
+        ParameterMode::Reference => format!("[{}]", value),
+        ParameterMode::Immediate => value.to_string(),
+        ParameterMode::Relative => format!("[rb{:+}]", value),
+    }
+}
+
+fn mnemonic(code: &OpCode) -> (&'static str, Vec<&ParameterMode>) {
+    match code {
+        OpCode::Add(a, b, c) => ("add", vec![a, b, c]),
+        OpCode::Multiply(a, b, c) => ("mul", vec![a, b, c]),
+        OpCode::Input(a) => ("in", vec![a]),
+        OpCode::Output(a) => ("out", vec![a]),
+        OpCode::JumpIfTrue(a, b) => ("jnz", vec![a, b]),
+        OpCode::JumpIfFalse(a, b) => ("jz", vec![a, b]),
+        OpCode::LessThan(a, b, c) => ("lt", vec![a, b, c]),
+        OpCode::Equals(a, b, c) => ("eq", vec![a, b, c]),
+        OpCode::AdjustRelativeBase(a) => ("arb", vec![a]),
+        OpCode::Exit => ("halt", vec![]),
+    }
+}
+
+/// The instruction at `address`, or a single cell of data when there is no
+/// valid one there.
+pub fn instruction_at(program: &[Value], address: usize) -> Instruction {
+    let data = || Instruction {
+        address,
+        length: 1,
+        text: format!("data {}", program[address]),
+    };
+    let code = match OpCode::try_from(program[address]) {
+        Ok(code) => code,
+        Err(_) => return data(),
+    };
+    let (name, modes) = mnemonic(&code);
+    let operands = match program.get(address + 1..address + 1 + modes.len()) {
+        Some(operands) => operands,
+        None => return data(),
+    };
+    let parameters: Vec<String> = modes
+        .iter()
+        .zip(operands)
+        .map(|(mode, value)| parameter(mode, *value))
+        .collect();
+    let text = if parameters.is_empty() {
+        name.to_string()
+    } else {
+        format!("{} {}", name, parameters.join(", "))
+    };
+    Instruction {
+        address,
+        length: modes.len() + 1,
+        text,
+    }
+}
+
+/// Every instruction of the program, in order.
+pub fn disassemble(program: &[Value]) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut address = 0;
+    while address < program.len() {
+        let instruction = instruction_at(program, address);
+        address += instruction.length;
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+#[cfg(test)]
+mod test {
+    use super::{disassemble, instruction_at};
+    use crate::programs::COUNTDOWN;
+
+    #[test]
+    fn test_instruction_at() {
+        let program = [1101, 2, -3, 5, 204, 7, 99, 1234, 1];
+        assert_eq!(instruction_at(&program, 0).text, "add 2, -3, [5]");
+        assert_eq!(instruction_at(&program, 4).text, "out [rb+7]");
+        assert_eq!(instruction_at(&program, 6).text, "halt");
+        assert_eq!(instruction_at(&program, 7).text, "data 1234");
+        // An instruction cut short by the end of the program is data
+        assert_eq!(instruction_at(&program, 8).text, "data 1");
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let listing = disassemble(&COUNTDOWN);
+        let text: Vec<String> = listing.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            text[..7],
+            [
+                "    0: in [20]",
+                "    2: add [19], [20], [19]",
+                "    6: add [20], -1, [20]",
+                "   10: jnz [20], 2",
+                "   13: out [19]",
+                "   15: halt",
+                "   16: data 0",
+            ]
+        );
+        let length: usize = listing.iter().map(|instruction| instruction.length).sum();
+        assert_eq!(length, COUNTDOWN.len());
+        assert!(listing[1].contains(5) && !listing[1].contains(6));
+    }
+}
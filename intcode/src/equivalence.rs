@@ -0,0 +1,163 @@
+//! Checking that two programs behave the same, by running both on the same
+//! inputs and comparing what they print. Useful after optimizing a program
+//! or patching it by hand.
+
+use crate::rng::Rng;
+use crate::{IntCodeError, IntCodeMachine, Machine};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+/// How a run of a program ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The program halted, printing the output.
+    Halted(Vec<isize>),
+    /// The program ran out of input, printing the output until then.
+    NeedsInput(Vec<isize>),
+    /// The program was still going when the instruction budget ran out.
+    OutOfBudget,
+    /// An addition, multiplication or relative address overflowed.
+    Overflowed,
+    /// The program failed, with the error's message.
+    Failed(String),
+}
+
+impl Outcome {
+    fn of(program: &[isize], input: &[isize], budget: u64) -> Self {
+        let mut machine = IntCodeMachine::new(program.to_vec());
+        match machine.execute_limited(input.iter().copied(), budget) {
+            Ok(output) if machine.finished() => Outcome::Halted(output),
+            Ok(output) => Outcome::NeedsInput(output),
+            Err(IntCodeError::InstructionLimit(_)) => Outcome::OutOfBudget,
+            Err(IntCodeError::Overflow) => Outcome::Overflowed,
+            Err(error) => Outcome::Failed(error.to_string()),
+        }
+    }
+}
+
+/// An input the two programs disagree on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    pub input: Vec<isize>,
+    pub first: Outcome,
+    pub second: Outcome,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "On {:?} the first program gave {:?} and the second {:?}",
+            self.input, self.first, self.second
+        )
+    }
+}
+
+/// Runs both programs on every input, each for at most `budget`
+/// instructions, returning the inputs where they end up differently. Two
+/// runs that both exhaust the budget aren't counted as different, there's
+/// no telling what they would have printed.
+pub fn check<I>(first: &[isize], second: &[isize], inputs: I, budget: u64) -> Vec<Difference>
+where
+    I: IntoIterator<Item = Vec<isize>>,
+{
+    inputs
+        .into_iter()
+        .filter_map(|input| {
+            let first = Outcome::of(first, &input, budget);
+            let second = Outcome::of(second, &input, budget);
+            if first == second {
+                None
+            } else {
+                Some(Difference {
+                    input,
+                    first,
+                    second,
+                })
+            }
+        })
+        .collect()
+}
+
+/// `count` inputs of `length` values each, taken from `range`. The same seed
+/// always gives the same inputs, so a difference can be looked at again.
+/// Panics if `range` is empty.
+pub fn random_inputs(
+    seed: u64,
+    count: usize,
+    length: usize,
+    range: RangeInclusive<isize>,
+) -> Vec<Vec<isize>> {
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|_| (0..length).map(|_| rng.range(range.clone())).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check, random_inputs, Outcome};
+    use crate::programs::{
+        COUNTDOWN, EQUAL_TO_8_IMMEDIATE, EQUAL_TO_8_POSITION, LESS_THAN_8_IMMEDIATE,
+    };
+
+    #[test]
+    fn test_equivalent_programs() {
+        let inputs = random_inputs(7, 50, 1, -20..=20);
+        assert!(check(&EQUAL_TO_8_POSITION, &EQUAL_TO_8_IMMEDIATE, inputs, 100).is_empty());
+    }
+
+    #[test]
+    fn test_differences() {
+        let inputs = (0..10).map(|input| vec![input]);
+        let differences = check(&EQUAL_TO_8_IMMEDIATE, &LESS_THAN_8_IMMEDIATE, inputs, 100);
+        // The two only agree on 9
+        assert_eq!(differences.len(), 9);
+        assert_eq!(differences[0].input, [0]);
+        assert_eq!(differences[0].first, Outcome::Halted(vec![0]));
+        assert_eq!(differences[0].second, Outcome::Halted(vec![1]));
+    }
+
+    #[test]
+    fn test_budget() {
+        // Both wait for input when given none, which is the same
+        let inputs = vec![vec![1_000], vec![]];
+        let differences = check(&COUNTDOWN, &EQUAL_TO_8_IMMEDIATE, inputs, 50);
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].first, Outcome::OutOfBudget);
+        assert_eq!(differences[0].second, Outcome::Halted(vec![0]));
+    }
+
+    #[test]
+    fn test_overflow() {
+        // Doubles the input, which overflows on the largest values
+        let doubled = [3, 9, 1002, 9, 2, 9, 4, 9, 99, 0];
+        let added = [3, 11, 1, 11, 11, 12, 4, 12, 99, 0, 0, 0, 0];
+        let inputs = vec![vec![21], vec![isize::MAX]];
+        assert!(check(&doubled, &added, inputs.clone(), 100).is_empty());
+        assert_eq!(Outcome::of(&doubled, &inputs[1], 100), Outcome::Overflowed);
+        assert_eq!(
+            Outcome::of(&doubled, &inputs[0], 100),
+            Outcome::Halted(vec![42])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_empty_range() {
+        let (start, end) = (2, 1);
+        random_inputs(1, 1, 1, start..=end);
+    }
+
+    #[test]
+    fn test_random_inputs() {
+        let inputs = random_inputs(1, 20, 3, -2..=2);
+        assert_eq!(inputs.len(), 20);
+        assert!(inputs
+            .iter()
+            .flatten()
+            .all(|value| (-2..=2).contains(value)));
+        assert_eq!(inputs, random_inputs(1, 20, 3, -2..=2));
+        assert_ne!(inputs, random_inputs(2, 20, 3, -2..=2));
+    }
+}
@@ -0,0 +1,273 @@
+//! Random programs that are sure to halt, for fuzzing the interpreter.
+//!
+//! Programs are built from blocks of arithmetic, comparisons, input and
+//! output, with jumps that only go forward inside their block and loops
+//! that run a fixed number of times on a counter nothing else writes to.
+//! Nothing writes to the code either, so every program halts, and the
+//! generator knows how many instructions it can run at most.
+//!
+//! Values only double, by adding two cells or multiplying by 2, a limited
+//! number of times over a whole run, so nothing overflows as long as the
+//! inputs stay within a million either way.
+
+use crate::rng::Rng;
+use crate::Value;
+
+/// Cells the instructions read and write, after the code.
+pub const DATA_CELLS: usize = 8;
+
+// Worst case of times a value can double over a run
+const MAX_DOUBLINGS: u64 = 40;
+
+const POSITION: Value = 0;
+const IMMEDIATE: Value = 1;
+const RELATIVE: Value = 2;
+
+#[derive(Debug, Clone, Copy)]
+enum Word {
+    Value(Value),
+    // The address of a data cell, loop counters come after the data cells
+    Cell(usize),
+    Label(usize),
+}
+
+/// A generated program, with what it needs to run to the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated {
+    pub program: Vec<Value>,
+    /// Most instructions the program can run, the halt included.
+    pub max_steps: u64,
+    /// Most input the program can read.
+    pub max_inputs: u64,
+}
+
+/// Makes up programs from a seed. The same seed and settings always give
+/// the same programs.
+pub struct Generator {
+    rng: Rng,
+    /// Most statements in a block.
+    pub block_length: usize,
+    /// How deep loops can nest.
+    pub max_depth: usize,
+    /// Most times a loop runs.
+    pub max_iterations: Value,
+    code: Vec<Word>,
+    labels: Vec<Option<usize>>,
+    doublings: u64,
+}
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: Rng::new(seed),
+            block_length: 8,
+            max_depth: 2,
+            max_iterations: 4,
+            code: Vec::new(),
+            labels: Vec::new(),
+            doublings: 0,
+        }
+    }
+
+    pub fn generate(&mut self) -> Generated {
+        self.code.clear();
+        self.labels.clear();
+        self.doublings = 0;
+
+        // Relative parameters address the data cells too
+        self.emit(9, &[(IMMEDIATE, Word::Cell(0))]);
+        let (steps, inputs) = self.block(0, 1);
+        self.code.push(Word::Value(99));
+
+        let data = self.code.len();
+        let mut program: Vec<Value> = self
+            .code
+            .iter()
+            .map(|word| match *word {
+                Word::Value(value) => value,
+                Word::Cell(index) => (data + index) as Value,
+                Word::Label(label) => self.labels[label].expect("label was never placed") as Value,
+            })
+            .collect();
+        program.resize(data + DATA_CELLS + self.max_depth, 0);
+        Generated {
+            program,
+            max_steps: steps + 2,
+            max_inputs: inputs,
+        }
+    }
+
+    fn emit(&mut self, opcode: Value, parameters: &[(Value, Word)]) {
+        let modes = parameters
+            .iter()
+            .rev()
+            .fold(0, |modes, (mode, _)| modes * 10 + mode);
+        self.code.push(Word::Value(opcode + modes * 100));
+        self.code.extend(parameters.iter().map(|(_, word)| *word));
+    }
+
+    fn label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    fn place(&mut self, label: usize) {
+        self.labels[label] = Some(self.code.len());
+    }
+
+    fn read(&mut self) -> (Value, Word) {
+        match self.rng.below(3) {
+            0 => (IMMEDIATE, Word::Value(self.rng.range(-100..=100))),
+            1 => self.write(),
+            _ => (POSITION, Word::Cell(self.rng.below(DATA_CELLS))),
+        }
+    }
+
+    fn write(&mut self) -> (Value, Word) {
+        let cell = self.rng.below(DATA_CELLS);
+        if self.rng.below(2) == 0 {
+            (RELATIVE, Word::Value(cell as Value))
+        } else {
+            (POSITION, Word::Cell(cell))
+        }
+    }
+
+    // Takes a doubling from the budget, if there's one left for every time
+    // this code can run
+    fn double(&mut self, multiplier: u64) -> bool {
+        if self.doublings + multiplier <= MAX_DOUBLINGS {
+            self.doublings += multiplier;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Emits a block of statements, returning the most instructions it can
+    /// run and the most input it can read. `multiplier` is how many times
+    /// the enclosing loops run it.
+    fn block(&mut self, depth: usize, multiplier: u64) -> (u64, u64) {
+        let length = 1 + self.rng.below(self.block_length);
+        // Labels at the boundaries between statements, for forward jumps
+        let mut boundaries: Vec<Option<usize>> = vec![None; length + 1];
+        let mut steps = 0;
+        let mut inputs = 0;
+        for index in 0..length {
+            if let Some(label) = boundaries[index] {
+                self.place(label);
+            }
+            match self.rng.below(10) {
+                0..=2 => {
+                    let left = self.read();
+                    let mut right = self.read();
+                    let both_cells = left.0 != IMMEDIATE && right.0 != IMMEDIATE;
+                    if both_cells && !self.double(multiplier) {
+                        right = (IMMEDIATE, Word::Value(self.rng.range(-100..=100)));
+                    }
+                    let result = self.write();
+                    self.emit(1, &[left, right, result]);
+                }
+                3 => {
+                    let value = self.read();
+                    let mut factor = self.rng.range(-2..=2);
+                    if factor.abs() == 2 && !self.double(multiplier) {
+                        factor /= 2;
+                    }
+                    let result = self.write();
+                    self.emit(2, &[value, (IMMEDIATE, Word::Value(factor)), result]);
+                }
+                4 | 5 => {
+                    let opcode = 7 + self.rng.below(2) as Value;
+                    let left = self.read();
+                    let right = self.read();
+                    let result = self.write();
+                    self.emit(opcode, &[left, right, result]);
+                }
+                6 => {
+                    let cell = self.write();
+                    self.emit(3, &[cell]);
+                    inputs += 1;
+                }
+                7 => {
+                    let value = self.read();
+                    self.emit(4, &[value]);
+                }
+                8 => {
+                    let target = index + 1 + self.rng.below(length - index);
+                    let label = match boundaries[target] {
+                        Some(label) => label,
+                        None => {
+                            let label = self.label();
+                            boundaries[target] = Some(label);
+                            label
+                        }
+                    };
+                    let opcode = 5 + self.rng.below(2) as Value;
+                    let condition = self.read();
+                    self.emit(opcode, &[condition, (IMMEDIATE, Word::Label(label))]);
+                }
+                _ if depth < self.max_depth => {
+                    let (loop_steps, loop_inputs) = self.repeat(depth, multiplier);
+                    steps += loop_steps;
+                    inputs += loop_inputs;
+                    continue;
+                }
+                _ => {
+                    let value = self.read();
+                    self.emit(4, &[value]);
+                }
+            }
+            steps += 1;
+        }
+        if let Some(label) = boundaries[length] {
+            self.place(label);
+        }
+        (steps, inputs)
+    }
+
+    fn repeat(&mut self, depth: usize, multiplier: u64) -> (u64, u64) {
+        let iterations = self.rng.range(1..=self.max_iterations);
+        let counter = (POSITION, Word::Cell(DATA_CELLS + depth));
+        let start = self.label();
+        let value = |value| (IMMEDIATE, Word::Value(value));
+
+        self.emit(1, &[value(iterations), value(0), counter]);
+        self.place(start);
+        let (steps, inputs) = self.block(depth + 1, multiplier * iterations as u64);
+        self.emit(1, &[counter, value(-1), counter]);
+        self.emit(5, &[counter, (IMMEDIATE, Word::Label(start))]);
+
+        let iterations = iterations as u64;
+        (1 + iterations * (steps + 2), iterations * inputs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Generator;
+    use crate::equivalence::random_inputs;
+    use crate::{IntCodeMachine, Machine};
+
+    #[test]
+    fn test_programs_halt() {
+        let mut generator = Generator::new(2019);
+        for seed in 0..500 {
+            let generated = generator.generate();
+            let length = generated.max_inputs as usize;
+            let input = random_inputs(seed, 1, length, -1_000_000..=1_000_000).remove(0);
+            let mut machine = IntCodeMachine::new(generated.program.clone());
+            machine
+                .execute_limited(input, generated.max_steps)
+                .unwrap_or_else(|error| panic!("{:?} failed: {}", generated, error));
+            assert!(machine.finished());
+            assert!(machine.executed() <= generated.max_steps);
+        }
+    }
+
+    #[test]
+    fn test_seeds() {
+        let first = Generator::new(5).generate();
+        assert_eq!(first, Generator::new(5).generate());
+        assert_ne!(first, Generator::new(6).generate());
+    }
+}
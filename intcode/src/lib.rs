@@ -1,8 +1,41 @@
+use log::{debug, log_enabled, trace, Level};
 use std::boxed::Box;
+use std::collections::VecDeque;
 use std::convert::{From, TryFrom, TryInto};
 use std::fmt;
 use std::io::BufRead;
 use std::num::TryFromIntError;
+use std::ops::BitOr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use symbols::Symbols;
+
+pub mod amplifier;
+pub mod ascii;
+pub mod assembler;
+pub mod cancel;
+pub mod cfg;
+pub mod compiler;
+pub mod debugger;
+pub mod decompile;
+pub mod diff;
+pub mod disassemble;
+pub mod equivalence;
+pub mod generate;
+pub mod linker;
+pub mod pace;
+pub mod paint;
+pub mod programs;
+pub mod replay;
+mod rng;
+pub mod schedule;
+pub mod screen;
+pub mod search;
+pub mod share;
+#[cfg(feature = "memory-stats")]
+pub mod stats;
+pub mod stream;
+pub mod symbols;
 
 type ParseError = Box<dyn std::error::Error>;
 
@@ -58,6 +91,29 @@ where
     }
 }
 
+/// `first | second` feeds the output of the first machine into the second,
+/// like [`Machine::pipe`].
+impl<M: Machine> BitOr<M> for IntCodeMachine {
+    type Output = Pipe<IntCodeMachine, M>;
+
+    fn bitor(self, other: M) -> Self::Output {
+        self.pipe(other)
+    }
+}
+
+impl<M1, M2, M> BitOr<M> for Pipe<M1, M2>
+where
+    M1: Machine,
+    M2: Machine,
+    M: Machine,
+{
+    type Output = Pipe<Pipe<M1, M2>, M>;
+
+    fn bitor(self, other: M) -> Self::Output {
+        self.pipe(other)
+    }
+}
+
 impl Machine for Pipe<Box<dyn Machine>, Box<dyn Machine>> {
     fn execute(&mut self, input: Vec<Value>) -> Result<Vec<Value>> {
         let out = self.first.execute(input)?;
@@ -77,6 +133,14 @@ pub enum IntCodeError {
     ImmediateModeOutput,
     UnexpectedEndOfFile,
     InputError,
+    /// The program printed something it shouldn't have, or nothing at all.
+    UnexpectedOutput(Vec<Value>),
+    InstructionLimit(u64),
+    /// An addition, multiplication or relative address didn't fit in a
+    /// [`Value`].
+    Overflow,
+    Timeout(Box<Snapshot>),
+    Cancelled(Box<Snapshot>),
 }
 
 type Result<T, E = IntCodeError> = std::result::Result<T, E>;
@@ -91,9 +155,27 @@ impl fmt::Display for IntCodeError {
             IntCodeError::InvalidAddress => write!(f, "Found invalid address"),
             IntCodeError::InputError => write!(f, "Expected input, found none"),
             IntCodeError::UnexpectedEndOfFile => write!(f, "Unexpected end of file"),
+            IntCodeError::UnexpectedOutput(output) if output.is_empty() => {
+                write!(f, "Expected output, found none")
+            }
+            IntCodeError::UnexpectedOutput(output) => write!(f, "Unexpected output {:?}", output),
             IntCodeError::ImmediateModeOutput => {
                 write!(f, "Instruction was set to output in immediate mode")
             }
+            IntCodeError::InstructionLimit(limit) => {
+                write!(f, "Still running after {} instructions", limit)
+            }
+            IntCodeError::Overflow => write!(f, "Arithmetic overflow"),
+            IntCodeError::Timeout(snapshot) => write!(
+                f,
+                "Timed out at instruction {} after {} instructions",
+                snapshot.instruction_pointer, snapshot.executed
+            ),
+            IntCodeError::Cancelled(snapshot) => write!(
+                f,
+                "Cancelled at instruction {} after {} instructions",
+                snapshot.instruction_pointer, snapshot.executed
+            ),
         }
     }
 }
@@ -106,11 +188,14 @@ impl From<TryFromIntError> for IntCodeError {
 
 impl std::error::Error for IntCodeError {}
 
+#[derive(Debug)]
 enum ParameterMode {
     Reference,
     Immediate,
+    Relative,
 }
 
+#[derive(Debug)]
 enum OpCode {
     Add(ParameterMode, ParameterMode, ParameterMode),
     Multiply(ParameterMode, ParameterMode, ParameterMode),
@@ -120,6 +205,7 @@ enum OpCode {
     JumpIfFalse(ParameterMode, ParameterMode),
     LessThan(ParameterMode, ParameterMode, ParameterMode),
     Equals(ParameterMode, ParameterMode, ParameterMode),
+    AdjustRelativeBase(ParameterMode),
     Exit,
 }
 
@@ -129,6 +215,7 @@ impl TryFrom<Value> for ParameterMode {
         match value {
             0 => Ok(ParameterMode::Reference),
             1 => Ok(ParameterMode::Immediate),
+            2 => Ok(ParameterMode::Relative),
             _ => Err(IntCodeError::InvalidParameterMode(value)),
         }
     }
@@ -158,24 +245,80 @@ impl TryFrom<Value> for OpCode {
             6 => Ok(OpCode::JumpIfFalse(params!(1), params!(2))),
             7 => Ok(OpCode::LessThan(params!(1), params!(2), params!(3))),
             8 => Ok(OpCode::Equals(params!(1), params!(2), params!(3))),
+            9 => Ok(OpCode::AdjustRelativeBase(params!(1))),
             99 => Ok(OpCode::Exit),
             _ => Err(IntCodeError::InvalidOpCode(value)),
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub enum IntCodeMachineState {
     InputRequired,
     Running,
     Finished,
 }
 
+/// The state of a machine when it was stopped, and what it printed until
+/// then. With the `serde` feature, snapshots can be saved and loaded.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    pub instruction_pointer: usize,
+    pub relative_base: Value,
+    pub memory: Vec<Value>,
+    pub output: Vec<Value>,
+    pub executed: u64,
+}
+
+// The clock and the cancel token are only read every so many instructions,
+// they cost more than running one
+const DEADLINE_CHECK_INTERVAL: u64 = 4096;
+
+/// How many cells a program may use unless told otherwise, 128MB worth.
+/// Writing past it fails with [`IntCodeError::InvalidAddress`] rather than
+/// trying to allocate whatever address a program asks for.
+pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 24;
+
+#[derive(Default)]
+struct Limits<'a> {
+    instructions: Option<u64>,
+    deadline: Option<Instant>,
+    // In cells, DEFAULT_MEMORY_LIMIT when not given
+    memory: Option<usize>,
+    cancel: Option<&'a cancel::CancelToken>,
+    pace: Option<pace::Pace<'a>>,
+}
+
+#[derive(Clone)]
+struct Checkpoint {
+    executed: u64,
+    instruction_pointer: usize,
+    relative_base: Value,
+    memory: Vec<Value>,
+}
+
+/// Copies of the machine taken every `interval` instructions, keeping only
+/// the most recent `capacity` of them.
+#[derive(Clone)]
+struct Checkpoints {
+    interval: u64,
+    capacity: usize,
+    history: VecDeque<Checkpoint>,
+}
+
 #[derive(Clone)]
 pub struct IntCodeMachine {
     memory: Vec<Value>,
     instruction_pointer: usize,
+    relative_base: Value,
     state: IntCodeMachineState,
+    executed: u64,
+    checkpoints: Option<Checkpoints>,
+    // Shared between clones, they describe the same program
+    symbols: Option<Arc<Symbols>>,
+    #[cfg(feature = "memory-stats")]
+    stats: stats::MemoryStats,
 }
 
 impl IntCodeMachine {
@@ -183,42 +326,200 @@ impl IntCodeMachine {
         Self {
             memory,
             instruction_pointer: 0,
+            relative_base: 0,
             state: IntCodeMachineState::InputRequired,
+            executed: 0,
+            checkpoints: None,
+            symbols: None,
+            #[cfg(feature = "memory-stats")]
+            stats: stats::MemoryStats::default(),
+        }
+    }
+
+    /// Starts `program` over from the beginning, reusing the memory the
+    /// machine already has instead of allocating it again. Symbols are kept,
+    /// while checkpoints and counters are cleared.
+    pub fn reset(&mut self, program: &[Value]) {
+        self.memory.clear();
+        self.memory.extend_from_slice(program);
+        self.instruction_pointer = 0;
+        self.relative_base = 0;
+        self.state = IntCodeMachineState::InputRequired;
+        self.executed = 0;
+        if let Some(checkpoints) = &mut self.checkpoints {
+            checkpoints.history.clear();
+        }
+        #[cfg(feature = "memory-stats")]
+        {
+            self.stats = stats::MemoryStats::default();
         }
     }
 
+    /// Names for addresses, used when the machine prints them.
+    pub fn set_symbols(&mut self, symbols: Symbols) {
+        self.symbols = Some(Arc::new(symbols));
+    }
+
+    pub fn symbols(&self) -> Option<&Symbols> {
+        self.symbols.as_deref()
+    }
+
+    /// An address, with its name if it has one.
+    fn label(&self, address: usize) -> String {
+        match self.symbols().and_then(|symbols| symbols.get(address)) {
+            Some(name) => format!("{:>6} <{}>", address, name),
+            None => format!("{:>6}", address),
+        }
+    }
+
+    /// How often each address was read and written since the machine was
+    /// created.
+    #[cfg(feature = "memory-stats")]
+    pub fn memory_stats(&self) -> &stats::MemoryStats {
+        &self.stats
+    }
+
+    /// Instructions run since the machine was created.
+    pub fn executed(&self) -> u64 {
+        self.executed
+    }
+
+    /// Starts taking a checkpoint every `interval` instructions, keeping the
+    /// last `capacity` of them to rewind to. Any earlier checkpoints are
+    /// dropped.
+    pub fn enable_checkpoints(&mut self, interval: u64, capacity: usize) {
+        assert!(interval > 0, "Checkpoints need an interval");
+        self.checkpoints = Some(Checkpoints {
+            interval,
+            capacity,
+            history: VecDeque::with_capacity(capacity),
+        });
+    }
+
+    /// The instruction counts the kept checkpoints were taken at, the most
+    /// recent first.
+    pub fn checkpoints(&self) -> Vec<u64> {
+        self.checkpoints
+            .as_ref()
+            .map_or_else(Vec::new, |checkpoints| {
+                checkpoints
+                    .history
+                    .iter()
+                    .rev()
+                    .map(|checkpoint| checkpoint.executed)
+                    .collect()
+            })
+    }
+
+    /// Puts the machine back the way it was at a checkpoint, counting back
+    /// from the most recent one at 0. Checkpoints after it are dropped.
+    /// Input read since then isn't given back, it has to be passed again.
+    /// Returns false, leaving the machine alone, if there's no such
+    /// checkpoint.
+    pub fn rewind_to_checkpoint(&mut self, k: usize) -> bool {
+        let checkpoints = match &mut self.checkpoints {
+            Some(checkpoints) if k < checkpoints.history.len() => checkpoints,
+            _ => return false,
+        };
+        let keep = checkpoints.history.len() - k;
+        checkpoints.history.truncate(keep);
+        let checkpoint = checkpoints.history.back().unwrap().clone();
+        self.executed = checkpoint.executed;
+        self.instruction_pointer = checkpoint.instruction_pointer;
+        self.relative_base = checkpoint.relative_base;
+        self.memory = checkpoint.memory;
+        self.state = IntCodeMachineState::Running;
+        true
+    }
+
+    fn take_checkpoint(&mut self) {
+        let executed = self.executed;
+        let checkpoints = match &mut self.checkpoints {
+            Some(checkpoints) if executed.is_multiple_of(checkpoints.interval) => checkpoints,
+            _ => return,
+        };
+        // Coming back from a rewind the checkpoint is already there
+        let latest = checkpoints.history.back();
+        if latest.is_some_and(|checkpoint| checkpoint.executed == executed) {
+            return;
+        }
+        if checkpoints.history.len() == checkpoints.capacity {
+            checkpoints.history.pop_front();
+        }
+        if checkpoints.capacity > 0 {
+            checkpoints.history.push_back(Checkpoint {
+                executed,
+                instruction_pointer: self.instruction_pointer,
+                relative_base: self.relative_base,
+                memory: self.memory.clone(),
+            });
+        }
+    }
+
+    // Memory past the end of the program is considered to be zeroed, and
+    // only gets allocated once something is written to it
+    fn read_memory(&self, addr: usize) -> Value {
+        self.memory.get(addr).copied().unwrap_or(0)
+    }
+
+    // A parameter read from memory, as opposed to the instruction itself
+    fn read_data(&mut self, addr: usize) -> Value {
+        #[cfg(feature = "memory-stats")]
+        self.stats.read(addr);
+        self.read_memory(addr)
+    }
+
+    fn write_memory(&mut self, addr: usize, value: Value, limit: usize) -> Result<()> {
+        if addr >= limit {
+            return Err(IntCodeError::InvalidAddress);
+        }
+        #[cfg(feature = "memory-stats")]
+        self.stats.write(addr);
+        if addr >= self.memory.len() {
+            self.memory.resize(addr + 1, 0);
+        }
+        self.memory[addr] = value;
+        Ok(())
+    }
+
     fn read_op_code(&mut self) -> Result<OpCode> {
-        let op_code = self.memory[self.instruction_pointer].try_into()?;
+        let op_code = self.read_memory(self.instruction_pointer).try_into()?;
         self.instruction_pointer += 1;
         Ok(op_code)
     }
 
     fn read_parameter(&mut self, mode: ParameterMode) -> Result<Value> {
-        let current = self.memory[self.instruction_pointer];
+        let current = self.read_memory(self.instruction_pointer);
         self.instruction_pointer += 1;
         Ok(match mode {
             ParameterMode::Immediate => current,
-            ParameterMode::Reference => {
-                let addr: usize = current.try_into()?;
-                self.memory[addr]
-            }
+            ParameterMode::Reference => self.read_data(current.try_into()?),
+            ParameterMode::Relative => self.read_data(self.relative(current)?.try_into()?),
         })
     }
 
     fn read_address(&mut self, mode: ParameterMode) -> Result<usize> {
-        let current = self.memory[self.instruction_pointer];
+        let current = self.read_memory(self.instruction_pointer);
         self.instruction_pointer += 1;
         match mode {
             ParameterMode::Reference => Ok(current.try_into()?),
+            ParameterMode::Relative => Ok(self.relative(current)?.try_into()?),
             ParameterMode::Immediate => Err(IntCodeError::ImmediateModeOutput),
         }
     }
 
-    fn execute_command(
+    fn relative(&self, offset: Value) -> Result<Value> {
+        self.relative_base
+            .checked_add(offset)
+            .ok_or(IntCodeError::Overflow)
+    }
+
+    fn execute_command<I: Iterator<Item = Value>>(
         &mut self,
         code: OpCode,
-        input: &mut Vec<Value>,
+        input: &mut I,
         output: &mut Vec<Value>,
+        memory_limit: usize,
     ) -> Result<()> {
         match code {
             OpCode::Exit => {
@@ -228,22 +529,23 @@ impl IntCodeMachine {
                 let x = self.read_parameter(m1)?;
                 let y = self.read_parameter(m2)?;
                 let addr = self.read_address(m3)?;
-                self.memory[addr] = x + y;
+                let sum = x.checked_add(y).ok_or(IntCodeError::Overflow)?;
+                self.write_memory(addr, sum, memory_limit)?;
             }
             OpCode::Multiply(m1, m2, m3) => {
                 let x = self.read_parameter(m1)?;
                 let y = self.read_parameter(m2)?;
                 let addr = self.read_address(m3)?;
-                self.memory[addr] = x * y;
+                let product = x.checked_mul(y).ok_or(IntCodeError::Overflow)?;
+                self.write_memory(addr, product, memory_limit)?;
             }
             OpCode::Input(mode) => {
-                if input.is_empty() {
+                if let Some(value) = input.next() {
+                    let addr = self.read_address(mode)?;
+                    self.write_memory(addr, value, memory_limit)?;
+                } else {
                     self.state = IntCodeMachineState::InputRequired;
                     self.instruction_pointer -= 1;
-                } else {
-                    let value = input.remove(0);
-                    let addr = self.read_address(mode)?;
-                    self.memory[addr] = value;
                 }
             }
             OpCode::Output(mode) => {
@@ -254,21 +556,13 @@ impl IntCodeMachine {
                 let x = self.read_parameter(m1)?;
                 let y = self.read_parameter(m2)?;
                 let addr = self.read_address(m3)?;
-                if x < y {
-                    self.memory[addr] = 1;
-                } else {
-                    self.memory[addr] = 0;
-                }
+                self.write_memory(addr, if x < y { 1 } else { 0 }, memory_limit)?;
             }
             OpCode::Equals(m1, m2, m3) => {
                 let x = self.read_parameter(m1)?;
                 let y = self.read_parameter(m2)?;
                 let addr = self.read_address(m3)?;
-                if x == y {
-                    self.memory[addr] = 1;
-                } else {
-                    self.memory[addr] = 0;
-                }
+                self.write_memory(addr, if x == y { 1 } else { 0 }, memory_limit)?;
             }
             OpCode::JumpIfTrue(m1, m2) => {
                 let cond = self.read_parameter(m1)?;
@@ -284,60 +578,231 @@ impl IntCodeMachine {
                     self.instruction_pointer = addr.try_into()?;
                 }
             }
+            OpCode::AdjustRelativeBase(mode) => {
+                let offset = self.read_parameter(mode)?;
+                self.relative_base = self.relative(offset)?;
+            }
         }
         Ok(())
     }
 
-    pub fn execute(&mut self, mut input: Vec<Value>) -> Result<Vec<Value>> {
+    /// Runs until the program halts or needs more input than it was given,
+    /// returning what it printed. The input can be anything that gives
+    /// values, like an array or an iterator.
+    pub fn execute<I: IntoIterator<Item = Value>>(&mut self, input: I) -> Result<Vec<Value>> {
+        self.run(input, Limits::default())
+    }
+
+    /// Like [`execute`](Self::execute), but gives up with
+    /// [`IntCodeError::InstructionLimit`] once `limit` instructions ran, in
+    /// case the program never stops. The machine can carry on from there,
+    /// though the output and input not read yet are lost.
+    pub fn execute_limited<I: IntoIterator<Item = Value>>(
+        &mut self,
+        input: I,
+        limit: u64,
+    ) -> Result<Vec<Value>> {
+        let limits = Limits {
+            instructions: Some(limit),
+            ..Limits::default()
+        };
+        self.run(input, limits)
+    }
+
+    /// Like [`execute`](Self::execute), but gives up with
+    /// [`IntCodeError::Timeout`] once the program ran for longer than
+    /// `timeout`. The error has a snapshot of where the program got to,
+    /// and the machine can carry on from there.
+    pub fn execute_with_timeout<I: IntoIterator<Item = Value>>(
+        &mut self,
+        input: I,
+        timeout: Duration,
+    ) -> Result<Vec<Value>> {
+        let limits = Limits {
+            deadline: Some(Instant::now() + timeout),
+            ..Limits::default()
+        };
+        self.run(input, limits)
+    }
+
+    /// Like [`execute`](Self::execute), but fails with
+    /// [`IntCodeError::InvalidAddress`] when the program writes at or past
+    /// `cells`, instead of [`DEFAULT_MEMORY_LIMIT`].
+    pub fn execute_with_memory_limit<I: IntoIterator<Item = Value>>(
+        &mut self,
+        input: I,
+        cells: usize,
+    ) -> Result<Vec<Value>> {
+        let limits = Limits {
+            memory: Some(cells),
+            ..Limits::default()
+        };
+        self.run(input, limits)
+    }
+
+    /// Like [`execute`](Self::execute), but gives up with
+    /// [`IntCodeError::Cancelled`] soon after `token` is cancelled from
+    /// another thread. The error has a snapshot of where the program got
+    /// to, between two instructions, and the machine can carry on from
+    /// there with another token.
+    pub fn execute_cancellable<I: IntoIterator<Item = Value>>(
+        &mut self,
+        input: I,
+        token: &cancel::CancelToken,
+    ) -> Result<Vec<Value>> {
+        let limits = Limits {
+            cancel: Some(token),
+            ..Limits::default()
+        };
+        self.run(input, limits)
+    }
+
+    /// Like [`execute`](Self::execute), but runs no faster than `rate`,
+    /// handing every value to `on_output` as soon as it's printed. Meant
+    /// for drawing what a program does at a speed that can be followed.
+    pub fn execute_paced<I, F>(
+        &mut self,
+        input: I,
+        rate: pace::Rate,
+        mut on_output: F,
+    ) -> Result<Vec<Value>>
+    where
+        I: IntoIterator<Item = Value>,
+        F: FnMut(Value),
+    {
+        let limits = Limits {
+            pace: Some(pace::Pace::new(rate, &mut on_output)),
+            ..Limits::default()
+        };
+        self.run(input, limits)
+    }
+
+    fn run<I: IntoIterator<Item = Value>>(
+        &mut self,
+        input: I,
+        mut limits: Limits,
+    ) -> Result<Vec<Value>> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("intcode", start = self.instruction_pointer).entered();
+        let mut input = input.into_iter();
         let mut output = Vec::new();
         let length = self.memory.len();
+        // Checked once, every instruction is logged at this level
+        let trace = log_enabled!(Level::Trace);
+        let mut executed = 0;
+        let memory_limit = limits.memory.unwrap_or(DEFAULT_MEMORY_LIMIT);
         self.state = IntCodeMachineState::Running;
         while let IntCodeMachineState::Running = self.state {
             if self.instruction_pointer > length {
                 return Err(IntCodeError::UnexpectedEndOfFile);
             }
+            if let Some(limit) = limits.instructions.filter(|limit| executed >= *limit) {
+                return Err(IntCodeError::InstructionLimit(limit));
+            }
+            if executed % DEADLINE_CHECK_INTERVAL == 0 {
+                if limits
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    return Err(IntCodeError::Timeout(self.stopped(output)));
+                }
+                if limits.cancel.is_some_and(|token| token.is_cancelled()) {
+                    return Err(IntCodeError::Cancelled(self.stopped(output)));
+                }
+            }
+            if self.checkpoints.is_some() {
+                self.take_checkpoint();
+            }
+            let address = self.instruction_pointer;
             let code = self.read_op_code()?;
-            self.execute_command(code, &mut input, &mut output)?;
+            if trace {
+                trace!("{}: {:?}", self.label(address), code);
+            }
+            let printed = output.len();
+            self.execute_command(code, &mut input, &mut output, memory_limit)?;
+            executed += 1;
+            self.executed += 1;
+            if let Some(pace) = &mut limits.pace {
+                pace.step(&output[printed..]);
+            }
         }
+        debug!(
+            "Stopped at {} with {:?} after {} instructions, {} outputs",
+            self.instruction_pointer,
+            self.state,
+            executed,
+            output.len()
+        );
+        // Counted the way tracing-opentelemetry turns fields into metrics
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            monotonic_counter.intcode_instructions = executed,
+            outputs = output.len()
+        );
         Ok(output)
     }
 
+    // Where a run was stopped before it could finish
+    fn stopped(&self, output: Vec<Value>) -> Box<Snapshot> {
+        Box::new(Snapshot {
+            instruction_pointer: self.instruction_pointer,
+            relative_base: self.relative_base,
+            memory: self.memory.clone(),
+            output,
+            executed: self.executed,
+        })
+    }
+
     pub fn memory(&self) -> &[Value] {
         &self.memory
     }
+
+    /// The machine as it is between runs, to carry on from later with
+    /// [`IntCodeMachine::from_snapshot`]. The output is left empty.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            instruction_pointer: self.instruction_pointer,
+            relative_base: self.relative_base,
+            memory: self.memory.clone(),
+            output: Vec::new(),
+            executed: self.executed,
+        }
+    }
+
+    /// A machine that picks up where the snapshot was taken. A machine that
+    /// had halted halts again as soon as it runs.
+    pub fn from_snapshot(snapshot: Snapshot) -> Self {
+        Self {
+            instruction_pointer: snapshot.instruction_pointer,
+            relative_base: snapshot.relative_base,
+            executed: snapshot.executed,
+            ..Self::new(snapshot.memory)
+        }
+    }
 }
 
 impl Machine for IntCodeMachine {
-    fn execute(&mut self, mut input: Vec<Value>) -> Result<Vec<Value>> {
-        let mut output = Vec::new();
-        let length = self.memory.len();
-        self.state = IntCodeMachineState::Running;
-        while let IntCodeMachineState::Running = self.state {
-            if self.instruction_pointer > length {
-                return Err(IntCodeError::UnexpectedEndOfFile);
-            }
-            let code = self.read_op_code()?;
-            self.execute_command(code, &mut input, &mut output)?;
-        }
-        Ok(output)
+    fn execute(&mut self, input: Vec<Value>) -> Result<Vec<Value>> {
+        IntCodeMachine::execute(self, input)
     }
 
     fn finished(&self) -> bool {
-        match self.state {
-            IntCodeMachineState::Finished => true,
-            _ => false,
-        }
+        matches!(self.state, IntCodeMachineState::Finished)
     }
 }
 
-pub fn read_intcode_input<T>(mut input: T) -> Result<Vec<Value>, ParseError>
+/// Reads a program from its first line. The input can be compressed, see
+/// [`aoc_utils::input::decompressed`], and Windows line endings or a byte
+/// order mark are left out.
+pub fn read_intcode_input<T>(input: T) -> Result<Vec<Value>, ParseError>
 where
     T: BufRead,
 {
     let mut buffer = String::new();
-    input.read_line(&mut buffer)?;
+    aoc_utils::input::decompressed(input)?.read_line(&mut buffer)?;
 
     let parsed_values: Result<Vec<_>, _> = buffer
+        .trim_start_matches('\u{feff}')
         .trim()
         .split(',')
         .map(|value| value.parse::<Value>())
@@ -347,7 +812,14 @@ where
 
 #[cfg(test)]
 mod test {
-    use super::IntCodeMachine;
+    use super::cancel::CancelToken;
+    use super::programs::{
+        run, COUNTDOWN, IS_NONZERO_IMMEDIATE, IS_NONZERO_POSITION, LARGE_NUMBER, QUINE,
+        SIXTEEN_DIGITS,
+    };
+    use super::{read_intcode_input, IntCodeError, IntCodeMachine, Machine, Symbols};
+    use std::thread;
+    use std::time::Duration;
 
     #[test]
     fn test_case_1() {
@@ -391,18 +863,238 @@ mod test {
     }
     #[test]
     fn test_case_7() {
-        let mut machine = IntCodeMachine::new(vec![
-            3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9,
-        ]);
-        let output = machine.execute(vec![0]).expect("Expect to work");
+        let output = run(&IS_NONZERO_POSITION, vec![0]).expect("Expect to work");
         assert_eq!(output, [0]);
     }
 
     #[test]
     fn test_case_8() {
-        let mut machine =
-            IntCodeMachine::new(vec![3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]);
-        let output = machine.execute(vec![0]).expect("Expect to work");
+        let output = run(&IS_NONZERO_IMMEDIATE, vec![0]).expect("Expect to work");
         assert_eq!(output, [0]);
     }
+
+    #[test]
+    fn test_relative_quine() {
+        let output = run(&QUINE, vec![]).expect("Expect to work");
+        assert_eq!(output, QUINE);
+    }
+
+    #[test]
+    fn test_instruction_limit() {
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        match machine.execute_limited(vec![100], 10) {
+            Err(IntCodeError::InstructionLimit(10)) => {}
+            result => panic!("Expected to hit the limit, got {:?}", result),
+        }
+        // The count is only kept for the call, the machine carries on
+        assert_eq!(machine.execute(vec![]).unwrap(), [5050]);
+    }
+
+    #[test]
+    fn test_timeout() {
+        // Jumps back to itself forever
+        let mut machine = IntCodeMachine::new(vec![104, 7, 1105, 1, 2]);
+        match machine.execute_with_timeout(vec![], Duration::from_millis(20)) {
+            Err(IntCodeError::Timeout(snapshot)) => {
+                assert_eq!(snapshot.instruction_pointer, 2);
+                assert_eq!(snapshot.output, [7]);
+                assert!(snapshot.executed > 1);
+            }
+            result => panic!("Expected a timeout, got {:?}", result),
+        }
+        let output = run(&COUNTDOWN, vec![100]).unwrap();
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        let timeout = Duration::from_secs(10);
+        assert_eq!(
+            machine.execute_with_timeout(vec![100], timeout).unwrap(),
+            output
+        );
+    }
+
+    #[test]
+    fn test_overflow() {
+        let programs = [
+            vec![1101, isize::MAX, 1, 0, 99],
+            vec![1102, isize::MAX, 2, 0, 99],
+            vec![109, isize::MAX, 109, 1, 99],
+            vec![109, isize::MAX, 204, 1, 99],
+        ];
+        for program in programs.iter() {
+            let mut machine = IntCodeMachine::new(program.clone());
+            assert!(matches!(
+                machine.execute(vec![]),
+                Err(IntCodeError::Overflow)
+            ));
+        }
+        let mut machine = IntCodeMachine::new(vec![1101, isize::MAX, -1, 0, 4, 0, 99]);
+        assert_eq!(machine.execute(vec![]).unwrap(), [isize::MAX - 1]);
+    }
+
+    #[test]
+    fn test_resume_after_timeout() {
+        // Prints 1 and waits for input, then jumps back to itself forever
+        let program = vec![104, 1, 3, 9, 1105, 1, 4, 0, 0, 0];
+        let mut machine = IntCodeMachine::new(program);
+        assert_eq!(machine.execute(vec![]).unwrap(), [1]);
+        let timeout = Duration::from_millis(20);
+        let snapshot = match machine.execute_with_timeout(vec![5], timeout) {
+            Err(IntCodeError::Timeout(snapshot)) => snapshot,
+            result => panic!("Expected a timeout, got {:?}", result),
+        };
+        // The count covers the whole run, not just the call that timed out
+        assert_eq!(snapshot.executed, machine.executed());
+
+        let mut restored = IntCodeMachine::from_snapshot(*snapshot);
+        assert_eq!(restored.executed(), machine.executed());
+        assert_eq!(restored.memory()[9], 5);
+        match restored.execute_with_timeout(vec![], timeout) {
+            Err(IntCodeError::Timeout(snapshot)) => {
+                assert_eq!(snapshot.executed, restored.executed());
+                assert!(restored.executed() > machine.executed());
+            }
+            result => panic!("Expected a timeout, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        // Adds 0 and 0 into an address far past any real memory
+        let mut machine = IntCodeMachine::new(vec![1101, 0, 0, 1_000_000_000_000_000, 99]);
+        assert!(matches!(
+            machine.execute(vec![]),
+            Err(IntCodeError::InvalidAddress)
+        ));
+        assert_eq!(machine.memory().len(), 5);
+
+        let program = vec![1101, 0, 7, 100, 4, 100, 99];
+        let mut machine = IntCodeMachine::new(program.clone());
+        assert!(matches!(
+            machine.execute_with_memory_limit(vec![], 100),
+            Err(IntCodeError::InvalidAddress)
+        ));
+        let mut machine = IntCodeMachine::new(program);
+        assert_eq!(machine.execute_with_memory_limit(vec![], 101).unwrap(), [7]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let token = CancelToken::new();
+        let handle = {
+            let token = token.clone();
+            thread::spawn(move || {
+                let mut machine = IntCodeMachine::new(vec![104, 7, 1105, 1, 2]);
+                let result = machine.execute_cancellable(vec![], &token);
+                (machine, result)
+            })
+        };
+        thread::sleep(Duration::from_millis(20));
+        token.cancel();
+        let (machine, result) = handle.join().unwrap();
+        match result {
+            Err(IntCodeError::Cancelled(snapshot)) => {
+                assert_eq!(snapshot.instruction_pointer, 2);
+                assert_eq!(snapshot.output, [7]);
+                assert_eq!(snapshot.executed, machine.executed());
+            }
+            result => panic!("Expected a cancellation, got {:?}", result),
+        }
+
+        // A cancelled token stops a run before it starts
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        assert!(matches!(
+            machine.execute_cancellable(vec![5], &token),
+            Err(IntCodeError::Cancelled(_))
+        ));
+        assert_eq!(
+            machine
+                .execute_cancellable(vec![5], &CancelToken::new())
+                .unwrap(),
+            [15]
+        );
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        machine.enable_checkpoints(10, 3);
+        let output = machine.execute(vec![20]).unwrap();
+        assert_eq!(output, [210]);
+        let executed = machine.executed();
+        let latest = executed / 10 * 10;
+        assert_eq!(machine.checkpoints(), [latest, latest - 10, latest - 20]);
+
+        // Only the input instruction came before the first checkpoints, so
+        // the rest of the run plays out the same
+        assert!(machine.rewind_to_checkpoint(2));
+        assert_eq!(machine.executed(), latest - 20);
+        assert_eq!(machine.checkpoints(), [latest - 20]);
+        assert_eq!(machine.execute(vec![]).unwrap(), [210]);
+        assert_eq!(machine.executed(), executed);
+        assert!(!machine.rewind_to_checkpoint(5));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        assert_eq!(machine.execute(vec![20]).unwrap(), [210]);
+        assert!(machine.finished());
+        machine.reset(&COUNTDOWN);
+        assert!(!machine.finished());
+        assert_eq!(machine.executed(), 0);
+        assert_eq!(machine.memory(), &COUNTDOWN[..]);
+        assert_eq!(machine.execute(vec![3]).unwrap(), [6]);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        machine.execute(vec![]).unwrap();
+        let snapshot = machine.snapshot();
+        assert_eq!(machine.execute(vec![4]).unwrap(), [10]);
+
+        let mut restored = IntCodeMachine::from_snapshot(snapshot);
+        assert_eq!(restored.execute(vec![4]).unwrap(), [10]);
+        assert_eq!(restored.executed(), machine.executed());
+        assert!(restored.finished());
+    }
+
+    #[test]
+    fn test_symbols() {
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        let mut symbols = Symbols::new();
+        symbols.insert(2, "loop");
+        machine.set_symbols(symbols);
+        assert_eq!(machine.label(2), "     2 <loop>");
+        assert_eq!(machine.label(13), "    13");
+        // Clones share the symbols
+        let clone = machine.clone();
+        assert_eq!(
+            clone.symbols().and_then(|symbols| symbols.get(2)),
+            Some("loop")
+        );
+    }
+
+    #[test]
+    fn test_bitor_pipe() {
+        let doubler = || IntCodeMachine::new(vec![3, 9, 1002, 9, 2, 9, 4, 9, 99, 0]);
+        let mut pipeline = doubler() | doubler() | doubler();
+        assert_eq!(pipeline.execute(vec![5]).unwrap(), [40]);
+        assert!(pipeline.finished());
+    }
+
+    #[test]
+    fn test_large_numbers() {
+        let output = run(&SIXTEEN_DIGITS, vec![]).expect("Expect to work");
+        assert_eq!(output, [1_219_070_632_396_864]);
+
+        let output = run(&LARGE_NUMBER, vec![]).expect("Expect to work");
+        assert_eq!(output, [1_125_899_906_842_624]);
+    }
+
+    #[test]
+    fn test_read_intcode_input() {
+        let program = read_intcode_input("\u{feff}1,0,0,3,99\r\n2,0\r\n".as_bytes());
+        assert_eq!(program.expect("Expect to parse"), [1, 0, 0, 3, 99]);
+        assert!(read_intcode_input("1,x".as_bytes()).is_err());
+    }
 }
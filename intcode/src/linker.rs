@@ -0,0 +1,171 @@
+//! Puts [assembled modules](crate::assembler) together into one program, so
+//! routines can be kept in their own source files and shared.
+//!
+//! The modules are laid out one after the other in the order given. Every
+//! address a module uses for its own labels is moved along with it, and
+//! every label it leaves undefined is filled in from the global labels of
+//! the others.
+
+use crate::assembler::Module;
+use crate::symbols::Symbols;
+use crate::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkError {
+    /// A global label defined by two modules, and the modules.
+    DuplicateGlobal(String, String, String),
+    /// A label no module makes global, and the module using it.
+    Undefined(String, String),
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LinkError::DuplicateGlobal(name, first, second) => {
+                write!(f, "{} is global in both {} and {}", name, first, second)
+            }
+            LinkError::Undefined(name, module) => {
+                write!(f, "{} uses {}, which no module makes global", module, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LinkError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Linked {
+    pub program: Vec<Value>,
+    /// Every label at its final address. Global labels keep their name, the
+    /// rest are named after their module, like `print.loop`.
+    pub symbols: Symbols,
+}
+
+pub fn link(modules: &[Module]) -> Result<Linked, LinkError> {
+    let mut bases = Vec::with_capacity(modules.len());
+    let mut size = 0;
+    for module in modules {
+        bases.push(size);
+        size += module.code.len();
+    }
+
+    let mut globals: HashMap<&str, (usize, &str)> = HashMap::new();
+    for (module, base) in modules.iter().zip(&bases) {
+        for name in module.globals.iter() {
+            let address = base + module.labels[name];
+            if let Some((_, first)) = globals.insert(name, (address, &module.name)) {
+                return Err(LinkError::DuplicateGlobal(
+                    name.clone(),
+                    first.to_string(),
+                    module.name.clone(),
+                ));
+            }
+        }
+    }
+
+    let mut program = Vec::with_capacity(size);
+    let mut symbols = Symbols::new();
+    for (module, base) in modules.iter().zip(&bases) {
+        let mut code = module.code.clone();
+        for cell in module.relocations.iter() {
+            code[*cell] += *base as Value;
+        }
+        for import in module.imports.iter() {
+            let (address, _) = globals
+                .get(import.name.as_str())
+                .ok_or_else(|| LinkError::Undefined(import.name.clone(), module.name.clone()))?;
+            code[import.cell] += *address as Value;
+        }
+        program.extend(code);
+
+        for (name, address) in module.labels.iter() {
+            if !module.globals.contains(name) {
+                symbols.insert(base + address, &format!("{}.{}", module.name, name));
+            }
+        }
+    }
+    // Globals go in last so they win when two labels share an address
+    for (name, (address, _)) in globals {
+        symbols.insert(address, name);
+    }
+    Ok(Linked { program, symbols })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{link, LinkError};
+    use crate::assembler::assemble_module;
+    use crate::programs::run;
+
+    const MAIN: &str = "
+        global main
+        main:   in [double_value]
+                add back, 0, [double_return]
+                jz 0, double
+        back:   out [double_value]
+                halt
+    ";
+
+    // Doubles `double_value` and jumps back to `double_return`
+    const DOUBLE: &str = "
+        global double, double_value, double_return
+        double:         mul [double_value], 2, [double_value]
+                        jz 0, [double_return]
+        double_value:   data 0
+        double_return:  data 0
+    ";
+
+    #[test]
+    fn test_link() {
+        let main = assemble_module("main", MAIN).unwrap();
+        let double = assemble_module("double", DOUBLE).unwrap();
+        let linked = link(&[main.clone(), double.clone()]).unwrap();
+        assert_eq!(linked.program.len(), main.code.len() + double.code.len());
+        assert_eq!(run(&linked.program, vec![21]).unwrap(), [42]);
+
+        // The library moved past main, and main's labels went with it
+        assert_eq!(linked.symbols.address("main"), Some(0));
+        assert_eq!(linked.symbols.address("double"), Some(main.code.len()));
+        assert_eq!(linked.symbols.address("main.back"), Some(9));
+        assert_eq!(linked.program[3], 9);
+    }
+
+    #[test]
+    fn test_relocation() {
+        // The library ahead of main, behind a jump to it
+        let start = assemble_module("start", "jz 0, main").unwrap();
+        let double = assemble_module("double", DOUBLE).unwrap();
+        let main = assemble_module("main", MAIN).unwrap();
+        let linked = link(&[start, double, main]).unwrap();
+        assert_eq!(run(&linked.program, vec![-4]).unwrap(), [-8]);
+
+        let base = 3 + 9;
+        assert_eq!(linked.program[..3], [1106, 0, base]);
+        assert_eq!(linked.symbols.address("double_value"), Some(3 + 7));
+        assert_eq!(linked.program[base as usize + 1], 3 + 7);
+        assert_eq!(linked.program[base as usize + 3], base + 9);
+    }
+
+    #[test]
+    fn test_errors() {
+        let main = assemble_module("main", MAIN).unwrap();
+        assert_eq!(
+            link(std::slice::from_ref(&main)),
+            Err(LinkError::Undefined(
+                "double_value".to_string(),
+                "main".to_string()
+            ))
+        );
+        let other = assemble_module("other", "global main\nmain: halt").unwrap();
+        assert_eq!(
+            link(&[main, other]),
+            Err(LinkError::DuplicateGlobal(
+                "main".to_string(),
+                "main".to_string(),
+                "other".to_string()
+            ))
+        );
+    }
+}
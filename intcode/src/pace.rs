@@ -0,0 +1,102 @@
+//! Running a machine no faster than a given rate, so programs that draw as
+//! they go, like the arcade cabinet or the painting robot, can be watched
+//! without each visualization capturing frames of its own.
+//!
+//! The pace is kept against the time the run started, so a slow step is
+//! made up for by the following ones instead of slowing the whole run down.
+
+use crate::Value;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rate {
+    InstructionsPerSecond(u32),
+    OutputsPerSecond(u32),
+}
+
+impl Rate {
+    // How long after the start the `count`th instruction or output is due
+    fn due(self, instructions: u64, outputs: u64) -> Duration {
+        let (count, per_second) = match self {
+            Rate::InstructionsPerSecond(per_second) => (instructions, per_second),
+            Rate::OutputsPerSecond(per_second) => (outputs, per_second),
+        };
+        Duration::from_secs_f64(count as f64 / per_second.max(1) as f64)
+    }
+}
+
+pub(crate) struct Pace<'a> {
+    rate: Rate,
+    start: Instant,
+    instructions: u64,
+    outputs: u64,
+    on_output: &'a mut dyn FnMut(Value),
+}
+
+impl<'a> Pace<'a> {
+    pub(crate) fn new(rate: Rate, on_output: &'a mut dyn FnMut(Value)) -> Self {
+        Self {
+            rate,
+            start: Instant::now(),
+            instructions: 0,
+            outputs: 0,
+            on_output,
+        }
+    }
+
+    fn wait(&self) {
+        let due = self.rate.due(self.instructions, self.outputs);
+        if let Some(early) = due.checked_sub(self.start.elapsed()) {
+            thread::sleep(early);
+        }
+    }
+
+    /// Called after every instruction, with what it printed.
+    pub(crate) fn step(&mut self, printed: &[Value]) {
+        self.instructions += 1;
+        if let Rate::InstructionsPerSecond(_) = self.rate {
+            self.wait();
+        }
+        for &value in printed {
+            self.outputs += 1;
+            if let Rate::OutputsPerSecond(_) = self.rate {
+                self.wait();
+            }
+            (self.on_output)(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rate;
+    use crate::programs::QUINE;
+    use crate::IntCodeMachine;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_due() {
+        let rate = Rate::OutputsPerSecond(4);
+        assert_eq!(rate.due(100, 0), Duration::ZERO);
+        assert_eq!(rate.due(100, 6), Duration::from_millis(1500));
+        let rate = Rate::InstructionsPerSecond(1000);
+        assert_eq!(rate.due(250, 6), Duration::from_millis(250));
+        // A rate of zero is taken as one a second rather than never
+        assert_eq!(Rate::OutputsPerSecond(0).due(0, 2), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_execute_paced() {
+        let mut machine = IntCodeMachine::new(QUINE.to_vec());
+        let mut printed = Vec::new();
+        let start = Instant::now();
+        let output = machine
+            .execute_paced([], Rate::OutputsPerSecond(500), |value| printed.push(value))
+            .unwrap();
+        // The last of the 16 outputs is due after 32ms
+        assert!(start.elapsed() >= Duration::from_millis(32));
+        assert_eq!(output, QUINE);
+        assert_eq!(printed, QUINE);
+    }
+}
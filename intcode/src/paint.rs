@@ -0,0 +1,234 @@
+//! The hull painting robot from day 11. The program reads the color of the
+//! panel under the robot and answers with a color to paint it and a turn to
+//! make, after which the robot moves forward one panel.
+
+use crate::pace::Rate;
+use crate::{IntCodeError, IntCodeMachine, Machine, Value};
+use aoc_utils::{Direction, Point2, SparseGrid};
+use std::collections::HashSet;
+use std::fmt;
+
+pub const BLACK: Value = 0;
+pub const WHITE: Value = 1;
+
+#[derive(Debug)]
+pub enum RobotError {
+    Machine(IntCodeError),
+    InvalidColor(Value),
+    InvalidTurn(Value),
+}
+
+impl fmt::Display for RobotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RobotError::Machine(error) => write!(f, "Robot program failed: {}", error),
+            RobotError::InvalidColor(color) => write!(f, "Invalid color: {}", color),
+            RobotError::InvalidTurn(turn) => write!(f, "Invalid turn: {}", turn),
+        }
+    }
+}
+
+impl std::error::Error for RobotError {}
+
+impl From<IntCodeError> for RobotError {
+    fn from(error: IntCodeError) -> Self {
+        RobotError::Machine(error)
+    }
+}
+
+/// The robot and the panels it has seen. Positions are in screen
+/// coordinates, with `y` growing downwards, so the hull renders upright.
+#[derive(Debug, Clone)]
+pub struct PaintRobot {
+    position: Point2,
+    heading: Direction,
+    panels: SparseGrid<Value>,
+    painted: HashSet<Point2>,
+    // A color waiting for its turn, when the output is split between runs
+    pending: Option<Value>,
+}
+
+impl Default for PaintRobot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PaintRobot {
+    /// A robot facing up on an all black hull.
+    pub fn new() -> Self {
+        Self {
+            position: Point2::ORIGIN,
+            heading: Direction::Up,
+            panels: SparseGrid::new(),
+            painted: HashSet::new(),
+            pending: None,
+        }
+    }
+
+    /// A robot starting on a panel of the given color, which doesn't count
+    /// as painted.
+    pub fn starting_on(color: Value) -> Self {
+        let mut robot = Self::new();
+        robot.panels.insert(Point2::ORIGIN, color);
+        robot
+    }
+
+    /// The color of the panel under the robot, the program's next input.
+    pub fn camera(&self) -> Value {
+        self.panels.get(self.position).copied().unwrap_or(BLACK)
+    }
+
+    /// Follows the program's output, a color and a turn for every step.
+    pub fn follow(&mut self, output: &[Value]) -> Result<(), RobotError> {
+        for &value in output {
+            match self.pending.take() {
+                None => match value {
+                    BLACK | WHITE => self.pending = Some(value),
+                    _ => return Err(RobotError::InvalidColor(value)),
+                },
+                Some(color) => {
+                    self.heading = match value {
+                        0 => self.heading.turn_left(),
+                        1 => self.heading.turn_right(),
+                        _ => return Err(RobotError::InvalidTurn(value)),
+                    };
+                    self.panels.insert(self.position, color);
+                    self.painted.insert(self.position);
+                    self.position += self.heading.screen_delta();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the program until it halts, feeding it the camera every time
+    /// it stops for input.
+    pub fn run<M: Machine>(&mut self, machine: &mut M) -> Result<(), RobotError> {
+        loop {
+            let output = machine.execute(vec![self.camera()])?;
+            self.follow(&output)?;
+            if machine.finished() {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Like [`run`](Self::run), but at `rate`, calling `on_step` every time
+    /// the robot moves so the painting can be shown as it goes.
+    pub fn run_paced<F>(
+        &mut self,
+        machine: &mut IntCodeMachine,
+        rate: Rate,
+        mut on_step: F,
+    ) -> Result<(), RobotError>
+    where
+        F: FnMut(&PaintRobot),
+    {
+        let mut error = None;
+        while !machine.finished() {
+            machine.execute_paced(vec![self.camera()], rate, |value| {
+                if error.is_some() {
+                    return;
+                }
+                match self.follow(&[value]) {
+                    Ok(()) if self.pending.is_none() => on_step(self),
+                    Ok(()) => {}
+                    Err(failed) => error = Some(failed),
+                }
+            })?;
+            if let Some(error) = error {
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn position(&self) -> Point2 {
+        self.position
+    }
+
+    pub fn heading(&self) -> Direction {
+        self.heading
+    }
+
+    pub fn panels(&self) -> &SparseGrid<Value> {
+        &self.panels
+    }
+
+    /// How many panels were painted at least once.
+    pub fn painted(&self) -> usize {
+        self.painted.len()
+    }
+
+    /// Draws the hull with `#` for white panels and spaces for the rest.
+    pub fn render(&self) -> String {
+        self.panels.render(|color| match color {
+            Some(&WHITE) => '#',
+            _ => ' ',
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PaintRobot, Rate, RobotError, BLACK, WHITE};
+    use crate::IntCodeMachine;
+    use aoc_utils::{Direction, Point2};
+
+    #[test]
+    fn test_example() {
+        let mut robot = PaintRobot::new();
+        assert_eq!(robot.camera(), BLACK);
+        robot.follow(&[1, 0, 0, 0]).unwrap();
+        robot.follow(&[1, 0, 1, 0]).unwrap();
+        assert_eq!(robot.position(), Point2::ORIGIN);
+        assert_eq!(robot.camera(), WHITE);
+        // The last turn comes in its own batch
+        robot.follow(&[0, 1, 1, 0, 1]).unwrap();
+        robot.follow(&[0]).unwrap();
+        assert_eq!(robot.painted(), 6);
+        assert_eq!(robot.heading(), Direction::Left);
+        assert_eq!(robot.position(), Point2::new(0, -1));
+        assert_eq!(robot.render(), "  #\n  #\n## \n");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut robot = PaintRobot::new();
+        assert!(matches!(
+            robot.follow(&[2, 0]),
+            Err(RobotError::InvalidColor(2))
+        ));
+        assert!(matches!(
+            robot.follow(&[1, 5]),
+            Err(RobotError::InvalidTurn(5))
+        ));
+    }
+
+    #[test]
+    fn test_run() {
+        // Paints the starting panel the opposite of its color and turns
+        // right, then halts
+        let program = vec![
+            3, 15, 1002, 15, -1, 15, 1001, 15, 1, 15, 4, 15, 104, 1, 99, 0,
+        ];
+        let mut robot = PaintRobot::starting_on(WHITE);
+        assert_eq!(robot.painted(), 0);
+        robot
+            .run(&mut IntCodeMachine::new(program.clone()))
+            .unwrap();
+        assert_eq!(robot.painted(), 1);
+        assert_eq!(robot.panels().get(Point2::ORIGIN), Some(&BLACK));
+        assert_eq!(robot.heading(), Direction::Right);
+
+        let mut robot = PaintRobot::starting_on(WHITE);
+        let mut steps = Vec::new();
+        let rate = Rate::InstructionsPerSecond(10_000);
+        let mut machine = IntCodeMachine::new(program);
+        robot
+            .run_paced(&mut machine, rate, |robot| steps.push(robot.position()))
+            .unwrap();
+        assert_eq!(steps, [Point2::new(1, 0)]);
+    }
+}
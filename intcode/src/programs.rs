@@ -0,0 +1,118 @@
+//! The example programs from the puzzles, kept in one place for the tests
+//! and benchmarks that use them.
+
+use crate::{IntCodeMachine, Result};
+
+/// Day 9: prints a copy of itself.
+pub const QUINE: [isize; 16] = [
+    109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+];
+
+/// Day 9: prints a 16 digit number.
+pub const SIXTEEN_DIGITS: [isize; 8] = [1102, 34915192, 34915192, 7, 4, 7, 99, 0];
+
+/// Day 9: prints the large number in its middle.
+pub const LARGE_NUMBER: [isize; 3] = [104, 1125899906842624, 99];
+
+/// Day 5: prints 1 if the input is equal to 8, 0 otherwise, using position
+/// mode.
+pub const EQUAL_TO_8_POSITION: [isize; 11] = [3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8];
+
+/// Day 5: prints 1 if the input is less than 8, 0 otherwise, using position
+/// mode.
+pub const LESS_THAN_8_POSITION: [isize; 11] = [3, 9, 7, 9, 10, 9, 4, 9, 99, -1, 8];
+
+/// Day 5: prints 1 if the input is equal to 8, 0 otherwise, using immediate
+/// mode.
+pub const EQUAL_TO_8_IMMEDIATE: [isize; 9] = [3, 3, 1108, -1, 8, 3, 4, 3, 99];
+
+/// Day 5: prints 1 if the input is less than 8, 0 otherwise, using
+/// immediate mode.
+pub const LESS_THAN_8_IMMEDIATE: [isize; 9] = [3, 3, 1107, -1, 8, 3, 4, 3, 99];
+
+/// Day 5: prints 0 if the input is zero, 1 otherwise, using jumps in
+/// position mode.
+pub const IS_NONZERO_POSITION: [isize; 16] =
+    [3, 12, 6, 12, 15, 1, 13, 14, 13, 4, 13, 99, -1, 0, 1, 9];
+
+/// Day 5: prints 0 if the input is zero, 1 otherwise, using jumps in
+/// immediate mode.
+pub const IS_NONZERO_IMMEDIATE: [isize; 13] = [3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1];
+
+/// Day 5: prints 999 if the input is below 8, 1000 if it's equal to 8 and
+/// 1001 if it's above.
+pub const COMPARE_TO_8: [isize; 47] = [
+    3, 21, 1008, 21, 8, 20, 1005, 20, 22, 107, 8, 21, 20, 1006, 20, 31, 1106, 0, 36, 98, 0, 0,
+    1002, 21, 125, 20, 4, 20, 1105, 1, 46, 104, 999, 1105, 1, 46, 1101, 1000, 1, 20, 4, 20, 1105,
+    1, 46, 98, 99,
+];
+
+/// Counts down from the input, adding every value to a total it prints at
+/// the end, so the loop runs for as long as asked.
+pub const COUNTDOWN: [isize; 21] = [
+    3, 20, 1, 19, 20, 19, 1001, 20, -1, 20, 1005, 20, 2, 4, 19, 99, 0, 0, 0, 0, 0,
+];
+
+/// Runs a program on a fresh machine, returning what it printed.
+pub fn run<I: IntoIterator<Item = isize>>(program: &[isize], input: I) -> Result<Vec<isize>> {
+    IntCodeMachine::new(program.to_vec()).execute(input)
+}
+
+/// Runs a program with a single input that prints a single value.
+pub fn run_single(program: &[isize], input: isize) -> Result<Option<isize>> {
+    Ok(run(program, [input])?.last().copied())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_day_9_programs() {
+        assert_eq!(run(&QUINE, vec![]).unwrap(), QUINE);
+        let output = run(&SIXTEEN_DIGITS, vec![]).unwrap();
+        assert_eq!(output[0].to_string().len(), 16);
+        assert_eq!(run(&LARGE_NUMBER, vec![]).unwrap(), [LARGE_NUMBER[1]]);
+    }
+
+    #[test]
+    fn test_comparisons() {
+        for input in [-3, 0, 7, 8, 9] {
+            let equal = (input == 8) as isize;
+            let less = (input < 8) as isize;
+            let nonzero = (input != 0) as isize;
+            assert_eq!(
+                run_single(&EQUAL_TO_8_POSITION, input).unwrap(),
+                Some(equal)
+            );
+            assert_eq!(
+                run_single(&EQUAL_TO_8_IMMEDIATE, input).unwrap(),
+                Some(equal)
+            );
+            assert_eq!(
+                run_single(&LESS_THAN_8_POSITION, input).unwrap(),
+                Some(less)
+            );
+            assert_eq!(
+                run_single(&LESS_THAN_8_IMMEDIATE, input).unwrap(),
+                Some(less)
+            );
+            assert_eq!(
+                run_single(&IS_NONZERO_POSITION, input).unwrap(),
+                Some(nonzero)
+            );
+            assert_eq!(
+                run_single(&IS_NONZERO_IMMEDIATE, input).unwrap(),
+                Some(nonzero)
+            );
+        }
+        assert_eq!(run_single(&COMPARE_TO_8, 7).unwrap(), Some(999));
+        assert_eq!(run_single(&COMPARE_TO_8, 8).unwrap(), Some(1000));
+        assert_eq!(run_single(&COMPARE_TO_8, 9).unwrap(), Some(1001));
+    }
+
+    #[test]
+    fn test_countdown() {
+        assert_eq!(run_single(&COUNTDOWN, 100).unwrap(), Some(5050));
+    }
+}
@@ -0,0 +1,190 @@
+//! Input for interactive programs, like a joystick for day 13's arcade or a
+//! keyboard for day 25's droid. The input comes either live or from a
+//! script, and everything supplied is recorded so a session can be saved
+//! and replayed exactly.
+//!
+//! Input is handed out in batches, one for every time the program stops to
+//! wait for input. A recording has one batch per line, as comma separated
+//! values.
+
+use crate::{Machine, ParseError, Result, Value};
+use aoc_utils::input::comma_separated;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+enum Source {
+    Live(Box<dyn FnMut() -> Option<Vec<Value>>>),
+    Scripted(VecDeque<Vec<Value>>),
+}
+
+pub struct InputDevice {
+    source: Source,
+    recording: Vec<Vec<Value>>,
+}
+
+impl InputDevice {
+    /// Asks `next` for every batch, until it returns `None`.
+    pub fn live<F>(next: F) -> Self
+    where
+        F: FnMut() -> Option<Vec<Value>> + 'static,
+    {
+        Self {
+            source: Source::Live(Box::new(next)),
+            recording: Vec::new(),
+        }
+    }
+
+    /// A joystick, asked for a single value every time.
+    pub fn joystick<F>(mut position: F) -> Self
+    where
+        F: FnMut() -> Option<Value> + 'static,
+    {
+        Self::live(move || position().map(|position| vec![position]))
+    }
+
+    /// A keyboard, asked for a line of text every time. The line is sent as
+    /// ASCII ending in a newline, whether it had one or not.
+    pub fn keyboard<F>(mut line: F) -> Self
+    where
+        F: FnMut() -> Option<String> + 'static,
+    {
+        Self::live(move || {
+            line().map(|line| {
+                line.trim_end_matches('\n')
+                    .bytes()
+                    .chain(Some(b'\n'))
+                    .map(Value::from)
+                    .collect()
+            })
+        })
+    }
+
+    /// Hands out the given batches in order.
+    pub fn scripted<I: IntoIterator<Item = Vec<Value>>>(batches: I) -> Self {
+        Self {
+            source: Source::Scripted(batches.into_iter().collect()),
+            recording: Vec::new(),
+        }
+    }
+
+    /// Replays a recording written by [`InputDevice::save`].
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, ParseError> {
+        let mut batches = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let batch = comma_separated(&line?).map_err(|error| error.on_line(index + 1))?;
+            batches.push(batch);
+        }
+        Ok(Self::scripted(batches))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ParseError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// The next batch of input, or `None` once there is no more.
+    pub fn next_batch(&mut self) -> Option<Vec<Value>> {
+        let batch = match &mut self.source {
+            Source::Live(next) => next(),
+            Source::Scripted(batches) => batches.pop_front(),
+        }?;
+        self.recording.push(batch.clone());
+        Some(batch)
+    }
+
+    /// Every batch handed out so far.
+    pub fn recording(&self) -> &[Vec<Value>] {
+        &self.recording
+    }
+
+    /// Writes the recording, one batch per line.
+    pub fn save<W: Write>(&self, mut out: W) -> io::Result<()> {
+        for batch in &self.recording {
+            let line: Vec<String> = batch.iter().map(Value::to_string).collect();
+            writeln!(out, "{}", line.join(","))?;
+        }
+        Ok(())
+    }
+
+    pub fn save_to<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.save(io::BufWriter::new(File::create(path)?))
+    }
+
+    /// Runs the machine until it halts or the input runs out, passing every
+    /// batch of output to `output`. Returns whether the machine halted.
+    pub fn run<M, F>(&mut self, machine: &mut M, mut output: F) -> Result<bool>
+    where
+        M: Machine,
+        F: FnMut(&[Value]),
+    {
+        output(&machine.execute(Vec::new())?);
+        while !machine.finished() {
+            let batch = match self.next_batch() {
+                Some(batch) => batch,
+                None => return Ok(false),
+            };
+            output(&machine.execute(batch)?);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::InputDevice;
+    use crate::programs::COUNTDOWN;
+    use crate::IntCodeMachine;
+
+    // Echoes every input until it reads a zero
+    const ECHO: [isize; 10] = [3, 9, 4, 9, 1005, 9, 0, 99, 0, 0];
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut presses = vec![0, -1, 1, 1].into_iter();
+        let mut device = InputDevice::joystick(move || presses.next_back());
+        let mut machine = IntCodeMachine::new(ECHO.to_vec());
+        let mut printed = Vec::new();
+        assert!(device
+            .run(&mut machine, |output| printed.extend_from_slice(output))
+            .unwrap());
+        assert_eq!(printed, [1, 1, -1, 0]);
+        assert_eq!(device.recording(), [[1], [1], [-1], [0]]);
+
+        let mut file = Vec::new();
+        device.save(&mut file).unwrap();
+        assert_eq!(String::from_utf8_lossy(&file), "1\n1\n-1\n0\n");
+
+        let mut replay = InputDevice::read(&file[..]).unwrap();
+        let mut machine = IntCodeMachine::new(ECHO.to_vec());
+        let mut replayed = Vec::new();
+        replay
+            .run(&mut machine, |output| replayed.extend_from_slice(output))
+            .unwrap();
+        assert_eq!(replayed, printed);
+        assert_eq!(replay.recording(), device.recording());
+    }
+
+    #[test]
+    fn test_keyboard() {
+        let mut lines = vec!["north\n".to_string(), "take".to_string()].into_iter();
+        let mut device = InputDevice::keyboard(move || lines.next());
+        assert_eq!(device.next_batch().unwrap().len(), 6);
+        assert_eq!(device.next_batch().unwrap(), [116, 97, 107, 101, 10]);
+        assert_eq!(device.next_batch(), None);
+        assert_eq!(device.recording().len(), 2);
+    }
+
+    #[test]
+    fn test_out_of_input() {
+        let mut device = InputDevice::scripted(vec![]);
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        assert!(!device.run(&mut machine, |_| {}).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_recording() {
+        let error = InputDevice::read(&b"1,2\n3,x\n"[..]).err().unwrap();
+        assert!(error.to_string().starts_with("line 2, column 3"));
+    }
+}
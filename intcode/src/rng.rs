@@ -0,0 +1,37 @@
+use crate::Value;
+use std::ops::RangeInclusive;
+
+/// xorshift64*, good enough to make up inputs and programs that can be
+/// made again from their seed.
+pub(crate) struct Rng {
+    // Must never be zero
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed | 1 }
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    pub(crate) fn range(&mut self, range: RangeInclusive<Value>) -> Value {
+        assert!(
+            !range.is_empty(),
+            "Can't pick from the empty range {:?}",
+            range
+        );
+        let span = (*range.end() as i128 - *range.start() as i128 + 1) as u128;
+        (*range.start() as i128 + (self.next() as u128 % span) as i128) as Value
+    }
+
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        assert!(bound > 0, "Can't pick a number below 0");
+        (self.next() % bound as u64) as usize
+    }
+}
@@ -0,0 +1,199 @@
+//! The order machines take turns in, when several of them run together in
+//! rounds, like the network of day 23.
+//!
+//! Every machine runs once a round. The policy picks the order, which is
+//! enough to change what a simulation does when machines race to send each
+//! other values. Every order used is recorded, so a run can be played back
+//! exactly with [`Policy::Replay`].
+//!
+//! Policies are written like `round-robin`, `random:42` or `priority:3,0`,
+//! and schedules one round per line, like `0,1,2`.
+
+use crate::rng::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Policy {
+    /// By index, every round.
+    #[default]
+    RoundRobin,
+    /// Shuffled every round, the same way for the same seed.
+    Random { seed: u64 },
+    /// The machines listed first, in that order, then the others by index.
+    Priority(Vec<usize>),
+    /// The rounds of a recorded schedule, then by index once they run out.
+    Replay(Vec<Vec<usize>>),
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        let (name, argument) = match text.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (text, None),
+        };
+        match (name, argument) {
+            ("round-robin", None) => Ok(Policy::RoundRobin),
+            ("random", Some(seed)) => seed
+                .parse()
+                .map(|seed| Policy::Random { seed })
+                .map_err(|_| format!("Invalid seed {}", seed)),
+            ("priority", Some(order)) => parse_round(order).map(Policy::Priority),
+            _ => Err(format!(
+                "Unknown policy {}, expected round-robin, random:<seed> or priority:<machines>",
+                text
+            )),
+        }
+    }
+}
+
+fn parse_round(line: &str) -> Result<Vec<usize>, String> {
+    line.split(',')
+        .map(|machine| {
+            machine
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid machine {}", machine))
+        })
+        .collect()
+}
+
+/// Reads a schedule written by [`Schedule`]'s `Display`, as a policy to
+/// replay it.
+pub fn parse_schedule(text: &str) -> Result<Policy, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            parse_round(line).map_err(|error| format!("line {}: {}", index + 1, error))
+        })
+        .collect::<Result<_, _>>()
+        .map(Policy::Replay)
+}
+
+/// The orders used so far, one per round.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schedule {
+    pub rounds: Vec<Vec<usize>>,
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for round in &self.rounds {
+            let machines: Vec<String> = round.iter().map(|machine| machine.to_string()).collect();
+            writeln!(f, "{}", machines.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Scheduler {
+    policy: Policy,
+    rng: Rng,
+    schedule: Schedule,
+}
+
+impl Scheduler {
+    pub fn new(policy: Policy) -> Self {
+        let seed = match policy {
+            Policy::Random { seed } => seed,
+            _ => 0,
+        };
+        Self {
+            policy,
+            rng: Rng::new(seed),
+            schedule: Schedule::default(),
+        }
+    }
+
+    /// The order to run `count` machines in for the next round.
+    pub fn next_round(&mut self, count: usize) -> Vec<usize> {
+        let by_index = || (0..count).collect::<Vec<_>>();
+        let order = match &self.policy {
+            Policy::RoundRobin => by_index(),
+            Policy::Random { .. } => {
+                let mut order = by_index();
+                for index in (1..count).rev() {
+                    order.swap(index, self.rng.below(index + 1));
+                }
+                order
+            }
+            Policy::Priority(first) => first_then_rest(first, count),
+            Policy::Replay(rounds) => match rounds.get(self.schedule.rounds.len()) {
+                Some(round) => first_then_rest(round, count),
+                None => by_index(),
+            },
+        };
+        self.schedule.rounds.push(order.clone());
+        order
+    }
+
+    pub fn schedule(&self) -> &Schedule {
+        &self.schedule
+    }
+}
+
+// Every machine once, the listed ones first. Machines out of range or
+// listed twice are left out.
+fn first_then_rest(first: &[usize], count: usize) -> Vec<usize> {
+    let mut order = Vec::with_capacity(count);
+    for &machine in first.iter().chain(&(0..count).collect::<Vec<_>>()) {
+        if machine < count && !order.contains(&machine) {
+            order.push(machine);
+        }
+    }
+    order
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_schedule, Policy, Scheduler};
+
+    #[test]
+    fn test_policies() {
+        let mut scheduler = Scheduler::new(Policy::RoundRobin);
+        assert_eq!(scheduler.next_round(3), [0, 1, 2]);
+
+        let mut scheduler = Scheduler::new(Policy::Priority(vec![2, 7, 2]));
+        assert_eq!(scheduler.next_round(4), [2, 0, 1, 3]);
+
+        let rounds = |seed| {
+            let mut scheduler = Scheduler::new(Policy::Random { seed });
+            (0..5).map(|_| scheduler.next_round(6)).collect::<Vec<_>>()
+        };
+        assert_eq!(rounds(7), rounds(7));
+        assert_ne!(rounds(7), rounds(8));
+        for mut round in rounds(7) {
+            round.sort_unstable();
+            assert_eq!(round, [0, 1, 2, 3, 4, 5]);
+        }
+    }
+
+    #[test]
+    fn test_replay() {
+        let mut scheduler = Scheduler::new(Policy::Random { seed: 3 });
+        for _ in 0..4 {
+            scheduler.next_round(5);
+        }
+        let recorded = scheduler.schedule().clone();
+        let mut replay = Scheduler::new(parse_schedule(&recorded.to_string()).unwrap());
+        for _ in 0..4 {
+            replay.next_round(5);
+        }
+        assert_eq!(replay.schedule(), &recorded);
+        // Once the recording runs out, machines go by index
+        assert_eq!(replay.next_round(5), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("round-robin".parse(), Ok(Policy::RoundRobin));
+        assert_eq!("random:42".parse(), Ok(Policy::Random { seed: 42 }));
+        assert_eq!("priority:3,0".parse(), Ok(Policy::Priority(vec![3, 0])));
+        assert!("random".parse::<Policy>().is_err());
+        assert!("priority:a".parse::<Policy>().is_err());
+        assert!(parse_schedule("0,1\n1,x\n").is_err());
+    }
+}
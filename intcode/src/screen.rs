@@ -0,0 +1,184 @@
+//! A screen for programs that draw by printing `x, y, tile` triples, like
+//! day 13's arcade cabinet. Printing `-1, 0, value` sets the score instead
+//! of drawing a tile.
+
+use crate::pace::Rate;
+use crate::{IntCodeMachine, Machine, Result, Value};
+use aoc_utils::{Point2, SparseGrid};
+use std::mem;
+
+/// The cell that holds the score instead of a tile.
+pub const SCORE: Point2 = Point2::new(-1, 0);
+
+type Hook = Box<dyn FnMut(&Screen)>;
+
+/// Every tile a program has drawn, and the last score it printed.
+#[derive(Default)]
+pub struct Screen {
+    tiles: SparseGrid<Value>,
+    score: Option<Value>,
+    // A triple split between two runs of the machine
+    pending: Vec<Value>,
+    hooks: Vec<Hook>,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `hook` with the screen after every batch of output is drawn,
+    /// to render it as the program goes.
+    pub fn on_frame<F: FnMut(&Screen) + 'static>(&mut self, hook: F) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    /// Draws a program's output. Values that don't make up a whole triple
+    /// yet are kept until the next call.
+    pub fn draw(&mut self, output: &[Value]) {
+        self.pending.extend_from_slice(output);
+        let whole = self.pending.len() - self.pending.len() % 3;
+        for triple in self.pending[..whole].chunks(3) {
+            let point = Point2::new(triple[0] as i64, triple[1] as i64);
+            if point == SCORE {
+                self.score = Some(triple[2]);
+            } else {
+                self.tiles.insert(point, triple[2]);
+            }
+        }
+        self.pending.drain(..whole);
+
+        let mut hooks = mem::take(&mut self.hooks);
+        for hook in hooks.iter_mut() {
+            hook(self);
+        }
+        self.hooks = hooks;
+    }
+
+    /// Runs the machine with `input` and draws what it printed.
+    pub fn run<M, I>(&mut self, machine: &mut M, input: I) -> Result<()>
+    where
+        M: Machine,
+        I: IntoIterator<Item = Value>,
+    {
+        let output = machine.execute(input.into_iter().collect())?;
+        self.draw(&output);
+        Ok(())
+    }
+
+    /// Like [`run`](Self::run), but at `rate`, drawing every triple as soon
+    /// as it's printed so each one makes a frame.
+    pub fn run_paced<I>(&mut self, machine: &mut IntCodeMachine, input: I, rate: Rate) -> Result<()>
+    where
+        I: IntoIterator<Item = Value>,
+    {
+        machine.execute_paced(input, rate, |value| {
+            self.pending.push(value);
+            if self.pending.len() == 3 {
+                self.draw(&[]);
+            }
+        })?;
+        Ok(())
+    }
+
+    pub fn tile(&self, point: Point2) -> Option<Value> {
+        self.tiles.get(point).copied()
+    }
+
+    pub fn tiles(&self) -> &SparseGrid<Value> {
+        &self.tiles
+    }
+
+    pub fn score(&self) -> Option<Value> {
+        self.score
+    }
+
+    /// How many cells currently show `tile`.
+    pub fn count(&self, tile: Value) -> usize {
+        self.tiles
+            .iter()
+            .filter(|(_, &value)| value == tile)
+            .count()
+    }
+
+    /// Where `tile` is shown, for tiles that only appear once like a ball.
+    pub fn find(&self, tile: Value) -> Option<Point2> {
+        self.tiles
+            .iter()
+            .find(|(_, &value)| value == tile)
+            .map(|(&point, _)| point)
+    }
+
+    /// Draws the screen as text, with `y` growing downwards. Cells that
+    /// were never drawn are passed as `None`.
+    pub fn render<F>(&self, mut draw: F) -> String
+    where
+        F: FnMut(Option<Value>) -> char,
+    {
+        self.tiles.render(|tile| draw(tile.copied()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Screen;
+    use aoc_utils::Point2;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_draw() {
+        let mut screen = Screen::new();
+        screen.draw(&[1, 2, 3, 6, 5, 4]);
+        assert_eq!(screen.tile(Point2::new(1, 2)), Some(3));
+        assert_eq!(screen.find(4), Some(Point2::new(6, 5)));
+        assert_eq!(screen.score(), None);
+
+        // The score, split over two batches of output
+        screen.draw(&[-1, 0]);
+        assert_eq!(screen.score(), None);
+        screen.draw(&[12345, 1, 2, 4]);
+        assert_eq!(screen.score(), Some(12345));
+        assert_eq!(screen.count(4), 2);
+        assert_eq!(screen.count(3), 0);
+    }
+
+    #[test]
+    fn test_render() {
+        let mut screen = Screen::new();
+        let frames = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&frames);
+        screen.on_frame(move |_| counter.set(counter.get() + 1));
+        screen.draw(&[0, 0, 1, 2, 1, 4]);
+        screen.draw(&[]);
+        assert_eq!(frames.get(), 2);
+
+        let text = screen.render(|tile| match tile {
+            Some(1) => '#',
+            Some(4) => 'o',
+            _ => ' ',
+        });
+        assert_eq!(text, "#  \n  o\n");
+    }
+
+    #[test]
+    fn test_run() {
+        // Draws a wall at (input, 0) and sets the score to 7
+        let program = vec![104, -1, 104, 0, 104, 7, 3, 100, 4, 100, 104, 0, 104, 1, 99];
+        let mut machine = crate::IntCodeMachine::new(program.clone());
+        let mut screen = Screen::new();
+        screen.run(&mut machine, [3]).unwrap();
+        assert_eq!(screen.score(), Some(7));
+        assert_eq!(screen.tile(Point2::new(3, 0)), Some(1));
+
+        let mut machine = crate::IntCodeMachine::new(program);
+        let mut screen = Screen::new();
+        let frames = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&frames);
+        screen.on_frame(move |_| counter.set(counter.get() + 1));
+        let rate = crate::pace::Rate::OutputsPerSecond(1000);
+        screen.run_paced(&mut machine, [3], rate).unwrap();
+        assert_eq!(frames.get(), 2);
+        assert_eq!(screen.tile(Point2::new(3, 0)), Some(1));
+    }
+}
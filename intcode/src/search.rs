@@ -0,0 +1,139 @@
+//! Searching for the noun and verb, the values at addresses 1 and 2, that
+//! make a program leave a given value at address 0.
+
+use crate::{IntCodeError, IntCodeMachine, Result, Value};
+use std::ops::RangeInclusive;
+
+/// How long a single noun and verb may run. The puzzle's programs take a
+/// few dozen instructions, some pairs send them into a loop instead.
+pub const INSTRUCTION_LIMIT: u64 = 100_000;
+
+/// Runs the program with the noun and verb in place, returning what it
+/// left at address 0. Fails with [`IntCodeError::InvalidAddress`] when the
+/// program has no room for them, and with
+/// [`IntCodeError::InstructionLimit`] when it runs for too long.
+pub fn run_with(program: &[Value], noun: Value, verb: Value) -> Result<Value> {
+    run_on(&mut IntCodeMachine::new(Vec::new()), program, noun, verb)
+}
+
+// Like `run_with`, starting the program over on a machine that already
+// exists, which keeps its memory from being allocated again for every pair
+fn run_on(
+    machine: &mut IntCodeMachine,
+    program: &[Value],
+    noun: Value,
+    verb: Value,
+) -> Result<Value> {
+    if program.len() < 3 {
+        return Err(IntCodeError::InvalidAddress);
+    }
+    machine.reset(program);
+    machine.memory[1] = noun;
+    machine.memory[2] = verb;
+    machine.execute_limited([], INSTRUCTION_LIMIT)?;
+    Ok(machine.memory[0])
+}
+
+fn pairs(range: RangeInclusive<Value>) -> impl Iterator<Item = (Value, Value)> {
+    range
+        .clone()
+        .flat_map(move |noun| range.clone().map(move |verb| (noun, verb)))
+}
+
+/// The first noun and verb, both taken from `range`, that give the target.
+/// Pairs that make the program fail or run for too long don't count. Fails
+/// when the program is too short to have a noun and verb.
+pub fn find_noun_verb(
+    program: &[Value],
+    target: Value,
+    range: RangeInclusive<Value>,
+) -> Result<Option<(Value, Value)>> {
+    if program.len() < 3 {
+        return Err(IntCodeError::InvalidAddress);
+    }
+    let mut machine = IntCodeMachine::new(Vec::with_capacity(program.len()));
+    Ok(pairs(range)
+        .find(|(noun, verb)| run_on(&mut machine, program, *noun, *verb).ok() == Some(target)))
+}
+
+/// Like [`find_noun_verb`], trying the pairs on every core.
+#[cfg(feature = "parallel")]
+pub fn find_noun_verb_parallel(
+    program: &[Value],
+    target: Value,
+    range: RangeInclusive<Value>,
+) -> Result<Option<(Value, Value)>> {
+    use rayon::prelude::*;
+
+    if program.len() < 3 {
+        return Err(IntCodeError::InvalidAddress);
+    }
+    let pairs: Vec<(Value, Value)> = pairs(range).collect();
+    // A machine for each of rayon's jobs, reset between the pairs it tries
+    Ok(pairs
+        .into_par_iter()
+        .map_init(
+            || IntCodeMachine::new(Vec::with_capacity(program.len())),
+            |machine, (noun, verb)| (noun, verb, run_on(machine, program, noun, verb).ok()),
+        )
+        .find_first(|(_, _, value)| *value == Some(target))
+        .map(|(noun, verb, _)| (noun, verb)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_noun_verb, run_on, run_with};
+    use crate::{IntCodeError, IntCodeMachine};
+
+    // Adds the values at the noun and verb addresses
+    const ADDER: [isize; 8] = [1, 0, 0, 0, 99, 30, 40, 50];
+
+    #[test]
+    fn test_find_noun_verb() {
+        assert_eq!(run_with(&ADDER, 5, 6).unwrap(), 70);
+        assert_eq!(find_noun_verb(&ADDER, 90, 0..=7).unwrap(), Some((6, 7)));
+        assert_eq!(find_noun_verb(&ADDER, 1000, 0..=7).unwrap(), None);
+    }
+
+    #[test]
+    fn test_reused_machine() {
+        // Nothing a pair wrote is left over for the next one
+        let mut machine = IntCodeMachine::new(Vec::new());
+        assert_eq!(run_on(&mut machine, &ADDER, 5, 6).unwrap(), 70);
+        assert_eq!(run_on(&mut machine, &ADDER, 0, 0).unwrap(), 2);
+        assert_eq!(machine.memory(), [2, 0, 0, 0, 99, 30, 40, 50]);
+    }
+
+    #[test]
+    fn test_bad_programs() {
+        assert!(matches!(
+            run_with(&[1, 0], 0, 0),
+            Err(IntCodeError::InvalidAddress)
+        ));
+        assert!(find_noun_verb(&[99], 0, 0..=1).is_err());
+
+        // Jumps to the verb when the noun is 0, which loops forever for a
+        // verb of 0, and leaves 7 at address 0 from address 3
+        let program = [1106, 0, 0, 1101, 3, 4, 0, 99];
+        assert!(matches!(
+            run_with(&program, 0, 0),
+            Err(IntCodeError::InstructionLimit(_))
+        ));
+        assert_eq!(find_noun_verb(&program, 7, 0..=3).unwrap(), Some((0, 3)));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_find_noun_verb_parallel() {
+        use super::find_noun_verb_parallel;
+
+        assert_eq!(
+            find_noun_verb_parallel(&ADDER, 90, 0..=7).unwrap(),
+            Some((6, 7))
+        );
+        assert_eq!(
+            find_noun_verb_parallel(&ADDER, 80, 0..=7).unwrap(),
+            Some((5, 7))
+        );
+    }
+}
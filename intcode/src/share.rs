@@ -0,0 +1,155 @@
+//! Programs packed into short strings that fit in a URL or a chat message,
+//! and back.
+//!
+//! Every value is zigzag encoded, so small negative numbers stay small, and
+//! written as a varint, seven bits a byte. The bytes are then written in
+//! URL-safe base64 without padding. A typical puzzle input takes about a
+//! third of the characters of its comma separated text.
+
+use crate::Value;
+use aoc_utils::base64::{self, URL_SAFE};
+use std::convert::TryFrom;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidCharacter {
+        position: usize,
+        character: char,
+    },
+    /// The text ends in the middle of a value.
+    Truncated,
+    /// A value doesn't fit in an intcode value.
+    Overflow {
+        index: usize,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::InvalidCharacter {
+                position,
+                character,
+            } => write!(
+                f,
+                "Invalid character {:?} at position {}",
+                character, position
+            ),
+            DecodeError::Truncated => write!(f, "The program ends in the middle of a value"),
+            DecodeError::Overflow { index } => write!(f, "Value {} is too large", index),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn decode_base64(text: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut count = 0;
+    for (position, character) in text.chars().enumerate() {
+        let value = URL_SAFE
+            .iter()
+            .position(|c| *c as char == character)
+            .ok_or(DecodeError::InvalidCharacter {
+                position,
+                character,
+            })?;
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Packs a program into a string of URL-safe characters.
+pub fn encode(program: &[Value]) -> String {
+    let mut bytes = Vec::with_capacity(program.len() * 2);
+    for value in program {
+        let value = *value as i64;
+        let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        while zigzag >= 0x80 {
+            bytes.push(zigzag as u8 | 0x80);
+            zigzag >>= 7;
+        }
+        bytes.push(zigzag as u8);
+    }
+    base64::encode(&bytes, URL_SAFE, false)
+}
+
+/// Unpacks a program packed by [`encode`]. Whitespace around the text, as
+/// left by copying it from a message, is ignored.
+pub fn decode(text: &str) -> Result<Vec<Value>, DecodeError> {
+    let mut program = Vec::new();
+    let mut zigzag = 0u64;
+    let mut shift = 0;
+    for byte in decode_base64(text.trim())? {
+        // The tenth byte only has room for the last bit
+        if shift > 63 || (shift == 63 && byte & 0x7e != 0) {
+            return Err(DecodeError::Overflow {
+                index: program.len(),
+            });
+        }
+        zigzag |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            let value = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+            let value = Value::try_from(value).map_err(|_| DecodeError::Overflow {
+                index: program.len(),
+            })?;
+            program.push(value);
+            zigzag = 0;
+            shift = 0;
+        }
+    }
+    if shift != 0 {
+        return Err(DecodeError::Truncated);
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode, DecodeError};
+    use crate::programs::{COUNTDOWN, LARGE_NUMBER, QUINE};
+
+    #[test]
+    fn test_round_trip() {
+        for program in [&COUNTDOWN[..], &LARGE_NUMBER[..], &QUINE[..], &[][..]] {
+            assert_eq!(decode(&encode(program)), Ok(program.to_vec()));
+        }
+        let extremes = [isize::MIN, -1, 0, 1, isize::MAX];
+        assert_eq!(decode(&encode(&extremes)), Ok(extremes.to_vec()));
+    }
+
+    #[test]
+    fn test_encoding() {
+        // 1 and -1 zigzag to 2 and 1, 64 takes two bytes
+        assert_eq!(encode(&[1, -1, 64]), "AgGAAQ");
+        assert_eq!(decode(" AgGAAQ\n"), Ok(vec![1, -1, 64]));
+        assert!(encode(&QUINE)
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(
+            decode("Ag+A"),
+            Err(DecodeError::InvalidCharacter {
+                position: 2,
+                character: '+'
+            })
+        );
+        // A single byte with its continuation bit set
+        assert_eq!(decode("gA"), Err(DecodeError::Truncated));
+        assert_eq!(
+            decode("________________"),
+            Err(DecodeError::Overflow { index: 0 })
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! Counting how often each address is read and written, to find the cells a
+//! program uses as its variables. Only built with the `memory-stats`
+//! feature, counting slows every access down.
+
+/// Reads and writes by address. Fetching the instructions themselves and
+/// their immediate parameters isn't counted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    reads: Vec<u64>,
+    writes: Vec<u64>,
+}
+
+/// How often an address was accessed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accesses {
+    pub address: usize,
+    pub reads: u64,
+    pub writes: u64,
+}
+
+fn count(counts: &mut Vec<u64>, address: usize) {
+    if address >= counts.len() {
+        counts.resize(address + 1, 0);
+    }
+    counts[address] += 1;
+}
+
+impl MemoryStats {
+    pub(crate) fn read(&mut self, address: usize) {
+        count(&mut self.reads, address);
+    }
+
+    pub(crate) fn write(&mut self, address: usize) {
+        count(&mut self.writes, address);
+    }
+
+    pub fn accesses(&self, address: usize) -> Accesses {
+        Accesses {
+            address,
+            reads: self.reads.get(address).copied().unwrap_or(0),
+            writes: self.writes.get(address).copied().unwrap_or(0),
+        }
+    }
+
+    /// The `n` addresses accessed the most, reads and writes together, with
+    /// ties going to the lower address.
+    pub fn hottest(&self, n: usize) -> Vec<Accesses> {
+        let mut accesses: Vec<Accesses> = (0..self.reads.len().max(self.writes.len()))
+            .map(|address| self.accesses(address))
+            .filter(|accesses| accesses.reads + accesses.writes > 0)
+            .collect();
+        accesses.sort_by_key(|accesses| std::cmp::Reverse(accesses.reads + accesses.writes));
+        accesses.truncate(n);
+        accesses
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Accesses;
+    use crate::programs::COUNTDOWN;
+    use crate::IntCodeMachine;
+
+    #[test]
+    fn test_hottest() {
+        let mut machine = IntCodeMachine::new(COUNTDOWN.to_vec());
+        machine.execute(vec![10]).unwrap();
+        let hottest = machine.memory_stats().hottest(2);
+        // The counter is read by the add, the decrement and the jump, and
+        // written by the input and every decrement
+        assert_eq!(
+            hottest,
+            [
+                Accesses {
+                    address: 20,
+                    reads: 30,
+                    writes: 11,
+                },
+                Accesses {
+                    address: 19,
+                    reads: 11,
+                    writes: 10,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Reading a program straight into memory, a few bytes at a time, for
+//! programs too big to comfortably read into a string first. Values can be
+//! spread over as many lines as needed.
+
+use crate::{IntCodeMachine, ParseError, Value};
+use std::io::BufRead;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Last {
+    Start,
+    Value,
+    // A value followed by spaces or tabs, still waiting for its separator
+    Space,
+    // A value followed by a line break, which separates it from the next
+    Line,
+    Comma,
+}
+
+struct Number {
+    value: Value,
+    negative: bool,
+    digits: usize,
+}
+
+/// Appends the values read to `memory`, returning how many there were.
+/// Values are separated by commas, by line breaks, or both, and may have
+/// whitespace around them.
+pub fn read_program_into<R: BufRead>(
+    mut reader: R,
+    memory: &mut Vec<Value>,
+) -> Result<usize, ParseError> {
+    let start = memory.len();
+    let mut last = Last::Start;
+    let mut number: Option<Number> = None;
+
+    let error = |memory: &Vec<Value>, message: &str| -> ParseError {
+        format!("value {}: {}", memory.len() - start + 1, message).into()
+    };
+    let finish =
+        |number: &mut Option<Number>, memory: &mut Vec<Value>| -> Result<bool, ParseError> {
+            match number.take() {
+                Some(Number { digits: 0, .. }) => Err(error(memory, "expected digits after '-'")),
+                Some(Number { value, .. }) => {
+                    memory.push(value);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        };
+
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            break;
+        }
+        let length = buffer.len();
+        for &byte in buffer {
+            if number.is_none() && last == Last::Space && (byte.is_ascii_digit() || byte == b'-') {
+                return Err(error(memory, "missing ',' between values"));
+            }
+            match byte {
+                b'0'..=b'9' => {
+                    let number = match &mut number {
+                        Some(number) => number,
+                        None => number.insert(Number {
+                            value: 0,
+                            negative: false,
+                            digits: 0,
+                        }),
+                    };
+                    let digit = (byte - b'0') as Value;
+                    // Negative values are built downwards so the smallest one fits
+                    let value = number.value.checked_mul(10).and_then(|value| {
+                        if number.negative {
+                            value.checked_sub(digit)
+                        } else {
+                            value.checked_add(digit)
+                        }
+                    });
+                    number.value = value.ok_or_else(|| error(memory, "value out of range"))?;
+                    number.digits += 1;
+                }
+                b'-' if number.is_none() => {
+                    number = Some(Number {
+                        value: 0,
+                        negative: true,
+                        digits: 0,
+                    });
+                }
+                b',' => {
+                    let after_value = matches!(last, Last::Space | Last::Line);
+                    if !finish(&mut number, memory)? && !after_value {
+                        return Err(error(memory, "missing value before ','"));
+                    }
+                    last = Last::Comma;
+                }
+                b' ' | b'\t' => {
+                    if finish(&mut number, memory)? {
+                        last = Last::Space;
+                    }
+                }
+                b'\r' | b'\n' => {
+                    if finish(&mut number, memory)? || last == Last::Space {
+                        last = Last::Line;
+                    }
+                }
+                _ => return Err(error(memory, &format!("unexpected {:?}", byte as char))),
+            }
+            if number.is_some() {
+                last = Last::Value;
+            }
+        }
+        reader.consume(length);
+    }
+    finish(&mut number, memory)?;
+    if last == Last::Comma {
+        return Err(error(memory, "missing value after ','"));
+    }
+    Ok(memory.len() - start)
+}
+
+/// Reads a whole program, see [`read_program_into`].
+pub fn read_program<R: BufRead>(reader: R) -> Result<Vec<Value>, ParseError> {
+    let mut memory = Vec::new();
+    read_program_into(reader, &mut memory)?;
+    Ok(memory)
+}
+
+impl IntCodeMachine {
+    /// A machine loaded with the program read, see [`read_program_into`].
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, ParseError> {
+        read_program(reader).map(IntCodeMachine::new)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_program, read_program_into};
+    use crate::programs::QUINE;
+    use crate::IntCodeMachine;
+    use std::io::{BufReader, Cursor};
+
+    #[test]
+    fn test_read_program() {
+        assert_eq!(
+            read_program(&b"1,0,0,0,99\n"[..]).unwrap(),
+            [1, 0, 0, 0, 99]
+        );
+        assert_eq!(
+            read_program(&b" 1, -2 ,\n3\r\n4 \n 5\n,6\n"[..]).unwrap(),
+            [1, -2, 3, 4, 5, 6]
+        );
+        assert_eq!(read_program(&b""[..]).unwrap(), []);
+        let min = isize::MIN.to_string();
+        assert_eq!(read_program(min.as_bytes()).unwrap(), [isize::MIN]);
+    }
+
+    #[test]
+    fn test_read_program_errors() {
+        for input in [
+            "1,,2",
+            "1,2,",
+            ",1",
+            "1,-,2",
+            "1,2x",
+            "1-2",
+            "1 2,3",
+            "1,2\t-3",
+            "99999999999999999999",
+        ] {
+            assert!(read_program(input.as_bytes()).is_err(), "{:?}", input);
+        }
+        let error = read_program(&b"1,2,,3"[..]).unwrap_err();
+        assert_eq!(error.to_string(), "value 3: missing value before ','");
+        let error = read_program(&b"1 2,3"[..]).unwrap_err();
+        assert_eq!(error.to_string(), "value 2: missing ',' between values");
+    }
+
+    #[test]
+    fn test_small_buffer() {
+        // Values split across reads still come out whole
+        let text = QUINE.map(|value| value.to_string()).join(",");
+        let reader = BufReader::with_capacity(3, Cursor::new(text));
+        let mut machine = IntCodeMachine::from_reader(reader).unwrap();
+        assert_eq!(machine.execute([]).unwrap(), QUINE);
+
+        let mut memory = vec![7];
+        assert_eq!(read_program_into(&b"8,9"[..], &mut memory).unwrap(), 2);
+        assert_eq!(memory, [7, 8, 9]);
+    }
+}
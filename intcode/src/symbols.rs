@@ -0,0 +1,139 @@
+//! Names for addresses, so what's been worked out about a program shows up
+//! wherever the machine prints addresses.
+//!
+//! Symbols can be kept in a file, one per line, like `223 input_reg`.
+//! Lines starting with `#` are comments.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbols {
+    names: BTreeMap<usize, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolError {
+    pub line: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for SymbolError {}
+
+impl Symbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names an address, returning the name it had before.
+    pub fn insert(&mut self, address: usize, name: &str) -> Option<String> {
+        self.names.insert(address, name.to_string())
+    }
+
+    pub fn remove(&mut self, address: usize) -> Option<String> {
+        self.names.remove(&address)
+    }
+
+    pub fn get(&self, address: usize) -> Option<&str> {
+        self.names.get(&address).map(String::as_str)
+    }
+
+    /// The address with the given name.
+    pub fn address(&self, name: &str) -> Option<usize> {
+        self.names
+            .iter()
+            .find(|(_, symbol)| *symbol == name)
+            .map(|(address, _)| *address)
+    }
+
+    /// How an address is printed: its name if it has one, the bare address
+    /// otherwise.
+    pub fn label(&self, address: usize) -> String {
+        match self.get(address) {
+            Some(name) => name.to_string(),
+            None => address.to_string(),
+        }
+    }
+
+    /// Every named address, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.names
+            .iter()
+            .map(|(address, name)| (*address, name.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, SymbolError> {
+        let mut symbols = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message| SymbolError {
+                line: index + 1,
+                message,
+            };
+            let (address, name) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| error("expected an address and a name"))?;
+            let address = address.parse().map_err(|_| error("invalid address"))?;
+            let name = name.trim();
+            if name.contains(char::is_whitespace) {
+                return Err(error("names can't have spaces"));
+            }
+            symbols.insert(address, name);
+        }
+        Ok(symbols)
+    }
+}
+
+/// Writes the symbols in the format read by [`Symbols::parse`].
+impl fmt::Display for Symbols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (address, name) in self.iter() {
+            writeln!(f, "{} {}", address, name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Symbols;
+
+    #[test]
+    fn test_symbols() {
+        let mut symbols = Symbols::new();
+        assert_eq!(symbols.insert(223, "input_reg"), None);
+        symbols.insert(12, "counter");
+        assert_eq!(symbols.label(223), "input_reg");
+        assert_eq!(symbols.label(224), "224");
+        assert_eq!(symbols.address("counter"), Some(12));
+        assert_eq!(symbols.insert(12, "total").as_deref(), Some("counter"));
+        assert_eq!(symbols.to_string(), "12 total\n223 input_reg\n");
+    }
+
+    #[test]
+    fn test_parse() {
+        let symbols = Symbols::parse("# day 9\n223 input_reg\n\n12   total\n").unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(Symbols::parse(&symbols.to_string()), Ok(symbols));
+        assert_eq!(Symbols::parse("1 a\nx b").unwrap_err().line, 2);
+        assert!(Symbols::parse("1 two words").is_err());
+        assert!(Symbols::parse("1").is_err());
+    }
+}
@@ -0,0 +1,201 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+type Color = [u8; 3];
+
+#[derive(Debug)]
+pub enum RecorderError {
+    Io(io::Error),
+    Encoding(gif::EncodingError),
+    NoFrames,
+    FrameTooLarge,
+}
+
+impl fmt::Display for RecorderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecorderError::Io(error) => write!(f, "Could not write recording: {}", error),
+            RecorderError::Encoding(error) => write!(f, "Could not encode recording: {}", error),
+            RecorderError::NoFrames => write!(f, "No frames were recorded"),
+            RecorderError::FrameTooLarge => write!(f, "Frame is too large for a GIF"),
+        }
+    }
+}
+
+impl std::error::Error for RecorderError {}
+
+impl From<io::Error> for RecorderError {
+    fn from(error: io::Error) -> Self {
+        RecorderError::Io(error)
+    }
+}
+
+impl From<gif::EncodingError> for RecorderError {
+    fn from(error: gif::EncodingError) -> Self {
+        RecorderError::Encoding(error)
+    }
+}
+
+struct Frame {
+    width: usize,
+    height: usize,
+    // Palette index of every cell, row by row
+    cells: Vec<u8>,
+}
+
+/// Collects grid snapshots and encodes them as an animated GIF. Every cell of
+/// a snapshot is an index into the palette, and gets drawn as a square of
+/// `scale` pixels.
+pub struct Recorder {
+    palette: Vec<Color>,
+    scale: usize,
+    delay: u16,
+    frames: Vec<Frame>,
+}
+
+impl Recorder {
+    /// Creates a recorder with the given palette, which can have up to 256
+    /// colors. Index 0 is used as the background when frames have different
+    /// sizes.
+    pub fn new(palette: &[Color]) -> Self {
+        Self {
+            palette: palette.to_vec(),
+            scale: 4,
+            delay: 5,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Time each frame stays on screen, in hundredths of a second
+    pub fn with_delay(mut self, delay: u16) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Records a frame, given as rows of palette indices
+    pub fn push_grid<R: AsRef<[u8]>>(&mut self, rows: &[R]) {
+        let height = rows.len();
+        let width = rows.iter().map(|row| row.as_ref().len()).max().unwrap_or(0);
+        let mut cells = vec![0; width * height];
+        for (y, row) in rows.iter().enumerate() {
+            let row = row.as_ref();
+            cells[y * width..y * width + row.len()].copy_from_slice(row);
+        }
+        self.frames.push(Frame {
+            width,
+            height,
+            cells,
+        });
+    }
+
+    /// Records a frame drawn as text, mapping every character to a palette
+    /// index.
+    pub fn push_text<F>(&mut self, text: &str, color: F)
+    where
+        F: Fn(char) -> u8,
+    {
+        let rows: Vec<Vec<u8>> = text
+            .lines()
+            .map(|line| line.chars().map(&color).collect())
+            .collect();
+        self.push_grid(&rows);
+    }
+
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<(), RecorderError> {
+        if self.frames.is_empty() {
+            return Err(RecorderError::NoFrames);
+        }
+        let width = self
+            .frames
+            .iter()
+            .map(|frame| frame.width)
+            .max()
+            .unwrap_or(0)
+            * self.scale;
+        let height = self
+            .frames
+            .iter()
+            .map(|frame| frame.height)
+            .max()
+            .unwrap_or(0)
+            * self.scale;
+        let (width, height) = match (to_u16(width), to_u16(height)) {
+            (Some(width), Some(height)) => (width, height),
+            _ => return Err(RecorderError::FrameTooLarge),
+        };
+
+        let palette: Vec<u8> = self.palette.iter().flatten().copied().collect();
+        let mut encoder = gif::Encoder::new(writer, width, height, &palette)?;
+        encoder.set_repeat(gif::Repeat::Infinite)?;
+
+        for frame in self.frames.iter() {
+            let mut pixels = vec![0; width as usize * height as usize];
+            for y in 0..frame.height * self.scale {
+                for x in 0..frame.width * self.scale {
+                    let cell = frame.cells[(y / self.scale) * frame.width + x / self.scale];
+                    pixels[y * width as usize + x] = cell;
+                }
+            }
+            let mut gif_frame = gif::Frame::from_indexed_pixels(width, height, pixels, None);
+            gif_frame.delay = self.delay;
+            encoder.write_frame(&gif_frame)?;
+        }
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RecorderError> {
+        let file = BufWriter::new(File::create(path)?);
+        self.write_to(file)
+    }
+}
+
+fn to_u16(value: usize) -> Option<u16> {
+    if value <= u16::MAX as usize {
+        Some(value as u16)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Recorder, RecorderError};
+
+    #[test]
+    fn test_write_frames() {
+        let mut recorder = Recorder::new(&[[0, 0, 0], [255, 255, 255]]).with_scale(2);
+        recorder.push_grid(&[vec![0, 1], vec![1, 0]]);
+        recorder.push_text("#.#\n.#.", |c| if c == '#' { 1 } else { 0 });
+        assert_eq!(recorder.len(), 2);
+
+        let mut buffer = Vec::new();
+        recorder.write_to(&mut buffer).unwrap();
+        assert!(buffer.starts_with(b"GIF89a"));
+        // The canvas fits the largest frame
+        assert_eq!(&buffer[6..10], &[6, 0, 4, 0]);
+    }
+
+    #[test]
+    fn test_no_frames() {
+        let recorder = Recorder::new(&[[0, 0, 0]]);
+        match recorder.write_to(Vec::new()) {
+            Err(RecorderError::NoFrames) => {}
+            _ => panic!("Expected an error"),
+        }
+    }
+}